@@ -1,3 +1,5 @@
+use crate::logs::LogFormat;
+use crate::server::RateLimitPeriod;
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
@@ -34,12 +36,37 @@ pub enum Commands {
         /// The MCP server Unix socket path
         #[arg(long, env = "SURREAL_MCP_SOCKET_PATH", group = "server")]
         socket_path: Option<String>,
-        /// Rate limit requests per second (default: 100)
+        /// Rate limit requests per --rate-limit-period, or seconds between replenished requests without a period (default: 100)
         #[arg(long, env = "SURREAL_MCP_RATE_LIMIT_RPS", default_value = "100")]
         rate_limit_rps: u32,
         /// Rate limit burst size (default: 200)
         #[arg(long, env = "SURREAL_MCP_RATE_LIMIT_BURST", default_value = "200")]
         rate_limit_burst: u32,
+        /// Period over which --rate-limit-rps requests are allowed: second, minute, or hour
+        #[arg(long, env = "SURREAL_MCP_RATE_LIMIT_PERIOD", value_enum)]
+        rate_limit_period: Option<RateLimitPeriod>,
+        /// Maximum number of concurrent HTTP requests before responding with 503 (default: 1000)
+        #[arg(
+            long,
+            env = "SURREAL_MCP_MAX_IN_FLIGHT_REQUESTS",
+            default_value = "1000"
+        )]
+        max_in_flight_requests: usize,
+        /// Exact origins allowed to make cross-origin requests (comma-separated)
+        #[arg(long, env = "SURREAL_MCP_CORS_ALLOWED_ORIGINS", value_delimiter = ',')]
+        cors_allowed_origins: Vec<String>,
+        /// Regex pattern that allowed cross-origin request origins must fully match
+        #[arg(long, env = "SURREAL_MCP_CORS_ORIGIN_REGEX")]
+        cors_origin_regex: Option<String>,
+        /// Path to a PEM-encoded certificate chain for serving HTTPS (requires --tls-key)
+        #[arg(long, env = "SURREAL_MCP_TLS_CERT", requires = "tls_key")]
+        tls_cert: Option<String>,
+        /// Path to a PEM-encoded private key for serving HTTPS (requires --tls-cert)
+        #[arg(long, env = "SURREAL_MCP_TLS_KEY", requires = "tls_cert")]
+        tls_key: Option<String>,
+        /// Path to PEM-encoded CA certificates which HTTPS clients must present a certificate signed by
+        #[arg(long, env = "SURREAL_MCP_TLS_CLIENT_CA", requires = "tls_cert")]
+        tls_client_ca: Option<String>,
         /// Whether to require authentication for the MCP server
         #[arg(long, env = "SURREAL_MCP_AUTH_DISABLED", default_value = "false")]
         auth_disabled: bool,
@@ -64,6 +91,108 @@ pub enum Commands {
             default_value = "https://mcp.surrealdb.com/"
         )]
         auth_audience: String,
+        /// Shared secret for validating HS256, HS384, and HS512 signed JWT tokens
+        #[arg(long, env = "SURREAL_MCP_JWT_HMAC_SECRET")]
+        jwt_hmac_secret: Option<String>,
+        /// Maximum number of concurrent outbound SurrealDB Cloud API requests
+        #[arg(long, env = "SURREAL_MCP_CLOUD_MAX_CONCURRENCY", default_value = "10")]
+        cloud_max_concurrency: usize,
+        /// Seconds after which an unused SurrealDB Cloud auth token is cleared (0 to disable)
+        #[arg(
+            long,
+            env = "SURREAL_MCP_CLOUD_TOKEN_IDLE_TIMEOUT",
+            default_value = "0"
+        )]
+        cloud_token_idle_timeout: u64,
+        /// Maximum time in milliseconds to wait for each query to complete (0 for unlimited)
+        #[arg(long, env = "SURREAL_MCP_QUERY_TIMEOUT_MS", default_value = "0")]
+        query_timeout_ms: u64,
+        /// Whether to bind query parameters which are empty strings as NONE
+        #[arg(
+            long,
+            env = "SURREAL_MCP_EMPTY_STRING_AS_NONE",
+            default_value = "false"
+        )]
+        empty_string_as_none: bool,
+        /// Whether to include statement execution times in query results
+        #[arg(long, env = "SURREAL_MCP_INCLUDE_TIMING", default_value = "false")]
+        include_timing: bool,
+        /// Whether to allow the reset_database tool to remove all tables from memory databases
+        #[arg(long, env = "SURREAL_MCP_ALLOW_RESET", default_value = "false")]
+        allow_reset: bool,
+        /// Directory that the export_to_file tool can write to (stdio and Unix socket only)
+        #[arg(long, env = "SURREAL_MCP_EXPORT_DIR")]
+        export_dir: Option<String>,
+        /// Whether to allow tools which execute DEFINE statements
+        #[arg(
+            long,
+            env = "SURREAL_MCP_ALLOW_DEFINE_STATEMENTS",
+            default_value = "false"
+        )]
+        allow_define_statements: bool,
+        /// Maximum depth of nested values in tool results, such as fetched records (0 for unlimited)
+        #[arg(long, env = "SURREAL_MCP_MAX_RESULT_DEPTH", default_value = "16")]
+        max_result_depth: usize,
+        /// Maximum size of serialized tool results, which are truncated beyond this (0 for unlimited)
+        #[arg(long, env = "SURREAL_MCP_MAX_RESULT_BYTES", default_value = "0")]
+        max_result_bytes: usize,
+        /// Functions which can be called, such as string::* (comma-separated, all if empty)
+        #[arg(long, env = "SURREAL_MCP_ALLOWED_FUNCTIONS", value_delimiter = ',')]
+        allowed_functions: Vec<String>,
+        /// Functions which can not be called, such as http::* (comma-separated)
+        #[arg(long, env = "SURREAL_MCP_DENIED_FUNCTIONS", value_delimiter = ',')]
+        denied_functions: Vec<String>,
+        /// Whether to also check the functions called in every query against the allowed and denied functions
+        #[arg(long, env = "SURREAL_MCP_STRICT_FUNCTIONS", default_value = "false")]
+        strict_functions: bool,
+        /// Whether UPDATE and DELETE statements without a WHERE clause require confirm_full_table
+        #[arg(
+            long,
+            env = "SURREAL_MCP_REQUIRE_WHERE_FOR_MUTATIONS",
+            default_value = "false"
+        )]
+        require_where_for_mutations: bool,
+        /// Whether to disable the SurrealDB Cloud tools
+        #[arg(long, env = "SURREAL_MCP_DISABLE_CLOUD", default_value = "false")]
+        disable_cloud: bool,
+        /// Whether to disable the tools which run raw SurrealQL (query, compute, and import)
+        #[arg(long, env = "SURREAL_MCP_DISABLE_QUERY", default_value = "false")]
+        disable_query: bool,
+        /// Whether to disable the MCP prompts
+        #[arg(long, env = "SURREAL_MCP_DISABLE_PROMPTS", default_value = "false")]
+        disable_prompts: bool,
+        /// Whether to disable the MCP resources
+        #[arg(long, env = "SURREAL_MCP_DISABLE_RESOURCES", default_value = "false")]
+        disable_resources: bool,
+        /// Whether to disable the tools which modify data, and reject queries which modify data
+        #[arg(long, env = "SURREAL_MCP_READ_ONLY", default_value = "false")]
+        read_only: bool,
+        /// Maximum number of MCP prompts to register (0 for unlimited)
+        #[arg(long, env = "SURREAL_MCP_MAX_PROMPTS", default_value = "100")]
+        max_prompts: usize,
+        /// Maximum number of MCP resources to register (0 for unlimited)
+        #[arg(long, env = "SURREAL_MCP_MAX_RESOURCES", default_value = "100")]
+        max_resources: usize,
+        /// Maximum number of tools, prompts, or resources returned in each list page (0 for unlimited)
+        #[arg(long, env = "SURREAL_MCP_MAX_RESPONSE_ITEMS", default_value = "0")]
+        max_response_items: usize,
+        /// Whether to wrap every tool result in a consistent JSON envelope
+        #[arg(long, env = "SURREAL_MCP_RESPONSE_ENVELOPE", default_value = "false")]
+        response_envelope: bool,
+        /// Maximum size of each serialized tool result, including any response envelope, which is truncated beyond this (0 for unlimited)
+        #[arg(long, env = "SURREAL_MCP_MAX_MESSAGE_BYTES", default_value = "1048576")]
+        max_message_bytes: usize,
+        /// Proportion of requests and queries to log, between 0.0 and 1.0 (errors are always logged)
+        #[arg(long, env = "SURREAL_MCP_TRACE_SAMPLE_RATE", default_value = "1.0")]
+        trace_sample_rate: f64,
+        /// Format of the log output: text or json (default: text)
+        #[arg(
+            long,
+            env = "SURREAL_MCP_LOG_FORMAT",
+            value_enum,
+            default_value = "text"
+        )]
+        log_format: LogFormat,
         /// SurrealDB Cloud access token (used instead of fetching tokens)
         #[arg(long, env = "SURREAL_MCP_CLOUD_ACCESS_TOKEN")]
         cloud_access_token: Option<String>,
@@ -0,0 +1,186 @@
+use crate::engine::error_data;
+use reqwest::StatusCode;
+use std::fmt;
+
+/// A SurrealDB Cloud API operation, and the resource it acts on
+///
+/// Every error carries the operation which failed, so that errors can be
+/// traced back to the organization or instance they concern.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operation {
+    /// A description of the operation, such as "fetch cloud instance"
+    pub name: &'static str,
+    /// The id of the organization, instance, or other resource, if any
+    pub resource: Option<String>,
+    /// Whether the operation can be safely repeated if its outcome is unknown
+    pub idempotent: bool,
+}
+
+impl Operation {
+    /// Create an idempotent operation on the given resource
+    pub fn new(name: &'static str, resource: Option<&str>) -> Self {
+        Self {
+            name,
+            resource: resource.map(str::to_string),
+            idempotent: true,
+        }
+    }
+
+    /// Mark the operation as creating or changing something each time it is sent
+    ///
+    /// A request which fails after it has been sent may already have been
+    /// applied, so these operations are not retried after transport errors.
+    pub fn non_idempotent(mut self) -> Self {
+        self.idempotent = false;
+        self
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.resource {
+            Some(resource) => write!(f, "Failed to {} '{resource}'", self.name),
+            None => write!(f, "Failed to {}", self.name),
+        }
+    }
+}
+
+/// An error returned by the SurrealDB Cloud client
+///
+/// Unsuccessful responses are classified by their HTTP status, so that tools
+/// can distinguish authentication failures and missing resources from other
+/// API errors, and report whether the request may succeed if retried.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CloudError {
+    /// The client is not authenticated, or its credentials were rejected
+    Unauthorized { operation: Operation, body: String },
+    /// The requested organization, instance, or resource does not exist
+    NotFound { operation: Operation, body: String },
+    /// Too many requests were sent to the API
+    RateLimited { operation: Operation, body: String },
+    /// The API returned any other unsuccessful response
+    Api {
+        operation: Operation,
+        status: StatusCode,
+        body: String,
+    },
+    /// The request could not be sent, or its response could not be read
+    Transport {
+        operation: Operation,
+        error: String,
+        retryable: bool,
+    },
+    /// The response was received, but could not be decoded
+    Decode { operation: Operation, error: String },
+}
+
+impl CloudError {
+    /// Create an error from an unsuccessful API response
+    pub async fn from_response(response: reqwest::Response, operation: Operation) -> Self {
+        let status = response.status();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return Self::transport(operation, e),
+        };
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Self::Unauthorized { operation, body }
+            }
+            StatusCode::NOT_FOUND => Self::NotFound { operation, body },
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited { operation, body },
+            status => Self::Api {
+                operation,
+                status,
+                body,
+            },
+        }
+    }
+
+    /// Create an error for a request which failed to be sent, or whose response failed to be read
+    ///
+    /// Requests which could not connect were never received, so they can always
+    /// be retried. Otherwise only idempotent operations are retryable, as the
+    /// request may already have been applied.
+    pub fn transport(operation: Operation, error: reqwest::Error) -> Self {
+        if error.is_decode() {
+            return Self::decode(operation, error);
+        }
+        let retryable = operation.idempotent || error.is_connect();
+        Self::Transport {
+            operation,
+            error: error.to_string(),
+            retryable,
+        }
+    }
+
+    /// Create an error for a response which could not be decoded
+    pub fn decode(operation: Operation, error: impl fmt::Display) -> Self {
+        Self::Decode {
+            operation,
+            error: error.to_string(),
+        }
+    }
+
+    /// The operation which failed
+    pub fn operation(&self) -> &Operation {
+        match self {
+            Self::Unauthorized { operation, .. }
+            | Self::NotFound { operation, .. }
+            | Self::RateLimited { operation, .. }
+            | Self::Api { operation, .. }
+            | Self::Transport { operation, .. }
+            | Self::Decode { operation, .. } => operation,
+        }
+    }
+
+    /// Whether the request may succeed if retried
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } => true,
+            Self::Transport { retryable, .. } => *retryable,
+            Self::Api { status, .. } => status.is_server_error(),
+            Self::Unauthorized { .. } | Self::NotFound { .. } | Self::Decode { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for CloudError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (message, detail) = match self {
+            Self::Unauthorized { body, .. } => ("Not authorized with SurrealDB Cloud", body),
+            Self::NotFound { body, .. } => ("Not found in SurrealDB Cloud", body),
+            Self::RateLimited { body, .. } => ("Too many requests to SurrealDB Cloud", body),
+            Self::Api { status, body, .. } => {
+                let operation = self.operation();
+                return match body.is_empty() {
+                    true => write!(f, "{operation}: SurrealDB Cloud API error ({status})"),
+                    false => write!(
+                        f,
+                        "{operation}: SurrealDB Cloud API error ({status}): {body}"
+                    ),
+                };
+            }
+            Self::Transport { error, .. } => ("Failed to communicate with SurrealDB Cloud", error),
+            Self::Decode { error, .. } => ("Invalid response from SurrealDB Cloud", error),
+        };
+        let operation = self.operation();
+        match detail.is_empty() {
+            true => write!(f, "{operation}: {message}"),
+            false => write!(f, "{operation}: {message}: {detail}"),
+        }
+    }
+}
+
+impl std::error::Error for CloudError {}
+
+impl From<CloudError> for rmcp::ErrorData {
+    fn from(error: CloudError) -> Self {
+        let data = Some(error_data(error.is_retryable()));
+        match error {
+            CloudError::NotFound { .. } => {
+                rmcp::ErrorData::resource_not_found(error.to_string(), data)
+            }
+            error => rmcp::ErrorData::internal_error(error.to_string(), data),
+        }
+    }
+}
@@ -1,10 +1,60 @@
-use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
-use tracing::{debug, error, info, trace};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::{RwLock, Semaphore};
+use tokio::time::Instant;
+use tracing::{debug, error, info, trace, warn};
+
+mod error;
+
+pub use error::{CloudError, Operation};
+
+/// The result of a SurrealDB Cloud client operation
+pub type Result<T> = std::result::Result<T, CloudError>;
 
 const CLOUD_API_BASE_URL: &str = "https://api.cloud.surrealdb.com/api/v1";
 
+/// The default maximum number of concurrent outbound Cloud API requests
+pub const DEFAULT_MAX_CONCURRENCY: usize = 10;
+
+// Limiter shared by all Cloud clients in this process
+static LIMITER: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+/// Configure the maximum number of concurrent outbound Cloud API requests.
+///
+/// This must be called at startup, before any Cloud client is created.
+/// Requests over the limit are queued until an earlier request completes.
+pub fn set_max_concurrency(max_concurrency: usize) -> anyhow::Result<()> {
+    // Ensure that requests can be made at all
+    if max_concurrency == 0 {
+        return Err(anyhow::anyhow!(
+            "The Cloud API maximum concurrency must be greater than zero"
+        ));
+    }
+    // Store the process-wide limiter
+    LIMITER
+        .set(Arc::new(Semaphore::new(max_concurrency)))
+        .map_err(|_| anyhow::anyhow!("The Cloud API maximum concurrency is already configured"))
+}
+
+/// Get the URL used to connect to a cloud instance with the given host.
+///
+/// Instance hosts are returned by the Cloud API without a scheme, so these
+/// are connected to over secure WebSockets unless a scheme is specified.
+pub fn instance_url(host: &str) -> String {
+    match host.contains("://") {
+        true => host.to_string(),
+        false => format!("wss://{host}"),
+    }
+}
+
+/// Get the process-wide limiter for outbound Cloud API requests
+fn shared_limiter() -> Arc<Semaphore> {
+    LIMITER
+        .get_or_init(|| Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENCY)))
+        .clone()
+}
+
 /// A response from signing in to SurrealDB Cloud
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CloudSignInResponse {
@@ -12,7 +62,14 @@ pub struct CloudSignInResponse {
     pub token: String,
 }
 
+/// The response from refreshing an auth token in SurrealDB Cloud
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloudRefreshResponse {
+    pub token: String,
+}
+
 /// A user in SurrealDB Cloud
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CloudUser {
     pub id: String,
@@ -43,6 +100,14 @@ pub struct CloudPlan {
     pub regions: Vec<String>,
 }
 
+/// A region in SurrealDB Cloud where instances can be created
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloudRegion {
+    pub slug: String,
+    pub display_name: Option<String>,
+    pub provider: Option<String>,
+}
+
 /// A cloud instance in SurrealDB Cloud
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CloudInstance {
@@ -59,6 +124,26 @@ pub struct CloudInstance {
     pub storage_size: Option<i32>,
     pub can_update_storage_size: Option<bool>,
     pub storage_size_update_cooloff_hours: Option<i32>,
+    pub capabilities: Option<CloudInstanceCapabilities>,
+}
+
+/// The capabilities which a cloud instance in SurrealDB Cloud permits
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloudInstanceCapabilities {
+    pub allow_scripting: Option<bool>,
+    pub allow_guests: Option<bool>,
+    pub allowed_experimental: Option<Vec<String>>,
+    pub denied_experimental: Option<Vec<String>>,
+    pub allowed_arbitrary_query: Option<Vec<String>>,
+    pub denied_arbitrary_query: Option<Vec<String>>,
+    pub allowed_rpc_methods: Option<Vec<String>>,
+    pub denied_rpc_methods: Option<Vec<String>>,
+    pub allowed_http_endpoints: Option<Vec<String>>,
+    pub denied_http_endpoints: Option<Vec<String>>,
+    pub allowed_networks: Option<Vec<String>>,
+    pub denied_networks: Option<Vec<String>>,
+    pub allowed_functions: Option<Vec<String>>,
+    pub denied_functions: Option<Vec<String>>,
 }
 
 /// A response from getting auth token for a cloud instance
@@ -81,11 +166,29 @@ pub struct CloudInstanceBackup {
     pub snapshot_id: String,
 }
 
+/// A request to upgrade the version of a cloud instance
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloudUpgradeInstanceRequest {
+    pub version: String,
+}
+
+/// A request to restore a cloud instance from a backup
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloudRestoreBackupRequest {
+    pub snapshot_id: String,
+}
+
 /// A request to create a cloud instance
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CloudCreateInstanceRequest {
     pub name: String,
     pub organization_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_units: Option<i32>,
 }
 
 /// A response from creating a cloud instance
@@ -98,12 +201,20 @@ pub struct CloudCreateInstanceResponse {
 pub struct Client {
     /// The HTTP client
     client: reqwest::Client,
+    /// The base URL of the SurrealDB Cloud API
+    base_url: String,
+    /// The limiter bounding concurrent outbound requests
+    limiter: Arc<Semaphore>,
     /// The MCP client token
     pub client_token: RwLock<Option<String>>,
     /// The SurrealDB Cloud auth token
     pub auth_token: RwLock<Option<String>>,
     /// The SurrealDB Cloud refresh token
     pub refresh_token: RwLock<Option<String>>,
+    /// The duration after which an unused auth token is cleared
+    idle_timeout: Option<Duration>,
+    /// When the auth token was last used
+    last_used: Mutex<Instant>,
 }
 
 impl Client {
@@ -111,9 +222,13 @@ impl Client {
     pub fn new() -> Self {
         Self {
             client: reqwest::Client::new(),
+            base_url: CLOUD_API_BASE_URL.to_string(),
+            limiter: shared_limiter(),
             client_token: RwLock::new(None),
             auth_token: RwLock::new(None),
             refresh_token: RwLock::new(None),
+            idle_timeout: None,
+            last_used: Mutex::new(Instant::now()),
         }
     }
 
@@ -121,98 +236,285 @@ impl Client {
     pub fn with_tokens(access_token: String, refresh_token: String) -> Self {
         Self {
             client: reqwest::Client::new(),
+            base_url: CLOUD_API_BASE_URL.to_string(),
+            limiter: shared_limiter(),
             client_token: RwLock::new(None),
             auth_token: RwLock::new(Some(access_token)),
             refresh_token: RwLock::new(Some(refresh_token)),
+            idle_timeout: None,
+            last_used: Mutex::new(Instant::now()),
         }
     }
 
-    /// Send a GET request to the given URL
-    async fn get(&self, url: &str) -> Result<reqwest::Response> {
+    /// Clear the auth token after it has been unused for the given duration.
+    ///
+    /// The token is fetched again using the MCP client token on next use.
+    /// Pre-configured tokens are not cleared when no client token is available
+    /// to fetch them again.
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Spawn a background task which clears the auth token once it is idle.
+    ///
+    /// The task exits when the client is dropped. Nothing is spawned when no
+    /// idle timeout is configured, or when called outside of a Tokio runtime.
+    pub fn spawn_idle_cleanup(client: &Arc<Self>) {
+        // Check that an idle timeout is configured
+        let Some(idle_timeout) = client.idle_timeout else {
+            return;
+        };
+        // Check that we are running within a runtime
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        // Hold a weak reference so that the client can be dropped
+        let client = Arc::downgrade(client);
+        runtime.spawn(async move {
+            loop {
+                tokio::time::sleep(idle_timeout).await;
+                match client.upgrade() {
+                    Some(client) => client.clear_if_idle().await,
+                    None => break,
+                };
+            }
+        });
+    }
+
+    /// Clear the auth token if it has not been used within the idle timeout
+    async fn clear_if_idle(&self) -> bool {
+        // Check that an idle timeout is configured
+        let Some(idle_timeout) = self.idle_timeout else {
+            return false;
+        };
+        // Check whether the auth token has been used recently
+        if self.last_used.lock().unwrap().elapsed() < idle_timeout {
+            return false;
+        }
+        // Only clear the auth token if it can be fetched again
+        if self.client_token.read().await.is_none() {
+            return false;
+        }
+        // Clear the stored auth token
+        if self.auth_token.write().await.take().is_some() {
+            info!("Cleared idle SurrealDB Cloud auth token");
+            return true;
+        }
+        false
+    }
+
+    /// Send a request, waiting for capacity if too many requests are in flight
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        operation: &Operation,
+    ) -> Result<reqwest::Response> {
+        // Wait until a request slot is available
+        let _permit = self
+            .limiter
+            .acquire()
+            .await
+            .map_err(|e| CloudError::Transport {
+                operation: operation.clone(),
+                error: e.to_string(),
+                retryable: false,
+            })?;
+        // Send the request
+        let response = request
+            .send()
+            .await
+            .map_err(|e| CloudError::transport(operation.clone(), e))?;
+        // Return the response
+        Ok(response)
+    }
+
+    /// Send an authenticated request built by the given function
+    ///
+    /// If the request is rejected as unauthorized and a refresh token is
+    /// available, the auth token is refreshed and the request is retried once.
+    async fn send_authorized<F>(
+        &self,
+        method: &str,
+        operation: &Operation,
+        build: F,
+    ) -> Result<reqwest::Response>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
         // Ensure we are authenticated
         self.authenticate().await?;
-        // Create the full URL path
-        let url = format!("{CLOUD_API_BASE_URL}{url}");
-        // Await the stored auth token
-        let auth_token = self.auth_token.read().await;
-        // Get the authentication token
-        let auth_token = auth_token
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Not authenticated with SurrealDB Cloud"))?;
-        // Create the request
-        let request = self
-            .client
-            .get(url)
-            .header("Authorization", format!("Bearer {auth_token}"));
+        // Send the request
+        let response = self.send_with_auth_token(method, operation, &build).await?;
+        // Return the response unless the auth token was rejected
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED
+            || self.refresh_token.read().await.is_none()
+        {
+            return Ok(response);
+        }
+        // Output debugging information
+        warn!("SurrealDB Cloud auth token was rejected, refreshing the auth token");
+        // Refresh the auth token
+        self.refresh().await?;
+        // Retry the request with the new auth token
+        self.send_with_auth_token(method, operation, &build).await
+    }
+
+    /// Send a request built by the given function with the stored auth token
+    async fn send_with_auth_token<F>(
+        &self,
+        method: &str,
+        operation: &Operation,
+        build: &F,
+    ) -> Result<reqwest::Response>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        // Create the request with the current auth token
+        let request = {
+            // Await the stored auth token
+            let auth_token = self.auth_token.read().await;
+            // Get the authentication token
+            let auth_token = auth_token
+                .as_ref()
+                .ok_or_else(|| CloudError::Unauthorized {
+                    operation: operation.clone(),
+                    body: String::new(),
+                })?;
+            // Add the authentication header
+            build().header("Authorization", format!("Bearer {auth_token}"))
+        };
         // Output debugging information
         trace!(
             request = ?request,
-            "Sending GET request to SurrealDB Cloud",
+            "Sending {method} request to SurrealDB Cloud",
         );
         // Send the request
-        let response = request.send().await?;
-        // Return the response
-        Ok(response)
+        self.send(request, operation).await
+    }
+
+    /// Send a GET request to the given URL
+    async fn get(&self, url: &str, operation: &Operation) -> Result<reqwest::Response> {
+        // Create the full URL path
+        let url = format!("{}{url}", self.base_url);
+        // Send the request
+        self.send_authorized("GET", operation, || self.client.get(&url))
+            .await
     }
 
     /// Send a POST request to the given URL with the given body
-    async fn post<T>(&self, url: &str, body: &T) -> Result<reqwest::Response>
+    async fn post<T>(&self, url: &str, body: &T, operation: &Operation) -> Result<reqwest::Response>
     where
         T: Serialize + ?Sized,
     {
-        // Ensure we are authenticated
-        self.authenticate().await?;
         // Create the full URL path
-        let url = format!("{CLOUD_API_BASE_URL}{url}");
-        // Await the stored auth token
-        let auth_token = self.auth_token.read().await;
-        // Get the authentication token
-        let auth_token = auth_token
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Not authenticated with SurrealDB Cloud"))?;
-        // Create the request
-        let request = self
-            .client
-            .post(url)
-            .header("Authorization", format!("Bearer {auth_token}"))
-            .json(body);
+        let url = format!("{}{url}", self.base_url);
+        // Send the request
+        self.send_authorized("POST", operation, || self.client.post(&url).json(body))
+            .await
+    }
+
+    /// Send a PATCH request to the given URL with the given body
+    async fn patch<T>(
+        &self,
+        url: &str,
+        body: &T,
+        operation: &Operation,
+    ) -> Result<reqwest::Response>
+    where
+        T: Serialize + ?Sized,
+    {
+        // Create the full URL path
+        let url = format!("{}{url}", self.base_url);
+        // Send the request
+        self.send_authorized("PATCH", operation, || self.client.patch(&url).json(body))
+            .await
+    }
+
+    /// Send a DELETE request to the given URL
+    async fn delete(&self, url: &str, operation: &Operation) -> Result<reqwest::Response> {
+        // Create the full URL path
+        let url = format!("{}{url}", self.base_url);
+        // Send the request
+        self.send_authorized("DELETE", operation, || self.client.delete(&url))
+            .await
+    }
+
+    /// Refresh the SurrealDB Cloud auth token using the stored refresh token
+    pub async fn refresh(&self) -> Result<()> {
+        let operation = Operation::new("refresh SurrealDB Cloud auth token", None);
+        // Get the stored refresh token
+        let refresh_token =
+            self.refresh_token
+                .read()
+                .await
+                .clone()
+                .ok_or_else(|| CloudError::Unauthorized {
+                    operation: operation.clone(),
+                    body: "No refresh token available".to_string(),
+                })?;
         // Output debugging information
-        trace!(
-            request = ?request,
-            "Sending POST request to SurrealDB Cloud",
-        );
+        debug!("Refreshing SurrealDB Cloud auth token");
+        // Create the full URL path
+        let url = format!("{}/refresh", self.base_url);
         // Send the request
-        let response = request.send().await?;
-        // Return the response
-        Ok(response)
+        let response = self
+            .send(self.client.post(url).json(&refresh_token), &operation)
+            .await?;
+        // Check the response status
+        if !response.status().is_success() {
+            let e = CloudError::from_response(response, operation).await;
+            error!("Failed to refresh SurrealDB Cloud auth token: {e}");
+            return Err(e);
+        }
+        // Parse the returned response
+        let result: CloudRefreshResponse = read_json(response, &operation).await?;
+        // Store the authentication token
+        *self.auth_token.write().await = Some(result.token);
+        // Mark the auth token as used
+        *self.last_used.lock().unwrap() = Instant::now();
+        // Output debugging information
+        info!("Successfully refreshed SurrealDB Cloud auth token");
+        // Return nothing
+        Ok(())
     }
 
     /// Authenticate with SurrealDB Cloud using a bearer token
     async fn authenticate(&self) -> Result<()> {
+        // Clear the auth token if it has been unused for too long
+        self.clear_if_idle().await;
+        // Mark the auth token as used
+        *self.last_used.lock().unwrap() = Instant::now();
         // If the auth token is already set, return
         if self.auth_token.read().await.is_some() {
             return Ok(());
         }
+        let operation = Operation::new("authenticate with SurrealDB Cloud", None);
         // Await the stored client token
         let client_token = self.client_token.read().await;
         // Get the client token
         let client_token = client_token
             .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("No authentication token available"))?;
+            .ok_or_else(|| CloudError::Unauthorized {
+                operation: operation.clone(),
+                body: "No authentication token available".to_string(),
+            })?;
         // Output debugging information
         debug!("Authenticating with SurrealDB Cloud using bearer token");
         // Create the full URL path
-        let url = format!("{CLOUD_API_BASE_URL}/signin");
+        let url = format!("{}/signin", self.base_url);
         // Send the request
-        let response = self.client.post(url).json(&client_token).send().await?;
+        let response = self
+            .send(self.client.post(url).json(&client_token), &operation)
+            .await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!("Failed to authenticate with SurrealDB Cloud: {e}");
-            return Err(anyhow::anyhow!("Authentication failed: {e}"));
+            return Err(e);
         }
         // Parse the returned response
-        let result: CloudSignInResponse = response.json().await?;
+        let result: CloudSignInResponse = read_json(response, &operation).await?;
         // Store the authentication token
         let mut auth_token = self.auth_token.write().await;
         *auth_token = Some(result.token);
@@ -227,20 +529,22 @@ impl Client {
 
     /// List organizations in SurrealDB Cloud
     pub async fn list_organizations(&self) -> Result<Vec<CloudOrganization>> {
+        let operation = Operation::new("fetch organizations", None);
         // Output debugging information
         debug!("Fetching organizations from SurrealDB Cloud");
         // Send the request
-        let response = self.get("/organizations").await?;
+        let response = self.get("/organizations", &operation).await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!("Failed to fetch organizations: {e}");
-            return Err(anyhow::anyhow!("Failed to fetch organizations: {e}"));
+            return Err(e);
         }
         // Parse the returned response as raw JSON
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = read_json(response, &operation).await?;
         // Parse the raw JSON into organizations
-        let result: Vec<CloudOrganization> = serde_json::from_value(json)?;
+        let result: Vec<CloudOrganization> =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
         // Output debugging information
         debug!(
             organisations = result.len(),
@@ -252,6 +556,7 @@ impl Client {
 
     /// List cloud instances in SurrealDB Cloud
     pub async fn list_instances(&self, organization_id: &str) -> Result<Vec<CloudInstance>> {
+        let operation = Operation::new("fetch cloud instances", Some(organization_id));
         // Output debugging information
         debug!(
             organization_id = organization_id,
@@ -259,21 +564,25 @@ impl Client {
         );
         // Send the request
         let response = self
-            .get(&format!("/organizations/{organization_id}/instances"))
+            .get(
+                &format!("/organizations/{organization_id}/instances"),
+                &operation,
+            )
             .await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!(
                 organization_id = organization_id,
                 "Failed to fetch cloud instances: {e}",
             );
-            return Err(anyhow::anyhow!("Failed to fetch cloud instances: {e}"));
+            return Err(e);
         }
         // Parse the returned response as raw JSON
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = read_json(response, &operation).await?;
         // Parse the raw JSON into instances
-        let result: Vec<CloudInstance> = serde_json::from_value(json)?;
+        let result: Vec<CloudInstance> =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
         // Output debugging information
         debug!(
             instances = result.len(),
@@ -283,28 +592,71 @@ impl Client {
         Ok(result)
     }
 
+    /// Fetch the regions available to an organization in SurrealDB Cloud
+    pub async fn list_regions(&self, organization_id: &str) -> Result<Vec<CloudRegion>> {
+        let operation = Operation::new("fetch cloud regions", Some(organization_id));
+        // Output debugging information
+        debug!(
+            organization_id = organization_id,
+            "Fetching cloud regions from SurrealDB Cloud",
+        );
+        // Send the request
+        let response = self
+            .get(
+                &format!("/organizations/{organization_id}/regions"),
+                &operation,
+            )
+            .await?;
+        // Check the response status
+        if !response.status().is_success() {
+            let e = CloudError::from_response(response, operation).await;
+            error!(
+                organization_id = organization_id,
+                "Failed to fetch cloud regions: {e}",
+            );
+            return Err(e);
+        }
+        // Parse the returned response as raw JSON
+        let json: serde_json::Value = read_json(response, &operation).await?;
+        // Organizations without a plan have no regions
+        if json.is_null() {
+            return Ok(Vec::new());
+        }
+        // Parse the raw JSON into regions
+        let result: Vec<CloudRegion> =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
+        // Output debugging information
+        debug!(regions = result.len(), "Successfully fetched cloud regions",);
+        // Return the regions
+        Ok(result)
+    }
+
     /// Get a single cloud instance by ID
     pub async fn get_instance(&self, instance_id: &str) -> Result<CloudInstance> {
+        let operation = Operation::new("fetch cloud instance", Some(instance_id));
         // Output debugging information
         debug!(
             instance_id = instance_id,
             "Fetching cloud instance from SurrealDB Cloud",
         );
         // Send the request
-        let response = self.get(&format!("/instances/{instance_id}")).await?;
+        let response = self
+            .get(&format!("/instances/{instance_id}"), &operation)
+            .await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!(
                 instance_id = instance_id,
                 "Failed to fetch cloud instance: {e}",
             );
-            return Err(anyhow::anyhow!("Failed to fetch cloud instance: {e}"));
+            return Err(e);
         }
         // Parse the returned response as raw JSON
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = read_json(response, &operation).await?;
         // Parse the raw JSON into instance
-        let result: CloudInstance = serde_json::from_value(json)?;
+        let result: CloudInstance =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
         // Output debugging information
         debug!(
             instance_id = instance_id,
@@ -317,39 +669,40 @@ impl Client {
     /// Create a cloud instance in SurrealDB Cloud
     pub async fn create_instance(
         &self,
-        organization_id: &str,
-        name: &str,
+        request: &CloudCreateInstanceRequest,
     ) -> Result<CloudInstance> {
+        let organization_id = request.organization_id.as_str();
+        let name = request.name.as_str();
+        let operation = Operation::new("create cloud instance", Some(name)).non_idempotent();
         // Output debugging information
         debug!(
             instance_name = name,
             organization_id = organization_id,
+            region = request.region.as_deref(),
+            version = request.version.as_deref(),
+            compute_units = request.compute_units,
             "Creating cloud instance in SurrealDB Cloud",
         );
-        // Create the request
-        let request = CloudCreateInstanceRequest {
-            name: name.to_string(),
-            organization_id: organization_id.to_string(),
-        };
         // Send the request
         let response = self
             .post(
                 &format!("/organizations/{organization_id}/instances"),
-                &request,
+                request,
+                &operation,
             )
             .await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!(
                 organization_id = organization_id,
                 instance_name = name,
                 "Failed to create cloud instance: {e}",
             );
-            return Err(anyhow::anyhow!("Failed to create cloud instance: {e}"));
+            return Err(e);
         }
         // Parse the returned response
-        let result: CloudCreateInstanceResponse = response.json().await?;
+        let result: CloudCreateInstanceResponse = read_json(response, &operation).await?;
         // Output debugging information
         info!(
             instance_id = result.instance.id,
@@ -363,6 +716,7 @@ impl Client {
 
     /// Pause a cloud instance in SurrealDB Cloud
     pub async fn pause_instance(&self, instance_id: &str) -> Result<CloudInstance> {
+        let operation = Operation::new("pause cloud instance", Some(instance_id));
         // Output debugging information
         debug!(
             instance_id = instance_id,
@@ -370,21 +724,22 @@ impl Client {
         );
         // Send the request
         let response = self
-            .post(&format!("/instances/{instance_id}/pause"), &())
+            .post(&format!("/instances/{instance_id}/pause"), &(), &operation)
             .await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!(
                 instance_id = instance_id,
                 "Failed to pause cloud instance: {e}",
             );
-            return Err(anyhow::anyhow!("Failed to pause cloud instance: {e}"));
+            return Err(e);
         }
         // Parse the returned response as raw JSON
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = read_json(response, &operation).await?;
         // Parse the raw JSON into instance
-        let result: CloudInstance = serde_json::from_value(json)?;
+        let result: CloudInstance =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
         // Output debugging information
         info!(
             instance_id = instance_id,
@@ -396,6 +751,7 @@ impl Client {
 
     /// Resume a cloud instance in SurrealDB Cloud
     pub async fn resume_instance(&self, instance_id: &str) -> Result<CloudInstance> {
+        let operation = Operation::new("resume cloud instance", Some(instance_id));
         // Output debugging information
         debug!(
             instance_id = instance_id,
@@ -403,21 +759,22 @@ impl Client {
         );
         // Send the request
         let response = self
-            .post(&format!("/instances/{instance_id}/resume"), &())
+            .post(&format!("/instances/{instance_id}/resume"), &(), &operation)
             .await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!(
                 instance_id = instance_id,
                 "Failed to resume cloud instance: {e}",
             );
-            return Err(anyhow::anyhow!("Failed to resume cloud instance: {e}"));
+            return Err(e);
         }
         // Parse the returned response as raw JSON
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = read_json(response, &operation).await?;
         // Parse the raw JSON into instance
-        let result: CloudInstance = serde_json::from_value(json)?;
+        let result: CloudInstance =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
         // Output debugging information
         info!(
             instance_id = instance_id,
@@ -427,8 +784,199 @@ impl Client {
         Ok(result)
     }
 
+    /// Upgrade a cloud instance in SurrealDB Cloud to the given version
+    pub async fn upgrade_instance(
+        &self,
+        instance_id: &str,
+        version: &str,
+    ) -> Result<CloudInstance> {
+        let operation = Operation::new("upgrade cloud instance", Some(instance_id));
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            version = version,
+            "Upgrading cloud instance in SurrealDB Cloud",
+        );
+        // Create the request body
+        let body = CloudUpgradeInstanceRequest {
+            version: version.to_string(),
+        };
+        // Send the request
+        let response = self
+            .post(
+                &format!("/instances/{instance_id}/version"),
+                &body,
+                &operation,
+            )
+            .await?;
+        // Check the response status
+        if !response.status().is_success() {
+            let e = CloudError::from_response(response, operation).await;
+            error!(
+                instance_id = instance_id,
+                version = version,
+                "Failed to upgrade cloud instance: {e}",
+            );
+            return Err(e);
+        }
+        // Parse the returned response as raw JSON
+        let json: serde_json::Value = read_json(response, &operation).await?;
+        // Parse the raw JSON into instance
+        let result: CloudInstance =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
+        // Output debugging information
+        info!(
+            instance_id = instance_id,
+            version = version,
+            "Successfully upgraded cloud instance",
+        );
+        // Return the instance
+        Ok(result)
+    }
+
+    /// Update the storage size of a cloud instance in SurrealDB Cloud
+    pub async fn update_instance_storage(
+        &self,
+        instance_id: &str,
+        storage_size: i32,
+    ) -> Result<CloudInstance> {
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            storage_size = storage_size,
+            "Updating cloud instance storage size in SurrealDB Cloud",
+        );
+        // Update the cloud instance
+        let body = serde_json::json!({ "storage_size": storage_size });
+        self.update_instance(instance_id, &body).await
+    }
+
+    /// Update the compute units of a cloud instance in SurrealDB Cloud
+    pub async fn update_instance_compute(
+        &self,
+        instance_id: &str,
+        compute_units: i32,
+    ) -> Result<CloudInstance> {
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            compute_units = compute_units,
+            "Updating cloud instance compute units in SurrealDB Cloud",
+        );
+        // Update the cloud instance
+        let body = serde_json::json!({ "compute_units": compute_units });
+        self.update_instance(instance_id, &body).await
+    }
+
+    /// Update the settings of a cloud instance in SurrealDB Cloud
+    async fn update_instance(
+        &self,
+        instance_id: &str,
+        body: &serde_json::Value,
+    ) -> Result<CloudInstance> {
+        let operation = Operation::new("update cloud instance", Some(instance_id));
+        // Send the request
+        let response = self
+            .patch(&format!("/instances/{instance_id}"), body, &operation)
+            .await?;
+        // Check the response status
+        if !response.status().is_success() {
+            let e = CloudError::from_response(response, operation).await;
+            error!(
+                instance_id = instance_id,
+                "Failed to update cloud instance: {e}",
+            );
+            return Err(e);
+        }
+        // Parse the returned response as raw JSON
+        let json: serde_json::Value = read_json(response, &operation).await?;
+        // Parse the raw JSON into instance
+        let result: CloudInstance =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
+        // Output debugging information
+        info!(
+            instance_id = instance_id,
+            "Successfully updated cloud instance",
+        );
+        // Return the instance
+        Ok(result)
+    }
+
+    /// Delete a cloud instance in SurrealDB Cloud
+    pub async fn delete_instance(&self, instance_id: &str) -> Result<()> {
+        let operation = Operation::new("delete cloud instance", Some(instance_id));
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            "Deleting cloud instance in SurrealDB Cloud",
+        );
+        // Send the request
+        let response = self
+            .delete(&format!("/instances/{instance_id}"), &operation)
+            .await?;
+        // Check the response status
+        if !response.status().is_success() {
+            let e = CloudError::from_response(response, operation).await;
+            error!(
+                instance_id = instance_id,
+                "Failed to delete cloud instance: {e}",
+            );
+            return Err(e);
+        }
+        // Output debugging information
+        info!(
+            instance_id = instance_id,
+            "Successfully deleted cloud instance",
+        );
+        // Return nothing
+        Ok(())
+    }
+
+    /// Restore a cloud instance in SurrealDB Cloud from one of its backups
+    pub async fn restore_backup(&self, instance_id: &str, snapshot_id: &str) -> Result<()> {
+        let operation =
+            Operation::new("restore cloud instance backup", Some(instance_id)).non_idempotent();
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            snapshot_id = snapshot_id,
+            "Restoring cloud instance backup in SurrealDB Cloud",
+        );
+        // Create the request body
+        let body = CloudRestoreBackupRequest {
+            snapshot_id: snapshot_id.to_string(),
+        };
+        // Send the request
+        let response = self
+            .post(
+                &format!("/instances/{instance_id}/restore"),
+                &body,
+                &operation,
+            )
+            .await?;
+        // Check the response status
+        if !response.status().is_success() {
+            let e = CloudError::from_response(response, operation).await;
+            error!(
+                instance_id = instance_id,
+                snapshot_id = snapshot_id,
+                "Failed to restore cloud instance backup: {e}",
+            );
+            return Err(e);
+        }
+        // Output debugging information
+        info!(
+            instance_id = instance_id,
+            snapshot_id = snapshot_id,
+            "Successfully started restoring cloud instance backup",
+        );
+        // Return nothing
+        Ok(())
+    }
+
     /// Fetch the status for a cloud instance in SurrealDB Cloud
     pub async fn get_instance_status(&self, instance_id: &str) -> Result<CloudInstanceStatus> {
+        let operation = Operation::new("fetch cloud instance status", Some(instance_id));
         // Output debugging information
         debug!(
             instance_id = instance_id,
@@ -436,23 +984,22 @@ impl Client {
         );
         // Send the request
         let response = self
-            .get(&format!("/instances/{instance_id}/status"))
+            .get(&format!("/instances/{instance_id}/status"), &operation)
             .await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!(
                 instance_id = instance_id,
                 "Failed to fetch status for cloud instance: {e}",
             );
-            return Err(anyhow::anyhow!(
-                "Failed to fetch status for cloud instance: {e}"
-            ));
+            return Err(e);
         }
         // Parse the returned response as raw JSON
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = read_json(response, &operation).await?;
         // Parse the raw JSON into instance status
-        let result: CloudInstanceStatus = serde_json::from_value(json)?;
+        let result: CloudInstanceStatus =
+            serde_json::from_value(json).map_err(|e| CloudError::decode(operation.clone(), e))?;
         // Output debugging information
         info!(
             instance_id = instance_id,
@@ -466,26 +1013,27 @@ impl Client {
 
     /// Get authentication token for a cloud instance
     pub async fn get_instance_auth(&self, instance_id: &str) -> Result<String> {
+        let operation = Operation::new("fetch cloud instance auth token", Some(instance_id));
         // Output debugging information
         debug!(
             instance_id = instance_id,
             "Fetching auth token for cloud instance",
         );
         // Send the request
-        let response = self.get(&format!("/instances/{instance_id}/auth")).await?;
+        let response = self
+            .get(&format!("/instances/{instance_id}/auth"), &operation)
+            .await?;
         // Check the response status
         if !response.status().is_success() {
-            let e = response.text().await?;
+            let e = CloudError::from_response(response, operation).await;
             error!(
                 instance_id = instance_id,
                 "Failed to fetch auth token for cloud instance: {e}",
             );
-            return Err(anyhow::anyhow!(
-                "Failed to fetch auth token for cloud instance: {e}"
-            ));
+            return Err(e);
         }
         // Parse the returned response
-        let result: CloudInstanceAuth = response.json().await?;
+        let result: CloudInstanceAuth = read_json(response, &operation).await?;
         // Output debugging information
         debug!(
             instance_id = instance_id,
@@ -496,9 +1044,48 @@ impl Client {
     }
 }
 
+/// Read and decode the JSON body of a successful response
+async fn read_json<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    operation: &Operation,
+) -> Result<T> {
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| CloudError::transport(operation.clone(), e))?;
+    serde_json::from_slice(&body).map_err(|e| CloudError::decode(operation.clone(), e))
+}
+
+/// Serve the given router on a local port, returning its base URL
+#[cfg(test)]
+pub(crate) async fn mock_cloud_api(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{addr}")
+}
+
+/// Create a pre-authenticated client for the given mock Cloud API
+#[cfg(test)]
+pub(crate) fn mock_client(base_url: String) -> Client {
+    let mut client = Client::with_tokens("access".to_string(), "refresh".to_string());
+    client.base_url = base_url;
+    client
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::Router;
+
+    #[test]
+    fn test_instance_url() {
+        assert_eq!(
+            instance_url("abc.aws-euw1.surreal.cloud"),
+            "wss://abc.aws-euw1.surreal.cloud"
+        );
+        assert_eq!(instance_url("ws://localhost:8000"), "ws://localhost:8000");
+    }
 
     #[test]
     fn test_client_new() {
@@ -595,6 +1182,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cloud_region_deserialization() {
+        let json_data = r#"
+        [
+            {
+                "slug": "aws-euw1",
+                "display_name": "Europe (Ireland)",
+                "provider": "aws"
+            },
+            {
+                "slug": "aws-use1"
+            }
+        ]
+        "#;
+        let regions: Vec<CloudRegion> = serde_json::from_str(json_data).unwrap();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].slug, "aws-euw1");
+        assert_eq!(regions[0].display_name.as_deref(), Some("Europe (Ireland)"));
+        assert_eq!(regions[0].provider.as_deref(), Some("aws"));
+        assert_eq!(regions[1].slug, "aws-use1");
+        assert_eq!(regions[1].display_name, None);
+    }
+
+    #[test]
+    fn test_create_instance_request_serialization() {
+        let mut request = CloudCreateInstanceRequest {
+            name: "Test".to_string(),
+            organization_id: "org".to_string(),
+            region: None,
+            version: None,
+            compute_units: None,
+        };
+        // Unspecified options are omitted from the request body
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({ "name": "Test", "organization_id": "org" })
+        );
+        // Specified options are included in the request body
+        request.region = Some("aws-euw1".to_string());
+        request.compute_units = Some(2);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "name": "Test",
+                "organization_id": "org",
+                "region": "aws-euw1",
+                "compute_units": 2,
+            })
+        );
+    }
+
     #[test]
     fn test_cloud_instance_deserialization() {
         // Sample API response data for instances
@@ -674,6 +1312,30 @@ mod tests {
                 assert_eq!(instance.storage_size, Some(1));
                 assert_eq!(instance.can_update_storage_size, Some(false));
                 assert_eq!(instance.storage_size_update_cooloff_hours, Some(6));
+                let capabilities = instance.capabilities.as_ref().unwrap();
+                assert_eq!(capabilities.allow_scripting, Some(true));
+                assert_eq!(capabilities.allow_guests, Some(false));
+                assert_eq!(capabilities.allowed_experimental, Some(vec![]));
+                assert_eq!(
+                    capabilities.denied_experimental,
+                    Some(vec!["*".to_string()])
+                );
+                assert_eq!(
+                    capabilities.allowed_arbitrary_query,
+                    Some(vec!["*".to_string()])
+                );
+                assert_eq!(
+                    capabilities.allowed_rpc_methods,
+                    Some(vec!["*".to_string()])
+                );
+                assert_eq!(
+                    capabilities.allowed_http_endpoints,
+                    Some(vec!["*".to_string()])
+                );
+                assert_eq!(capabilities.allowed_networks, Some(vec![]));
+                assert_eq!(capabilities.denied_networks, Some(vec!["*".to_string()]));
+                assert_eq!(capabilities.allowed_functions, Some(vec!["*".to_string()]));
+                assert_eq!(capabilities.denied_functions, Some(vec![]));
                 println!("✅ Successfully deserialized instance with new fields");
             }
             Err(e) => {
@@ -754,4 +1416,263 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_limited() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+        // Track how many requests are being handled at once
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let (current, max) = (in_flight.clone(), max_in_flight.clone());
+        let router = Router::new().route(
+            "/organizations",
+            axum::routing::get(move || {
+                let (current, max) = (current.clone(), max.clone());
+                async move {
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(25)).await;
+                    current.fetch_sub(1, Ordering::SeqCst);
+                    axum::Json(serde_json::json!([]))
+                }
+            }),
+        );
+        let mut client = mock_client(mock_cloud_api(router).await);
+        client.limiter = Arc::new(Semaphore::new(1));
+        let client = Arc::new(client);
+        // Issue more concurrent requests than the limit allows
+        let tasks: Vec<_> = (0..5)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.list_organizations().await })
+            })
+            .collect();
+        for task in tasks {
+            assert!(task.await.unwrap().unwrap().is_empty());
+        }
+        // Requests were serialized by the limiter
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_zero_max_concurrency_is_rejected() {
+        assert!(set_max_concurrency(0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_idle_auth_token_is_fetched_again() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        // Count the number of sign in requests
+        let signins = Arc::new(AtomicUsize::new(0));
+        let counter = signins.clone();
+        let router = Router::new()
+            .route(
+                "/signin",
+                axum::routing::post(move || {
+                    let counter = counter.clone();
+                    async move {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        axum::Json(serde_json::json!({ "id": "refresh", "token": "access" }))
+                    }
+                }),
+            )
+            .route(
+                "/organizations",
+                axum::routing::get(|| async { axum::Json(serde_json::json!([])) }),
+            );
+        let mut client = Client::new().with_idle_timeout(Some(Duration::from_secs(60)));
+        client.base_url = mock_cloud_api(router).await;
+        client
+            .client_token
+            .write()
+            .await
+            .replace("client".to_string());
+        // The auth token is fetched once and reused while in use
+        client.list_organizations().await.unwrap();
+        client.list_organizations().await.unwrap();
+        assert_eq!(signins.load(Ordering::SeqCst), 1);
+        // The auth token is cleared once the idle timeout has passed
+        tokio::time::pause();
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(client.clear_if_idle().await);
+        assert_eq!(*client.auth_token.read().await, None);
+        // The auth token is fetched again on the next call
+        client.list_organizations().await.unwrap();
+        assert_eq!(signins.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pre_configured_auth_token_is_not_cleared() {
+        let client = Client::with_tokens("access".to_string(), "refresh".to_string())
+            .with_idle_timeout(Some(Duration::from_secs(60)));
+        tokio::time::pause();
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(!client.clear_if_idle().await);
+        assert_eq!(*client.auth_token.read().await, Some("access".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_auth_token_is_refreshed_and_retried() {
+        use axum::http::{HeaderMap, StatusCode};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        // Count the number of refresh requests
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let counter = refreshes.clone();
+        let router = Router::new()
+            .route(
+                "/refresh",
+                axum::routing::post(move |axum::Json(token): axum::Json<String>| {
+                    let counter = counter.clone();
+                    async move {
+                        assert_eq!(token, "refresh");
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        axum::Json(serde_json::json!({ "token": "fresh" }))
+                    }
+                }),
+            )
+            .route(
+                "/organizations",
+                axum::routing::get(|headers: HeaderMap| async move {
+                    match headers["authorization"].to_str().unwrap() {
+                        "Bearer fresh" => Ok(axum::Json(serde_json::json!([]))),
+                        _ => Err(StatusCode::UNAUTHORIZED),
+                    }
+                }),
+            )
+            .route(
+                "/instances/abc",
+                axum::routing::delete(|| async { StatusCode::UNAUTHORIZED }),
+            );
+        let client = mock_client(mock_cloud_api(router).await);
+        // The expired auth token is refreshed and the request retried
+        client.list_organizations().await.unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(*client.auth_token.read().await, Some("fresh".to_string()));
+        // The refreshed auth token is reused
+        client.list_organizations().await.unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        // Requests are only retried once
+        assert!(client.delete_instance("abc").await.is_err());
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_cloud_instance_capabilities_deserialization() {
+        // Capabilities which are missing from the response are unknown
+        let json_data = r#"{ "allow_guests": true, "denied_functions": ["http::*"] }"#;
+        let capabilities: CloudInstanceCapabilities = serde_json::from_str(json_data).unwrap();
+        assert_eq!(capabilities.allow_guests, Some(true));
+        assert_eq!(capabilities.allow_scripting, None);
+        assert_eq!(
+            capabilities.denied_functions,
+            Some(vec!["http::*".to_string()])
+        );
+        assert_eq!(capabilities.allowed_functions, None);
+        // Instances without capabilities can still be deserialized
+        let json_data = r#"{ "id": "abc", "name": "Test" }"#;
+        let instance: CloudInstance = serde_json::from_str(json_data).unwrap();
+        assert!(instance.capabilities.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_error_responses_are_classified() {
+        use axum::http::StatusCode;
+        let router = Router::new()
+            .route(
+                "/instances/limited",
+                axum::routing::get(|| async { (StatusCode::TOO_MANY_REQUESTS, "slow down") }),
+            )
+            .route(
+                "/instances/broken",
+                axum::routing::get(|| async { (StatusCode::BAD_GATEWAY, "upstream") }),
+            )
+            .route(
+                "/instances/invalid",
+                axum::routing::get(|| async { "not json" }),
+            );
+        let client = mock_client(mock_cloud_api(router).await);
+        // A missing instance is not found, and the error describes the operation
+        let err = client.get_instance("missing").await.unwrap_err();
+        assert_eq!(
+            err,
+            CloudError::NotFound {
+                operation: Operation::new("fetch cloud instance", Some("missing")),
+                body: String::new(),
+            }
+        );
+        assert!(!err.is_retryable());
+        let err = rmcp::ErrorData::from(err);
+        assert_eq!(err.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+        assert_eq!(
+            err.message,
+            "Failed to fetch cloud instance 'missing': Not found in SurrealDB Cloud"
+        );
+        // Rate limited requests can be retried
+        let err = client.get_instance("limited").await.unwrap_err();
+        assert!(matches!(
+            &err,
+            CloudError::RateLimited { body, .. } if body == "slow down"
+        ));
+        assert!(err.is_retryable());
+        // Other errors keep their status and body
+        let err = client.get_instance("broken").await.unwrap_err();
+        assert_eq!(
+            err,
+            CloudError::Api {
+                operation: Operation::new("fetch cloud instance", Some("broken")),
+                status: StatusCode::BAD_GATEWAY,
+                body: "upstream".to_string(),
+            }
+        );
+        assert!(err.is_retryable());
+        // Responses which can not be decoded are not retryable
+        let err = client.get_instance("invalid").await.unwrap_err();
+        assert!(matches!(err, CloudError::Decode { .. }));
+        assert!(!err.is_retryable());
+        // Requests without credentials are unauthorized
+        let err = Client::new().list_organizations().await.unwrap_err();
+        assert!(matches!(err, CloudError::Unauthorized { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_transport_errors_are_only_retryable_when_idempotent() {
+        // Accept each connection and close it without responding
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = [0; 4096];
+                let _ = tokio::io::AsyncReadExt::read(&mut stream, &mut buffer).await;
+            }
+        });
+        let client = mock_client(format!("http://{addr}"));
+        // Requests which only read data can be retried
+        let err = client.get_instance("abc").await.unwrap_err();
+        assert!(matches!(err, CloudError::Transport { .. }));
+        assert!(err.is_retryable());
+        // Requests which create resources may have been applied, so are not retried
+        let request = CloudCreateInstanceRequest {
+            name: "Test".to_string(),
+            organization_id: "org".to_string(),
+            region: None,
+            version: None,
+            compute_units: None,
+        };
+        let err = client.create_instance(&request).await.unwrap_err();
+        assert!(matches!(err, CloudError::Transport { .. }));
+        assert!(!err.is_retryable());
+        let err = client.restore_backup("abc", "snapshot").await.unwrap_err();
+        assert!(matches!(err, CloudError::Transport { .. }));
+        assert!(!err.is_retryable());
+        // Requests which could not connect were never received, so can be retried
+        drop(client);
+        let addr = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+        let client = mock_client(format!("http://{addr}"));
+        let err = client.create_instance(&request).await.unwrap_err();
+        assert!(err.is_retryable());
+    }
 }
@@ -1,6 +1,64 @@
 use anyhow::{Result, anyhow};
 use surrealdb::{Surreal, engine::any, engine::any::Any, opt::auth::Root};
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
+
+/// The oldest SurrealDB version (major, minor) which this server has been tested with
+pub const MIN_TESTED_VERSION: (u64, u64) = (2, 0);
+
+/// The newest SurrealDB version (major, minor) which this server has been tested with
+pub const MAX_TESTED_VERSION: (u64, u64) = (2, 3);
+
+/// Fetch the version of the connected SurrealDB server
+///
+/// A warning is logged if the version is outside the tested range, as some
+/// generated SurrealQL syntax may not be supported by the server. Returns
+/// `None` if the version could not be retrieved.
+pub async fn probe_version(instance: &Surreal<Any>) -> Option<String> {
+    // Fetch the server version
+    match instance.version().await {
+        Ok(version) => {
+            let version = version.to_string();
+            // Output debugging information
+            debug!(version = %version, "Retrieved SurrealDB server version");
+            // Warn if the version has not been tested
+            check_version(&version);
+            Some(version)
+        }
+        Err(e) => {
+            // Output debugging information
+            warn!(error = %e, "Failed to retrieve SurrealDB server version");
+            None
+        }
+    }
+}
+
+/// Check whether a SurrealDB version is within the tested range
+///
+/// Logs a warning and returns `false` if the version is outside the tested
+/// range, or if the version could not be parsed.
+pub fn check_version(version: &str) -> bool {
+    // Parse the major and minor version numbers
+    let mut parts = version.split(['.', '-', '+']);
+    let major = parts.next().and_then(|v| v.parse::<u64>().ok());
+    let minor = parts.next().and_then(|v| v.parse::<u64>().ok());
+    // Check the version against the tested range
+    let tested = match (major, minor) {
+        (Some(major), Some(minor)) => {
+            (MIN_TESTED_VERSION..=MAX_TESTED_VERSION).contains(&(major, minor))
+        }
+        _ => false,
+    };
+    // Output a warning if the version has not been tested
+    if !tested {
+        warn!(
+            version = %version,
+            min_tested = %format!("{}.{}", MIN_TESTED_VERSION.0, MIN_TESTED_VERSION.1),
+            max_tested = %format!("{}.{}", MAX_TESTED_VERSION.0, MAX_TESTED_VERSION.1),
+            "Connected SurrealDB server version is outside the tested range, some queries may not be supported"
+        );
+    }
+    tested
+}
 
 /// Create a new SurrealDB connection for a client
 #[instrument(skip(username, password, namespace, database), fields(url = %url))]
@@ -98,3 +156,51 @@ pub async fn create_client_connection_with_token(
     // Return the instance
     Ok(instance)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::Capture;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Check a version, returning the result and the captured logs
+    fn capture_check_version(version: &str) -> (bool, String) {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(capture.clone())
+                .with_ansi(false),
+        );
+        let tested = tracing::subscriber::with_default(subscriber, || check_version(version));
+        (tested, capture.output())
+    }
+
+    #[test]
+    fn test_check_version_within_tested_range() {
+        for version in ["2.0.0", "2.3.7", "2.3.0-beta.1"] {
+            let (tested, logs) = capture_check_version(version);
+            assert!(tested, "{version} should be tested");
+            assert!(!logs.contains("outside the tested range"));
+        }
+    }
+
+    #[test]
+    fn test_check_version_outside_tested_range_warns() {
+        for version in ["1.5.4", "2.4.0", "3.0.0", "unknown"] {
+            let (tested, logs) = capture_check_version(version);
+            assert!(!tested, "{version} should not be tested");
+            assert!(logs.contains("WARN"));
+            assert!(logs.contains("outside the tested range"));
+            assert!(logs.contains(version));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_version_records_server_version() {
+        let instance = any::connect("memory").await.unwrap();
+        let version = probe_version(&instance)
+            .await
+            .expect("version not recorded");
+        assert!(check_version(&version));
+    }
+}
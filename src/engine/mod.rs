@@ -1,11 +1,136 @@
 use anyhow::Result;
 use metrics::{counter, histogram};
 use rmcp::model::Content;
+use std::fmt;
 use std::time::Instant;
 use std::{collections::HashMap, time::Duration};
+use surrealdb::error::{Api, Db};
 use surrealdb::{Surreal, Value, engine::any::Any};
 use tracing::{debug, error, info};
 
+use crate::utils::{StatementKind, statement_kinds};
+
+/// The marker which replaces values nested deeper than the maximum result depth
+pub const TRUNCATED_MARKER: &str = "[truncated: maximum result depth exceeded]";
+
+/// An error which occurred when executing a query or one of its statements
+#[derive(Clone, Debug)]
+pub struct QueryError {
+    /// The error message
+    pub message: String,
+    /// Whether the error is transient, and the query may succeed if retried
+    pub retryable: bool,
+}
+
+impl QueryError {
+    /// Create a query error from a SurrealDB error, classifying whether it is retryable
+    ///
+    /// A query which writes data may have been applied before it timed out, so
+    /// timeouts are only retryable for queries which only read data.
+    pub fn new(error: &surrealdb::Error, read_only: bool) -> Self {
+        Self {
+            message: error.to_string(),
+            retryable: is_retryable(error) && (read_only || !is_timeout(error)),
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<QueryError> for rmcp::ErrorData {
+    fn from(error: QueryError) -> Self {
+        rmcp::ErrorData::internal_error(error.message, Some(error_data(error.retryable)))
+    }
+}
+
+/// Get the MCP error data which describes whether a failed call can be retried
+pub fn error_data(retryable: bool) -> serde_json::Value {
+    serde_json::json!({ "retryable": retryable })
+}
+
+/// Check whether a SurrealDB error is transient, and the operation may succeed if retried
+///
+/// Timeouts, transaction conflicts, and connection failures are retryable. Errors
+/// such as syntax errors, permission errors, or constraint violations are permanent.
+pub fn is_retryable(error: &surrealdb::Error) -> bool {
+    match error {
+        surrealdb::Error::Db(e) => matches!(
+            e,
+            Db::QueryTimedout | Db::TxRetryable | Db::TxFailure | Db::QueryCancelled
+        ),
+        surrealdb::Error::Api(e) => match e {
+            Api::Ws(_) | Api::Http(_) | Api::ConnectionUninitialised => true,
+            Api::Query(message) => is_retryable_message(message),
+            _ => false,
+        },
+    }
+}
+
+/// Check whether a SurrealDB error is caused by a query exceeding its timeout
+pub fn is_timeout(error: &surrealdb::Error) -> bool {
+    match error {
+        surrealdb::Error::Db(e) => matches!(e, Db::QueryTimedout),
+        surrealdb::Error::Api(Api::Query(message)) => is_timeout_message(message),
+        _ => false,
+    }
+}
+
+/// Check whether an error message describes a query exceeding its timeout
+pub fn is_timeout_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["exceeded the timeout", "timed out"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// Check whether an error message describes a transient failure
+///
+/// Errors returned by remote instances, or when establishing connections, are
+/// only available as messages, so these are classified by their content.
+pub fn is_retryable_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "exceeded the timeout",
+        "timed out",
+        "can be retried",
+        "cancelled transaction",
+        "connection refused",
+        "connection reset",
+        "connection closed",
+        "connection uninitialised",
+        "temporarily unavailable",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// Check whether an error message describes a connection which has been lost
+pub fn is_connection_lost_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "connection closed",
+        "connection reset",
+        "connection uninitialised",
+        "closed connection",
+        "broken pipe",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// The outcome of a single statement within a query
+#[derive(Debug)]
+pub struct Statement {
+    /// The execution time reported by the server for this statement
+    pub time: Option<Duration>,
+    /// The result of the statement, or an error if it failed
+    pub result: Result<Value, QueryError>,
+}
+
 /// Response from executing a SurrealDB query
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -16,30 +141,188 @@ pub struct Response {
     pub query: String,
     /// Duration of the query execution
     pub duration: Duration,
-    /// Error message if the query failed
-    pub error: Option<String>,
-    /// The result of the query as a formatted string
-    pub result: Option<surrealdb::Response>,
+    /// Error if the query failed
+    pub error: Option<QueryError>,
+    /// The outcome of each statement in the query
+    pub statements: Vec<Statement>,
+    /// Whether to include statement execution times in the result
+    pub include_timing: bool,
 }
 
 impl Response {
+    /// Check whether the query failed because the connection was lost
+    pub fn connection_lost(&self) -> bool {
+        self.error
+            .as_ref()
+            .is_some_and(|error| is_connection_lost_message(&error.message))
+    }
+
+    /// Take the result of the statement at the given index
+    pub fn take(&mut self, index: usize) -> Result<Value, QueryError> {
+        // Check if the query as a whole failed
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        // Get the statement at the specified index
+        let statement = self.statements.get_mut(index).ok_or_else(|| QueryError {
+            message: format!("No result for statement {index}"),
+            retryable: false,
+        })?;
+        // Take the statement result
+        std::mem::replace(&mut statement.result, Ok(Value::default()))
+    }
+
     /// Convert the response to an MCP Tool Result
     pub fn to_mcp_result(&self) -> Result<rmcp::model::CallToolResult, rmcp::ErrorData> {
-        if let Some(res) = &self.result {
-            Ok(rmcp::model::CallToolResult::success(vec![Content::text(
-                format!("{res:?}"),
-            )]))
-        } else {
-            let error_msg = self
-                .error
-                .as_ref()
-                .unwrap_or(&"Unknown error".to_string())
-                .clone();
-            Err(rmcp::ErrorData::internal_error(error_msg, None))
+        // Check if the query as a whole failed
+        if let Some(error) = &self.error {
+            return Err(error.clone().into());
+        }
+        // Convert each statement result to JSON
+        let mut results = Vec::with_capacity(self.statements.len());
+        for (index, statement) in self.statements.iter().enumerate() {
+            match &statement.result {
+                Ok(value) => results.push(self.statement_json(value)),
+                Err(error) => {
+                    return Err(rmcp::ErrorData::internal_error(
+                        format!("Statement {index} failed: {error}"),
+                        Some(error_data(error.retryable)),
+                    ));
+                }
+            }
+        }
+        // Return the MCP result
+        Ok(self.to_content(serde_json::Value::Array(results)))
+    }
+
+    /// Convert the response to an MCP Tool Result which reports the outcome of each statement
+    ///
+    /// Unlike [`Response::to_mcp_result`], a failing statement does not fail the
+    /// whole result. Each statement is reported with its index, status, execution
+    /// time, and either its result or its error.
+    pub fn to_statement_results(&self) -> Result<rmcp::model::CallToolResult, rmcp::ErrorData> {
+        // Check if the query as a whole failed
+        if let Some(error) = &self.error {
+            return Err(error.clone().into());
+        }
+        // Convert each statement outcome to JSON
+        let results = self
+            .statements
+            .iter()
+            .enumerate()
+            .map(|(index, statement)| {
+                let time_ms = statement.time.map(|time| time.as_secs_f64() * 1000.0);
+                match &statement.result {
+                    Ok(value) => serde_json::json!({
+                        "statement": index,
+                        "status": "OK",
+                        "time_ms": time_ms,
+                        "result": self.statement_json(value),
+                    }),
+                    Err(error) => serde_json::json!({
+                        "statement": index,
+                        "status": "ERR",
+                        "time_ms": time_ms,
+                        "error": error.message,
+                        "retryable": error.retryable,
+                    }),
+                }
+            })
+            .collect();
+        // Return the MCP result
+        Ok(self.to_content(serde_json::Value::Array(results)))
+    }
+
+    /// Convert the result of a statement to JSON
+    fn statement_json(&self, value: &Value) -> serde_json::Value {
+        to_json(value.clone().into_inner())
+    }
+
+    /// Build the MCP Tool Result for the JSON results
+    fn to_content(&self, results: serde_json::Value) -> rmcp::model::CallToolResult {
+        // Add the statement results as the main content
+        let mut content = vec![Content::text(results.to_string())];
+        // Add the statement execution times if requested
+        if self.include_timing {
+            content.push(Content::text(self.timing().to_string()));
         }
+        // Return the MCP result
+        rmcp::model::CallToolResult::success(content)
+    }
+
+    /// Get the execution time metadata for the query and each statement
+    pub fn timing(&self) -> serde_json::Value {
+        let statements: Vec<serde_json::Value> = self
+            .statements
+            .iter()
+            .enumerate()
+            .map(|(index, statement)| {
+                serde_json::json!({
+                    "statement": index,
+                    "execution_time_ms": statement.time.map(|time| time.as_secs_f64() * 1000.0),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "timing": {
+                "total_ms": self.duration.as_secs_f64() * 1000.0,
+                "statements": statements,
+            }
+        })
+    }
+}
+
+/// Convert a SurrealDB value into JSON for returning to MCP clients
+///
+/// Values which have no JSON representation, such as ranges or futures, are
+/// rendered as SurrealQL strings rather than being serialized, which could fail.
+pub fn to_json(value: surrealdb::sql::Value) -> serde_json::Value {
+    use surrealdb::sql::Value as V;
+    match value {
+        V::Array(array) => serde_json::Value::Array(array.into_iter().map(to_json).collect()),
+        V::Object(object) => serde_json::Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (key, to_json(value)))
+                .collect(),
+        ),
+        V::None
+        | V::Null
+        | V::Bool(_)
+        | V::Number(_)
+        | V::Strand(_)
+        | V::Duration(_)
+        | V::Datetime(_)
+        | V::Uuid(_)
+        | V::Geometry(_)
+        | V::Bytes(_)
+        | V::Thing(_) => value.into_json(),
+        value => serde_json::Value::String(value.to_string()),
     }
 }
 
+/// Replace any objects or arrays nested deeper than the maximum depth with a marker
+///
+/// The top-level value is at depth 0. Returns whether any values were truncated.
+pub fn truncate_depth(value: &mut serde_json::Value, max_depth: usize) -> bool {
+    fn truncate(value: &mut serde_json::Value, depth: usize, max_depth: usize) -> bool {
+        match value {
+            serde_json::Value::Object(_) | serde_json::Value::Array(_) if depth > max_depth => {
+                *value = serde_json::Value::String(TRUNCATED_MARKER.to_string());
+                true
+            }
+            serde_json::Value::Object(object) => object.values_mut().fold(false, |truncated, v| {
+                truncate(v, depth + 1, max_depth) | truncated
+            }),
+            serde_json::Value::Array(array) => array.iter_mut().fold(false, |truncated, v| {
+                truncate(v, depth + 1, max_depth) | truncated
+            }),
+            _ => false,
+        }
+    }
+    truncate(value, 0, max_depth)
+}
+
 /// Execute a SurrealQL query against the specified SurrealDB endpoint
 ///
 /// This function executes a SurrealQL query against the provided SurrealDB client.
@@ -51,6 +334,7 @@ impl Response {
 /// * `parameters` - Optional parameters to bind to the query
 /// * `query_id` - Unique identifier for tracking this query
 /// * `connection_id` - Connection ID for logging purposes
+/// * `timeout` - The maximum duration to wait for the query to complete, if limited
 ///
 /// # Returns
 /// * `Result<Response, anyhow::Error>` - The query response or an error
@@ -60,6 +344,7 @@ pub async fn execute_query(
     query_string: String,
     parameters: Option<HashMap<String, Value>>,
     connection_id: &str,
+    timeout: Option<Duration>,
 ) -> Response {
     // Start the measurement timer
     let start_time = Instant::now();
@@ -70,6 +355,8 @@ pub async fn execute_query(
         query_string = %query_string,
         "Executing SurrealQL query"
     );
+    // Record whether the statements read or write data
+    record_statement_kinds(&query_string);
     // Build the query string
     let mut query = db.query(&query_string);
     // Bind any parameters
@@ -78,11 +365,58 @@ pub async fn execute_query(
             query = query.bind((key, value));
         }
     }
-    // Execute the query
-    match query.await {
-        Ok(res) => {
+    // Check whether the query only reads data, and so can be retried after a timeout
+    let read_only =
+        statement_kinds(&query_string).is_ok_and(|kinds| !kinds.contains(&StatementKind::Write));
+    // Execute the query, waiting no longer than the timeout
+    let result = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, query.with_stats()).await {
+            Ok(result) => result,
+            Err(_) => {
+                // Get the duration of the query
+                let duration = start_time.elapsed();
+                // Output debugging information
+                error!(
+                    connection_id = %connection_id,
+                    query_id,
+                    query = %query_string,
+                    duration_ms = duration.as_millis(),
+                    timeout_ms = timeout.as_millis(),
+                    "Query execution timed out"
+                );
+                // Update query metrics
+                counter!("surrealmcp.total_query_errors").increment(1);
+                counter!("surrealmcp.total_query_timeouts").increment(1);
+                histogram!("surrealmcp.query_duration_ms").record(duration.as_millis() as f64);
+                // Return the response
+                return Response {
+                    query: query_string,
+                    statements: vec![],
+                    error: Some(QueryError {
+                        message: format!("Query timed out after {}ms", timeout.as_millis()),
+                        retryable: read_only,
+                    }),
+                    duration,
+                    query_id,
+                    include_timing: false,
+                };
+            }
+        },
+        None => query.with_stats().await,
+    };
+    // Process the query result
+    match result {
+        Ok(mut res) => {
             // Get the duration of the query
             let duration = start_time.elapsed();
+            // Collect the outcome of each statement
+            let statements = (0..res.num_statements())
+                .filter_map(|index| res.take::<Value>(index))
+                .map(|(stats, result)| Statement {
+                    time: stats.execution_time,
+                    result: result.map_err(|e| QueryError::new(&e, read_only)),
+                })
+                .collect();
             // Output debugging information
             info!(
                 connection_id = %connection_id,
@@ -97,10 +431,11 @@ pub async fn execute_query(
             // Return the response
             Response {
                 query: query_string,
-                result: Some(res),
+                statements,
                 error: None,
                 duration,
                 query_id,
+                include_timing: false,
             }
         }
         Err(e) => {
@@ -121,11 +456,270 @@ pub async fn execute_query(
             // Return the response
             Response {
                 query: query_string,
-                result: None,
-                error: Some(e.to_string()),
+                statements: vec![],
+                error: Some(QueryError::new(&e, read_only)),
                 duration,
                 query_id,
+                include_timing: false,
             }
         }
     }
 }
+
+/// Increment the read and write query metrics for each statement in a query
+///
+/// Queries which fail to parse are not recorded, as they are rejected by the
+/// database without executing any statements.
+fn record_statement_kinds(query: &str) {
+    let Ok(kinds) = statement_kinds(query) else {
+        return;
+    };
+    for kind in kinds {
+        match kind {
+            StatementKind::Read => counter!("surrealmcp.read_queries").increment(1),
+            StatementKind::Write => counter!("surrealmcp.write_queries").increment(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_execute_query_includes_statement_timing() {
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        let mut res = execute_query(
+            &db,
+            0,
+            "RETURN 1; RETURN 2;".to_string(),
+            None,
+            "test",
+            None,
+        )
+        .await;
+        res.include_timing = true;
+        let result = res.to_mcp_result().unwrap();
+        assert_eq!(result.content.len(), 2);
+        // The statement results are returned as JSON
+        let values: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(values, serde_json::json!([1, 2]));
+        // The timing metadata is numeric for every statement
+        let timing: serde_json::Value =
+            serde_json::from_str(&result.content[1].as_text().unwrap().text).unwrap();
+        assert!(timing["timing"]["total_ms"].is_number());
+        let statements = timing["timing"]["statements"].as_array().unwrap();
+        assert_eq!(statements.len(), 2);
+        for statement in statements {
+            assert!(statement["execution_time_ms"].is_number());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_query_excludes_timing_by_default() {
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        let res = execute_query(&db, 0, "RETURN 1".to_string(), None, "test", None).await;
+        let result = res.to_mcp_result().unwrap();
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_errors_are_retryable_when_read_only() {
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        let query = "SELECT * FROM sleep(500ms) TIMEOUT 10ms".to_string();
+        let res = execute_query(&db, 0, query, None, "test", None).await;
+        let err = res.to_mcp_result().unwrap_err();
+        assert!(err.message.contains("timeout"));
+        assert_eq!(err.data, Some(serde_json::json!({ "retryable": true })));
+        // Queries which write data may have been applied, so are not retryable
+        let query = "CREATE person SET value = sleep(500ms) TIMEOUT 10ms".to_string();
+        let res = execute_query(&db, 1, query, None, "test", None).await;
+        let err = res.to_mcp_result().unwrap_err();
+        assert!(err.message.contains("timeout"));
+        assert_eq!(err.data, Some(serde_json::json!({ "retryable": false })));
+    }
+
+    #[tokio::test]
+    async fn test_syntax_errors_are_not_retryable() {
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        let res = execute_query(
+            &db,
+            0,
+            "SELEC * FROM person".to_string(),
+            None,
+            "test",
+            None,
+        )
+        .await;
+        let err = res.to_mcp_result().unwrap_err();
+        assert_eq!(err.data, Some(serde_json::json!({ "retryable": false })));
+    }
+
+    #[test]
+    fn test_retryable_messages_are_classified() {
+        assert!(is_retryable_message(
+            "The query was not executed because it exceeded the timeout"
+        ));
+        assert!(is_retryable_message("Connection refused (os error 111)"));
+        assert!(!is_retryable_message("Parse error: Unexpected token"));
+        assert!(!is_retryable_message(
+            "Not enough permissions to perform this action"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_statement_results_report_each_outcome() {
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        let query = "RETURN 1; THROW \"boom\"; RETURN 2";
+        let res = execute_query(&db, 0, query.to_string(), None, "test", None).await;
+        // The failing statement fails the default result
+        let error = res.to_mcp_result().unwrap_err();
+        assert!(error.message.contains("Statement 1 failed"));
+        // Each statement is reported separately
+        let result = res.to_statement_results().unwrap();
+        let results: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        let results = results.as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["statement"], 0);
+        assert_eq!(results[0]["status"], "OK");
+        assert_eq!(results[0]["result"], 1);
+        assert_eq!(results[1]["statement"], 1);
+        assert_eq!(results[1]["status"], "ERR");
+        assert!(results[1]["error"].as_str().unwrap().contains("boom"));
+        assert_eq!(results[1]["retryable"], false);
+        assert!(results[1].get("result").is_none());
+        assert_eq!(results[2]["status"], "OK");
+        assert_eq!(results[2]["result"], 2);
+        assert!(results[2]["time_ms"].is_number());
+    }
+
+    #[test]
+    fn test_truncate_depth_leaves_shallow_values() {
+        let mut value = serde_json::json!([{ "a": [1, 2], "b": "c" }]);
+        let expected = value.clone();
+        assert!(!truncate_depth(&mut value, 2));
+        assert_eq!(value, expected);
+        assert!(truncate_depth(&mut value, 1));
+        assert_eq!(
+            value,
+            serde_json::json!([{ "a": TRUNCATED_MARKER, "b": "c" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_select_results_are_returned_as_json() {
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        // SurrealQL requires a FROM clause, so select from a literal value
+        let res = execute_query(
+            &db,
+            0,
+            "SELECT 1 AS one FROM 1".to_string(),
+            None,
+            "test",
+            None,
+        )
+        .await;
+        let result = res.to_mcp_result().unwrap();
+        assert_eq!(result.content.len(), 1);
+        let text = &result.content[0].as_text().unwrap().text;
+        let values: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(values, serde_json::json!([[{ "one": 1 }]]));
+    }
+
+    #[tokio::test]
+    async fn test_values_without_json_representation_are_rendered() {
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        let res = execute_query(
+            &db,
+            0,
+            "RETURN { range: 1..3, id: person:john, at: d'2024-01-01T00:00:00Z' }".to_string(),
+            None,
+            "test",
+            None,
+        )
+        .await;
+        let result = res.to_mcp_result().unwrap();
+        let values: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(
+            values,
+            serde_json::json!([{
+                "range": "1..3",
+                "id": "person:john",
+                "at": "2024-01-01T00:00:00Z",
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_query_times_out() {
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        let res = execute_query(
+            &db,
+            0,
+            "SLEEP 2s".to_string(),
+            None,
+            "test",
+            Some(Duration::from_millis(50)),
+        )
+        .await;
+        assert!(res.duration < Duration::from_secs(2));
+        let error = res.to_mcp_result().unwrap_err();
+        assert_eq!(error.message, "Query timed out after 50ms");
+        assert_eq!(error.data, Some(serde_json::json!({ "retryable": true })));
+        // Queries which write data are not retryable after timing out
+        let res = execute_query(
+            &db,
+            1,
+            "CREATE person SET value = sleep(2s)".to_string(),
+            None,
+            "test",
+            Some(Duration::from_millis(50)),
+        )
+        .await;
+        let error = res.to_mcp_result().unwrap_err();
+        assert_eq!(error.message, "Query timed out after 50ms");
+        assert_eq!(error.data, Some(serde_json::json!({ "retryable": false })));
+        // Queries which complete within the timeout succeed
+        let res = execute_query(
+            &db,
+            1,
+            "RETURN 1".to_string(),
+            None,
+            "test",
+            Some(Duration::from_secs(5)),
+        )
+        .await;
+        assert!(res.to_mcp_result().is_ok());
+    }
+
+    #[test]
+    fn test_execute_query_records_read_and_write_metrics() {
+        let counters = crate::logs::Counters::default();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        metrics::with_local_recorder(&counters, || {
+            runtime.block_on(async {
+                let db = surrealdb::engine::any::connect("memory").await.unwrap();
+                db.use_ns("test").use_db("test").await.unwrap();
+                let query = "CREATE person:john".to_string();
+                execute_query(&db, 0, query, None, "test", None).await;
+                assert_eq!(counters.get("surrealmcp.read_queries"), 0);
+                assert_eq!(counters.get("surrealmcp.write_queries"), 1);
+                let query = "SELECT * FROM person; SELECT * FROM person:john".to_string();
+                execute_query(&db, 0, query, None, "test", None).await;
+                assert_eq!(counters.get("surrealmcp.read_queries"), 2);
+                assert_eq!(counters.get("surrealmcp.write_queries"), 1);
+            })
+        });
+    }
+}
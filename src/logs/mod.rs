@@ -1,9 +1,56 @@
 use metrics::{counter, gauge};
-use tracing::info;
-use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{Subscriber, info};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer, layer::SubscriberExt, util::SubscriberInitExt};
+
+mod sampling;
+
+pub use sampling::SamplingFilter;
+
+/// The format in which logs are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON object per line, for log aggregation
+    Json,
+}
+
+/// Create the layer which formats and writes logs to the given writer
+///
+/// In JSON format the fields of each event, such as the connection_id and
+/// query_id, are written as top-level keys of the JSON object.
+fn fmt_layer<S, W>(
+    format: LogFormat,
+    writer: W,
+    trace_sample_rate: f64,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_target(true)
+        .with_writer(writer);
+    match format {
+        LogFormat::Text => layer
+            .with_filter(SamplingFilter::new(trace_sample_rate))
+            .boxed(),
+        LogFormat::Json => layer
+            .json()
+            .flatten_event(true)
+            .with_filter(SamplingFilter::new(trace_sample_rate))
+            .boxed(),
+    }
+}
 
 /// Initialize structured logging and metrics collection
-pub fn init_logging_and_metrics(stdio: bool) {
+///
+/// The trace sample rate (between 0.0 and 1.0) controls the proportion of
+/// requests and queries which are logged. Warnings and errors are always logged.
+pub fn init_logging_and_metrics(stdio: bool, trace_sample_rate: f64, log_format: LogFormat) {
     // Check if we are running in stdio mode
     if stdio {
         // Set up environment filter for log levels
@@ -12,11 +59,7 @@ pub fn init_logging_and_metrics(stdio: bool) {
         // Initialize tracing subscriber with stderr output
         tracing_subscriber::registry()
             .with(filter)
-            .with(
-                tracing_subscriber::fmt::layer()
-                    .with_target(true)
-                    .with_writer(std::io::stderr),
-            )
+            .with(fmt_layer(log_format, std::io::stderr, trace_sample_rate))
             .init();
     } else {
         // Set up environment filter for log levels
@@ -25,11 +68,7 @@ pub fn init_logging_and_metrics(stdio: bool) {
         // Initialize tracing subscriber with stdout output
         tracing_subscriber::registry()
             .with(filter)
-            .with(
-                tracing_subscriber::fmt::layer()
-                    .with_target(true)
-                    .with_writer(std::io::stdout),
-            )
+            .with(fmt_layer(log_format, std::io::stdout, trace_sample_rate))
             .init();
     }
     // Output debugging information
@@ -38,13 +77,20 @@ pub fn init_logging_and_metrics(stdio: bool) {
     gauge!("surrealmcp.active_connections").set(0.0);
     counter!("surrealmcp.total_connections").absolute(0);
     counter!("surrealmcp.total_queries").absolute(0);
+    counter!("surrealmcp.read_queries").absolute(0);
+    counter!("surrealmcp.write_queries").absolute(0);
     // Error metrics - general
     counter!("surrealmcp.total_errors").absolute(0);
     // Error metrics - specific categories
     counter!("surrealmcp.total_query_errors").absolute(0);
+    counter!("surrealmcp.total_query_timeouts").absolute(0);
     counter!("surrealmcp.total_connection_errors").absolute(0);
     counter!("surrealmcp.total_configuration_errors").absolute(0);
     counter!("surrealmcp.total_rate_limit_errors").absolute(0);
+    counter!("surrealmcp.total_overload_errors").absolute(0);
+    counter!("surrealmcp.reconnects").absolute(0);
+    // Result metrics
+    counter!("surrealmcp.result_truncations").absolute(0);
     // Operation-specific error metrics
     counter!("surrealmcp.errors.connect_endpoint").absolute(0);
     counter!("surrealmcp.errors.use_namespace").absolute(0);
@@ -54,22 +100,67 @@ pub fn init_logging_and_metrics(stdio: bool) {
     counter!("surrealmcp.errors.list_databases").absolute(0);
     // Tool method call counters
     counter!("surrealmcp.tools.query").absolute(0);
+    counter!("surrealmcp.tools.compute").absolute(0);
+    counter!("surrealmcp.tools.run_function").absolute(0);
     counter!("surrealmcp.tools.select").absolute(0);
+    counter!("surrealmcp.tools.count").absolute(0);
+    counter!("surrealmcp.tools.vector_search").absolute(0);
+    counter!("surrealmcp.tools.full_text_search").absolute(0);
     counter!("surrealmcp.tools.insert").absolute(0);
     counter!("surrealmcp.tools.create").absolute(0);
     counter!("surrealmcp.tools.upsert").absolute(0);
     counter!("surrealmcp.tools.update").absolute(0);
     counter!("surrealmcp.tools.delete").absolute(0);
     counter!("surrealmcp.tools.relate").absolute(0);
+    counter!("surrealmcp.tools.get_edges").absolute(0);
+    counter!("surrealmcp.tools.traverse").absolute(0);
     counter!("surrealmcp.tools.connect_endpoint").absolute(0);
+    counter!("surrealmcp.tools.connect_cloud_instance").absolute(0);
+    counter!("surrealmcp.tools.list_cloud_regions").absolute(0);
+    counter!("surrealmcp.tools.list_cloud_backups").absolute(0);
+    counter!("surrealmcp.tools.restore_cloud_backup").absolute(0);
+    counter!("surrealmcp.tools.update_cloud_instance_storage").absolute(0);
+    counter!("surrealmcp.tools.update_cloud_instance_compute").absolute(0);
+    counter!("surrealmcp.tools.upgrade_cloud_instance").absolute(0);
     counter!("surrealmcp.tools.list_namespaces").absolute(0);
     counter!("surrealmcp.tools.list_databases").absolute(0);
     counter!("surrealmcp.tools.use_namespace").absolute(0);
     counter!("surrealmcp.tools.use_database").absolute(0);
     counter!("surrealmcp.tools.disconnect_endpoint").absolute(0);
+    counter!("surrealmcp.tools.current_context").absolute(0);
+    counter!("surrealmcp.tools.connection_info").absolute(0);
+    counter!("surrealmcp.tools.session_stats").absolute(0);
+    counter!("surrealmcp.tools.whoami").absolute(0);
+    counter!("surrealmcp.tools.save_connection_profile").absolute(0);
+    counter!("surrealmcp.tools.list_connection_profiles").absolute(0);
+    counter!("surrealmcp.tools.connect_profile").absolute(0);
+    counter!("surrealmcp.tools.maintenance").absolute(0);
+    counter!("surrealmcp.tools.reset_database").absolute(0);
+    counter!("surrealmcp.tools.export_to_file").absolute(0);
+    counter!("surrealmcp.tools.export").absolute(0);
+    counter!("surrealmcp.tools.import").absolute(0);
+    counter!("surrealmcp.tools.list_tables").absolute(0);
+    counter!("surrealmcp.tools.info").absolute(0);
+    counter!("surrealmcp.tools.dump_schema").absolute(0);
+    counter!("surrealmcp.tools.define_param").absolute(0);
+    counter!("surrealmcp.tools.define_user").absolute(0);
+    counter!("surrealmcp.tools.define_table").absolute(0);
+    counter!("surrealmcp.tools.define_index").absolute(0);
+    counter!("surrealmcp.tools.list_params").absolute(0);
+    counter!("surrealmcp.tools.show_changes").absolute(0);
+    counter!("surrealmcp.tools.preview_merge").absolute(0);
+    counter!("surrealmcp.tools.diff_records").absolute(0);
+    counter!("surrealmcp.tools.live_query").absolute(0);
+    counter!("surrealmcp.tools.poll_live_query").absolute(0);
+    counter!("surrealmcp.tools.kill_live_query").absolute(0);
+    counter!("surrealmcp.tools.begin_transaction").absolute(0);
+    counter!("surrealmcp.tools.commit_transaction").absolute(0);
+    counter!("surrealmcp.tools.cancel_transaction").absolute(0);
     counter!("surrealmcp.tools.list_cloud_organizations").absolute(0);
     counter!("surrealmcp.tools.list_cloud_instances").absolute(0);
     counter!("surrealmcp.tools.create_cloud_instance").absolute(0);
+    counter!("surrealmcp.tools.delete_cloud_instance").absolute(0);
+    counter!("surrealmcp.tools.get_cloud_instance_capabilities").absolute(0);
     counter!("surrealmcp.tools.pause_cloud_instance").absolute(0);
     counter!("surrealmcp.tools.resume_cloud_instance").absolute(0);
     counter!("surrealmcp.tools.get_cloud_instance_status").absolute(0);
@@ -77,3 +168,125 @@ pub fn init_logging_and_metrics(stdio: bool) {
     // Output debugging information
     info!("Metrics collection initialized");
 }
+
+/// A writer which captures log output in memory
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub(crate) struct Capture(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl Capture {
+    /// Get the captured log output
+    pub(crate) fn output(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for Capture {
+    type Writer = Capture;
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// A metrics recorder which stores counter values, for checking metrics in tests
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub(crate) struct Counters(
+    std::sync::Arc<
+        std::sync::Mutex<
+            std::collections::HashMap<String, std::sync::Arc<std::sync::atomic::AtomicU64>>,
+        >,
+    >,
+);
+
+#[cfg(test)]
+impl Counters {
+    /// Get the current value of a counter
+    pub(crate) fn get(&self, name: &str) -> u64 {
+        self.0
+            .lock()
+            .unwrap()
+            .get(name)
+            .map_or(0, |value| value.load(std::sync::atomic::Ordering::SeqCst))
+    }
+}
+
+#[cfg(test)]
+impl metrics::Recorder for Counters {
+    fn describe_counter(
+        &self,
+        _: metrics::KeyName,
+        _: Option<metrics::Unit>,
+        _: metrics::SharedString,
+    ) {
+    }
+    fn describe_gauge(
+        &self,
+        _: metrics::KeyName,
+        _: Option<metrics::Unit>,
+        _: metrics::SharedString,
+    ) {
+    }
+    fn describe_histogram(
+        &self,
+        _: metrics::KeyName,
+        _: Option<metrics::Unit>,
+        _: metrics::SharedString,
+    ) {
+    }
+    fn register_counter(&self, key: &metrics::Key, _: &metrics::Metadata<'_>) -> metrics::Counter {
+        let mut counters = self.0.lock().unwrap();
+        let value = counters.entry(key.name().to_string()).or_default();
+        metrics::Counter::from_arc(value.clone())
+    }
+    fn register_gauge(&self, _: &metrics::Key, _: &metrics::Metadata<'_>) -> metrics::Gauge {
+        metrics::Gauge::noop()
+    }
+    fn register_histogram(
+        &self,
+        _: &metrics::Key,
+        _: &metrics::Metadata<'_>,
+    ) -> metrics::Histogram {
+        metrics::Histogram::noop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_json_logs_have_structured_fields() {
+        let capture = Capture::default();
+        let subscriber =
+            tracing_subscriber::registry().with(fmt_layer(LogFormat::Json, capture.clone(), 1.0));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        crate::engine::execute_query(&db, 7, "RETURN 1".to_string(), None, "abc", None).await;
+        // Each log line is a JSON object with the event fields as keys
+        let output = capture.output();
+        let line = output
+            .lines()
+            .find(|line| line.contains("Query execution succeeded"))
+            .expect("expected a query log line");
+        let log: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(log["connection_id"], "abc");
+        assert_eq!(log["query_id"], 7);
+        assert_eq!(log["level"], "INFO");
+        assert_eq!(log["message"], "Query execution succeeded");
+    }
+}
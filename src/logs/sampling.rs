@@ -0,0 +1,127 @@
+use tracing::{Level, Metadata, Subscriber, span};
+use tracing_subscriber::layer::{Context, Filter};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Whether the events within a span have been sampled for logging
+struct Sampled(bool);
+
+/// A filter which samples a proportion of logs to reduce tracing overhead
+///
+/// Warnings and errors are always logged. For all other events, a sampling
+/// decision is made when a root span (such as an HTTP request) is created,
+/// and is inherited by all of its child spans and events, so that sampled
+/// requests are logged in full. Events outside of any span are sampled
+/// individually.
+#[derive(Clone, Debug)]
+pub struct SamplingFilter {
+    rate: f64,
+}
+
+impl SamplingFilter {
+    /// Create a new sampling filter with a sample rate between 0.0 and 1.0
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate: rate.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Randomly decide whether to sample, according to the sample rate
+    fn sample(&self) -> bool {
+        self.rate >= 1.0 || (self.rate > 0.0 && rand::random::<f64>() < self.rate)
+    }
+}
+
+impl<S> Filter<S> for SamplingFilter
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn enabled(&self, meta: &Metadata<'_>, cx: &Context<'_, S>) -> bool {
+        // Spans are always enabled so that sampling decisions can be stored
+        if meta.is_span() {
+            return true;
+        }
+        // Warnings and errors are always logged
+        if *meta.level() <= Level::WARN {
+            return true;
+        }
+        // Use the sampling decision of the current span, if any
+        match cx.lookup_current() {
+            Some(span) => span
+                .extensions()
+                .get::<Sampled>()
+                .is_none_or(|sampled| sampled.0),
+            None => self.sample(),
+        }
+    }
+
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            // Inherit the sampling decision of the parent span, or make a new decision
+            let sampled = span
+                .parent()
+                .and_then(|parent| parent.extensions().get::<Sampled>().map(|s| s.0))
+                .unwrap_or_else(|| self.sample());
+            span.extensions_mut().insert(Sampled(sampled));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::Capture;
+    use tracing_subscriber::{Layer, layer::SubscriberExt};
+
+    /// Run queries with the given sample rate, returning the captured logs
+    async fn capture_query_logs(rate: f64) -> String {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(capture.clone())
+                .with_ansi(false)
+                .with_filter(SamplingFilter::new(rate)),
+        );
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let db = surrealdb::engine::any::connect("memory").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        crate::engine::execute_query(&db, 0, "RETURN 1".to_string(), None, "test", None).await;
+        crate::engine::execute_query(&db, 1, "SELEC 1".to_string(), None, "test", None).await;
+        capture.output()
+    }
+
+    #[tokio::test]
+    async fn test_zero_sample_rate_only_logs_errors() {
+        let logs = capture_query_logs(0.0).await;
+        assert!(!logs.contains("Query execution succeeded"));
+        assert!(logs.contains("Query execution failed"));
+    }
+
+    #[tokio::test]
+    async fn test_full_sample_rate_logs_everything() {
+        let logs = capture_query_logs(1.0).await;
+        assert!(logs.contains("Query execution succeeded"));
+        assert!(logs.contains("Query execution failed"));
+    }
+
+    #[test]
+    fn test_sampling_decision_is_inherited_by_child_spans() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(capture.clone())
+                .with_ansi(false)
+                .with_filter(SamplingFilter::new(0.0)),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let request = tracing::info_span!("request");
+            let _request = request.enter();
+            let child = tracing::info_span!("child");
+            let _child = child.enter();
+            tracing::info!("sampled out");
+            tracing::warn!("always logged");
+        });
+        let logs = capture.output();
+        assert!(!logs.contains("sampled out"));
+        assert!(logs.contains("always logged"));
+    }
+}
@@ -38,10 +38,44 @@ async fn main() -> Result<()> {
             auth_disabled,
             rate_limit_rps,
             rate_limit_burst,
+            rate_limit_period,
+            max_in_flight_requests,
+            cors_allowed_origins,
+            cors_origin_regex,
+            tls_cert,
+            tls_key,
+            tls_client_ca,
             auth_server,
             auth_audience,
+            jwt_hmac_secret,
             cloud_access_token,
             cloud_refresh_token,
+            cloud_max_concurrency,
+            cloud_token_idle_timeout,
+            query_timeout_ms,
+            empty_string_as_none,
+            include_timing,
+            allow_reset,
+            export_dir,
+            allow_define_statements,
+            max_result_depth,
+            max_result_bytes,
+            allowed_functions,
+            denied_functions,
+            strict_functions,
+            require_where_for_mutations,
+            disable_cloud,
+            disable_query,
+            disable_prompts,
+            disable_resources,
+            read_only,
+            max_prompts,
+            max_resources,
+            max_response_items,
+            response_envelope,
+            max_message_bytes,
+            trace_sample_rate,
+            log_format,
         } => {
             // Create the server config
             let config = ServerConfig {
@@ -56,10 +90,44 @@ async fn main() -> Result<()> {
                 auth_disabled,
                 rate_limit_rps,
                 rate_limit_burst,
+                rate_limit_period,
+                max_in_flight_requests,
+                cors_allowed_origins,
+                cors_origin_regex,
+                tls_cert,
+                tls_key,
+                tls_client_ca,
                 auth_server,
                 auth_audience,
+                jwt_hmac_secret,
                 cloud_access_token,
                 cloud_refresh_token,
+                cloud_max_concurrency,
+                cloud_token_idle_timeout,
+                query_timeout_ms,
+                empty_string_as_none,
+                include_timing,
+                allow_reset,
+                export_dir,
+                allow_define_statements,
+                max_result_depth,
+                max_result_bytes,
+                allowed_functions,
+                denied_functions,
+                strict_functions,
+                require_where_for_mutations,
+                disable_cloud,
+                disable_query,
+                disable_prompts,
+                disable_resources,
+                read_only,
+                max_prompts,
+                max_resources,
+                max_response_items,
+                response_envelope,
+                max_message_bytes,
+                trace_sample_rate,
+                log_format,
             };
             server::start_server(config).await
         }
@@ -1,3 +1,4 @@
+use rmcp::ErrorData as McpError;
 use rmcp::model::{Prompt, PromptArgument, PromptMessage, PromptMessageRole};
 use serde_json::{Map, Value};
 
@@ -15,6 +16,11 @@ pub trait PromptGenerator {
     /// Get the prompt arguments
     fn arguments(&self) -> Vec<PromptArgument>;
 
+    /// Check that the provided arguments are valid for this prompt
+    fn validate(&self, _arguments: Option<&Map<String, Value>>) -> Result<(), McpError> {
+        Ok(())
+    }
+
     /// Generate the prompt messages based on the provided arguments
     fn generate(&self, arguments: Option<Map<String, Value>>) -> Vec<PromptMessage>;
 }
@@ -255,6 +261,98 @@ If details are missing, ask concise clarifying questions before executing risky
     }
 }
 
+/// Schema Migration Expert prompt
+pub struct SchemaMigrationExpert;
+
+impl PromptGenerator for SchemaMigrationExpert {
+    fn name(&self) -> &'static str {
+        "schema_migration_expert"
+    }
+
+    fn summary(&self) -> &'static str {
+        "Schema migration expert prompt"
+    }
+
+    fn description(&self) -> &'static str {
+        "An expert assistant for planning SurrealDB schema migrations with forward and rollback steps"
+    }
+
+    fn arguments(&self) -> Vec<PromptArgument> {
+        vec![
+            PromptArgument {
+                name: "current_schema".to_string(),
+                description: Some("The current schema, such as the output of INFO FOR DB or the existing DEFINE statements".to_string()),
+                required: Some(false),
+            },
+            PromptArgument {
+                name: "desired_change".to_string(),
+                description: Some("The change to make to the schema (e.g., add a required email field to user)".to_string()),
+                required: Some(true),
+            },
+            PromptArgument {
+                name: "strategy".to_string(),
+                description: Some("The migration strategy: online (while serving traffic) or offline (during downtime)".to_string()),
+                required: Some(false),
+            },
+        ]
+    }
+
+    fn validate(&self, arguments: Option<&Map<String, Value>>) -> Result<(), McpError> {
+        // Ensure that the strategy is one of the supported strategies
+        match arguments.and_then(|args| args.get("strategy")) {
+            None => Ok(()),
+            Some(Value::String(strategy)) if matches!(strategy.as_str(), "online" | "offline") => {
+                Ok(())
+            }
+            Some(strategy) => Err(McpError::invalid_params(
+                format!("Invalid migration strategy {strategy}, expected 'online' or 'offline'"),
+                None,
+            )),
+        }
+    }
+
+    fn generate(&self, arguments: Option<Map<String, Value>>) -> Vec<PromptMessage> {
+        // Get the arguments
+        let current_schema = arguments
+            .as_ref()
+            .and_then(|args| args.get("current_schema"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("not provided");
+        let desired_change = arguments
+            .as_ref()
+            .and_then(|args| args.get("desired_change"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("evolve the schema");
+        let strategy = arguments
+            .as_ref()
+            .and_then(|args| args.get("strategy"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("online");
+        // Describe the constraints of the migration strategy
+        let strategy_guidance = match strategy {
+            "offline" => {
+                "The migration runs offline during downtime, so it can rewrite existing records in a single transaction."
+            }
+            _ => {
+                "The migration runs online while the database serves traffic, so each step must stay compatible with existing queries: add new fields as optional or with a DEFAULT before backfilling, and only tighten ASSERT clauses or REMOVE old definitions once nothing depends on them."
+            }
+        };
+        // Return the prompt messages
+        vec![
+            PromptMessage::new_text(
+                PromptMessageRole::User,
+                format!(
+                    "You are a SurrealDB schema migration expert. Help me plan a migration to {desired_change}.\n\nCurrent schema:\n{current_schema}\n\nStrategy: {strategy}. {strategy_guidance}\n\nProvide a forward migration using DEFINE statements (with OVERWRITE or IF NOT EXISTS where appropriate) and any UPDATE statements needed to backfill existing records, wrapped in BEGIN TRANSACTION and COMMIT TRANSACTION. Then provide a rollback migration using REMOVE statements and DEFINE statements which restores the current schema."
+                ),
+            ),
+            PromptMessage::new_text(
+                PromptMessageRole::Assistant,
+                "I'll help you plan this schema migration. I'll review the current schema, then provide the forward SurrealQL migration with the DEFINE and backfill statements, followed by the rollback SurrealQL with the REMOVE and DEFINE statements which restore the current schema.".to_string(),
+            ),
+        ]
+    }
+}
+
 /// Registry of all available prompts
 pub struct PromptRegistry;
 
@@ -265,6 +363,7 @@ impl PromptRegistry {
             Box::new(DatabaseQueryAssistant),
             Box::new(DataModelingExpert),
             Box::new(SurrealQlGuide),
+            Box::new(SchemaMigrationExpert),
         ]
     }
 
@@ -289,21 +388,26 @@ pub fn list_prompts() -> Vec<Prompt> {
 }
 
 /// Get a specific prompt by name with arguments
+///
+/// Returns `None` if the prompt does not exist, or an error if the arguments
+/// are not valid for the prompt.
 pub fn get_prompt_with_arguments(
     name: &str,
     arguments: Option<Map<String, Value>>,
-) -> Option<(String, Vec<PromptMessage>)> {
+) -> Option<Result<(String, Vec<PromptMessage>), McpError>> {
     PromptRegistry::find_by_name(name).map(|generator| {
-        (
+        generator.validate(arguments.as_ref())?;
+        Ok((
             generator.summary().to_string(),
             generator.generate(arguments),
-        )
+        ))
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rmcp::model::PromptMessageContent;
 
     #[test]
     fn test_prompt_generator_trait() {
@@ -325,23 +429,33 @@ mod tests {
     #[test]
     fn test_prompt_registry() {
         let generators = PromptRegistry::get_generators();
-        assert_eq!(generators.len(), 3);
+        assert_eq!(generators.len(), 4);
 
         let db_generator = PromptRegistry::find_by_name("database_query_assistant");
         assert!(db_generator.is_some());
 
+        let guide_generator = PromptRegistry::find_by_name("surrealql_guide");
+        assert!(guide_generator.is_some());
+
         let unknown_generator = PromptRegistry::find_by_name("unknown_prompt");
         assert!(unknown_generator.is_none());
     }
 
     #[test]
-    fn test_get_available_prompts() {
+    fn test_list_prompts() {
         let prompts = list_prompts();
-        assert_eq!(prompts.len(), 3);
+        assert_eq!(prompts.len(), 4);
 
         let prompt_names: Vec<&str> = prompts.iter().map(|p| p.name.as_str()).collect();
         assert!(prompt_names.contains(&"database_query_assistant"));
         assert!(prompt_names.contains(&"data_modeling_expert"));
+        assert!(prompt_names.contains(&"surrealql_guide"));
+        assert!(prompt_names.contains(&"schema_migration_expert"));
+
+        for prompt in &prompts {
+            let arguments = prompt.arguments.as_ref().unwrap();
+            assert!(!arguments.is_empty(), "{} has no arguments", prompt.name);
+        }
     }
 
     #[test]
@@ -356,10 +470,66 @@ mod tests {
         let result = get_prompt_with_arguments("database_query_assistant", Some(args));
         assert!(result.is_some());
 
-        let (description, messages) = result.unwrap();
+        let (description, messages) = result.unwrap().unwrap();
         assert_eq!(description, "Database query assistant prompt");
         assert_eq!(messages.len(), 2);
         assert_eq!(messages[0].role, PromptMessageRole::User);
         assert_eq!(messages[1].role, PromptMessageRole::Assistant);
     }
+
+    #[test]
+    fn test_get_schema_migration_prompt() {
+        let mut args = Map::new();
+        args.insert(
+            "current_schema".to_string(),
+            Value::String("DEFINE TABLE user SCHEMAFULL;".to_string()),
+        );
+        args.insert(
+            "desired_change".to_string(),
+            Value::String("add a required email field to user".to_string()),
+        );
+        args.insert("strategy".to_string(), Value::String("offline".to_string()));
+
+        let result = get_prompt_with_arguments("schema_migration_expert", Some(args));
+        assert!(result.is_some());
+
+        let (description, messages) = result.unwrap().unwrap();
+        assert_eq!(description, "Schema migration expert prompt");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, PromptMessageRole::User);
+        assert_eq!(messages[1].role, PromptMessageRole::Assistant);
+
+        let PromptMessageContent::Text { text } = &messages[0].content else {
+            panic!("expected a text message");
+        };
+        assert!(text.contains("add a required email field to user"));
+        assert!(text.contains("DEFINE TABLE user SCHEMAFULL;"));
+        assert!(text.contains("runs offline"));
+        assert!(text.contains("DEFINE"));
+        assert!(text.contains("REMOVE"));
+    }
+
+    #[test]
+    fn test_schema_migration_prompt_rejects_unknown_strategy() {
+        let mut args = Map::new();
+        args.insert(
+            "desired_change".to_string(),
+            Value::String("add a required email field to user".to_string()),
+        );
+        // The strategy defaults to online when it is not specified
+        let result = get_prompt_with_arguments("schema_migration_expert", Some(args.clone()));
+        assert!(result.unwrap().is_ok());
+        // Only the online and offline strategies are supported
+        args.insert("strategy".to_string(), Value::String("online".to_string()));
+        let result = get_prompt_with_arguments("schema_migration_expert", Some(args.clone()));
+        assert!(result.unwrap().is_ok());
+        args.insert(
+            "strategy".to_string(),
+            Value::String("blue-green".to_string()),
+        );
+        let result = get_prompt_with_arguments("schema_migration_expert", Some(args));
+        let err = result.unwrap().unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("\"blue-green\""));
+    }
 }
@@ -31,6 +31,12 @@ const JWKS_ENDPOINT: &str = "https://auth.surrealdb.com/.well-known/jwks.json";
 /// JWKS cache duration (1 hour)
 const JWKS_CACHE_DURATION: Duration = Duration::from_secs(3600);
 
+/// JWKS background refresh interval (50 minutes), so the cache never expires
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(3000);
+
+/// Minimum age of the JWKS cache before an unknown key ID causes a refetch (30 seconds)
+const JWKS_MIN_REFETCH_AGE: Duration = Duration::from_secs(30);
+
 /// JWKS (JSON Web Key Set) structure
 #[derive(Debug, Serialize, Deserialize)]
 struct Jwks {
@@ -64,6 +70,7 @@ struct JwksKey {
 #[derive(Debug, Clone)]
 struct CachedJwks {
     keys: HashMap<String, JwksKey>,
+    fetched_at: SystemTime,
     expires_at: SystemTime,
 }
 
@@ -77,9 +84,11 @@ impl CachedJwks {
             store.insert(key.key_id.clone(), key);
         }
         // Create a new cached JWKS
+        let now = SystemTime::now();
         Self {
             keys: store,
-            expires_at: SystemTime::now() + JWKS_CACHE_DURATION,
+            fetched_at: now,
+            expires_at: now + JWKS_CACHE_DURATION,
         }
     }
 
@@ -88,6 +97,11 @@ impl CachedJwks {
         SystemTime::now() > self.expires_at
     }
 
+    /// Gets the time since the JWKS were fetched
+    fn age(&self) -> Duration {
+        self.fetched_at.elapsed().unwrap_or_default()
+    }
+
     /// Gets a JWK by key ID
     fn get_key(&self, kid: &str) -> Option<&JwksKey> {
         self.keys.get(kid)
@@ -99,6 +113,8 @@ impl CachedJwks {
 pub struct JwksManager {
     /// HTTP client for fetching JWKS
     client: reqwest::Client,
+    /// The URL which the JWKS are fetched from
+    endpoint: String,
     /// Temporary cache for JWKS
     cache: Arc<RwLock<Option<CachedJwks>>>,
 }
@@ -106,20 +122,44 @@ pub struct JwksManager {
 impl JwksManager {
     /// Create a new JWKS manager
     pub fn new() -> Self {
+        Self::with_endpoint(JWKS_ENDPOINT)
+    }
+
+    /// Create a new JWKS manager which fetches JWKS from a specific URL
+    pub fn with_endpoint(endpoint: impl Into<String>) -> Self {
         Self {
             client: reqwest::Client::new(),
+            endpoint: endpoint.into(),
             cache: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Spawn a task which periodically refreshes the JWKS cache in the background
+    ///
+    /// The JWKS are fetched immediately, and then before the cache would expire,
+    /// so that requests do not wait for the JWKS to be fetched. Failures are
+    /// logged, and the cache is then refreshed on demand when it expires.
+    pub fn spawn_refresh_task(&self) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(JWKS_REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = manager.refresh_jwks(Duration::ZERO).await {
+                    warn!("Failed to refresh JWKS in the background: {e}");
+                }
+            }
+        })
+    }
+
     /// Fetch JWKS from the authentication endpoint
     async fn fetch_jwks(&self) -> Result<Jwks, String> {
         // Output debugging information
-        debug!("Fetching JWKS from {JWKS_ENDPOINT}");
+        debug!("Fetching JWKS from {}", self.endpoint);
         // Fetch the JWKS from the endpoint
         let response = self
             .client
-            .get(JWKS_ENDPOINT)
+            .get(&self.endpoint)
             .send()
             .await
             .map_err(|e| format!("Failed to fetch JWKS: {e}"))?;
@@ -153,6 +193,26 @@ impl JwksManager {
         }
         // Fetch new JWKS
         debug!("JWK cache expired or missing, fetching new JWKS");
+        self.update_cache(&mut cache).await
+    }
+
+    /// Fetches JWKS, unless the cached JWKS were fetched within the minimum age
+    async fn refresh_jwks(&self, min_age: Duration) -> Result<CachedJwks, String> {
+        // Acquire a write lock on the cache
+        let mut cache = self.cache.write().await;
+        // Check if the cached JWKS were fetched recently
+        if let Some(cache) = cache.as_ref()
+            && cache.age() < min_age
+        {
+            return Ok(cache.clone());
+        }
+        // Fetch new JWKS
+        debug!("Refreshing JWK cache");
+        self.update_cache(&mut cache).await
+    }
+
+    /// Fetches JWKS and stores them in the locked cache
+    async fn update_cache(&self, cache: &mut Option<CachedJwks>) -> Result<CachedJwks, String> {
         // Fetch the updated JWKS
         let jwks = self.fetch_jwks().await?;
         // Create a new JWKS cache
@@ -166,9 +226,17 @@ impl JwksManager {
     }
 
     /// Get a decoding key for a specific key ID
+    ///
+    /// If the key ID is not in the cached JWKS, the keys may have been rotated,
+    /// so the JWKS are fetched again, unless they were fetched very recently.
     pub async fn get_decoding_key(&self, kid: &str) -> Result<DecodingKey, String> {
         // Get the cached JWKS
-        let cached_jwks = self.get_jwks().await?;
+        let mut cached_jwks = self.get_jwks().await?;
+        // Refetch the JWKS if the key ID is unknown
+        if cached_jwks.get_key(kid).is_none() {
+            debug!(kid = %kid, "Key ID not found in cached JWKS, refetching JWKS");
+            cached_jwks = self.refresh_jwks(JWKS_MIN_REFETCH_AGE).await?;
+        }
         // Get the specific JWK
         let jwk = cached_jwks
             .get_key(kid)
@@ -213,6 +281,8 @@ pub struct TokenValidationConfig {
     pub expected_audience: String,
     /// Public key for JWT validation
     pub jwt_public_key: Option<String>,
+    /// Shared secret for HMAC signed JWT validation
+    pub jwt_shared_secret: Option<String>,
     /// Whether to validate token expiration
     pub validate_expiration: bool,
     /// Whether to validate token issued at
@@ -229,6 +299,7 @@ impl Default for TokenValidationConfig {
             expected_issuer: EXPECTED_ISSUER.to_string(),
             expected_audience: EXPECTED_AUDIENCE.to_string(),
             jwt_public_key: None,
+            jwt_shared_secret: None,
             validate_expiration: true,
             validate_issued_at: true,
             clock_skew_seconds: 300, // 5 minutes
@@ -246,17 +317,8 @@ struct JweHeader {
     enc: String,
     /// The issuer of the token
     iss: String,
-}
-
-/// JWT header structure
-#[derive(Debug, Serialize, Deserialize)]
-struct JwtHeader {
-    /// The algorithm used to sign the token
-    alg: String,
-    /// The key ID of the token
-    kid: Option<String>,
-    /// The type of the token
-    typ: Option<String>,
+    /// The audience of the token, if replicated in the header
+    aud: Option<String>,
 }
 
 /// Token claims structure for both JWE and JWT tokens
@@ -272,8 +334,32 @@ struct TokenClaims {
     iat: Option<u64>,
     /// The subject of the token
     sub: Option<String>,
+    /// The space-separated scopes granted to the token
+    scope: Option<String>,
+    /// The email address of the subject
+    email: Option<String>,
 }
 
+/// The identity of the subject of a validated bearer token
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TokenIdentity {
+    /// The subject of the token
+    pub subject: Option<String>,
+    /// The email address of the subject, if included in the token
+    pub email: Option<String>,
+    /// The issuer of the token
+    pub issuer: String,
+    /// The expiration time of the token, in seconds since the Unix epoch
+    pub expires_at: Option<u64>,
+}
+
+/// The scopes granted to a validated bearer token
+///
+/// This is only stored in the request extensions when the token has a scope
+/// claim. Tokens without a scope claim are not restricted to any scopes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenScopes(pub Vec<String>);
+
 /// Validate a JWE token header from SurrealDB auth service
 ///
 /// This function validates the JWE token header structure and issuer.
@@ -320,21 +406,41 @@ async fn validate_jwe_token(
     // Create the default claims
     let claims = TokenClaims {
         iss: header.iss,
-        aud: None,
+        aud: header.aud,
         exp: None,
         iat: None,
         sub: None,
+        scope: None,
+        email: None,
     };
+    // Validate the audience, when present
+    validate_audience(&claims, config)?;
     // Output debugging information
     debug!(
         token = %token,
         issuer = %claims.iss,
+        audience = ?claims.aud,
         "JWE token validated successfully"
     );
     // Return the claims
     Ok(claims)
 }
 
+/// Validate the audience claim of a token, when present
+///
+/// JWT tokens have their audience validated when decoded, but the claims of
+/// JWE tokens are validated separately, so they are checked against the same
+/// expected audience here.
+fn validate_audience(claims: &TokenClaims, config: &TokenValidationConfig) -> Result<(), String> {
+    match &claims.aud {
+        Some(aud) if *aud != config.expected_audience => Err(format!(
+            "Invalid audience: expected {}, got {aud}",
+            config.expected_audience
+        )),
+        _ => Ok(()),
+    }
+}
+
 /// Validate a standard JWT token using JWKS
 ///
 /// This function validates JWT tokens using the jsonwebtoken crate and JWKS.
@@ -356,7 +462,14 @@ async fn validate_jwt_token(
     validation.validate_aud = true;
     validation.validate_exp = true;
     // Get the decoding key
-    let key = if let Some(jwks_manager) = &config.jwks_manager {
+    let key = if let Some(secret) = &config.jwt_shared_secret
+        && matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+        // Use the shared secret for HMAC signed tokens
+        DecodingKey::from_secret(secret.as_bytes())
+    } else if let Some(jwks_manager) = &config.jwks_manager {
         // Get the key ID from the header
         let kid = header
             .kid
@@ -471,7 +584,8 @@ async fn validate_bearer_token(
 /// 1. Allows access to /.well-known/ and /health endpoints without authentication
 /// 2. Extracts the Bearer token from the Authorization header
 /// 3. Validates the token structure, issuer, and claims (where available)
-/// 4. Stores the validated token in the context extensions for use by subsequent services
+/// 4. Stores the validated token, its identity, and any scopes it grants, in the context
+///    extensions for use by subsequent services
 /// 5. Returns 401 Unauthorized with proper WWW-Authenticate header if validation fails
 ///
 /// Security considerations:
@@ -488,8 +602,8 @@ pub async fn require_bearer_auth(
 ) -> Result<Response, StatusCode> {
     // Get the current request path
     let path = req.uri().path();
-    // Allow access to auth metadata and health check endpoint
-    if path.starts_with("/.well-known/") || path == "/health" {
+    // Allow access to auth metadata, health check, and service descriptor endpoints
+    if path.starts_with("/.well-known/") || matches!(path, "/health" | "/health/live" | "/") {
         return Ok(next.run(req).await);
     }
     // Extract the bearer token from the Authorization header
@@ -509,10 +623,23 @@ pub async fn require_bearer_auth(
                     subject = claims.sub.as_deref().unwrap_or("unknown"),
                     expiration = ?claims.exp,
                     issued_at = ?claims.iat,
+                    scope = ?claims.scope,
                     "Bearer token validated successfully"
                 );
                 // Store the token on the request context
                 req.extensions_mut().insert(token);
+                // Store the granted scopes on the request context
+                if let Some(scope) = &claims.scope {
+                    let scopes = scope.split_whitespace().map(String::from).collect();
+                    req.extensions_mut().insert(TokenScopes(scopes));
+                }
+                // Store the authenticated identity on the request context
+                req.extensions_mut().insert(TokenIdentity {
+                    subject: claims.sub,
+                    email: claims.email,
+                    issuer: claims.iss,
+                    expires_at: claims.exp,
+                });
                 // Continue to the next middleware
                 return Ok(next.run(req).await);
             }
@@ -583,6 +710,48 @@ mod tests {
         assert_eq!(claims.iss, EXPECTED_ISSUER);
     }
 
+    #[tokio::test]
+    async fn test_jwe_token_with_wrong_audience() {
+        let header = |aud: &str| {
+            URL_SAFE_NO_PAD.encode(
+                serde_json::json!({
+                    "alg": "dir",
+                    "enc": "A256GCM",
+                    "iss": EXPECTED_ISSUER,
+                    "aud": aud,
+                })
+                .to_string(),
+            )
+        };
+        let config = TokenValidationConfig::default();
+        // A token minted for a different audience is rejected
+        let token = format!(
+            "{}..iv.ciphertext.tag",
+            header("https://other.example.com/")
+        );
+        let result = validate_jwe_token(&token, &config).await;
+        assert!(result.unwrap_err().starts_with("Invalid audience"));
+        // A token minted for the expected audience is accepted
+        let token = format!("{}..iv.ciphertext.tag", header(EXPECTED_AUDIENCE));
+        let claims = validate_jwe_token(&token, &config).await.unwrap();
+        assert_eq!(claims.aud.as_deref(), Some(EXPECTED_AUDIENCE));
+        // A decrypted claim set with a different audience is rejected
+        let claims = TokenClaims {
+            iss: EXPECTED_ISSUER.to_string(),
+            aud: Some("https://other.example.com/".to_string()),
+            exp: None,
+            iat: None,
+            sub: None,
+            scope: None,
+            email: None,
+        };
+        let err = validate_audience(&claims, &config).unwrap_err();
+        assert_eq!(
+            err,
+            "Invalid audience: expected https://mcp.surrealdb.com/, got https://other.example.com/"
+        );
+    }
+
     #[tokio::test]
     async fn test_jwe_token_without_decryption_key() {
         let token = "eyJhbGciOiJkaXIiLCJlbmMiOiJBMjU2R0NNIiwiaXNzIjoiaHR0cHM6Ly9hdXRoLnN1cnJlYWxkYi5jb20vIn0..i2Rd5nBEMkJSz6dC.KWp44r7imTAq0nOEXYGC6J4ABuaLFt_4EKFYIUEjN7sNB98aiRatF7nfoopZUqVsp4OWHA1AtnBL8FNuIeHZwH1WthdhAb3P4cbE-KvgrfS3RFyRCXqX9tqzxF9K3wTAvAnI3Lyp510jt9k3ytNKycfJi1mlXKw-WpU8WfqlgKRVd4QkWAn_OKMjfOZDgcCfiKxoHY5FYF77KymTQfQbauKjt4kpLFuFsJf5MleplV5T6cOy-ehJSbfsOUVeRNSeMdkZ4eLLG_vvTNJB.lJop5ReVf6pWw5rb_E5ILg";
@@ -610,6 +779,7 @@ mod tests {
             expected_issuer: "https://custom.issuer.com/".to_string(),
             expected_audience: "https://custom.audience.com/".to_string(),
             jwt_public_key: None,
+            jwt_shared_secret: None,
             validate_expiration: false,
             validate_issued_at: false,
             clock_skew_seconds: 600,
@@ -646,6 +816,122 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_middleware_stores_token_scopes() {
+        let app = Router::new()
+            .route(
+                "/test",
+                get(|req: Request<Body>| async move {
+                    match req.extensions().get::<TokenScopes>() {
+                        Some(scopes) => scopes.0.join(","),
+                        None => "unrestricted".to_string(),
+                    }
+                }),
+            )
+            .layer(axum::middleware::from_fn(|req, next| {
+                let config = TokenValidationConfig {
+                    jwks_manager: None,
+                    ..Default::default()
+                };
+                require_bearer_auth(config, req, next)
+            }));
+        // Sign tokens with the key used when no JWKS or public key is configured
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let token = |scope: Option<&str>| {
+            let claims = TokenClaims {
+                iss: EXPECTED_ISSUER.to_string(),
+                aud: Some(EXPECTED_AUDIENCE.to_string()),
+                exp: Some(now + 60),
+                iat: Some(now),
+                sub: Some("user".to_string()),
+                scope: scope.map(String::from),
+                email: None,
+            };
+            jsonwebtoken::encode(
+                &jsonwebtoken::Header::default(),
+                &claims,
+                &jsonwebtoken::EncodingKey::from_secret(b"dummy-key"),
+            )
+            .unwrap()
+        };
+
+        for (scope, expected) in [
+            (Some("db:read  db:write"), "db:read,db:write"),
+            (Some("db:read"), "db:read"),
+            (None, "unrestricted"),
+        ] {
+            let request = Request::builder()
+                .uri("/test")
+                .header("Authorization", format!("Bearer {}", token(scope)))
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hmac_signed_jwt_token() {
+        let config = TokenValidationConfig {
+            jwt_shared_secret: Some("local-secret".to_string()),
+            ..Default::default()
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let token = |secret: &[u8], aud: &str, exp: u64| {
+            let claims = TokenClaims {
+                iss: EXPECTED_ISSUER.to_string(),
+                aud: Some(aud.to_string()),
+                exp: Some(exp),
+                iat: Some(now),
+                sub: Some("developer".to_string()),
+                scope: None,
+                email: None,
+            };
+            jsonwebtoken::encode(
+                &jsonwebtoken::Header::new(Algorithm::HS256),
+                &claims,
+                &jsonwebtoken::EncodingKey::from_secret(secret),
+            )
+            .unwrap()
+        };
+        // A token signed with the shared secret is accepted
+        let valid = token(b"local-secret", EXPECTED_AUDIENCE, now + 60);
+        let claims = validate_bearer_token(&valid, &config).await.unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("developer"));
+        // The signature, audience, and expiration are still enforced
+        for invalid in [
+            token(b"other-secret", EXPECTED_AUDIENCE, now + 60),
+            token(b"local-secret", "https://other.example.com/", now + 60),
+            token(b"local-secret", EXPECTED_AUDIENCE, now - 3600),
+        ] {
+            assert!(validate_bearer_token(&invalid, &config).await.is_err());
+        }
+        // The token is accepted by the middleware end-to-end
+        let app =
+            Router::new()
+                .route("/test", get(|| async { "OK" }))
+                .layer(axum::middleware::from_fn(move |req, next| {
+                    require_bearer_auth(config.clone(), req, next)
+                }));
+        let request = Request::builder()
+            .uri("/test")
+            .header("Authorization", format!("Bearer {valid}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
     #[tokio::test]
     async fn test_middleware_with_invalid_token() {
         let app =
@@ -735,6 +1021,68 @@ mod tests {
         assert!(key.is_none());
     }
 
+    #[tokio::test]
+    async fn test_rotated_key_id_triggers_one_refetch() {
+        use std::sync::Mutex;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        // Serve a JWKS containing a single key, counting each fetch
+        let key = |kid: &str| JwksKey {
+            key_type: "RSA".to_string(),
+            key_id: kid.to_string(),
+            key_use: Some("sig".to_string()),
+            algorithm: Some("RS256".to_string()),
+            modulus: Some("AQAB".to_string()),
+            exponent: Some("AQAB".to_string()),
+            x_coordinate: None,
+            y_coordinate: None,
+            curve: None,
+        };
+        let keys = Arc::new(Mutex::new(vec![key("key-1")]));
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let app = Router::new().route(
+            "/jwks.json",
+            get({
+                let keys = keys.clone();
+                let fetches = fetches.clone();
+                move || async move {
+                    fetches.fetch_add(1, Ordering::SeqCst);
+                    let keys = keys.lock().unwrap().clone();
+                    axum::Json(Jwks { keys })
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let manager = JwksManager::with_endpoint(format!("http://{addr}/jwks.json"));
+        // The first lookup fetches the JWKS
+        assert!(manager.get_decoding_key("key-1").await.is_ok());
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        // The key is rotated once the cached JWKS are no longer recent
+        *keys.lock().unwrap() = vec![key("key-2")];
+        if let Some(cache) = manager.cache.write().await.as_mut() {
+            cache.fetched_at -= JWKS_MIN_REFETCH_AGE;
+        }
+        // The rotated key ID triggers exactly one refetch
+        assert!(manager.get_decoding_key("key-2").await.is_ok());
+        assert!(manager.get_decoding_key("key-2").await.is_ok());
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        // Unknown key IDs do not refetch the recently fetched JWKS
+        let err = manager.get_decoding_key("key-3").await.err().unwrap();
+        assert_eq!(err, "Key ID 'key-3' not found in JWKS");
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        // The background refresh task fetches the JWKS immediately
+        let task = manager.spawn_refresh_task();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while fetches.load(Ordering::SeqCst) < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        task.abort();
+    }
+
     #[tokio::test]
     async fn test_jwks_fetching() {
         let manager = JwksManager::new();
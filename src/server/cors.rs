@@ -0,0 +1,125 @@
+use anyhow::{Result, anyhow};
+use axum::http::{HeaderValue, Method, header};
+use regex::Regex;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::debug;
+
+/// Create the CORS layer used for the /.well-known endpoints
+///
+/// When neither an exact origin allowlist nor an origin regex is configured,
+/// all origins are allowed. Otherwise an origin is allowed if it is present in
+/// the exact allowlist, or if it matches the origin regex in its entirety.
+pub fn create_cors_layer(
+    allowed_origins: &[String],
+    origin_regex: Option<&str>,
+) -> Result<CorsLayer> {
+    // Output debugging information
+    debug!(
+        allowed_origins = ?allowed_origins,
+        origin_regex = origin_regex,
+        "Configuring the HTTP CORS layer"
+    );
+    // Compile and validate the origin regex, anchoring it to the whole origin
+    let origin_regex = match origin_regex {
+        Some(pattern) => Some(
+            Regex::new(&format!("^(?:{pattern})$"))
+                .map_err(|e| anyhow!("Invalid CORS origin regex '{pattern}': {e}"))?,
+        ),
+        None => None,
+    };
+    // Determine which origins are allowed
+    let allow_origin = if allowed_origins.is_empty() && origin_regex.is_none() {
+        AllowOrigin::from(Any)
+    } else {
+        let allowed_origins = allowed_origins.to_vec();
+        AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+            let Ok(origin) = origin.to_str() else {
+                return false;
+            };
+            allowed_origins.iter().any(|allowed| allowed == origin)
+                || origin_regex
+                    .as_ref()
+                    .is_some_and(|regex| regex.is_match(origin))
+        })
+    };
+    // Return the CORS layer
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::OPTIONS])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+        .allow_credentials(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    async fn request_with_origin(layer: CorsLayer, origin: &str) -> Option<HeaderValue> {
+        let app = Router::new()
+            .route("/test", get(|| async { "ok" }))
+            .layer(layer);
+        let request = Request::builder()
+            .uri("/test")
+            .header(header::ORIGIN, origin)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .cloned()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_any_origin_by_default() {
+        let layer = create_cors_layer(&[], None).unwrap();
+        let allowed = request_with_origin(layer, "https://example.com").await;
+        assert_eq!(allowed.unwrap(), "*");
+    }
+
+    #[tokio::test]
+    async fn test_cors_regex_allows_matching_origin() {
+        let layer = create_cors_layer(&[], Some(r"https://[a-z0-9-]+\.example\.com")).unwrap();
+        let allowed = request_with_origin(layer, "https://preview-42.example.com").await;
+        assert_eq!(allowed.unwrap(), "https://preview-42.example.com");
+    }
+
+    #[tokio::test]
+    async fn test_cors_regex_blocks_non_matching_origin() {
+        let layer = create_cors_layer(&[], Some(r"https://[a-z0-9-]+\.example\.com")).unwrap();
+        let allowed = request_with_origin(layer, "https://example.com.evil.com").await;
+        assert!(allowed.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cors_exact_origins_combined_with_regex() {
+        let origins = vec!["https://app.surrealdb.com".to_string()];
+        let layer = create_cors_layer(&origins, Some(r"https://[a-z]+\.example\.com")).unwrap();
+        let allowed = request_with_origin(layer.clone(), "https://app.surrealdb.com").await;
+        assert_eq!(allowed.unwrap(), "https://app.surrealdb.com");
+        let allowed = request_with_origin(layer.clone(), "https://docs.example.com").await;
+        assert_eq!(allowed.unwrap(), "https://docs.example.com");
+        let allowed = request_with_origin(layer, "https://other.surrealdb.com").await;
+        assert!(allowed.is_none());
+    }
+
+    #[test]
+    fn test_cors_invalid_regex_is_rejected() {
+        let result = create_cors_layer(&[], Some("https://(unclosed"));
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid CORS origin regex")
+        );
+    }
+}
@@ -1,6 +1,222 @@
+use axum::Json;
+use axum::extract::State;
 use axum::http::StatusCode;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use surrealdb::{Surreal, engine::any::Any};
+use tokio::sync::Mutex;
+use tracing::warn;
 
-/// Health check endpoint for load balancer health status checking
-pub async fn health() -> StatusCode {
+use crate::db;
+
+/// The maximum duration to wait for each step of a readiness check
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The duration for which a failed readiness check is reported without retrying
+const HEALTH_FAILURE_CACHE: Duration = Duration::from_secs(5);
+
+/// Check whether an endpoint is a remote server, rather than an embedded datastore
+///
+/// Embedded datastores can not be checked with a separate connection, as a
+/// new `memory` connection creates a new, empty datastore, and `rocksdb` and
+/// `surrealkv` connections would hold the lock on the datastore files.
+fn is_remote_endpoint(endpoint: &str) -> bool {
+    matches!(
+        endpoint.split_once("://").map(|(scheme, _)| scheme),
+        Some("ws" | "wss" | "http" | "https")
+    )
+}
+
+/// The state used to check that the configured SurrealDB endpoint is reachable
+#[derive(Clone, Default)]
+pub struct HealthState {
+    /// The SurrealDB endpoint configured at server startup, if any
+    endpoint: Option<String>,
+    /// The SurrealDB username configured at server startup, if any
+    user: Option<String>,
+    /// The SurrealDB password configured at server startup, if any
+    pass: Option<String>,
+    /// The connection used for readiness checks, created when first needed
+    connection: Arc<Mutex<Option<Surreal<Any>>>>,
+    /// The time and error of the last failed readiness check, if any
+    failure: Arc<Mutex<Option<(Instant, String)>>>,
+}
+
+impl HealthState {
+    /// Create the health state for the configured startup endpoint
+    pub fn new(endpoint: Option<String>, user: Option<String>, pass: Option<String>) -> Self {
+        Self {
+            endpoint,
+            user,
+            pass,
+            connection: Arc::new(Mutex::new(None)),
+            failure: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Check that the configured endpoint can run a trivial query
+    ///
+    /// Only remote endpoints are checked. A failed check is reported again for
+    /// a short time, so that requests do not repeatedly wait to connect while
+    /// the database is unavailable.
+    async fn check(&self) -> Result<(), String> {
+        // The server is ready when no remote startup endpoint is configured
+        let Some(endpoint) = self.endpoint.as_deref().filter(|e| is_remote_endpoint(e)) else {
+            return Ok(());
+        };
+        // Report a recent failure without connecting again
+        let mut failure = self.failure.lock().await;
+        if let Some((at, error)) = failure.as_ref()
+            && at.elapsed() < HEALTH_FAILURE_CACHE
+        {
+            return Err(error.clone());
+        }
+        let result = self.query(endpoint).await;
+        *failure = result.as_ref().err().map(|e| (Instant::now(), e.clone()));
+        result
+    }
+
+    /// Run a trivial query against the endpoint, connecting if necessary
+    async fn query(&self, endpoint: &str) -> Result<(), String> {
+        // Connect to the endpoint if not already connected
+        let mut connection = self.connection.lock().await;
+        if connection.is_none() {
+            let user = self.user.as_deref();
+            let pass = self.pass.as_deref();
+            let instance = tokio::time::timeout(
+                HEALTH_CHECK_TIMEOUT,
+                db::create_client_connection(endpoint, user, pass, None, None),
+            )
+            .await
+            .map_err(|_| "Timed out connecting to the database".to_string())?
+            .map_err(|e| format!("Failed to connect to the database: {e}"))?;
+            *connection = Some(instance);
+        }
+        // Run a trivial query against the endpoint
+        let instance = connection.as_ref().unwrap();
+        let result = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, instance.query("RETURN 1")).await;
+        match result {
+            Ok(Ok(response)) => response.check().map(|_| ()).map_err(|e| e.to_string()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err("Timed out querying the database".to_string()),
+        }
+        .inspect_err(|_| {
+            // Reconnect on the next check
+            *connection = None;
+        })
+    }
+}
+
+/// Readiness check endpoint for load balancer health status checking
+///
+/// When a SurrealDB endpoint is configured at startup, this responds with a
+/// 503 if the endpoint can not run a trivial query.
+pub async fn health(State(state): State<HealthState>) -> StatusCode {
+    match state.check().await {
+        Ok(()) => StatusCode::OK,
+        Err(e) => {
+            warn!(error = %e, "Health check failed");
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+    }
+}
+
+/// Liveness check endpoint, which responds as long as the server is running
+pub async fn live() -> StatusCode {
     StatusCode::OK
 }
+
+/// Root endpoint describing the service and its available endpoints
+pub async fn root() -> Json<Value> {
+    Json(json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "description": "SurrealDB MCP Server",
+        "version": env!("CARGO_PKG_VERSION"),
+        "endpoints": {
+            "mcp": "/mcp",
+            "health": "/health",
+            "health_live": "/health/live",
+            "oauth_protected_resource": "/.well-known/oauth-protected-resource",
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::Request, routing::get};
+    use tower::ServiceExt;
+
+    fn health_app(state: HealthState) -> Router {
+        Router::new()
+            .route("/health", get(health))
+            .route("/health/live", get(live))
+            .with_state(state)
+    }
+
+    async fn status(app: &Router, uri: &str) -> StatusCode {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_health_without_endpoint() {
+        let app = health_app(HealthState::default());
+        assert_eq!(status(&app, "/health").await, StatusCode::OK);
+        assert_eq!(status(&app, "/health/live").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_health_with_embedded_endpoint() {
+        // Embedded endpoints are not checked with a separate connection
+        let state = HealthState::new(Some("memory".to_string()), None, None);
+        let app = health_app(state.clone());
+        assert_eq!(status(&app, "/health").await, StatusCode::OK);
+        assert!(state.connection.lock().await.is_none());
+        assert!(!is_remote_endpoint("rocksdb:/data/mydb"));
+        assert!(!is_remote_endpoint("surrealkv://data/mydb"));
+        assert!(is_remote_endpoint("wss://cloud.surrealdb.com"));
+    }
+
+    #[tokio::test]
+    async fn test_health_with_unreachable_endpoint() {
+        // Bind and drop a listener to find a port with nothing listening
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let endpoint = format!("ws://127.0.0.1:{port}");
+        let state = HealthState::new(Some(endpoint), None, None);
+        let app = health_app(state.clone());
+        assert_eq!(
+            status(&app, "/health").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        // The failure is reported again without connecting
+        let cached = state.failure.lock().await.clone().unwrap();
+        assert_eq!(
+            status(&app, "/health").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(state.failure.lock().await.clone(), Some(cached));
+        // Liveness is unaffected by the database
+        assert_eq!(status(&app, "/health/live").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_root_returns_service_descriptor() {
+        let app = Router::new().route("/", get(root));
+        let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let descriptor: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(descriptor["name"], "surrealmcp");
+        assert_eq!(descriptor["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(descriptor["endpoints"]["mcp"], "/mcp");
+        assert_eq!(descriptor["endpoints"]["health"], "/health");
+        assert_eq!(descriptor["endpoints"]["health_live"], "/health/live");
+    }
+}
@@ -1,6 +1,10 @@
 mod auth;
+mod cors;
 mod http;
 mod limit;
 mod start;
+mod tls;
 
+pub use auth::{TokenIdentity, TokenScopes};
+pub use limit::RateLimitPeriod;
 pub use start::{ServerConfig, start_server};
@@ -6,22 +6,29 @@ use rmcp::transport::{
     streamable_http_server::{session::local::LocalSessionManager, tower::StreamableHttpService},
 };
 use serde_json::json;
-use std::path::Path;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::net::{TcpListener, UnixListener};
 use tokio::signal;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tokio::sync::Semaphore;
+use tower_http::trace::TraceLayer;
 use tracing::{debug, error, info, warn};
 
-use crate::logs::init_logging_and_metrics;
+use crate::cloud;
+use crate::logs::{LogFormat, init_logging_and_metrics};
+use crate::prompts;
+use crate::resources;
 use crate::server::auth::{TokenValidationConfig, require_bearer_auth};
-use crate::server::http::health;
-use crate::server::limit::create_rate_limit_layer;
-use crate::tools::SurrealService;
-use crate::utils::{format_duration, generate_connection_id};
+use crate::server::cors::create_cors_layer;
+use crate::server::http::{HealthState, health, live, root};
+use crate::server::limit::{RateLimitPeriod, create_rate_limit_layer, limit_in_flight_requests};
+use crate::server::tls::{TlsClient, load_tls_config, tls_server};
+use crate::tools::{ServiceOptions, SurrealService};
+use crate::utils::{format_duration, generate_connection_id, limit_registered};
 
 /// Configuration for server startup
 #[derive(Clone)]
@@ -37,10 +44,78 @@ pub struct ServerConfig {
     pub auth_disabled: bool,
     pub rate_limit_rps: u32,
     pub rate_limit_burst: u32,
+    pub rate_limit_period: Option<RateLimitPeriod>,
+    pub max_in_flight_requests: usize,
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_origin_regex: Option<String>,
+    pub tls_cert: Option<String>,
+    pub tls_key: Option<String>,
+    pub tls_client_ca: Option<String>,
     pub auth_server: String,
     pub auth_audience: String,
+    pub jwt_hmac_secret: Option<String>,
     pub cloud_access_token: Option<String>,
     pub cloud_refresh_token: Option<String>,
+    pub cloud_max_concurrency: usize,
+    pub cloud_token_idle_timeout: u64,
+    pub query_timeout_ms: u64,
+    pub empty_string_as_none: bool,
+    pub include_timing: bool,
+    pub allow_reset: bool,
+    pub export_dir: Option<String>,
+    pub allow_define_statements: bool,
+    pub max_result_depth: usize,
+    pub max_result_bytes: usize,
+    pub allowed_functions: Vec<String>,
+    pub denied_functions: Vec<String>,
+    pub strict_functions: bool,
+    pub require_where_for_mutations: bool,
+    pub disable_cloud: bool,
+    pub disable_query: bool,
+    pub disable_prompts: bool,
+    pub disable_resources: bool,
+    pub read_only: bool,
+    pub max_prompts: usize,
+    pub max_resources: usize,
+    pub max_response_items: usize,
+    pub response_envelope: bool,
+    pub max_message_bytes: usize,
+    pub trace_sample_rate: f64,
+    /// The format of the log output
+    pub log_format: LogFormat,
+}
+
+impl ServerConfig {
+    /// Get the options which control the behaviour of each client session
+    fn service_options(&self) -> ServiceOptions {
+        ServiceOptions {
+            include_timing: self.include_timing,
+            empty_string_as_none: self.empty_string_as_none,
+            query_timeout: Some(Duration::from_millis(self.query_timeout_ms))
+                .filter(|timeout| !timeout.is_zero()),
+            allow_reset: self.allow_reset,
+            export_dir: self.export_dir.as_ref().map(PathBuf::from),
+            allow_define_statements: self.allow_define_statements,
+            max_result_depth: Some(self.max_result_depth).filter(|depth| *depth > 0),
+            max_result_bytes: Some(self.max_result_bytes).filter(|max| *max > 0),
+            allowed_functions: self.allowed_functions.clone(),
+            denied_functions: self.denied_functions.clone(),
+            strict_functions: self.strict_functions,
+            require_where_for_mutations: self.require_where_for_mutations,
+            disable_cloud: self.disable_cloud,
+            disable_query: self.disable_query,
+            disable_prompts: self.disable_prompts,
+            disable_resources: self.disable_resources,
+            read_only: self.read_only,
+            max_prompts: Some(self.max_prompts).filter(|max| *max > 0),
+            max_resources: Some(self.max_resources).filter(|max| *max > 0),
+            max_response_items: Some(self.max_response_items).filter(|max| *max > 0),
+            cloud_token_idle_timeout: Some(Duration::from_secs(self.cloud_token_idle_timeout))
+                .filter(|timeout| !timeout.is_zero()),
+            response_envelope: self.response_envelope,
+            max_message_bytes: Some(self.max_message_bytes).filter(|max| *max > 0),
+        }
+    }
 }
 
 // Global metrics
@@ -88,10 +163,62 @@ pub async fn start_server(config: ServerConfig) -> Result<()> {
         auth_disabled = config.auth_disabled,
         rate_limit_rps = config.rate_limit_rps,
         rate_limit_burst = config.rate_limit_burst,
+        rate_limit_period = ?config.rate_limit_period,
+        max_in_flight_requests = config.max_in_flight_requests,
+        cors_allowed_origins = ?config.cors_allowed_origins,
+        cors_origin_regex = config.cors_origin_regex.as_deref(),
+        tls_cert = config.tls_cert.as_deref(),
+        tls_key = config.tls_key.as_deref(),
+        tls_client_ca = config.tls_client_ca.as_deref(),
         auth_server = config.auth_server,
         auth_audience = config.auth_audience,
+        jwt_hmac_secret = config.jwt_hmac_secret.is_some(),
+        cloud_max_concurrency = config.cloud_max_concurrency,
+        cloud_token_idle_timeout = config.cloud_token_idle_timeout,
+        query_timeout_ms = config.query_timeout_ms,
+        empty_string_as_none = config.empty_string_as_none,
+        include_timing = config.include_timing,
+        allow_reset = config.allow_reset,
+        export_dir = config.export_dir.as_deref(),
+        allow_define_statements = config.allow_define_statements,
+        max_result_depth = config.max_result_depth,
+        max_result_bytes = config.max_result_bytes,
+        allowed_functions = ?config.allowed_functions,
+        denied_functions = ?config.denied_functions,
+        strict_functions = config.strict_functions,
+        require_where_for_mutations = config.require_where_for_mutations,
+        disable_cloud = config.disable_cloud,
+        disable_query = config.disable_query,
+        disable_prompts = config.disable_prompts,
+        disable_resources = config.disable_resources,
+        read_only = config.read_only,
+        max_prompts = config.max_prompts,
+        max_resources = config.max_resources,
+        max_response_items = config.max_response_items,
+        response_envelope = config.response_envelope,
+        max_message_bytes = config.max_message_bytes,
+        trace_sample_rate = config.trace_sample_rate,
+        log_format = ?config.log_format,
         "Server configuration loaded"
     );
+    // Ensure the trace sample rate is a valid proportion
+    if !(0.0..=1.0).contains(&config.trace_sample_rate) {
+        return Err(anyhow!("The trace sample rate must be between 0.0 and 1.0"));
+    }
+    // Ensure that TLS is configured with both a certificate and a private key
+    if config.tls_cert.is_some() != config.tls_key.is_some() {
+        return Err(anyhow!(
+            "Both --tls-cert and --tls-key must be provided to enable TLS"
+        ));
+    }
+    // Ensure that client certificates are only required when TLS is enabled
+    if config.tls_client_ca.is_some() && config.tls_cert.is_none() {
+        return Err(anyhow!(
+            "The --tls-client-ca option requires --tls-cert and --tls-key"
+        ));
+    }
+    // Limit concurrent outbound SurrealDB Cloud requests
+    cloud::set_max_concurrency(config.cloud_max_concurrency)?;
     match (config.bind_address.is_some(), config.socket_path.is_some()) {
         // We are running as a STDIO server
         (false, false) => start_stdio_server(config).await,
@@ -106,8 +233,45 @@ pub async fn start_server(config: ServerConfig) -> Result<()> {
     }
 }
 
+/// Warn if more prompts or resources are registered than the configured limits
+///
+/// The items are limited in the same way as each session limits them, and the
+/// number of prompts and resources which are served is returned.
+fn check_registered_limits(options: &ServiceOptions) -> (usize, usize) {
+    // Warn when the limit ignores some of the registered items
+    let check = |kind: &str, registered: usize, served: usize| {
+        if served < registered {
+            warn!(
+                registered,
+                max = served,
+                "More {kind} are registered than the configured maximum, ignoring the remainder"
+            );
+        }
+        served
+    };
+    let registered = prompts::list_prompts();
+    let count = registered.len();
+    let prompts = check(
+        "prompts",
+        count,
+        limit_registered(registered, options.max_prompts).len(),
+    );
+    let registered = resources::list_resources();
+    let count = registered.len();
+    let resources = check(
+        "resources",
+        count,
+        limit_registered(registered, options.max_resources).len(),
+    );
+    // Output debugging information
+    debug!(prompts, resources, "Registered prompts and resources");
+    (prompts, resources)
+}
+
 /// Start the MCP server in stdio mode
 async fn start_stdio_server(config: ServerConfig) -> Result<()> {
+    // Get the session options
+    let options = config.service_options();
     // Extract configuration values
     let ServerConfig {
         endpoint,
@@ -117,10 +281,14 @@ async fn start_stdio_server(config: ServerConfig) -> Result<()> {
         pass,
         cloud_access_token,
         cloud_refresh_token,
+        trace_sample_rate,
+        log_format,
         ..
     } = config;
     // Initialize structured logging and metrics
-    init_logging_and_metrics(true);
+    init_logging_and_metrics(true, trace_sample_rate, log_format);
+    // Check the number of registered prompts and resources
+    check_registered_limits(&options);
     // Output debugging information
     info!("Starting MCP server in stdio mode");
     // Generate a connection ID for this connection
@@ -135,6 +303,7 @@ async fn start_stdio_server(config: ServerConfig) -> Result<()> {
         pass,
         cloud_access_token,
         cloud_refresh_token,
+        options,
     );
     // Initialize the connection using startup configuration
     if let Err(e) = service.initialize_connection().await {
@@ -174,6 +343,8 @@ async fn start_stdio_server(config: ServerConfig) -> Result<()> {
 
 /// Start the MCP server in Unix socket mode
 async fn start_unix_server(config: ServerConfig) -> Result<()> {
+    // Get the session options
+    let options = config.service_options();
     // Extract configuration values
     let ServerConfig {
         endpoint,
@@ -184,12 +355,16 @@ async fn start_unix_server(config: ServerConfig) -> Result<()> {
         socket_path,
         cloud_access_token,
         cloud_refresh_token,
+        trace_sample_rate,
+        log_format,
         ..
     } = config;
     // Get the specified socket path
     let socket_path = socket_path.as_deref().unwrap();
     // Initialize structured logging and metrics
-    init_logging_and_metrics(false);
+    init_logging_and_metrics(false, trace_sample_rate, log_format);
+    // Check the number of registered prompts and resources
+    check_registered_limits(&options);
     // Get the specified socket path
     let socket_path = Path::new(socket_path);
     // Remove existing socket file if it exists
@@ -241,6 +416,7 @@ async fn start_unix_server(config: ServerConfig) -> Result<()> {
         let pass = pass.clone();
         let cloud_access_token = cloud_access_token.clone();
         let cloud_refresh_token = cloud_refresh_token.clone();
+        let options = options.clone();
         // Spawn a new async task to handle this client connection
         tokio::spawn(async move {
             let _span =
@@ -257,6 +433,7 @@ async fn start_unix_server(config: ServerConfig) -> Result<()> {
                 pass,
                 cloud_access_token,
                 cloud_refresh_token,
+                options,
             );
             // Initialize the connection using startup configuration only if endpoint is specified
             if let Err(e) = service.initialize_connection().await {
@@ -304,6 +481,8 @@ async fn start_unix_server(config: ServerConfig) -> Result<()> {
 
 /// Start the MCP server in HTTP mode
 async fn start_http_server(config: ServerConfig) -> Result<()> {
+    // Get the session options
+    let mut options = config.service_options();
     // Extract configuration values
     let ServerConfig {
         endpoint,
@@ -316,24 +495,60 @@ async fn start_http_server(config: ServerConfig) -> Result<()> {
         auth_disabled,
         rate_limit_rps,
         rate_limit_burst,
+        rate_limit_period,
+        max_in_flight_requests,
+        cors_allowed_origins,
+        cors_origin_regex,
+        tls_cert,
+        tls_key,
+        tls_client_ca,
         auth_server,
         auth_audience,
+        jwt_hmac_secret,
         cloud_access_token,
         cloud_refresh_token,
+        trace_sample_rate,
+        log_format,
         ..
     } = config;
     // Get the specified bind address
     let bind_address = bind_address.as_deref().unwrap();
     // Initialize structured logging and metrics
-    init_logging_and_metrics(false);
+    init_logging_and_metrics(false, trace_sample_rate, log_format);
+    // Check the number of registered prompts and resources
+    check_registered_limits(&options);
     // Output debugging information
     info!(
         server_url = %server_url,
         bind_address = %bind_address,
         rate_limit_rps = rate_limit_rps,
         rate_limit_burst = rate_limit_burst,
+        rate_limit_period = ?rate_limit_period,
+        tls = tls_cert.is_some(),
+        mutual_tls = tls_client_ca.is_some(),
         "Starting MCP server in HTTP mode with rate limiting"
     );
+    // Ensure that at least one request can be processed at a time
+    if max_in_flight_requests == 0 {
+        return Err(anyhow!(
+            "The maximum number of in-flight requests must be at least 1"
+        ));
+    }
+    // File exports are only available for local transports
+    if options.export_dir.take().is_some() {
+        warn!("File exports are not available in HTTP mode, ignoring --export-dir");
+    }
+    // Create CORS layer for /.well-known endpoints, validating the origin regex
+    let cors_layer = create_cors_layer(&cors_allowed_origins, cors_origin_regex.as_deref())?;
+    // Load the TLS certificate and private key before binding, if specified
+    let tls_config = match (&tls_cert, &tls_key) {
+        (Some(cert), Some(key)) => Some(load_tls_config(
+            Path::new(cert),
+            Path::new(key),
+            tls_client_ca.as_deref().map(Path::new),
+        )?),
+        _ => None,
+    };
     // Create a TCP listener for the HTTP server
     let listener = TcpListener::bind(&bind_address)
         .await
@@ -350,19 +565,12 @@ async fn start_http_server(config: ServerConfig) -> Result<()> {
             auth_audience,
         ],
     }));
-    // Create CORS layer for /.well-known endpoints
-    let cors_layer = CorsLayer::new()
-        .allow_origin(tower_http::cors::Any)
-        .allow_methods([axum::http::Method::GET, axum::http::Method::OPTIONS])
-        .allow_headers([
-            axum::http::header::AUTHORIZATION,
-            axum::http::header::CONTENT_TYPE,
-        ])
-        .allow_credentials(false);
     // Create a service for /.well-known endpoints with CORS
     let well_known_service = Router::new()
         .route("/oauth-protected-resource", get(protected_resource))
         .layer(cors_layer);
+    // Create the state for checking that the startup endpoint is reachable
+    let health_state = HealthState::new(endpoint.clone(), user.clone(), pass.clone());
     // Create a session manager for the HTTP server
     let session_manager = Arc::new(LocalSessionManager::default());
     // Create a new SurrealDB service instance for the HTTP server
@@ -377,6 +585,7 @@ async fn start_http_server(config: ServerConfig) -> Result<()> {
                 pass.clone(),
                 cloud_access_token.clone(),
                 cloud_refresh_token.clone(),
+                options.clone(),
             ))
         },
         session_manager,
@@ -386,16 +595,23 @@ async fn start_http_server(config: ServerConfig) -> Result<()> {
         },
     );
     // Create rate limiting layer with metrics
-    let rate_limit_layer = create_rate_limit_layer(rate_limit_rps, rate_limit_burst);
+    let rate_limit_layer =
+        create_rate_limit_layer(rate_limit_rps, rate_limit_burst, rate_limit_period)?;
     // Create tracing layer for request logging
     let trace_layer = TraceLayer::new_for_http()
         .make_span_with(|request: &axum::http::Request<_>| {
             let connection_id = generate_connection_id();
+            // Identify clients which presented a TLS client certificate
+            let client_subject = request
+                .extensions()
+                .get::<TlsClient>()
+                .and_then(|client| client.subject.as_deref());
             tracing::info_span!(
                 "http_request",
                 connection_id = %connection_id,
                 method = %request.method(),
                 uri = %request.uri(),
+                client_subject = client_subject,
             )
         })
         .on_request(|request: &axum::http::Request<_>, _span: &tracing::Span| {
@@ -427,7 +643,9 @@ async fn start_http_server(config: ServerConfig) -> Result<()> {
     let mut router = Router::new()
         .nest_service("/.well-known", well_known_service)
         .nest_service("/mcp", mcp_service)
-        .route("/health", get(health))
+        .route("/health", get(health).with_state(health_state))
+        .route("/health/live", get(live))
+        .route("/", get(root))
         .layer(trace_layer)
         .layer(rate_limit_layer);
     // Add bearer authentication middleware if specified
@@ -435,20 +653,49 @@ async fn start_http_server(config: ServerConfig) -> Result<()> {
         // Set the token validation config
         let token_config = TokenValidationConfig {
             expected_audience: auth_audience.clone(),
+            jwt_shared_secret: jwt_hmac_secret,
             ..Default::default()
         };
+        // Refresh the JWKS in the background, so requests do not wait for them
+        if let Some(jwks_manager) = &token_config.jwks_manager {
+            jwks_manager.spawn_refresh_task();
+        }
         // Add bearer authentication middleware
         router = router.layer(axum::middleware::from_fn(move |req, next| {
             let config = token_config.clone();
             require_bearer_auth(config, req, next)
         }));
     }
+    // Shed load once the maximum number of in-flight requests is reached
+    router = router.layer(axum::middleware::from_fn_with_state(
+        Arc::new(Semaphore::new(max_in_flight_requests)),
+        limit_in_flight_requests,
+    ));
     // Use the shared double ctrl-c handler
     let signal = handle_double_ctrl_c();
-    // Serve the Axum router over HTTP
-    axum::serve(listener, router)
-        .with_graceful_shutdown(signal)
-        .await?;
+    // Expose the peer address to the rate limiter
+    let service = router.into_make_service_with_connect_info::<SocketAddr>();
+    match tls_config {
+        // Serve the Axum router over HTTPS, terminating TLS for each connection
+        Some(tls_config) => {
+            let handle = axum_server::Handle::new();
+            let shutdown = handle.clone();
+            tokio::spawn(async move {
+                signal.await;
+                shutdown.graceful_shutdown(None);
+            });
+            tls_server(listener, tls_config)?
+                .handle(handle)
+                .serve(service)
+                .await?;
+        }
+        // Serve the Axum router over HTTP
+        None => {
+            axum::serve(listener, service)
+                .with_graceful_shutdown(signal)
+                .await?;
+        }
+    }
     // All ok
     Ok(())
 }
@@ -464,9 +711,8 @@ mod tests {
     };
     use tower::ServiceExt;
 
-    #[tokio::test]
-    async fn test_auth_discovery_includes_audience() {
-        let config = ServerConfig {
+    fn test_config() -> ServerConfig {
+        ServerConfig {
             endpoint: None,
             ns: None,
             db: None,
@@ -478,11 +724,72 @@ mod tests {
             auth_disabled: true,
             rate_limit_rps: 100,
             rate_limit_burst: 200,
+            rate_limit_period: None,
+            max_in_flight_requests: 1000,
+            cors_allowed_origins: vec![],
+            cors_origin_regex: None,
+            tls_cert: None,
+            tls_key: None,
+            tls_client_ca: None,
             auth_server: "https://auth.surrealdb.com".to_string(),
             auth_audience: "https://custom.audience.com/".to_string(),
+            jwt_hmac_secret: None,
             cloud_access_token: None,
             cloud_refresh_token: None,
+            cloud_max_concurrency: 10,
+            cloud_token_idle_timeout: 0,
+            query_timeout_ms: 0,
+            empty_string_as_none: false,
+            include_timing: false,
+            allow_reset: false,
+            export_dir: None,
+            allow_define_statements: false,
+            max_result_depth: 16,
+            max_result_bytes: 0,
+            allowed_functions: vec![],
+            denied_functions: vec![],
+            strict_functions: false,
+            require_where_for_mutations: false,
+            disable_cloud: false,
+            disable_query: false,
+            disable_prompts: false,
+            disable_resources: false,
+            read_only: false,
+            max_prompts: 100,
+            max_resources: 100,
+            max_response_items: 0,
+            response_envelope: false,
+            max_message_bytes: 1048576,
+            trace_sample_rate: 1.0,
+            log_format: LogFormat::Text,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tls_options_are_validated() {
+        let config = ServerConfig {
+            tls_cert: Some("cert.pem".to_string()),
+            ..test_config()
         };
+        let err = start_server(config).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Both --tls-cert and --tls-key must be provided to enable TLS"
+        );
+        let config = ServerConfig {
+            tls_client_ca: Some("ca.pem".to_string()),
+            ..test_config()
+        };
+        let err = start_server(config).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The --tls-client-ca option requires --tls-cert and --tls-key"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auth_discovery_includes_audience() {
+        let config = test_config();
 
         // Create a simple router to test the discovery endpoint
         let auth_servers = Json(json!({
@@ -509,4 +816,35 @@ mod tests {
         // The actual JSON parsing would require additional dependencies
         assert!(response.headers().get("content-type").is_some());
     }
+
+    #[test]
+    fn test_check_registered_limits_warns_when_truncated() {
+        use tracing_subscriber::layer::SubscriberExt;
+        let registered = prompts::list_prompts().len();
+        assert!(registered > 1);
+        let capture = crate::logs::Capture::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(capture.clone())
+                .with_ansi(false),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            // A maximum of 0 is unlimited
+            let options = ServiceOptions {
+                max_prompts: Some(0),
+                ..Default::default()
+            };
+            assert_eq!(check_registered_limits(&options).0, registered);
+            assert!(!capture.output().contains("WARN"));
+            // Prompts over the limit are ignored with a warning
+            let options = ServiceOptions {
+                max_prompts: Some(1),
+                ..Default::default()
+            };
+            assert_eq!(check_registered_limits(&options).0, 1);
+        });
+        let logs = capture.output();
+        assert!(logs.contains("WARN"));
+        assert!(logs.contains("More prompts are registered than the configured maximum"));
+    }
 }
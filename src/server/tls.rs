@@ -0,0 +1,268 @@
+use anyhow::{Result, anyhow};
+use axum::Extension;
+use axum::middleware::AddExtension;
+use axum_server::Server;
+use axum_server::accept::Accept;
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use futures::future::BoxFuture;
+use rustls::RootCertStore;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+use rustls::server::WebPkiClientVerifier;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tower::Layer;
+use tracing::debug;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// Load all PEM-encoded certificates from a file, ensuring there is at least one
+fn load_certificates(path: &Path, kind: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| anyhow!("Failed to load {kind} {}: {e}", path.display()))?;
+    // Ensure that the certificate file was not empty
+    if certs.is_empty() {
+        return Err(anyhow!(
+            "No certificates found in {kind} {}",
+            path.display()
+        ));
+    }
+    Ok(certs)
+}
+
+/// Load a TLS server configuration from PEM-encoded certificate and key files
+///
+/// When a client CA is specified, every client must present a certificate which
+/// was signed by one of the certificate authorities in the file, and connections
+/// from other clients are rejected during the TLS handshake.
+pub fn load_tls_config(
+    cert: &Path,
+    key: &Path,
+    client_ca: Option<&Path>,
+) -> Result<Arc<rustls::ServerConfig>> {
+    // Load the certificate chain, starting with the server certificate
+    let certs = load_certificates(cert, "TLS certificate")?;
+    // Load the private key for the server certificate
+    let key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|e| anyhow!("Failed to load TLS private key {}: {e}", key.display()))?;
+    // Use the ring crypto provider for both the server and client verification
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    // Require client certificates signed by the client CA, if specified
+    let builder = match client_ca {
+        Some(client_ca) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certificates(client_ca, "TLS client CA")? {
+                roots
+                    .add(cert)
+                    .map_err(|e| anyhow!("Invalid TLS client CA {}: {e}", client_ca.display()))?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(roots.into(), provider)
+                .build()
+                .map_err(|e| anyhow!("Invalid TLS client CA {}: {e}", client_ca.display()))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    // Create the server configuration
+    let mut config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| anyhow!("Invalid TLS certificate or private key: {e}"))?;
+    // Advertise support for both HTTP/2 and HTTP/1.1
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    // All ok
+    Ok(Arc::new(config))
+}
+
+/// The client of a connection which was accepted over TLS
+#[derive(Clone, Debug)]
+pub struct TlsClient {
+    /// The subject of the verified client certificate, if one was presented
+    pub subject: Option<String>,
+}
+
+/// Get the subject of a DER-encoded certificate, formatted as in `CN=name, O=org`
+fn certificate_subject(cert: &CertificateDer<'_>) -> Option<String> {
+    let (_, cert) = X509Certificate::from_der(cert).ok()?;
+    Some(cert.subject().to_string())
+}
+
+/// A TLS acceptor which exposes the client certificate to downstream handlers
+///
+/// Each request on the connection has a [`TlsClient`] extension, containing
+/// the subject of the verified client certificate, if one was presented.
+#[derive(Clone)]
+pub struct TlsClientAcceptor {
+    /// The acceptor which performs the TLS handshake
+    inner: RustlsAcceptor,
+}
+
+impl<I, S> Accept<I, S> for TlsClientAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = <RustlsAcceptor as Accept<I, S>>::Stream;
+    type Service = AddExtension<S, TlsClient>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let acceptor = self.inner.clone();
+        Box::pin(async move {
+            let (stream, service) = acceptor.accept(stream, service).await?;
+            // Identify the client by its verified certificate, if any
+            let subject = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .and_then(certificate_subject);
+            if let Some(subject) = &subject {
+                debug!(subject = %subject, "Accepted TLS client certificate");
+            }
+            let service = Extension(TlsClient { subject }).layer(service);
+            Ok((stream, service))
+        })
+    }
+}
+
+/// Create a server which terminates TLS for each connection on the listener
+pub fn tls_server(
+    listener: TcpListener,
+    config: Arc<rustls::ServerConfig>,
+) -> io::Result<Server<TlsClientAcceptor>> {
+    let config = RustlsConfig::from_config(config);
+    let server = axum_server::from_tcp_rustls(listener.into_std()?, config);
+    Ok(server.map(|inner| TlsClientAcceptor { inner }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::ConnectInfo;
+    use axum::{Router, routing::get};
+    use std::net::SocketAddr;
+    use std::path::PathBuf;
+
+    // The fixtures were generated with the following commands, and are valid until 2126:
+    //
+    //   openssl req -x509 -newkey ec -pkeyopt ec_paramgen_curve:P-256 -nodes -days 36500 \
+    //     -subj "/CN=SurrealMCP Test CA" -keyout ca.key -out ca.pem
+    //   openssl req -newkey ec -pkeyopt ec_paramgen_curve:P-256 -nodes \
+    //     -subj "/CN=localhost" -keyout server.key -out server.csr
+    //   openssl x509 -req -in server.csr -CA ca.pem -CAkey ca.key -CAcreateserial -days 36500 \
+    //     -extfile <(printf "subjectAltName=DNS:localhost,IP:127.0.0.1") -out server.pem
+    //   openssl req -newkey ec -pkeyopt ec_paramgen_curve:P-256 -nodes \
+    //     -subj "/O=SurrealDB/CN=SurrealMCP Test Client" -keyout client.key -out client.csr
+    //   openssl x509 -req -in client.csr -CA ca.pem -CAkey ca.key -CAcreateserial -days 36500 \
+    //     -extfile <(printf "extendedKeyUsage=clientAuth") -out client.pem
+    fn testdata(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/server/testdata")
+            .join(name)
+    }
+
+    /// Serve a router which responds with the client certificate subject
+    async fn serve(config: Arc<rustls::ServerConfig>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new().route(
+            "/",
+            get(
+                |ConnectInfo(peer): ConnectInfo<SocketAddr>,
+                 Extension(client): Extension<TlsClient>| async move {
+                    assert!(peer.ip().is_loopback());
+                    client.subject.unwrap_or_default()
+                },
+            ),
+        );
+        let server = tls_server(listener, config).unwrap();
+        tokio::spawn(async move {
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            server.serve(service).await.unwrap();
+        });
+        addr
+    }
+
+    /// Create a client which trusts the test certificate authority
+    fn client(addr: SocketAddr, identity: Option<reqwest::Identity>) -> reqwest::Client {
+        let ca = std::fs::read(testdata("ca.pem")).unwrap();
+        let mut builder = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(&ca).unwrap())
+            .resolve("localhost", addr);
+        if let Some(identity) = identity {
+            builder = builder.identity(identity);
+        }
+        builder.build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tls_handshake_with_self_signed_certificate() {
+        let config =
+            load_tls_config(&testdata("server.pem"), &testdata("server.key"), None).unwrap();
+        let addr = serve(config).await;
+        let response = client(addr, None)
+            .get(format!("https://localhost:{}/", addr.port()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "");
+    }
+
+    #[tokio::test]
+    async fn test_mutual_tls_requires_trusted_client_certificate() {
+        let config = load_tls_config(
+            &testdata("server.pem"),
+            &testdata("server.key"),
+            Some(&testdata("ca.pem")),
+        )
+        .unwrap();
+        let addr = serve(config).await;
+        let url = format!("https://localhost:{}/", addr.port());
+        // A client with a certificate signed by the client CA is accepted
+        let mut pem = std::fs::read(testdata("client.pem")).unwrap();
+        pem.extend(std::fs::read(testdata("client.key")).unwrap());
+        let identity = reqwest::Identity::from_pem(&pem).unwrap();
+        let response = client(addr, Some(identity)).get(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(
+            response.text().await.unwrap(),
+            "O=SurrealDB, CN=SurrealMCP Test Client"
+        );
+        // A client without a certificate is rejected during the handshake
+        assert!(client(addr, None).get(&url).send().await.is_err());
+    }
+
+    #[test]
+    fn test_load_tls_config_reports_missing_files() {
+        let err =
+            load_tls_config(&testdata("missing.pem"), &testdata("server.key"), None).unwrap_err();
+        assert!(err.to_string().contains("Failed to load TLS certificate"));
+        let err =
+            load_tls_config(&testdata("server.pem"), &testdata("missing.key"), None).unwrap_err();
+        assert!(err.to_string().contains("Failed to load TLS private key"));
+        let err = load_tls_config(
+            &testdata("server.pem"),
+            &testdata("server.key"),
+            Some(&testdata("missing.pem")),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Failed to load TLS client CA"));
+    }
+
+    #[test]
+    fn test_load_tls_config_rejects_mismatched_files() {
+        // A certificate file can not be used as the private key
+        let err =
+            load_tls_config(&testdata("server.pem"), &testdata("server.pem"), None).unwrap_err();
+        assert!(err.to_string().contains("Failed to load TLS private key"));
+        // A private key file contains no certificates
+        let err =
+            load_tls_config(&testdata("server.key"), &testdata("server.key"), None).unwrap_err();
+        assert!(err.to_string().contains("No certificates found"));
+    }
+}
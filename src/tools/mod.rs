@@ -1,33 +1,135 @@
 use anyhow::Result;
+use futures::StreamExt;
 use http::request::Parts;
 use metrics::counter;
 use rmcp::{
     ErrorData as McpError, RoleServer, ServerHandler,
     handler::server::router::tool::ToolRouter,
-    handler::server::tool::Parameters,
+    handler::server::tool::{Parameters, ToolCallContext},
     model::{CallToolResult, Content, ServerCapabilities, ServerInfo},
     service::RequestContext,
-    tool, tool_handler, tool_router,
+    tool, tool_router,
 };
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
-use surrealdb::{Surreal, Value, engine::any::Any};
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use surrealdb::{Surreal, Uuid, Value, engine::any::Any};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
 use tracing::{debug, error, info, trace, warn};
 
-use crate::cloud::Client;
+use crate::cloud::{Client, CloudCreateInstanceRequest};
 use crate::db;
 use crate::engine;
 use crate::prompts;
 use crate::resources;
-use crate::utils::{convert_json_to_surreal, parse_target, parse_targets};
+use crate::server::{TokenIdentity, TokenScopes};
+use crate::utils::{
+    StatementKind, buffered_statements, check_function, convert_json_to_surreal, diff_json,
+    execution_clauses, explain_query, format_duration, full_table_mutations, import_statements,
+    limit_registered, merge_json, parse_record_id, parse_target, parse_targets,
+    query_function_names, quote_field_path, quote_ident, range_target, resolve_path_within,
+    statement_kinds, validate_clause, validate_return_clause,
+};
+
+/// The tools which manage SurrealDB Cloud organizations and instances
+const CLOUD_TOOLS: &[&str] = &[
+    "list_cloud_organizations",
+    "list_cloud_instances",
+    "pause_cloud_instance",
+    "resume_cloud_instance",
+    "get_cloud_instance_status",
+    "create_cloud_instance",
+    "delete_cloud_instance",
+    "get_cloud_instance_capabilities",
+    "connect_cloud_instance",
+    "list_cloud_regions",
+    "list_cloud_backups",
+    "restore_cloud_backup",
+    "update_cloud_instance_storage",
+    "update_cloud_instance_compute",
+    "upgrade_cloud_instance",
+];
+
+/// The maximum number of notifications buffered for each live query
+const LIVE_QUERY_BUFFER_SIZE: usize = 1000;
+
+/// The tools which run caller-supplied SurrealQL scripts or expressions
+const RAW_QUERY_TOOLS: &[&str] = &["query", "compute", "import"];
+
+/// The tools which modify data or schema in the connected database
+const WRITE_TOOLS: &[&str] = &[
+    "insert",
+    "create",
+    "upsert",
+    "update",
+    "delete",
+    "relate",
+    "reset_database",
+    "import",
+    "define_param",
+    "define_user",
+    "define_table",
+    "define_index",
+];
+
+/// The token scopes required to call the tools which modify data or resources
+const TOOL_SCOPES: &[(&str, &str)] = &[
+    ("insert", "db:write"),
+    ("create", "db:write"),
+    ("upsert", "db:write"),
+    ("update", "db:write"),
+    ("delete", "db:write"),
+    ("relate", "db:write"),
+    ("import", "db:write"),
+    ("commit_transaction", "db:write"),
+    ("reset_database", "db:admin"),
+    ("define_param", "db:admin"),
+    ("define_user", "db:admin"),
+    ("define_table", "db:admin"),
+    ("define_index", "db:admin"),
+    ("export_to_file", "db:admin"),
+    ("pause_cloud_instance", "cloud:write"),
+    ("resume_cloud_instance", "cloud:write"),
+    ("create_cloud_instance", "cloud:write"),
+    ("delete_cloud_instance", "cloud:write"),
+    ("restore_cloud_backup", "cloud:write"),
+    ("update_cloud_instance_storage", "cloud:write"),
+    ("update_cloud_instance_compute", "cloud:write"),
+    ("upgrade_cloud_instance", "cloud:write"),
+];
+
+/// The distance functions which can be used to compare vectors
+const VECTOR_DISTANCES: &[&str] = &[
+    "euclidean",
+    "cosine",
+    "manhattan",
+    "chebyshev",
+    "hamming",
+    "jaccard",
+    "pearson",
+];
 
 // Global metrics
 static QUERY_COUNTER: AtomicU64 = AtomicU64::new(0);
 
+tokio::task_local! {
+    /// The id of the last query executed by the current tool call
+    static LAST_QUERY_ID: Cell<Option<u64>>;
+    /// The scopes granted to the bearer token of the current tool call, if restricted
+    static GRANTED_SCOPES: Option<Vec<String>>;
+}
+
+/// The number of records fetched per query when exporting a table to a file
+const EXPORT_BATCH_SIZE: usize = 1000;
+
+/// The default and largest maximum size, in bytes, of an export returned by the export tool
+const EXPORT_MAX_BYTES: usize = 1024 * 1024;
+
 #[derive(Deserialize)]
 struct ListNamespaces {
     namespaces: Vec<Namespace>,
@@ -54,12 +156,63 @@ pub struct QueryParams {
     pub query: String,
     #[schemars(description = "Optional parameters to bind to the query")]
     pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(description = "Whether to include the execution time of each statement")]
+    pub include_timing: Option<bool>,
+    #[schemars(
+        description = "Whether to bind parameters which are empty strings as NONE, overriding the server default"
+    )]
+    pub empty_string_as_none: Option<bool>,
+    #[schemars(
+        description = "Set to true to confirm that every record in a table should be modified, when a WHERE clause is required"
+    )]
+    pub confirm_full_table: Option<bool>,
+    #[schemars(
+        description = "Whether to return the query plan of a single SELECT statement instead of its results"
+    )]
+    pub explain: Option<bool>,
+    #[schemars(
+        description = "Whether to return the full query plan, including the number of records fetched (EXPLAIN FULL)"
+    )]
+    pub explain_full: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct OrderField {
+    #[schemars(description = "The field to sort by, such as 'name' or 'address.city'.")]
+    pub field: String,
+    #[schemars(description = "The sort direction: 'ASC' or 'DESC'. Defaults to 'ASC'.")]
+    pub direction: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct RecordRange {
+    #[schemars(description = "The table containing the records.")]
+    pub table: String,
+    #[schemars(
+        description = "The record ID key which the range starts at, such as 1 or 'a'. Omit for an open start."
+    )]
+    pub start: Option<serde_json::Value>,
+    #[schemars(
+        description = "The record ID key which the range ends at, such as 100 or 'z'. Omit for an open end."
+    )]
+    pub end: Option<serde_json::Value>,
+    #[schemars(description = "Whether the end key is included in the range. Defaults to false.")]
+    pub inclusive: Option<bool>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
 pub struct SelectParams {
     #[schemars(description = "Array of table names or record IDs to select from.")]
+    #[serde(default)]
     pub targets: Vec<String>,
+    #[schemars(
+        description = "Optional range of record IDs to select from, in addition to the targets."
+    )]
+    pub range: Option<RecordRange>,
+    #[schemars(
+        description = "Optional array of fields to return, such as 'name' or 'address.city'. Defaults to all fields."
+    )]
+    pub fields: Option<Vec<String>>,
     #[schemars(description = "Optional WHERE clause to filter records.")]
     pub where_clause: Option<String>,
     #[schemars(description = "Optional SPLIT ON clause to split records on specific fields.")]
@@ -72,8 +225,88 @@ pub struct SelectParams {
     pub limit_clause: Option<String>,
     #[schemars(description = "Optional START clause to specify the pagination start position.")]
     pub start_clause: Option<String>,
+    #[schemars(
+        description = "Optional FETCH clause to resolve linked records, such as 'author, comments'."
+    )]
+    pub fetch_clause: Option<String>,
+    #[schemars(description = "Optional parameters to bind to the query.")]
+    pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(
+        description = "Optional array of record IDs which the selected records must match."
+    )]
+    pub ids: Option<Vec<String>>,
+    #[schemars(
+        description = "Optional array of fields and directions to sort by, as an alternative to order_clause."
+    )]
+    pub order_by: Option<Vec<OrderField>>,
+    #[schemars(
+        description = "Optional array of fields to group by, as an alternative to group_clause."
+    )]
+    pub group_by: Option<Vec<String>>,
+    #[schemars(description = "Optional array of index names which the query is forced to use.")]
+    pub with_index: Option<Vec<String>>,
+    #[schemars(description = "Whether to return the query plan instead of the results.")]
+    pub explain: Option<bool>,
+    #[schemars(
+        description = "Whether to return the full query plan, including the number of records fetched (EXPLAIN FULL)."
+    )]
+    pub explain_full: Option<bool>,
+    #[schemars(description = "Whether to process the records in parallel (PARALLEL).")]
+    pub parallel: Option<bool>,
+    #[schemars(
+        description = "Optional maximum duration the query can run for, such as '5s' (TIMEOUT)."
+    )]
+    pub timeout: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct CountParams {
+    #[schemars(description = "The table name or record ID to count records in.")]
+    pub target: String,
+    #[schemars(description = "Optional WHERE clause to filter the counted records.")]
+    pub where_clause: Option<String>,
+    #[schemars(description = "Optional parameters to bind to the query.")]
+    pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct VectorSearchParams {
+    #[schemars(description = "The table name to search for records in.")]
+    pub target: String,
+    #[schemars(description = "The field which contains the vectors, such as 'embedding'.")]
+    pub field: String,
+    #[schemars(description = "The vector to find the nearest neighbours of.")]
+    pub vector: Vec<f64>,
+    #[schemars(description = "The number of nearest neighbours to return.")]
+    pub k: u32,
+    #[schemars(description = "Optional WHERE clause to filter the records.")]
+    pub where_clause: Option<String>,
     #[schemars(description = "Optional parameters to bind to the query.")]
     pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(
+        description = "The size of the dynamic candidate list, required when the field has an HNSW index."
+    )]
+    pub ef: Option<u32>,
+    #[schemars(
+        description = "The distance function for a search without an index, such as 'euclidean' or 'cosine'."
+    )]
+    pub distance: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct FullTextParams {
+    #[schemars(description = "The table name to search for records in.")]
+    pub target: String,
+    #[schemars(description = "The field to search, which must have a full-text search index.")]
+    pub field: String,
+    #[schemars(description = "The text to search for.")]
+    pub query: String,
+    #[schemars(
+        description = "Whether to include the matching text, with the matched terms wrapped in <b></b>, as the highlight field."
+    )]
+    pub highlight: Option<bool>,
+    #[schemars(description = "Optional maximum number of records to return.")]
+    pub limit: Option<u32>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -86,6 +319,12 @@ pub struct InsertParams {
     pub relation: Option<bool>,
     #[schemars(description = "Array of JSON objects to be inserted as the record content.")]
     pub values: Vec<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(description = "Whether to insert the records in parallel (PARALLEL).")]
+    pub parallel: Option<bool>,
+    #[schemars(
+        description = "Optional maximum duration the insert can run for, such as '5s' (TIMEOUT)."
+    )]
+    pub timeout: Option<String>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -94,6 +333,10 @@ pub struct CreateParams {
     pub target: String,
     #[schemars(description = "The JSON data to be inserted as the record content.")]
     pub data: serde_json::Map<String, serde_json::Value>,
+    #[schemars(
+        description = "Optional RETURN clause: NONE, BEFORE, AFTER, DIFF, or a list of fields to return."
+    )]
+    pub return_clause: Option<String>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -108,6 +351,10 @@ pub struct UpsertParams {
     pub content_data: Option<serde_json::Map<String, serde_json::Value>>,
     #[schemars(description = "The JSON data to apply to the record or records.")]
     pub replace_data: Option<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(
+        description = "SET expressions to apply to the record or records, such as 'age += 1, updated_at = time::now()'. Prefer parameters for values."
+    )]
+    pub set_clause: Option<String>,
     #[schemars(description = "Optional WHERE clause to filter records before upserting.")]
     pub where_clause: Option<String>,
     #[schemars(description = "Optional parameters to bind to the query.")]
@@ -126,10 +373,22 @@ pub struct UpdateParams {
     pub content_data: Option<serde_json::Map<String, serde_json::Value>>,
     #[schemars(description = "The JSON data to apply to the record or records.")]
     pub replace_data: Option<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(
+        description = "SET expressions to apply to the record or records, such as 'age += 1, updated_at = time::now()'. Prefer parameters for values."
+    )]
+    pub set_clause: Option<String>,
     #[schemars(description = "Optional WHERE clause to filter records before upserting.")]
     pub where_clause: Option<String>,
     #[schemars(description = "Optional parameters to bind to the query.")]
     pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(
+        description = "Set to true to confirm that every record in a table should be modified, when a WHERE clause is required"
+    )]
+    pub confirm_full_table: Option<bool>,
+    #[schemars(
+        description = "Optional RETURN clause: NONE, BEFORE, AFTER, DIFF, or a list of fields to return."
+    )]
+    pub return_clause: Option<String>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -140,6 +399,15 @@ pub struct DeleteParams {
     pub where_clause: Option<String>,
     #[schemars(description = "Optional parameters to bind to the query.")]
     pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(
+        description = "Set to true to confirm that every record in a table should be deleted, when a table is given without a WHERE clause"
+    )]
+    #[serde(alias = "confirm_full_table")]
+    pub confirm_all: Option<bool>,
+    #[schemars(
+        description = "Optional RETURN clause: NONE, BEFORE, AFTER, DIFF, or a list of fields to return."
+    )]
+    pub return_clause: Option<String>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -156,6 +424,259 @@ pub struct RelateParams {
     pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct GetEdgesParams {
+    #[schemars(description = "The record ID whose edges to fetch, in the format 'table:id'.")]
+    pub record: String,
+    #[schemars(description = "Optional edge direction: 'in', 'out', or 'both' (default).")]
+    pub direction: Option<String>,
+    #[schemars(description = "Optional edge table name to filter the returned edges.")]
+    pub edge: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct TraverseParams {
+    #[schemars(description = "The record ID to start from, in the format 'table:id'.")]
+    pub start: String,
+    #[schemars(description = "The steps to follow from the start record, in order.")]
+    pub path: Vec<TraverseStep>,
+    #[schemars(
+        description = "Optional WHERE clause to filter the records at the end of the path."
+    )]
+    pub where_clause: Option<String>,
+    #[schemars(
+        description = "Optional FETCH clause to resolve linked records, such as 'author, comments'."
+    )]
+    pub fetch: Option<String>,
+    #[schemars(description = "Optional parameters to bind to the query.")]
+    pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct TraverseStep {
+    #[schemars(
+        description = "The direction to follow the edges: 'out' (->), 'in' (<-), or 'both' (<->)."
+    )]
+    pub direction: String,
+    #[schemars(description = "The edge table to follow, such as 'knows', or '?' for any edge.")]
+    pub edge: String,
+    #[schemars(
+        description = "Optional table of the records at the other end of the edges. Defaults to any table."
+    )]
+    pub target: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct MaintenanceParams {
+    #[schemars(
+        description = "The maintenance operation to run: 'rebuild_indexes'. The 'compact' operation is rejected, as SurrealDB does not expose manual compaction."
+    )]
+    pub operation: String,
+    #[schemars(description = "The table to run the operation on, for table-level operations.")]
+    pub table: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct ResetDatabaseParams {
+    #[schemars(description = "Must be true to confirm that all tables should be removed.")]
+    pub confirm: bool,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct ExportToFileParams {
+    #[schemars(description = "The table whose records will be exported.")]
+    pub table: String,
+    #[schemars(description = "The file path, relative to the configured export directory.")]
+    pub path: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct ExportParams {
+    #[schemars(
+        description = "The export format: 'surql' for a SurrealQL script (the default), or 'ndjson' for one JSON record per line."
+    )]
+    pub format: Option<String>,
+    #[schemars(
+        description = "The maximum size of the export in bytes. Defaults to 1048576, which is also the largest size allowed."
+    )]
+    pub max_bytes: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct ImportParams {
+    #[schemars(
+        description = "The SurrealQL script to import, such as the output of the export tool."
+    )]
+    pub content: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct DefineParamParams {
+    #[schemars(description = "The name of the parameter, with or without the leading '$'.")]
+    pub name: String,
+    #[schemars(description = "The JSON value to store in the parameter.")]
+    pub value: serde_json::Value,
+    #[schemars(description = "Whether to overwrite the parameter if it is already defined.")]
+    pub overwrite: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct InfoParams {
+    #[schemars(description = "The level to describe: 'root', 'ns', 'db', or 'table'.")]
+    pub level: String,
+    #[schemars(description = "The table to describe, required when the level is 'table'.")]
+    pub table: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct ListTablesParams {
+    #[schemars(description = "Whether to count the records in each table, which can be slow.")]
+    pub include_counts: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct ComputeParams {
+    #[schemars(description = "The SurrealQL expression to compute.")]
+    pub expression: String,
+    #[schemars(description = "Optional parameters to bind to the expression.")]
+    pub parameters: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct RunFunctionParams {
+    #[schemars(
+        description = "The name of the function to run, such as 'string::uppercase' or 'fn::greet'."
+    )]
+    pub name: String,
+    #[schemars(description = "Optional array of arguments to pass to the function.")]
+    pub args: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct DefineUserParams {
+    #[schemars(description = "The name of the user.")]
+    pub name: String,
+    #[schemars(description = "The level of the user: 'root', 'namespace', or 'database'.")]
+    pub level: String,
+    #[schemars(description = "The password of the user, which is never logged.")]
+    pub password: String,
+    #[schemars(
+        description = "The roles of the user: 'owner', 'editor', or 'viewer'. Defaults to 'viewer'."
+    )]
+    pub roles: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct DefineTableParams {
+    #[schemars(description = "The name of the table.")]
+    pub name: String,
+    #[schemars(
+        description = "Whether the table is SCHEMAFULL, only storing the defined fields. Defaults to false."
+    )]
+    pub schemafull: Option<bool>,
+    #[schemars(description = "Optional fields to define on the table.")]
+    pub fields: Option<Vec<FieldDef>>,
+    #[schemars(
+        description = "Optional PERMISSIONS clause for the table, such as 'FULL', 'NONE', or 'FOR select FULL'."
+    )]
+    pub permissions: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct FieldDef {
+    #[schemars(description = "The name of the field, such as 'name' or 'address.city'.")]
+    pub name: String,
+    #[serde(rename = "type")]
+    #[schemars(
+        description = "The type of the field, such as 'string', 'int', or 'record<person>'."
+    )]
+    pub kind: String,
+    #[schemars(
+        description = "Whether the field can be empty, making its type option<type>. Defaults to false."
+    )]
+    pub optional: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct DefineIndexParams {
+    #[schemars(description = "The table to define the index on.")]
+    pub table: String,
+    #[schemars(description = "The name of the index.")]
+    pub name: String,
+    #[schemars(
+        description = "The fields to index, such as ['email'] or ['name.first', 'name.last']."
+    )]
+    pub fields: Vec<String>,
+    #[schemars(description = "Whether the indexed values must be unique. Defaults to false.")]
+    pub unique: Option<bool>,
+    #[schemars(description = "Whether to define a full-text search index. Defaults to false.")]
+    pub search: Option<bool>,
+    #[schemars(
+        description = "The analyzer used by a full-text search index, which must already be defined."
+    )]
+    pub analyzer: Option<String>,
+    #[schemars(description = "Optional vector index on a field of numeric arrays.")]
+    pub vector: Option<VectorIndexSpec>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct VectorIndexSpec {
+    #[schemars(description = "The type of vector index: 'mtree' or 'hnsw'.")]
+    pub kind: String,
+    #[schemars(description = "The number of dimensions of the indexed vectors.")]
+    pub dimension: u32,
+    #[schemars(
+        description = "The distance function: 'euclidean', 'cosine', 'manhattan', 'chebyshev', 'hamming', 'jaccard', or 'pearson'. Defaults to 'euclidean'."
+    )]
+    pub distance: Option<String>,
+    #[schemars(
+        description = "The type of the vector elements: 'f64', 'f32', 'i64', 'i32', or 'i16'. Defaults to 'f64'."
+    )]
+    pub element_type: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct ShowChangesParams {
+    #[schemars(description = "The table to read changes from, which must have a change feed.")]
+    pub table: String,
+    #[schemars(
+        description = "The versionstamp (such as 0) or RFC 3339 datetime to read changes since."
+    )]
+    pub since: String,
+    #[schemars(description = "The maximum number of change batches to return. Defaults to 100.")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct DiffRecordsParams {
+    #[schemars(description = "The record ID to transform, in the format 'table:id'.")]
+    pub from: String,
+    #[schemars(description = "The record ID to transform it into, in the format 'table:id'.")]
+    pub to: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct LiveQueryParams {
+    #[schemars(description = "The table to receive change notifications for.")]
+    pub table: String,
+    #[schemars(description = "Optional WHERE clause to filter the records to be notified about.")]
+    pub where_clause: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct LiveQueryIdParams {
+    #[schemars(description = "The live query ID returned by live_query.")]
+    pub id: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct PreviewMergeParams {
+    #[schemars(description = "The record ID to preview the merge for, in the format 'table:id'.")]
+    pub record: String,
+    #[schemars(description = "The JSON data to merge into the existing record.")]
+    pub merge_data: serde_json::Map<String, serde_json::Value>,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 pub struct CloudParams {}
 
@@ -171,15 +692,59 @@ pub struct CloudInstanceParams {
     pub instance_id: String,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct RestoreCloudBackupParams {
+    #[schemars(description = "ID of the SurrealDB Cloud instance")]
+    pub instance_id: String,
+    #[schemars(description = "ID of the backup snapshot to restore, from list_cloud_backups")]
+    pub snapshot_id: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct UpdateCloudStorageParams {
+    #[schemars(description = "ID of the SurrealDB Cloud instance")]
+    pub instance_id: String,
+    #[schemars(description = "The new storage size of the instance, in gigabytes (GB)")]
+    pub storage_size: i32,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct UpdateCloudComputeParams {
+    #[schemars(description = "ID of the SurrealDB Cloud instance")]
+    pub instance_id: String,
+    #[schemars(description = "The new number of compute units for the instance")]
+    pub compute_units: i32,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct UpgradeCloudInstanceParams {
+    #[schemars(description = "ID of the SurrealDB Cloud instance")]
+    pub instance_id: String,
+    #[schemars(
+        description = "The SurrealDB version to upgrade to, from the available_versions of the instance"
+    )]
+    pub version: String,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 pub struct CreateCloudInstanceParams {
     #[schemars(description = "Name of the SurrealDB Cloud instance")]
     pub name: String,
     #[schemars(description = "ID of the SurrealDB Cloud organization")]
     pub organization_id: String,
+    #[schemars(
+        description = "The region slug to create the instance in, from list_cloud_regions (optional, defaults to the organization's default region)"
+    )]
+    pub region: Option<String>,
+    #[schemars(
+        description = "The SurrealDB version to run on the instance (optional, defaults to the latest version)"
+    )]
+    pub version: Option<String>,
+    #[schemars(description = "The number of compute units for the instance (optional)")]
+    pub compute_units: Option<i32>,
 }
 
-#[derive(Deserialize, schemars::JsonSchema)]
+#[derive(Clone, Deserialize, schemars::JsonSchema)]
 pub struct ConnectParams {
     #[schemars(
         description = "The SurrealDB endpoint URL. For cloud instances, use 'cloud:instance_id' format."
@@ -207,24 +772,285 @@ pub struct UseDatabaseParams {
     pub database: String,
 }
 
-#[derive(Clone)]
-pub struct SurrealService {
-    /// The SurrealDB client instance to use for database operations
-    pub db: Arc<Mutex<Option<Surreal<Any>>>>,
-    /// Connection ID for tracking this client session
-    pub connection_id: String,
-    /// The configured SurrealDB endpoint URL (optionally set at server startup)
-    pub endpoint: Option<String>,
-    /// The configured SurrealDB namespace (optionally set at server startup)
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct SaveConnectionProfileParams {
+    #[schemars(description = "The name to save the connection profile as.")]
+    pub name: String,
+    #[schemars(
+        description = "The SurrealDB endpoint URL. For cloud instances, use 'cloud:instance_id' format."
+    )]
+    pub endpoint: String,
+    #[schemars(description = "The namespace to use for organizing data.")]
     pub namespace: Option<String>,
-    /// The configured SurrealDB database (optionally set at server startup)
+    #[schemars(description = "The database name within the namespace.")]
     pub database: Option<String>,
-    /// The configured SurrealDB username (optionally set at server startup)
-    pub user: Option<String>,
+    #[schemars(description = "Username for authentication.")]
+    pub username: Option<String>,
+    #[schemars(description = "Password for authentication.")]
+    pub password: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct ConnectProfileParams {
+    #[schemars(description = "The name of the saved connection profile to connect with.")]
+    pub name: String,
+}
+
+/// The details used to establish the current connection, kept so that the
+/// session can reconnect if the connection is lost
+#[derive(Clone)]
+pub struct ConnectionDetails {
+    /// The URL of the SurrealDB server
+    pub url: String,
+    /// The authentication token, for SurrealDB Cloud instances
+    pub token: Option<String>,
+    /// The username used for authentication
+    pub username: Option<String>,
+    /// The password used for authentication
+    pub password: Option<String>,
+}
+
+impl ConnectionDetails {
+    /// Establish a new connection, selecting the given namespace and database
+    pub async fn connect(
+        &self,
+        namespace: Option<&str>,
+        database: Option<&str>,
+    ) -> Result<Surreal<Any>, anyhow::Error> {
+        let user = self.username.as_deref();
+        let pass = self.password.as_deref();
+        match &self.token {
+            Some(token) => {
+                db::create_client_connection_with_token(
+                    &self.url, token, user, pass, namespace, database,
+                )
+                .await
+            }
+            None => db::create_client_connection(&self.url, user, pass, namespace, database).await,
+        }
+    }
+
+    /// Whether a new connection would reach the same data
+    ///
+    /// Connecting to an in-memory endpoint again creates a new, empty database,
+    /// so sessions using one are not reconnected.
+    pub fn can_reconnect(&self) -> bool {
+        storage_engine(&self.url) != "memory"
+    }
+}
+
+/// The endpoint, namespace, and database currently in use by a session
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ConnectionContext {
+    /// The endpoint that the session is connected to
+    pub endpoint: Option<String>,
+    /// The namespace that is currently selected
+    pub namespace: Option<String>,
+    /// The database that is currently selected
+    pub database: Option<String>,
+    /// The version of the connected SurrealDB server, if known
+    pub version: Option<String>,
+    /// The time at which the current connection was established
+    #[serde(skip)]
+    pub connected_at: Option<Instant>,
+}
+
+/// Statistics about the queries executed by a session since it last connected
+#[derive(Clone, Debug, Default)]
+pub struct SessionStats {
+    /// The number of queries executed
+    pub queries: u64,
+    /// The number of queries in which the query or any statement failed
+    pub failed_queries: u64,
+    /// The total time spent executing queries
+    pub total_duration: Duration,
+}
+
+impl SessionStats {
+    /// Record the outcome of an executed query
+    fn record(&mut self, res: &engine::Response) {
+        self.queries += 1;
+        self.total_duration += res.duration;
+        if res.error.is_some() || res.statements.iter().any(|s| s.result.is_err()) {
+            self.failed_queries += 1;
+        }
+    }
+
+    /// Convert the statistics to a JSON object
+    fn to_json(&self) -> serde_json::Value {
+        // Calculate the total and average durations in milliseconds
+        let total_ms = self.total_duration.as_secs_f64() * 1000.0;
+        let average_ms = match self.queries {
+            0 => 0.0,
+            n => total_ms / n as f64,
+        };
+        serde_json::json!({
+            "queries": self.queries,
+            "failed_queries": self.failed_queries,
+            "total_duration_ms": total_ms,
+            "average_duration_ms": average_ms,
+        })
+    }
+}
+
+/// The notifications received for a live query which have not yet been polled
+#[derive(Debug, Default)]
+pub struct LiveNotifications {
+    /// The buffered notifications, oldest first
+    pub pending: VecDeque<serde_json::Value>,
+    /// The number of notifications discarded since the last poll, as the buffer was full
+    pub dropped: u64,
+}
+
+impl LiveNotifications {
+    /// Buffer a notification, discarding the oldest notification if the buffer is full
+    fn push(&mut self, notification: serde_json::Value) {
+        if self.pending.len() >= LIVE_QUERY_BUFFER_SIZE {
+            self.pending.pop_front();
+            self.dropped += 1;
+        }
+        self.pending.push_back(notification);
+    }
+}
+
+/// A live query started by a session
+#[derive(Debug)]
+pub struct LiveQuery {
+    /// The table which the live query selects from
+    pub table: String,
+    /// The notifications received which have not yet been polled
+    pub notifications: Arc<Mutex<LiveNotifications>>,
+    /// The task receiving notifications, which kills the live query when aborted
+    pub task: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for LiveQuery {
+    /// Abort the task receiving notifications, as dropping its handle would
+    /// detach the task, leaving the live query running after the session ends
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A query buffered by the open transaction of a session
+#[derive(Clone, Debug)]
+pub struct BufferedQuery {
+    /// The query, with LET statements which bind its parameters before it, and
+    /// which reset its parameters after it, so they are not seen by later queries
+    pub statements: String,
+    /// Whether each statement has a result of the query, rather than binding or
+    /// resetting a parameter
+    pub results: Vec<bool>,
+}
+
+/// Options which control the behaviour of each client session
+#[derive(Clone, Debug, Default)]
+pub struct ServiceOptions {
+    /// Whether to include statement execution times in query results
+    pub include_timing: bool,
+    /// Whether the reset_database tool is allowed to remove all tables
+    pub allow_reset: bool,
+    /// The directory which the export_to_file tool is allowed to write to
+    pub export_dir: Option<PathBuf>,
+    /// Whether tools which execute DEFINE statements are enabled
+    pub allow_define_statements: bool,
+    /// The maximum depth of nested values in tool results, if limited
+    pub max_result_depth: Option<usize>,
+    /// The maximum size of the serialized tool results, if limited
+    pub max_result_bytes: Option<usize>,
+    /// Whether the SurrealDB Cloud tools are disabled
+    pub disable_cloud: bool,
+    /// Whether the tools which run raw SurrealQL are disabled
+    pub disable_query: bool,
+    /// Whether the MCP prompts are disabled
+    pub disable_prompts: bool,
+    /// Whether the MCP resources are disabled
+    pub disable_resources: bool,
+    /// Whether the tools which modify data are disabled
+    pub read_only: bool,
+    /// Whether parameters which are empty strings are bound as NONE
+    pub empty_string_as_none: bool,
+    /// The patterns of functions which can be called (all if empty)
+    pub allowed_functions: Vec<String>,
+    /// The patterns of functions which can not be called
+    pub denied_functions: Vec<String>,
+    /// Whether to check the functions called in every query
+    pub strict_functions: bool,
+    /// Whether UPDATE and DELETE statements require a WHERE clause unless confirmed
+    pub require_where_for_mutations: bool,
+    /// The maximum duration to wait for each query to complete, if limited
+    pub query_timeout: Option<Duration>,
+    /// The maximum number of MCP prompts which are registered, if limited
+    pub max_prompts: Option<usize>,
+    /// The maximum number of MCP resources which are registered, if limited
+    pub max_resources: Option<usize>,
+    /// The maximum number of tools, prompts, or resources in each list page, if limited
+    pub max_response_items: Option<usize>,
+    /// The duration after which an unused SurrealDB Cloud auth token is cleared
+    pub cloud_token_idle_timeout: Option<Duration>,
+    /// Whether tool results are wrapped in a consistent JSON envelope
+    pub response_envelope: bool,
+    /// The maximum size of each serialized tool result, if limited
+    pub max_message_bytes: Option<usize>,
+}
+
+impl ServiceOptions {
+    /// Get the names of the feature groups which are disabled for this session
+    pub fn disabled_features(&self) -> Vec<&'static str> {
+        [
+            ("cloud", self.disable_cloud),
+            ("raw_query", self.disable_query),
+            ("prompts", self.disable_prompts),
+            ("resources", self.disable_resources),
+            ("writes", self.read_only),
+        ]
+        .into_iter()
+        .filter(|(_, disabled)| *disabled)
+        .map(|(feature, _)| feature)
+        .collect()
+    }
+}
+
+#[derive(Clone)]
+pub struct SurrealService {
+    /// The SurrealDB client instance to use for database operations
+    pub db: Arc<Mutex<Option<Surreal<Any>>>>,
+    /// Connection ID for tracking this client session
+    pub connection_id: String,
+    /// The configured SurrealDB endpoint URL (optionally set at server startup)
+    pub endpoint: Option<String>,
+    /// The configured SurrealDB namespace (optionally set at server startup)
+    ///
+    /// This restricts which namespace can be used, and is not changed when
+    /// switching namespace. The namespace in use is tracked by `context`.
+    pub namespace: Option<String>,
+    /// The configured SurrealDB database (optionally set at server startup)
+    ///
+    /// This restricts which database can be used, and is not changed when
+    /// switching database. The database in use is tracked by `context`.
+    pub database: Option<String>,
+    /// The configured SurrealDB username (optionally set at server startup)
+    pub user: Option<String>,
     /// The configured SurrealDB password (optionally set at server startup)
     pub pass: Option<String>,
-    /// Timestamp when this connection was established
+    /// The endpoint, namespace, and database currently in use
+    pub context: Arc<Mutex<ConnectionContext>>,
+    /// The details of the last successful connection, used to reconnect
+    pub connection: Arc<Mutex<Option<ConnectionDetails>>>,
+    /// Timestamp when this client session was created
     pub connected_at: std::time::Instant,
+    /// Statistics about the queries executed since the session last connected
+    pub stats: Arc<Mutex<SessionStats>>,
+    /// The connection profiles saved by this session, by name
+    pub profiles: Arc<Mutex<HashMap<String, ConnectParams>>>,
+    /// The live queries started by this session, by live query ID
+    pub live_queries: Arc<Mutex<HashMap<Uuid, LiveQuery>>>,
+    /// The queries buffered by the open transaction, if a transaction is open
+    pub transaction: Arc<Mutex<Option<Vec<BufferedQuery>>>>,
+    /// The identity of the authenticated bearer token, if authenticated
+    pub identity: Arc<Mutex<Option<TokenIdentity>>>,
+    /// Options which control the behaviour of this session
+    pub options: ServiceOptions,
     /// Router containing all available tools
     pub tool_router: ToolRouter<Self>,
     /// Cloud client for SurrealDB Cloud operations
@@ -255,12 +1081,41 @@ impl SurrealService {
             database: None,
             user: None,
             pass: None,
+            context: Arc::new(Mutex::new(ConnectionContext::default())),
+            connection: Arc::new(Mutex::new(None)),
+            stats: Arc::new(Mutex::new(SessionStats::default())),
+            profiles: Arc::new(Mutex::new(HashMap::new())),
+            live_queries: Arc::new(Mutex::new(HashMap::new())),
+            transaction: Arc::new(Mutex::new(None)),
+            identity: Arc::new(Mutex::new(None)),
             connected_at: Instant::now(),
+            options: ServiceOptions::default(),
             tool_router: Self::tool_router(),
             cloud_client: Arc::new(Client::new()),
         }
     }
 
+    /// Create the tool router, removing any tools which are disabled by the options
+    fn tool_router_with_options(options: &ServiceOptions) -> ToolRouter<Self> {
+        let mut router = Self::tool_router();
+        // Get the tools which are disabled
+        let mut disabled: Vec<&str> = Vec::new();
+        if options.disable_cloud {
+            disabled.extend(CLOUD_TOOLS);
+        }
+        if options.disable_query {
+            disabled.extend(RAW_QUERY_TOOLS);
+        }
+        if options.read_only {
+            disabled.extend(WRITE_TOOLS);
+        }
+        // Remove the disabled tools
+        for name in disabled {
+            router.remove_route::<(), ()>(name);
+        }
+        router
+    }
+
     /// Create a new SurrealService instance with startup configuration.
     ///
     /// This function initializes a new SurrealService instance with predefined
@@ -274,6 +1129,9 @@ impl SurrealService {
     /// * `database` - The database to use (optional)
     /// * `user` - Username for authentication (optional)
     /// * `pass` - Password for authentication (optional)
+    /// * `access_token` - SurrealDB Cloud access token (optional)
+    /// * `refresh_token` - SurrealDB Cloud refresh token (optional)
+    /// * `options` - Options which control the behaviour of this session
     #[allow(clippy::too_many_arguments)]
     pub fn with_config(
         connection_id: String,
@@ -284,6 +1142,7 @@ impl SurrealService {
         pass: Option<String>,
         access_token: Option<String>,
         refresh_token: Option<String>,
+        options: ServiceOptions,
     ) -> Self {
         // Output debugging information
         info!(
@@ -303,13 +1162,17 @@ impl SurrealService {
                 "Creating new cloud client with tokens"
             );
             // Create cloud client with tokens
-            Arc::new(Client::with_tokens(access, refresh))
+            Client::with_tokens(access, refresh)
         } else {
             // Output debugging information
             info!("Creating new cloud client without tokens");
             // Create cloud client without tokens
-            Arc::new(Client::new())
+            Client::new()
         };
+        // Clear the cloud auth token when it is unused for too long
+        let cloud_client =
+            Arc::new(cloud_client.with_idle_timeout(options.cloud_token_idle_timeout));
+        Client::spawn_idle_cleanup(&cloud_client);
         // Create a new service instance
         Self {
             db: Arc::new(Mutex::new(None)),
@@ -319,8 +1182,16 @@ impl SurrealService {
             database,
             user,
             pass,
+            context: Arc::new(Mutex::new(ConnectionContext::default())),
+            connection: Arc::new(Mutex::new(None)),
+            stats: Arc::new(Mutex::new(SessionStats::default())),
+            profiles: Arc::new(Mutex::new(HashMap::new())),
+            live_queries: Arc::new(Mutex::new(HashMap::new())),
+            transaction: Arc::new(Mutex::new(None)),
+            identity: Arc::new(Mutex::new(None)),
             connected_at: Instant::now(),
-            tool_router: Self::tool_router(),
+            tool_router: Self::tool_router_with_options(&options),
+            options,
             cloud_client,
         }
     }
@@ -346,7 +1217,21 @@ For security, you can use parameterized queries to prevent SQL injection by prov
 parameters that will be safely bound to the query. Use $param_name syntax in your query 
 and provide the parameters in the parameters field.
 
-The query results are returned as text, or an error occurs if the query execution fails.
+The query results are returned as a JSON array with the outcome of each statement, 
+numbered from 0, such as {"statement": 0, "status": "OK", "result": [...], "time_ms": 0.2} 
+or {"statement": 1, "status": "ERR", "error": "...", "time_ms": 0.1}, so that a failing 
+statement does not prevent the results of the other statements from being returned. An 
+error occurs if the query as a whole can not be executed, such as when it can not be 
+parsed. Set include_timing to true to also return the server-reported execution time of 
+each statement.
+
+Parameters which are empty strings are bound as empty strings by default. Set 
+empty_string_as_none to true to bind them as NONE instead. Only top-level parameter 
+values are affected, and empty strings nested in objects or arrays are left unchanged.
+
+Set explain to true to return the query plan of a single SELECT statement instead of its 
+results, such as to check which indexes it uses, or explain_full to also return the 
+number of records fetched. Statements which modify data can not be explained.
 
 Examples:
 - SELECT * FROM person
@@ -365,11 +1250,23 @@ Parameterized query examples:
         let QueryParams {
             query: query_string,
             parameters,
+            include_timing,
+            empty_string_as_none,
+            confirm_full_table,
+            explain,
+            explain_full,
         } = params.0;
         // Increment tool usage counter
         counter!("surrealmcp.tools.query").increment(1);
         // Output debugging information
         debug!(query_string = %query_string, "Executing SurrealQL query");
+        // Return the query plan instead of the results if requested
+        let query_string = match (explain, explain_full) {
+            (_, Some(true)) => explain_query(&query_string, true),
+            (Some(true), _) => explain_query(&query_string, false),
+            _ => Ok(query_string),
+        }
+        .map_err(|e| McpError::internal_error(e, None))?;
         // Convert tool parameters to SurrealQL parameters
         let parameters = if let Some(params) = parameters {
             let mut converted = HashMap::new();
@@ -382,10 +1279,144 @@ Parameterized query examples:
         } else {
             None
         };
+        // Bind empty strings as NONE if configured for this call
+        let empty_string_as_none =
+            empty_string_as_none.unwrap_or(self.options.empty_string_as_none);
         // Use the internal query function
-        self.query_internal(query_string, parameters)
+        let mut res = self
+            .query_internal_with(
+                query_string,
+                parameters,
+                empty_string_as_none,
+                confirm_full_table,
+            )
+            .await?;
+        // Override the statement timing setting for this call
+        if let Some(include_timing) = include_timing {
+            res.include_timing = include_timing;
+        }
+        // Return the MCP result, reporting the outcome of each statement
+        res.to_statement_results()
+    }
+
+    /// Compute the value of a SurrealQL expression.
+    ///
+    /// This function executes a SurrealDB RETURN statement to evaluate a single
+    /// expression, without needing to query a table. The expression can reference
+    /// bound parameters, and can not contain multiple statements.
+    #[tool(description = r#"
+Compute the value of a SurrealQL expression using a RETURN statement.
+
+This function evaluates a single SurrealQL expression, such as arithmetic, function calls, 
+or string operations, and returns the computed value. Use parameters to pass values into 
+the expression safely. The expression can not contain multiple statements or comments.
+
+Examples:
+- compute("math::pi * $r * $r", Some({"r": 2}))  # Area of a circle
+- compute("string::uppercase('hello')")  # String function
+- compute("time::now()")  # Current time
+"#)]
+    pub async fn compute(
+        &self,
+        params: Parameters<ComputeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let ComputeParams {
+            expression,
+            parameters,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.compute").increment(1);
+        // Output debugging information
+        debug!(expression = %expression, "Computing expression");
+        // Validate the expression
+        let expression = validate_clause("RETURN", &expression)
+            .map_err(|e| McpError::internal_error(e, None))?;
+        // Build the RETURN statement
+        let query = format!("RETURN {expression}");
+        // Create parameters with native SurrealDB types
+        let mut params = HashMap::new();
+        // Add user-provided parameters if any
+        bind_user_parameters(&mut params, parameters)?;
+        // Output debugging information
+        trace!("Computing expression with query: {query}");
+        // Execute the final query
+        let value = self
+            .query_internal(query, Some(params))
             .await?
-            .to_mcp_result()
+            .take(0)
+            .map_err(McpError::from)?;
+        // Return the computed value
+        Ok(CallToolResult::success(vec![Content::text(
+            value.into_inner().into_json().to_string(),
+        )]))
+    }
+
+    /// Run a SurrealDB function with the given arguments.
+    ///
+    /// This function calls a builtin or custom SurrealDB function by name, with
+    /// each argument bound as a query parameter. The function must be permitted
+    /// by the allowed and denied functions configured for the server.
+    #[tool(description = r#"
+Run a SurrealDB function with the given arguments.
+
+This function calls a builtin function (such as string::uppercase or math::max) or a 
+custom function defined with DEFINE FUNCTION (such as fn::greet), and returns its result. 
+Each argument is bound as a query parameter, so values do not need to be escaped. The 
+server can be configured to only allow, or to deny, specific functions or packages of 
+functions, in which case calling a disallowed function returns an error.
+
+Examples:
+- run_function("string::uppercase", ["hello"])  # "HELLO"
+- run_function("math::max", [[1, 5, 3]])  # 5
+- run_function("fn::greet", ["Tobie"])  # Custom function
+"#)]
+    pub async fn run_function(
+        &self,
+        params: Parameters<RunFunctionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let RunFunctionParams { name, args } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.run_function").increment(1);
+        // Output debugging information
+        debug!(function = %name, "Running function");
+        // Validate the function name
+        let valid = name.split("::").all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        if !valid || !name.contains("::") {
+            return Err(McpError::internal_error(
+                format!(
+                    "Invalid function name '{name}'. Expected a name such as 'string::len' or 'fn::greet'"
+                ),
+                None,
+            ));
+        }
+        // Check the function can be called
+        self.require_allowed_function(&name)?;
+        // Create parameters with native SurrealDB types
+        let mut params = HashMap::new();
+        let mut placeholders = Vec::new();
+        for (i, arg) in args.unwrap_or_default().into_iter().enumerate() {
+            let key = format!("arg{i}");
+            let val = convert_json_to_surreal(arg, &key)
+                .map_err(|e| McpError::internal_error(e, None))?;
+            placeholders.push(format!("${key}"));
+            params.insert(key, val);
+        }
+        // Build the RETURN statement
+        let query = format!("RETURN {name}({})", placeholders.join(", "));
+        // Output debugging information
+        trace!("Running function with query: {query}");
+        // Execute the final query
+        let value = self
+            .query_internal(query, Some(params))
+            .await?
+            .take(0)
+            .map_err(McpError::from)?;
+        // Return the function result
+        Ok(CallToolResult::success(vec![Content::text(
+            value.into_inner().into_json().to_string(),
+        )]))
     }
 
     /// Execute a SurrealDB SELECT statement to retrieve records from the database.
@@ -402,13 +1433,35 @@ Execute a SurrealDB SELECT statement to retrieve records from the database.
 This function executes a SurrealDB SELECT statement to query records from the specified 
 tables or record IDs. Each item in the what parameter is parsed to determine if it's a 
 table name or a record ID. You can optionally add various clauses to filter, group, sort, 
-and paginate the results.
+and paginate the results. The ids parameter limits the results to a list of record IDs, 
+which are bound as a query parameter, and can be combined with a WHERE clause. The 
+order_by and group_by parameters are structured alternatives to the order and group 
+clauses, with each field name and sort direction validated before being added. The 
+with_index parameter forces the query to use specific indexes, and the explain parameter 
+returns the query plan instead of the results, to confirm which indexes are used, with 
+explain_full also returning the number of records fetched. The 
+range parameter selects a range of record IDs from a table, such as person:1..=100, with 
+the start and end keys converted to record ID keys. Range targets such as person:1..100 
+can also be passed directly in the targets. The fetch parameter resolves record links 
+in the selected fields into the linked records, in the same query. The fields parameter 
+returns only the given fields of each record, instead of every field. The parallel 
+parameter processes the records in parallel, and the timeout parameter limits how long 
+the query can run for, with a duration such as '5s'.
 
 Examples:
 - select(["person"])  # All records from person table
 - select(["person:john"])  # Specific record
 - select(["person", "article"])  # All records from both tables
 - select(["person:john", "article:123"])  # Specific records
+- select(["person"], ids=["person:john", "person:jane"])  # Records matching a list of record IDs
+- select(range={"table": "person", "start": 1, "end": 100, "inclusive": true})  # Records person:1 to person:100
+- select(["person:1..=100"])  # The same record range as a target
+- select(["person"], fields=["name", "address.city"])  # Only the name and city of each person
+- select(["post"], limit_clause="10", fetch_clause="author, comments")  # Posts with their linked author and comments
+- select(["person"], order_by=[{"field": "age", "direction": "DESC"}, {"field": "name"}])  # Sorted by validated fields
+- select(["person"], group_by=["city"])  # Grouped by validated fields
+- select(["event"], Some("type = 'click'"), parallel=true, timeout="5s")  # Parallel scan with a timeout
+- select(["person"], Some("age > 25"), with_index=["person_age"], explain=true)  # Query plan when forcing an index
 - select(["person"], Some("age > 25"), None, None, Some("name ASC"), Some("10"), None)  # Filtered and sorted
 - select(["person"], Some("age > $min_age"), None, Some("city"), Some("age DESC"), Some("10"), Some("20"), Some({"min_age": 25}))  # With parameters
 - select(["article"], Some("published = true"), Some("author"), None, Some("created_at DESC"), Some("5"), None)  # With split and pagination
@@ -418,60 +1471,209 @@ Examples:
         &self,
         params: Parameters<SelectParams>,
     ) -> Result<CallToolResult, McpError> {
-        let SelectParams {
-            targets,
+        let params = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.select").increment(1);
+        // Output debugging information
+        debug!(targets = ?params.targets, "Selecting records");
+        // Build the SELECT statement and its parameters
+        let (query, params) = build_select_query(params)?;
+        // Output debugging information
+        trace!("Selecting records with query: {query}");
+        // Execute the final query
+        self.query_internal(query, Some(params))
+            .await?
+            .to_mcp_result()
+    }
+
+    /// Count the records in a table, optionally filtered by a WHERE clause.
+    ///
+    /// This function executes a SurrealDB SELECT count() statement grouped across
+    /// all records, so that only the number of matching records is returned rather
+    /// than the records themselves.
+    #[tool(description = r#"
+Count the records in a table, optionally filtered by a WHERE clause.
+
+This function returns the number of matching records as {"count": n}, without fetching 
+the records themselves. Use this instead of select when only the number of records is 
+needed.
+
+Examples:
+- count("person")  # {"count": 3}
+- count("person", Some("age > $min_age"), Some({"min_age": 25}))  # Filtered count
+"#)]
+    pub async fn count(&self, params: Parameters<CountParams>) -> Result<CallToolResult, McpError> {
+        let CountParams {
+            target,
             where_clause,
-            split_clause,
-            group_clause,
-            order_clause,
-            limit_clause,
-            start_clause,
             parameters,
         } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.select").increment(1);
+        counter!("surrealmcp.tools.count").increment(1);
         // Output debugging information
-        debug!(targets = ?targets, "Selecting records");
+        debug!(target = %target, "Counting records");
         // Build the initial query string
-        let mut query = "SELECT * FROM ".to_string();
-        // Process the tables and Record IDs
-        query.push_str(&parse_targets(targets).map_err(|e| McpError::internal_error(e, None))?);
+        let mut query = "SELECT count() FROM ".to_string();
+        // Process the table or Record ID
+        query.push_str(&parse_target(target).map_err(|e| McpError::internal_error(e, None))?);
         // Add the where clause if provided
         if let Some(v) = where_clause {
+            let v = validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
             query.push_str(&format!(" WHERE {v}"));
         }
-        // Add the split on clause if provided
-        if let Some(v) = split_clause {
-            query.push_str(&format!(" SPLIT ON {v}"));
-        }
-        // Add the group by clause if provided
-        if let Some(v) = group_clause {
-            query.push_str(&format!(" GROUP BY {v}"));
-        }
-        // Add the order by clause if provided
-        if let Some(v) = order_clause {
-            query.push_str(&format!(" ORDER BY {v}"));
-        }
-        // Add the limit clause if provided
-        if let Some(v) = limit_clause {
-            query.push_str(&format!(" LIMIT BY {v}"));
-        }
-        // Add the start at clause if provided
-        if let Some(v) = start_clause {
-            query.push_str(&format!(" START AT {v}"));
+        // Group all records into a single count
+        query.push_str(" GROUP ALL");
+        // Create parameters with native SurrealDB types
+        let mut params = HashMap::new();
+        // Add user-provided parameters if any
+        bind_user_parameters(&mut params, parameters)?;
+        // Output debugging information
+        trace!("Counting records with query: {query}");
+        // Execute the final query
+        let value = self
+            .query_internal(query, Some(params))
+            .await?
+            .take(0)
+            .map_err(McpError::from)?
+            .into_inner()
+            .into_json();
+        // No rows are returned when no records match
+        let count = value[0]["count"].as_u64().unwrap_or(0);
+        // Return the count
+        let result = serde_json::json!({ "count": count });
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Find the records with the nearest vectors to a given vector.
+    ///
+    /// This function executes a SurrealDB SELECT statement with the KNN operator,
+    /// returning the nearest neighbours ranked by their distance to the vector,
+    /// which is bound as a query parameter.
+    #[tool(description = r#"
+Find the records whose vectors are nearest to a given vector, such as similar embeddings.
+
+This function returns up to k records ordered by their distance to the vector, with the 
+distance included as the dist field of each record. The records can also be filtered 
+with a WHERE clause. The KNN operator depends on how the field is indexed:
+- With an MTREE index, omit ef and distance.
+- With an HNSW index, set ef to the size of the candidate list, such as 40.
+- Without an index, set distance to 'euclidean', 'cosine', 'manhattan', 'chebyshev', 
+  'hamming', 'jaccard', or 'pearson' to compare every record.
+
+Examples:
+- vector_search("document", "embedding", [0.1, 0.2, 0.3], 5)  # MTREE index
+- vector_search("document", "embedding", [0.1, 0.2, 0.3], 5, None, None, 40)  # HNSW index
+- vector_search("document", "embedding", [0.1, 0.2, 0.3], 5, Some("lang = $lang"), Some({"lang": "en"}), None, "cosine")
+"#)]
+    pub async fn vector_search(
+        &self,
+        params: Parameters<VectorSearchParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let VectorSearchParams {
+            target,
+            field,
+            vector,
+            k,
+            where_clause,
+            parameters,
+            ef,
+            distance,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.vector_search").increment(1);
+        // Output debugging information
+        debug!(target = %target, field = %field, k, "Searching for nearest vectors");
+        // Validate the search options
+        let operator = knn_operator(&field, k, ef, distance.as_deref())
+            .map_err(|e| McpError::internal_error(e, None))?;
+        // Build the initial query string
+        let mut query = "SELECT *, vector::distance::knn() AS dist FROM ".to_string();
+        // Process the table
+        query.push_str(&parse_target(target).map_err(|e| McpError::internal_error(e, None))?);
+        // Add the nearest neighbour condition
+        query.push_str(&format!(" WHERE {operator}"));
+        // Add the where clause if provided
+        if let Some(v) = where_clause {
+            let v = validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" AND ({v})"));
         }
+        // Rank the records by distance
+        query.push_str(" ORDER BY dist");
         // Create parameters with native SurrealDB types
         let mut params = HashMap::new();
         // Add user-provided parameters if any
-        if let Some(variables) = parameters {
-            for (key, val) in variables {
-                let val = convert_json_to_surreal(val, &key)
-                    .map_err(|e| McpError::internal_error(e, None))?;
-                params.insert(key, val);
-            }
+        bind_user_parameters(&mut params, parameters)?;
+        // Add the vector as a native array
+        let vector = convert_json_to_surreal(serde_json::json!(vector), "vector")
+            .map_err(|e| McpError::internal_error(e, None))?;
+        params.insert("vector_search_vector".to_string(), vector);
+        // Output debugging information
+        trace!("Searching for nearest vectors with query: {query}");
+        // Execute the final query
+        self.query_internal(query, Some(params))
+            .await?
+            .to_mcp_result()
+    }
+
+    /// Find the records which match a full-text search query.
+    ///
+    /// This function executes a SurrealDB SELECT statement with the full-text
+    /// match operator, which uses the search index on the field. The search text
+    /// is bound as a query parameter.
+    #[tool(description = r#"
+Find the records whose text matches a full-text search query.
+
+This function returns the records where the field matches the search text, using the 
+analyzer of the search index on the field, so that words are matched regardless of case 
+or word endings depending on the analyzer. A full-text search index must already exist on 
+the field, which can be defined with define_index, otherwise SurrealDB returns an error. Set 
+highlight to true to include the field text with the matched terms wrapped in <b></b> as 
+the highlight field of each record.
+
+Examples:
+- full_text_search("post", "body", "quick fox")  # Find matching posts
+- full_text_search("post", "body", "quick fox", true, 10)  # Highlight the matches in the first 10 posts
+"#)]
+    pub async fn full_text_search(
+        &self,
+        params: Parameters<FullTextParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let FullTextParams {
+            target,
+            field,
+            query: text,
+            highlight,
+            limit,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.full_text_search").increment(1);
+        // Output debugging information
+        debug!(target = %target, field = %field, "Searching for matching text");
+        // Validate the field name
+        validate_field_name(&field).map_err(|e| McpError::internal_error(e, None))?;
+        // Build the initial query string
+        let mut query = match highlight {
+            Some(true) => "SELECT *, search::highlight('<b>', '</b>', 0) AS highlight FROM ",
+            _ => "SELECT * FROM ",
+        }
+        .to_string();
+        // Process the table
+        query.push_str(&parse_target(target).map_err(|e| McpError::internal_error(e, None))?);
+        // Add the full-text match condition
+        query.push_str(&format!(" WHERE {field} @0@ $full_text_query"));
+        // Add the limit clause if provided
+        if let Some(limit) = limit {
+            query.push_str(&format!(" LIMIT {limit}"));
         }
+        // Bind the search text
+        let params = HashMap::from([(
+            "full_text_query".to_string(),
+            Value::from_inner(surrealdb::sql::Value::from(text)),
+        )]);
         // Output debugging information
-        trace!("Selecting records with query: {query}");
+        trace!("Searching for matching text with query: {query}");
         // Execute the final query
         self.query_internal(query, Some(params))
             .await?
@@ -493,50 +1695,27 @@ and will be used as the content for the new records.
 
 This is useful for batch inserting multiple records at once into a table.
 The INSERT statement uses the syntax: INSERT [ IGNORE | RELATION ] INTO table [obj1, obj2, ...]
+For bulk inserts, set parallel to true to process the records in parallel, and timeout to 
+a duration such as '30s' to limit how long the insert can run for.
 
 Examples:
 - insert("person", [{"name": "Tobie", "age": 38}, {"name": "Jaime", "age": 40}])
 - insert("article", [{"id": "article:123", "title": "New Article", "content": "Hello World"}])
 - insert("person", [{"id": "jaime", "name": "Jaime"}], Some(true))  # With IGNORE
 - insert("likes", [{"in": "person:1", "out": "person:2"}], None, Some(true))  # Relation table
+- insert("event", [{"type": "click"}, {"type": "view"}], parallel=true, timeout="30s")  # Bulk insert
 "#)]
     pub async fn insert(
         &self,
         params: Parameters<InsertParams>,
     ) -> Result<CallToolResult, McpError> {
-        let InsertParams {
-            target,
-            values,
-            ignore,
-            relation,
-        } = params.0;
+        let params = params.0;
         // Increment tool usage counter
         counter!("surrealmcp.tools.insert").increment(1);
         // Output debugging information
-        debug!(target = %target, "Inserting records");
-        // Build the initial query string
-        let mut query = "INSERT ".to_string();
-        // Add IGNORE keyword if specified
-        if ignore.unwrap_or(false) {
-            query.push_str("IGNORE ");
-        }
-        // Add RELATION keyword if specified
-        if relation.unwrap_or(false) {
-            query.push_str("RELATION ");
-        }
-        query.push_str("INTO ");
-        // Process the table and Record ID
-        query.push_str(&parse_target(target).map_err(|e| McpError::internal_error(e, None))?);
-        // Add the data content clause
-        query.push_str(" $data");
-        // Create parameters with native SurrealDB types
-        let mut params = HashMap::new();
-        // Add the record data
-        let values_array: Vec<serde_json::Value> =
-            values.into_iter().map(serde_json::Value::Object).collect();
-        let data = convert_json_to_surreal(serde_json::Value::Array(values_array), "data")
-            .map_err(|e| McpError::internal_error(e, None))?;
-        params.insert("data".to_string(), data);
+        debug!(target = %params.target, "Inserting records");
+        // Build the INSERT statement and its parameters
+        let (query, params) = build_insert_query(params)?;
         // Output debugging information
         trace!("Inserting records with query: {query}");
         // Execute the final query
@@ -559,12 +1738,19 @@ specified table or with specific record ID. The data is provided as a JSON value
 and will be used as the content for the new record.
 
 This is useful for creating users, articles, products, or any other entity in your database.
+
+Set return_clause to NONE, BEFORE, AFTER, DIFF, or a list of fields to control what is 
+returned for the created record.
 "#)]
     pub async fn create(
         &self,
         params: Parameters<CreateParams>,
     ) -> Result<CallToolResult, McpError> {
-        let CreateParams { target, data } = params.0;
+        let CreateParams {
+            target,
+            data,
+            return_clause,
+        } = params.0;
         // Increment tool usage counter
         counter!("surrealmcp.tools.create").increment(1);
         // Output debugging information
@@ -575,6 +1761,11 @@ This is useful for creating users, articles, products, or any other entity in yo
         query.push_str(&parse_target(target).map_err(|e| McpError::internal_error(e, None))?);
         // Add the data content clause
         query.push_str(" CONTENT $data");
+        // Add the return clause if provided
+        if let Some(v) = return_clause {
+            let v = validate_return_clause(&v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" RETURN {v}"));
+        }
         // Create parameters with native SurrealDB types
         let mut params = HashMap::new();
         // Add the record data
@@ -606,6 +1797,10 @@ Examples:
 - upsert(["person:john"], {"name": "John", "age": 30})  # Creates or updates specific record
 - upsert(["person"], {"name": "Jane", "age": 25}, Some("age > 18"))  # Upserts with condition
 - upsert(["article:123"], {"title": "New Title"}, None, Some({"status": "published"}))  # Merge mode
+- upsert(["counter:visits"], None, None, None, None, "count += 1")  # Increments a counter, creating it if needed
+
+Instead of data, set_clause can apply SET expressions such as 'count += 1'. Bind values 
+with parameters, such as 'name = $name', rather than writing them into the expressions.
 "#)]
     pub async fn upsert(
         &self,
@@ -617,6 +1812,7 @@ Examples:
             merge_data,
             replace_data,
             content_data,
+            set_clause,
             where_clause,
             parameters,
         } = params.0;
@@ -630,60 +1826,22 @@ Examples:
         let mut query = "UPSERT ".to_string();
         // Process the tables and Record IDs
         query.push_str(&parse_targets(targets).map_err(|e| McpError::internal_error(e, None))?);
-        // Add the data content clause based on the mode
-        match (replace_data, content_data, merge_data, patch_data) {
-            (Some(v), None, None, None) => {
-                query.push_str(" REPLACE $data");
-                // Add the data input as a parameter
-                params.insert(
-                    "data".to_string(),
-                    convert_json_to_surreal(v, "data")
-                        .map_err(|e| McpError::internal_error(e, None))?,
-                );
-            }
-            (None, Some(v), None, None) => {
-                query.push_str(" CONTENT $data");
-                // Add the data input as a parameter
-                params.insert(
-                    "data".to_string(),
-                    convert_json_to_surreal(v, "data")
-                        .map_err(|e| McpError::internal_error(e, None))?,
-                );
-            }
-            (None, None, Some(v), None) => {
-                query.push_str(" MERGE $data");
-                // Add the data input as a parameter
-                params.insert(
-                    "data".to_string(),
-                    convert_json_to_surreal(v, "data")
-                        .map_err(|e| McpError::internal_error(e, None))?,
-                );
-            }
-            (None, None, None, Some(v)) => {
-                query.push_str(" PATCH $data");
-                // Add the data input as a parameter
-                params.insert(
-                    "data".to_string(),
-                    convert_json_to_surreal(v, "data")
-                        .map_err(|e| McpError::internal_error(e, None))?,
-                );
-            }
-            _ => {
-                return Err(McpError::internal_error("Invalid upsert mode", None));
-            }
-        };
-        // Add the where clause if provided
-        if let Some(v) = where_clause {
-            query.push_str(&format!(" WHERE {v}"));
+        // Add the data clause for the supplied data mode
+        query.push_str(&data_clause(
+            &mut params,
+            content_data,
+            merge_data,
+            replace_data,
+            patch_data,
+            set_clause,
+        )?);
+        // Add the where clause if provided
+        if let Some(v) = where_clause {
+            let v = validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" WHERE {v}"));
         }
         // Add user-provided parameters if any
-        if let Some(variables) = parameters {
-            for (key, val) in variables {
-                let val = convert_json_to_surreal(val, &key)
-                    .map_err(|e| McpError::internal_error(e, None))?;
-                params.insert(key, val);
-            }
-        }
+        bind_user_parameters(&mut params, parameters)?;
         // Output debugging information
         trace!("Upserting records with query: {query}");
         // Execute the final query
@@ -706,6 +1864,12 @@ This function executes a SurrealDB UPDATE statement to modify the content of rec
 in the database. The what parameter accepts an array where each item can be either a 
 table name or a specific record ID, similar to the select function.
 
+If the server requires a WHERE clause for mutations, updating a table without a WHERE 
+clause is rejected unless confirm_full_table is set to true.
+
+Set return_clause to NONE, BEFORE, AFTER, DIFF, or a list of fields to control what is 
+returned for each updated record. Use NONE for large batch updates.
+
 Examples:
 - update(["person"], {"age": 31})  # Updates all records in person table
 - update(["person:john"], {"age": 31})  # Updates specific record
@@ -713,6 +1877,11 @@ Examples:
 - update(["person"], {"city": "NYC"}, Some("age > 25"), Some("merge"))  # Merges data for filtered records
 - update(["article"], {"status": "published"}, Some("draft = true"))  # Updates draft articles
 - update(["user"], {"last_login": "2024-01-15"}, Some("last_login < $cutoff_date"), Some("replace"), Some({ "cutoff_date": "2024-01-01" }))  # Parameterized query
+- update(["person"], None, None, None, None, "age += 1", Some("age < 30"))  # Increments the age of filtered records
+
+Instead of data, set_clause can apply SET expressions such as 'age += 1' or 
+'tags += $tag'. Bind values with parameters, such as 'name = $name', rather than 
+writing them into the expressions.
 "#)]
     pub async fn update(
         &self,
@@ -724,8 +1893,11 @@ Examples:
             merge_data,
             content_data,
             replace_data,
+            set_clause,
             where_clause,
             parameters,
+            confirm_full_table,
+            return_clause,
         } = params.0;
         // Increment tool usage counter
         counter!("surrealmcp.tools.update").increment(1);
@@ -737,66 +1909,38 @@ Examples:
         let mut query = "UPDATE ".to_string();
         // Process the tables and Record IDs
         query.push_str(&parse_targets(targets).map_err(|e| McpError::internal_error(e, None))?);
-        // Add the data content clause
-        match (replace_data, content_data, merge_data, patch_data) {
-            (Some(v), None, None, None) => {
-                query.push_str(" REPLACE $data");
-                // Add the data input as a parameter
-                params.insert(
-                    "data".to_string(),
-                    convert_json_to_surreal(v, "data")
-                        .map_err(|e| McpError::internal_error(e, None))?,
-                );
-            }
-            (None, Some(v), None, None) => {
-                query.push_str(" CONTENT $data");
-                // Add the data input as a parameter
-                params.insert(
-                    "data".to_string(),
-                    convert_json_to_surreal(v, "data")
-                        .map_err(|e| McpError::internal_error(e, None))?,
-                );
-            }
-            (None, None, Some(v), None) => {
-                query.push_str(" MERGE $data");
-                // Add the data input as a parameter
-                params.insert(
-                    "data".to_string(),
-                    convert_json_to_surreal(v, "data")
-                        .map_err(|e| McpError::internal_error(e, None))?,
-                );
-            }
-            (None, None, None, Some(v)) => {
-                query.push_str(" PATCH $data");
-                // Add the data input as a parameter
-                params.insert(
-                    "data".to_string(),
-                    convert_json_to_surreal(v, "data")
-                        .map_err(|e| McpError::internal_error(e, None))?,
-                );
-            }
-            _ => {
-                return Err(McpError::internal_error("Invalid update mode", None));
-            }
-        };
+        // Add the data clause for the supplied data mode
+        query.push_str(&data_clause(
+            &mut params,
+            content_data,
+            merge_data,
+            replace_data,
+            patch_data,
+            set_clause,
+        )?);
         // Add the where clause if provided
         if let Some(v) = where_clause {
+            let v = validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
             query.push_str(&format!(" WHERE {v}"));
         }
-        // Add user-provided parameters if any
-        if let Some(variables) = parameters {
-            for (key, val) in variables {
-                let val = convert_json_to_surreal(val, &key)
-                    .map_err(|e| McpError::internal_error(e, None))?;
-                params.insert(key, val);
-            }
+        // Add the return clause if provided
+        if let Some(v) = return_clause {
+            let v = validate_return_clause(&v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" RETURN {v}"));
         }
+        // Add user-provided parameters if any
+        bind_user_parameters(&mut params, parameters)?;
         // Output debugging information
         trace!("Updating records with query: {query}");
-        // Execute the final query
-        self.query_internal(query, Some(params))
-            .await?
-            .to_mcp_result()
+        // Execute the final query, which can modify whole tables if confirmed
+        self.query_internal_with(
+            query,
+            Some(params),
+            self.options.empty_string_as_none,
+            confirm_full_table,
+        )
+        .await?
+        .to_mcp_result()
     }
 
     /// Execute a SurrealDB DELETE statement to remove records from the database.
@@ -814,10 +1958,17 @@ This function executes a SurrealDB DELETE statement to remove records from the
 specified tables or specific record IDs. The what parameter accepts an array where 
 each item can be either a table name or a specific record ID, similar to the select function.
 
+Deleting from a table without a WHERE clause removes every record in the table, so it 
+is rejected unless confirm_all is set to true. Deleting specific record IDs does 
+not need to be confirmed.
+
+Set return_clause to BEFORE to return the deleted records, which are not returned by 
+default.
+
 Examples:
-- delete(["person"])  # Deletes all records from person table
+- delete(["person"], None, None, true)  # Deletes all records from person table
 - delete(["person:john"])  # Deletes specific record
-- delete(["person", "article"])  # Deletes all records from multiple tables
+- delete(["person", "article"], None, None, true)  # Deletes all records from multiple tables
 - delete(["person"], Some("age < 18"))  # Deletes records where age < 18
 - delete(["article"], Some("published = false"))  # Deletes unpublished articles
 - delete(["user"], Some("last_login < '2024-01-01'"))  # Deletes inactive users
@@ -831,6 +1982,8 @@ Examples:
             targets,
             where_clause,
             parameters,
+            confirm_all,
+            return_clause,
         } = params.0;
         // Increment tool usage counter
         counter!("surrealmcp.tools.delete").increment(1);
@@ -842,24 +1995,32 @@ Examples:
         query.push_str(&parse_targets(targets).map_err(|e| McpError::internal_error(e, None))?);
         // Add the where clause if provided
         if let Some(v) = where_clause {
+            let v = validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
             query.push_str(&format!(" WHERE {v}"));
         }
+        // Add the return clause if provided
+        if let Some(v) = return_clause {
+            let v = validate_return_clause(&v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" RETURN {v}"));
+        }
         // Create parameters with native SurrealDB types
         let mut params = HashMap::new();
         // Add user-provided parameters if any
-        if let Some(variables) = parameters {
-            for (key, val) in variables {
-                let val = convert_json_to_surreal(val, &key)
-                    .map_err(|e| McpError::internal_error(e, None))?;
-                params.insert(key, val);
-            }
-        }
+        bind_user_parameters(&mut params, parameters)?;
+        // Check the query does not delete whole tables unless confirmed, which is
+        // always required, as a missing WHERE clause empties the whole table
+        reject_full_table_mutations(&query, confirm_all, "confirm_all")?;
         // Output debugging information
         trace!("Deleting records with query: {query}");
-        // Execute the final query
-        self.query_internal(query, Some(params))
-            .await?
-            .to_mcp_result()
+        // Execute the final query, which can modify whole tables if confirmed
+        self.query_internal_with(
+            query,
+            Some(params),
+            self.options.empty_string_as_none,
+            confirm_all,
+        )
+        .await?
+        .to_mcp_result()
     }
 
     /// Create a relationship between two records in the database.
@@ -908,7 +2069,7 @@ Examples:
         query.push_str(&format!(
             "[{}]->{}->[{}]",
             parse_targets(from).map_err(|e| McpError::internal_error(e, None))?,
-            table,
+            quote_ident(&table).map_err(|e| McpError::internal_error(e, None))?,
             parse_targets(with).map_err(|e| McpError::internal_error(e, None))?
         ));
         // Add the data content clause
@@ -922,13 +2083,7 @@ Examples:
             );
         }
         // Add user-provided parameters if any
-        if let Some(variables) = parameters {
-            for (key, val) in variables {
-                let val = convert_json_to_surreal(val, &key)
-                    .map_err(|e| McpError::internal_error(e, None))?;
-                params.insert(key, val);
-            }
-        }
+        bind_user_parameters(&mut params, parameters)?;
         // Output debugging information
         trace!("Relating records with query: {query}");
         // Execute the final query
@@ -937,1173 +2092,9221 @@ Examples:
             .to_mcp_result()
     }
 
-    #[tool(description = "List SurrealDB Cloud organizations")]
-    pub async fn list_cloud_organizations(
+    /// Preview the result of merging data into a record, without applying it.
+    ///
+    /// This function fetches the current state of a single record, and merges the
+    /// provided data into it in memory, using the same semantics as UPDATE MERGE.
+    /// The database is not modified, so the result can be confirmed before the
+    /// merge is applied with the update tool.
+    #[tool(description = r#"
+Preview the result of merging data into a record, without modifying the database.
+
+This function fetches the specified record and merges the data into it in memory, 
+returning the record as it would be after running UPDATE ... MERGE. Nested objects are 
+merged recursively, while all other values (including arrays) are replaced. Use this to 
+confirm the effect of a merge before applying it with the update tool.
+
+Examples:
+- preview_merge('person:john', {"age": 31})  # Preview changing a single field
+- preview_merge('person:john', {"address": {"city": "London"}})  # Preview a nested merge
+"#)]
+    pub async fn preview_merge(
         &self,
-        _params: Parameters<CloudParams>,
+        params: Parameters<PreviewMergeParams>,
     ) -> Result<CallToolResult, McpError> {
+        let PreviewMergeParams { record, merge_data } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.list_cloud_organizations").increment(1);
+        counter!("surrealmcp.tools.preview_merge").increment(1);
         // Output debugging information
-        debug!("Listing cloud organizations");
-        // Fetch the cloud organisations
-        let organisations = self
-            .cloud_client
-            .list_organizations()
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        // Convert result to JSON
-        let organisations: Vec<serde_json::Value> = organisations
-            .into_iter()
-            .map(|org| {
-                serde_json::json!({
-                    "id": org.id,
-                    "name": org.name,
-                    "billing_info": org.billing_info,
-                    "payment_info": org.payment_info,
-                    "max_free_instances": org.max_free_instances,
-                    "max_paid_instances": org.max_paid_instances,
-                    "member_count": org.member_count,
-                    "user_role": org.user_role,
-                    "plan": org.plan,
-                })
-            })
-            .collect();
-        // Create the result JSON
-        let result = serde_json::json!({
-            "organizations": organisations,
-            "count": organisations.len()
-        });
-        // Return the MCP result
+        debug!(record = %record, "Previewing record merge");
+        // Validate the record ID
+        let record_id = parse_record_id(&record).map_err(|e| McpError::internal_error(e, None))?;
+        // The record ID can not be changed by a merge
+        if merge_data.contains_key("id") {
+            return Err(McpError::internal_error(
+                "The merge data can not contain the 'id' field".to_string(),
+                None,
+            ));
+        }
+        // Fetch the current state of the record
+        let params = HashMap::from([("record".to_string(), Value::from(record_id))]);
+        let mut current = self
+            .query_internal("SELECT * FROM ONLY $record".to_string(), Some(params))
+            .await?
+            .take(0)
+            .map_err(McpError::from)?
+            .into_inner()
+            .into_json();
+        if current.is_null() {
+            return Err(McpError::internal_error(
+                format!("Record '{record}' does not exist"),
+                None,
+            ));
+        }
+        // Merge the data into the record in memory
+        merge_json(&mut current, serde_json::Value::Object(merge_data));
+        // Return the merged record
         Ok(CallToolResult::success(vec![Content::text(
-            result.to_string(),
+            current.to_string(),
         )]))
     }
 
-    #[tool(description = "List SurrealDB Cloud instances for a given organization")]
-    pub async fn list_cloud_instances(
+    /// Compute the differences between two records as a JSON Patch.
+    ///
+    /// This function fetches both records, and returns the JSON Patch (RFC 6902)
+    /// operations which transform the first record into the second. The record
+    /// IDs are not compared, so the patch can be applied with the update tool.
+    #[tool(description = r#"
+Compute the differences between two records as a JSON Patch.
+
+This function fetches both records, and returns a JSON Patch (RFC 6902) describing how 
+to transform the first record into the second, as a list of add, remove, and replace 
+operations. The id fields of the records are not compared. Applying the returned 
+operations to the first record with update(['from'], patch_data) makes its fields 
+match the second record. An empty list means the records have the same fields.
+
+Examples:
+- diff_records('person:john', 'person:jane')  # [{"op": "replace", "path": "/name", "value": "Jane"}]
+"#)]
+    pub async fn diff_records(
         &self,
-        params: Parameters<CloudOrganizationParams>,
+        params: Parameters<DiffRecordsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let CloudOrganizationParams { organization_id } = params.0;
+        let DiffRecordsParams { from, to } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.list_cloud_instances").increment(1);
+        counter!("surrealmcp.tools.diff_records").increment(1);
         // Output debugging information
-        debug!(
-            organization_id = organization_id,
-            "Listing cloud instances for organization"
-        );
-        // Fetch the cloud instances
-        let instances = self
-            .cloud_client
-            .list_instances(&organization_id)
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        // Convert result to JSON
-        let instances: Vec<serde_json::Value> = instances
-            .into_iter()
-            .map(|instance| {
-                serde_json::json!({
-                    "id": instance.id,
-                    "name": instance.name,
-                    "slug": instance.slug,
-                    "version": instance.version,
-                    "available_versions": instance.available_versions,
-                    "host": instance.host,
-                    "region": instance.region,
-                    "organization_id": instance.organization_id,
-                    "compute_units": instance.compute_units,
-                    "state": instance.state,
-                    "storage_size": instance.storage_size,
-                    "can_update_storage_size": instance.can_update_storage_size,
-                    "storage_size_update_cooloff_hours": instance.storage_size_update_cooloff_hours,
-                })
-            })
-            .collect();
-        // Create the result JSON
-        let result = serde_json::json!({
-            "instances": instances,
-            "count": instances.len()
-        });
-        // Return the MCP result
+        debug!(from = %from, to = %to, "Diffing records");
+        // Validate the record IDs
+        let from_id = parse_record_id(&from).map_err(|e| McpError::internal_error(e, None))?;
+        let to_id = parse_record_id(&to).map_err(|e| McpError::internal_error(e, None))?;
+        // Fetch both records
+        let params = HashMap::from([
+            ("from".to_string(), Value::from(from_id)),
+            ("to".to_string(), Value::from(to_id)),
+        ]);
+        let mut res = self
+            .query_internal(
+                "SELECT * FROM ONLY $from; SELECT * FROM ONLY $to;".to_string(),
+                Some(params),
+            )
+            .await?;
+        let mut records = Vec::with_capacity(2);
+        for (index, record) in [from, to].iter().enumerate() {
+            let mut value = res
+                .take(index)
+                .map_err(McpError::from)?
+                .into_inner()
+                .into_json();
+            // Ignore the record ID, which always differs
+            match value.as_object_mut() {
+                Some(object) => {
+                    object.remove("id");
+                }
+                None => {
+                    return Err(McpError::internal_error(
+                        format!("Record '{record}' does not exist"),
+                        None,
+                    ));
+                }
+            }
+            records.push(value);
+        }
+        // Compute the patch which transforms the first record into the second
+        let patch = diff_json(&records[0], &records[1]);
+        // Return the patch
         Ok(CallToolResult::success(vec![Content::text(
-            result.to_string(),
+            serde_json::Value::Array(patch).to_string(),
         )]))
     }
 
-    #[tool(description = "Pause SurrealDB Cloud instance")]
-    pub async fn pause_cloud_instance(
+    /// Fetch the edges connected to a single record.
+    ///
+    /// This function executes graph SELECT statements to fetch the edges which are
+    /// connected to the specified record, along with the record at the other end
+    /// of each edge. The edges can be limited to a single direction, and filtered
+    /// to a specific edge table.
+    #[tool(description = r#"
+Fetch the edges connected to a single record.
+
+This function executes graph SELECT statements to find the relationships of a record. 
+Each returned edge includes its own fields (id, in, out, and any edge data), along with 
+the full record at the other end of the edge in the 'target' field.
+
+The direction can be 'out' (edges from this record), 'in' (edges to this record), or 
+'both' (the default). The edge parameter limits the results to a single edge table.
+
+Examples:
+- get_edges('person:john')  # All inbound and outbound edges
+- get_edges('person:john', Some('out'))  # Only outbound edges
+- get_edges('person:john', Some('in'), Some('knows'))  # Only inbound 'knows' edges
+"#)]
+    pub async fn get_edges(
         &self,
-        params: Parameters<CloudInstanceParams>,
+        params: Parameters<GetEdgesParams>,
     ) -> Result<CallToolResult, McpError> {
-        let CloudInstanceParams { instance_id } = params.0;
+        let GetEdgesParams {
+            record,
+            direction,
+            edge,
+        } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.pause_cloud_instance").increment(1);
+        counter!("surrealmcp.tools.get_edges").increment(1);
         // Output debugging information
-        debug!(instance_id = instance_id, "Pausing cloud instance");
-        // Pause the cloud instance
-        let instance = self
-            .cloud_client
-            .pause_instance(&instance_id)
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        // Create the result JSON
-        let result = serde_json::json!({
-            "message": "Successfully paused cloud instance",
-            "instance": instance,
-        });
-        // Return the MCP result
+        debug!(record = %record, direction = ?direction, edge = ?edge, "Fetching record edges");
+        // Validate the record ID
+        let record_id = parse_record_id(&record).map_err(|e| McpError::internal_error(e, None))?;
+        // Validate the edge direction
+        let directions: &[&str] = match direction.as_deref().unwrap_or("both") {
+            "in" => &["in"],
+            "out" => &["out"],
+            "both" => &["in", "out"],
+            other => {
+                return Err(McpError::internal_error(
+                    format!("Invalid edge direction '{other}'. Expected 'in', 'out', or 'both'"),
+                    None,
+                ));
+            }
+        };
+        // Validate the edge table filter
+        let edge = match edge {
+            Some(edge) => quote_ident(&edge).map_err(|e| McpError::internal_error(e, None))?,
+            None => "?".to_string(),
+        };
+        // Build a graph SELECT statement for each direction
+        let query = directions
+            .iter()
+            .map(|direction| match *direction {
+                "in" => format!("SELECT *, in.* AS target FROM $record<-{edge};"),
+                _ => format!("SELECT *, out.* AS target FROM $record->{edge};"),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Add the record ID as a parameter
+        let params = HashMap::from([("record".to_string(), Value::from(record_id))]);
+        // Output debugging information
+        trace!("Fetching record edges with query: {query}");
+        // Execute the final query
+        let mut exec_res = self.query_internal(query, Some(params)).await?;
+        // Collect the edges for each direction
+        let mut result = serde_json::Map::new();
+        result.insert("record".to_string(), serde_json::json!(record));
+        for (index, direction) in directions.iter().enumerate() {
+            let edges = exec_res.take(index).map_err(McpError::from)?;
+            result.insert(direction.to_string(), edges.into_inner().into_json());
+        }
+        // Return the result
         Ok(CallToolResult::success(vec![Content::text(
-            result.to_string(),
+            serde_json::Value::Object(result).to_string(),
         )]))
     }
 
-    #[tool(description = "Resume SurrealDB Cloud instance")]
-    pub async fn resume_cloud_instance(
+    /// Traverse the graph from a record, following a path of edges.
+    ///
+    /// This function builds a graph path expression from each step, such as
+    /// ->knows->person, and selects the records at the end of the path, starting
+    /// from the record ID which is bound as a query parameter.
+    #[tool(description = r#"
+Traverse the graph from a record, returning the records at the end of a path of edges.
+
+This function follows each step of the path in order, starting from the record. Each step 
+follows an edge table in a direction: 'out' follows the edges from the record (->), 'in' 
+follows the edges to the record (<-), and 'both' follows the edges in either direction 
+(<->). A step can also limit the records at the other end of the edges to a table. The 
+records at the end of the path are returned, and can be filtered with a WHERE clause.
+
+Examples:
+- traverse("person:john", [{"direction": "out", "edge": "knows"}])  # People john knows
+- traverse("person:john", [{"direction": "out", "edge": "knows", "target": "person"}, {"direction": "out", "edge": "knows", "target": "person"}], Some("age > 25"))  # Friends of friends
+- traverse("post:1", [{"direction": "in", "edge": "likes"}])  # Records which like the post
+"#)]
+    pub async fn traverse(
         &self,
-        params: Parameters<CloudInstanceParams>,
+        params: Parameters<TraverseParams>,
     ) -> Result<CallToolResult, McpError> {
-        let CloudInstanceParams { instance_id } = params.0;
+        let TraverseParams {
+            start,
+            path,
+            where_clause,
+            fetch,
+            parameters,
+        } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.resume_cloud_instance").increment(1);
+        counter!("surrealmcp.tools.traverse").increment(1);
         // Output debugging information
-        debug!(instance_id = instance_id, "Resuming cloud instance");
-        // Resume the cloud instance
-        let instance = self
-            .cloud_client
-            .resume_instance(&instance_id)
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        // Create the result JSON
-        let result = serde_json::json!({
-            "message": "Successfully resumed cloud instance",
-            "instance": instance,
-        });
-        // Return the MCP result
-        Ok(CallToolResult::success(vec![Content::text(
-            result.to_string(),
-        )]))
+        debug!(start = %start, steps = path.len(), "Traversing graph");
+        // Validate the start record ID
+        let record_id = parse_record_id(&start).map_err(|e| McpError::internal_error(e, None))?;
+        // Build the graph path expression
+        let path = graph_path(&path).map_err(|e| McpError::internal_error(e, None))?;
+        // Build the initial query string
+        let mut query = format!("SELECT * FROM $traverse_start{path}");
+        // Add the where clause if provided
+        if let Some(v) = where_clause {
+            let v = validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" WHERE {v}"));
+        }
+        // Add the fetch clause if provided
+        if let Some(v) = fetch {
+            let v = validate_clause("FETCH", &v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" FETCH {v}"));
+        }
+        // Create parameters with native SurrealDB types
+        let mut params = HashMap::new();
+        // Add user-provided parameters if any
+        bind_user_parameters(&mut params, parameters)?;
+        // Add the start record ID as a parameter
+        params.insert("traverse_start".to_string(), Value::from(record_id));
+        // Output debugging information
+        trace!("Traversing graph with query: {query}");
+        // Execute the final query
+        self.query_internal(query, Some(params))
+            .await?
+            .to_mcp_result()
     }
 
-    #[tool(description = "Get SurrealDB Cloud instance status")]
-    pub async fn get_cloud_instance_status(
+    /// Run a maintenance operation against the connected storage engine.
+    ///
+    /// This function runs maintenance operations which keep the storage of long-running
+    /// deployments in good shape. Index rebuilding is supported by all engines. SurrealDB
+    /// 2.x does not expose manual storage compaction, so compaction is rejected for every
+    /// engine with an error naming the connected engine.
+    #[tool(description = r#"
+Run a maintenance operation against the connected storage engine.
+
+Supported operations:
+- rebuild_indexes: Rebuild every index defined on the specified table. This is supported 
+  by all storage engines, and can recover indexes after large imports or deletions.
+
+Compaction is not supported by any storage engine, as SurrealDB does not expose manual 
+compaction. The embedded rocksdb and surrealkv engines compact their storage automatically 
+in the background, so requesting the compact operation returns an error.
+
+The result includes the operation, the storage engine, and the outcome of the operation.
+
+Examples:
+- maintenance('rebuild_indexes', Some('person'))  # Rebuild all indexes on the person table
+"#)]
+    pub async fn maintenance(
         &self,
-        params: Parameters<CloudInstanceParams>,
+        params: Parameters<MaintenanceParams>,
     ) -> Result<CallToolResult, McpError> {
-        let CloudInstanceParams { instance_id } = params.0;
+        let MaintenanceParams { operation, table } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.get_cloud_instance_status").increment(1);
+        counter!("surrealmcp.tools.maintenance").increment(1);
         // Output debugging information
-        debug!("Getting status for cloud instance: {instance_id}");
-        // Fetch the cloud instance status
-        let status = self
-            .cloud_client
-            .get_instance_status(&instance_id)
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        // Create the result JSON
-        let result = serde_json::json!({
-            "instance_id": instance_id,
-            "phase": status.phase,
-            "db_backups": status.db_backups,
-            "backup_count": status.db_backups.len()
-        });
-        // Return the MCP result
+        debug!(operation = %operation, table = ?table, "Running maintenance operation");
+        // Get the storage engine for the current connection
+        let endpoint = self.context.lock().await.endpoint.clone();
+        let endpoint = endpoint.ok_or_else(|| self.not_connected())?;
+        let engine = storage_engine(&endpoint);
+        // Run the requested operation
+        match operation.as_str() {
+            "rebuild_indexes" => {
+                // Validate the table name
+                let table = table.ok_or_else(|| {
+                    McpError::internal_error("The rebuild_indexes operation requires a table", None)
+                })?;
+                let table = quote_ident(&table).map_err(|e| McpError::internal_error(e, None))?;
+                // Fetch the indexes defined on the table
+                let mut exec_res = self
+                    .query_internal(format!("INFO FOR TABLE {table}"), None)
+                    .await?;
+                let info = exec_res
+                    .take(0)
+                    .map_err(McpError::from)?
+                    .into_inner()
+                    .into_json();
+                let indexes: Vec<String> = info["indexes"]
+                    .as_object()
+                    .map(|indexes| indexes.keys().cloned().collect())
+                    .unwrap_or_default();
+                // Rebuild each of the indexes
+                if !indexes.is_empty() {
+                    let query = indexes
+                        .iter()
+                        .map(|index| {
+                            quote_ident(index)
+                                .map(|index| format!("REBUILD INDEX {index} ON {table};"))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| McpError::internal_error(e, None))?
+                        .join(" ");
+                    // Output debugging information
+                    trace!("Rebuilding indexes with query: {query}");
+                    // Execute the rebuild statements
+                    self.query_internal(query, None).await?.to_mcp_result()?;
+                }
+                // Return the result
+                let result = serde_json::json!({
+                    "operation": operation,
+                    "engine": engine,
+                    "table": table,
+                    "indexes": indexes,
+                });
+                Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]))
+            }
+            "compact" => Err(McpError::invalid_params(
+                format!(
+                    "The compact operation is not supported by the {engine} engine, as SurrealDB does not expose manual compaction"
+                ),
+                None,
+            )),
+            other => Err(McpError::internal_error(
+                format!("Unknown maintenance operation '{other}'. Expected 'rebuild_indexes'"),
+                None,
+            )),
+        }
+    }
+
+    /// Remove all tables from the current database.
+    ///
+    /// This function is intended for iterative testing against the in-memory engine,
+    /// allowing all state to be wiped so that work can start afresh. It is only
+    /// available when the server is started with --allow-reset, only operates on
+    /// memory endpoints, and requires explicit confirmation.
+    #[tool(description = r#"
+Remove all tables, and all of their data, from the current database.
+
+This function is intended for iterative testing workflows using the in-memory engine. 
+It is only available when the server has been started with --allow-reset, and it refuses 
+to run against any endpoint other than 'memory'. The confirm parameter must be set to 
+true, otherwise the operation is rejected.
+
+The result lists the tables which were removed.
+
+Examples:
+- reset_database(true)  # Remove all tables from the current database
+"#)]
+    pub async fn reset_database(
+        &self,
+        params: Parameters<ResetDatabaseParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let ResetDatabaseParams { confirm } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.reset_database").increment(1);
+        // Output debugging information
+        debug!(confirm, "Resetting the current database");
+        // Check that database resets are enabled
+        if !self.options.allow_reset {
+            return Err(McpError::internal_error(
+                "Resetting the database is disabled. Start the server with --allow-reset to enable it",
+                None,
+            ));
+        }
+        // Check that the reset has been confirmed
+        if !confirm {
+            return Err(McpError::internal_error(
+                "Resetting the database removes all data. Set confirm to true to proceed",
+                None,
+            ));
+        }
+        // Check that we are connected to an in-memory database
+        let endpoint = self.context.lock().await.endpoint.clone();
+        let endpoint = endpoint.ok_or_else(|| self.not_connected())?;
+        let engine = storage_engine(&endpoint);
+        if engine != "memory" {
+            return Err(McpError::internal_error(
+                format!(
+                    "Refusing to reset a {engine} database. Only memory endpoints can be reset"
+                ),
+                None,
+            ));
+        }
+        // Fetch the tables defined in the current database
+        let mut exec_res = self.query_internal("INFO FOR DB".to_string(), None).await?;
+        let info = exec_res
+            .take(0)
+            .map_err(McpError::from)?
+            .into_inner()
+            .into_json();
+        let tables: Vec<String> = info["tables"]
+            .as_object()
+            .map(|tables| tables.keys().cloned().collect())
+            .unwrap_or_default();
+        // Remove each of the tables
+        if !tables.is_empty() {
+            let query = tables
+                .iter()
+                .map(|table| quote_ident(table).map(|table| format!("REMOVE TABLE {table};")))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| McpError::internal_error(e, None))?
+                .join(" ");
+            // Output debugging information
+            trace!("Resetting database with query: {query}");
+            // Execute the remove statements
+            self.query_internal(query, None).await?.to_mcp_result()?;
+        }
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            tables = ?tables,
+            "Database reset completed"
+        );
+        // Return the result
+        let result = serde_json::json!({ "removed_tables": tables });
         Ok(CallToolResult::success(vec![Content::text(
             result.to_string(),
         )]))
     }
 
-    #[tool(description = "Create SurrealDB Cloud instance")]
-    pub async fn create_cloud_instance(
+    /// Export the records of a table to an NDJSON file.
+    ///
+    /// This function streams the records of a table, in batches, to a newline-delimited
+    /// JSON file, so that large tables are never buffered in memory. The file is written
+    /// within the export directory configured with --export-dir, which is only available
+    /// for the stdio and Unix socket transports.
+    #[tool(description = r#"
+Export all records of a table to a newline-delimited JSON (NDJSON) file.
+
+This function streams the records of a table to a file, writing one JSON record per line in 
+order of the record id, without buffering the whole table in memory. The path is relative to the export directory 
+configured on the server with --export-dir, and can not be absolute or contain '..'. Any 
+existing file at the path is overwritten. This tool is only available when the server is 
+running locally over stdio or a Unix socket.
+
+The result includes the resolved file path and the number of exported records.
+
+Examples:
+- export_to_file('person', 'person.ndjson')  # Export the person table
+- export_to_file('order', 'exports/order.ndjson')  # Export into a subdirectory
+"#)]
+    pub async fn export_to_file(
         &self,
-        params: Parameters<CreateCloudInstanceParams>,
+        params: Parameters<ExportToFileParams>,
     ) -> Result<CallToolResult, McpError> {
-        let CreateCloudInstanceParams {
-            name,
-            organization_id,
-        } = params.0;
+        let ExportToFileParams { table, path } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.create_cloud_instance").increment(1);
+        counter!("surrealmcp.tools.export_to_file").increment(1);
         // Output debugging information
-        debug!("Creating cloud instance: {name} in organization: {organization_id}");
-        // Fetch the cloud instance status
-        let instance = self
-            .cloud_client
-            .create_instance(&organization_id, &name)
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        // Create the result JSON
+        debug!(table = %table, path = %path, "Exporting table to file");
+        // Check that file exports are enabled
+        let Some(export_dir) = &self.options.export_dir else {
+            return Err(McpError::internal_error(
+                "File exports are disabled. Start the server locally with --export-dir to enable them",
+                None,
+            ));
+        };
+        // Validate the table name and file path
+        let table = quote_ident(&table).map_err(|e| McpError::internal_error(e, None))?;
+        let path = resolve_path_within(export_dir, &path)
+            .map_err(|e| McpError::internal_error(e, None))?;
+        // Create the export file
+        let file = tokio::fs::File::create(&path).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to create export file: {e}"), None)
+        })?;
+        let mut writer = tokio::io::BufWriter::new(file);
+        // Stream the records to the file in batches
+        let mut count = 0;
+        loop {
+            // Fetch the next batch of records
+            let query = format!(
+                "SELECT * FROM {table} ORDER BY id LIMIT {EXPORT_BATCH_SIZE} START {count}"
+            );
+            let mut exec_res = self.query_internal(query, None).await?;
+            let records = match exec_res
+                .take(0)
+                .map_err(McpError::from)?
+                .into_inner()
+                .into_json()
+            {
+                serde_json::Value::Array(records) => records,
+                _ => vec![],
+            };
+            // Write each record as a single line
+            for record in &records {
+                let mut line = record.to_string();
+                line.push('\n');
+                writer.write_all(line.as_bytes()).await.map_err(|e| {
+                    McpError::internal_error(format!("Failed to write export file: {e}"), None)
+                })?;
+            }
+            count += records.len();
+            // Stop once the final batch has been written
+            if records.len() < EXPORT_BATCH_SIZE {
+                break;
+            }
+        }
+        // Ensure all records are written to the file
+        writer.flush().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to write export file: {e}"), None)
+        })?;
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            table = %table,
+            path = %path.display(),
+            count,
+            "Table export completed"
+        );
+        // Return the result
         let result = serde_json::json!({
-            "message": "Successfully created cloud instance",
-            "instance": instance,
+            "table": table,
+            "path": path.display().to_string(),
+            "count": count,
         });
-        // Return the MCP result
         Ok(CallToolResult::success(vec![Content::text(
             result.to_string(),
         )]))
     }
 
-    /// Connect to a different SurrealDB endpoint.
-    ///
-    /// This function allows you to dynamically connect to a different SurrealDB
-    /// endpoint during your session. The endpoint can be any supported SurrealDB
-    /// engine type (memory, rocksdb, surrealkv, tikv, http, https, ws, wss).
+    /// Export the current database as a SurrealQL script or as NDJSON.
     ///
-    /// # Arguments
-    /// * `endpoint` - The SurrealDB endpoint URL (e.g., "memory", "file:/path/to/db", "ws://localhost:8000")
-    /// * `namespace` - The namespace to use (optional, defaults to "test")
-    /// * `database` - The database to use (optional, defaults to "test")
-    /// * `username` - Username for authentication (optional, only needed for remote connections)
-    /// * `password` - Password for authentication (optional, only needed for remote connections)
+    /// The SurrealQL format streams the output of the database export, and the
+    /// NDJSON format reads the records of each table in batches. In both cases the
+    /// output stops at the configured size, ending on a complete line, and a note
+    /// is added to the result to indicate that the export was truncated.
     #[tool(description = r#"
-Connect to a different SurrealDB endpoint.
-
-This function allows you to dynamically connect to a different SurrealDB endpoint 
-during your session. The endpoint can be any supported SurrealDB engine type including 
-memory (for testing), file-based storage, distributed storage, remote connections, or 
-SurrealDB Cloud instances.
+Export the current database as text, either as a SurrealQL script or as NDJSON.
 
-Each client connection is completely isolated, so you can switch between different 
-databases as needed. The connection is persistent until you disconnect or connect to 
-a different endpoint. The username and password are optional.
+The 'surql' format (the default) returns a SurrealQL script containing the schema and the 
+records of the current database, which can be run against an empty database with the query 
+tool to restore it. The 'ndjson' format returns every record of every table as one JSON 
+object per line, in table order.
 
-For SurrealDB Cloud instances, use the format 'cloud:instance_id' where instance_id 
-is the ID of your cloud instance. The tool will automatically fetch the authentication 
-token from the SurrealDB Cloud API and establish a secure connection.
+The export is limited to max_bytes (1048576 by default, and at most 1048576) and always ends on 
+a complete line. Records are exported in order of their id. 
+When the export is larger than this, a second result item notes that it was truncated. 
+Use export_to_file to export large tables without a size limit.
 
 Examples:
-- connect_endpoint('memory')  # For testing
-- connect_endpoint('file:/tmp/mydb', Some('myapp'), Some('production'))  # Local file storage
-- connect_endpoint('ws://localhost:8000', Some('myapp'), Some('production'), Some('root'), Some('password'))  # Remote connection
-- connect_endpoint('rocksdb:/data/mydb', Some('analytics'), Some('events'))  # High-performance local storage
-- connect_endpoint('cloud:abc123def456', Some('myapp'), Some('production'))  # SurrealDB Cloud instance
+- export()  # "-- ------------------------------\n-- OPTION\n..."
+- export('ndjson')  # "{\"id\":\"person:1\",\"name\":\"Tobie\"}\n..."
+- export('surql', 65536)  # Export at most 64KiB
 "#)]
-    pub async fn connect_endpoint(
+    pub async fn export(
         &self,
-        params: Parameters<ConnectParams>,
+        params: Parameters<ExportParams>,
     ) -> Result<CallToolResult, McpError> {
-        let ConnectParams {
-            endpoint,
-            namespace,
-            database,
-            username,
-            password,
-        } = params.0;
-        // Start the measurement timer
-        let start_time = Instant::now();
+        let ExportParams { format, max_bytes } = params.0;
+        let format = format.unwrap_or_else(|| "surql".to_string());
+        let max_bytes = max_bytes.unwrap_or(EXPORT_MAX_BYTES).min(EXPORT_MAX_BYTES);
         // Increment tool usage counter
-        counter!("surrealmcp.tools.connect_endpoint").increment(1);
+        counter!("surrealmcp.tools.export").increment(1);
+        // Output debugging information
+        debug!(format = %format, max_bytes, "Exporting database");
+        // Collect the export, stopping once it exceeds the maximum size
+        let mut output = Vec::new();
+        match format.as_str() {
+            "surql" => {
+                // Get the database connection
+                let db = self.require_connection().await?;
+                // Stream the export until it exceeds the maximum size
+                let mut backup = db
+                    .export(())
+                    .await
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                while let Some(chunk) = backup.next().await {
+                    let chunk = chunk.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                    output.extend_from_slice(&chunk);
+                    if output.len() > max_bytes {
+                        break;
+                    }
+                }
+            }
+            "ndjson" => {
+                // Fetch the tables defined in the current database
+                let mut exec_res = self.query_internal("INFO FOR DB".to_string(), None).await?;
+                let info = exec_res
+                    .take(0)
+                    .map_err(McpError::from)?
+                    .into_inner()
+                    .into_json();
+                let mut tables: Vec<String> = info["tables"]
+                    .as_object()
+                    .map(|tables| tables.keys().cloned().collect())
+                    .unwrap_or_default();
+                tables.sort();
+                // Read the records of each table in batches
+                'tables: for table in tables {
+                    let table =
+                        quote_ident(&table).map_err(|e| McpError::internal_error(e, None))?;
+                    let mut count = 0;
+                    loop {
+                        let query = format!(
+                            "SELECT * FROM {table} ORDER BY id LIMIT {EXPORT_BATCH_SIZE} START {count}"
+                        );
+                        let mut exec_res = self.query_internal(query, None).await?;
+                        let records = match exec_res
+                            .take(0)
+                            .map_err(McpError::from)?
+                            .into_inner()
+                            .into_json()
+                        {
+                            serde_json::Value::Array(records) => records,
+                            _ => vec![],
+                        };
+                        // Write each record as a single line
+                        for record in &records {
+                            output.extend_from_slice(record.to_string().as_bytes());
+                            output.push(b'\n');
+                            if output.len() > max_bytes {
+                                break 'tables;
+                            }
+                        }
+                        count += records.len();
+                        // Move to the next table once the final batch has been read
+                        if records.len() < EXPORT_BATCH_SIZE {
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(McpError::internal_error(
+                    format!("Unsupported export format '{format}'. Use 'surql' or 'ndjson'"),
+                    None,
+                ));
+            }
+        }
+        // Truncate the export to the last complete line within the maximum size
+        let truncated = output.len() > max_bytes;
+        if truncated {
+            let end = output[..max_bytes]
+                .iter()
+                .rposition(|b| *b == b'\n')
+                .map_or(0, |i| i + 1);
+            output.truncate(end);
+        }
         // Output debugging information
         info!(
             connection_id = %self.connection_id,
-            endpoint = %endpoint,
-            namespace = namespace.as_deref(),
-            database = database.as_deref(),
-            has_username = username.is_some(),
-            "Attempting to connect to SurrealDB endpoint"
+            format = %format,
+            bytes = output.len(),
+            truncated,
+            "Database export completed"
         );
-        // Check if endpoint is restricted by startup configuration
-        if let Some(configured_endpoint) = &self.endpoint
-            && endpoint != *configured_endpoint
-        {
-            // Output debugging information
-            warn!(
-                connection_id = %self.connection_id,
-                requested_endpoint = %endpoint,
-                configured_endpoint = %configured_endpoint,
-                "Connection rejected: endpoint not allowed by server configuration"
-            );
-            // Increment error metrics
-            counter!("surrealmcp.total_errors").increment(1);
-            counter!("surrealmcp.total_configuration_errors").increment(1);
-            counter!("surrealmcp.errors.connect_endpoint").increment(1);
-            // Return error message
+        // Return the result
+        let mut contents = vec![Content::text(String::from_utf8_lossy(&output).into_owned())];
+        if truncated {
+            contents.push(Content::text(format!(
+                "The export was truncated to {} bytes as it exceeded max_bytes ({max_bytes}). Increase max_bytes, or use export_to_file to export large tables.",
+                output.len()
+            )));
+        }
+        Ok(CallToolResult::success(contents))
+    }
+
+    /// Import a SurrealQL script into the current database.
+    ///
+    /// This function parses the script, removes any transaction statements, and
+    /// runs the remaining statements within a single transaction, so that a
+    /// failing statement does not leave partially imported data behind.
+    #[tool(description = r#"
+Import a SurrealQL script, such as the output of the export tool, into the current database.
+
+The statements of the script are run within a single transaction, so if any statement fails, 
+none of the changes are applied. BEGIN and COMMIT statements in the script are ignored, and 
+scripts containing CANCEL statements are rejected. The script is checked like any other query, 
+so it can not call disallowed functions, or update or delete whole tables when a WHERE clause is 
+required. This tool is not available in read-only mode, when raw queries are disabled, or while 
+a transaction started with begin_transaction is open.
+
+The result includes the number of statements which were executed.
+
+Examples:
+- import('DEFINE TABLE person SCHEMALESS; INSERT [{ id: person:tobie, name: "Tobie" }];')  # {"statements": 2}
+"#)]
+    pub async fn import(
+        &self,
+        params: Parameters<ImportParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let ImportParams { content } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.import").increment(1);
+        // Output debugging information
+        debug!(bytes = content.len(), "Importing SurrealQL script");
+        // Check that the server is not in read-only mode
+        if self.options.read_only {
             return Err(McpError::internal_error(
-                format!(
-                    "Cannot connect to endpoint '{endpoint}'. Server is configured to only use endpoint '{configured_endpoint}'"
-                ),
+                "Imports are disabled as the server is in read-only mode",
                 None,
             ));
         }
-        // Check if namespace is restricted by startup configuration
-        if let Some(configured_namespace) = &self.namespace
-            && let Some(namespace) = &namespace
-            && namespace != configured_namespace
-        {
-            // Output debugging information
-            warn!(
-                connection_id = %self.connection_id,
-                requested_namespace = %namespace,
-                configured_namespace = %configured_namespace,
-                "Connection rejected: namespace not allowed by server configuration"
-            );
-            // Return error message
+        // Check that the import would not be buffered in an open transaction
+        if self.transaction.lock().await.is_some() {
             return Err(McpError::internal_error(
-                format!(
-                    "Cannot use namespace '{namespace}'. Server is configured to only use namespace '{configured_namespace}'"
-                ),
+                "A transaction is open. Commit or cancel it before importing a script",
                 None,
             ));
         }
-        // Check if database is restricted by startup configuration
-        if let Some(configured_database) = &self.database
-            && let Some(database) = &database
-            && database != configured_database
-        {
-            // Output debugging information
-            warn!(
-                connection_id = %self.connection_id,
-                requested_database = %database,
-                configured_database = %configured_database,
-                "Connection rejected: database not allowed by server configuration"
-            );
-            // Return error message
+        // Split the script into its statements
+        let statements =
+            import_statements(&content).map_err(|e| McpError::internal_error(e, None))?;
+        if statements.is_empty() {
+            return Err(McpError::internal_error(
+                "The script does not contain any statements",
+                None,
+            ));
+        }
+        // Execute the statements within a single transaction
+        let query = format!(
+            "BEGIN TRANSACTION;\n{};\nCOMMIT TRANSACTION;",
+            statements.join(";\n")
+        );
+        let res = self.query_internal(query, None).await?;
+        // Report the statement which caused the transaction to fail, numbered
+        // from 0 in the order of the statements of the script
+        let failed = res
+            .statements
+            .iter()
+            .enumerate()
+            .find_map(|(index, statement)| {
+                statement
+                    .result
+                    .as_ref()
+                    .err()
+                    .filter(|error| {
+                        !error
+                            .message
+                            .contains("not executed due to a failed transaction")
+                    })
+                    .map(|error| (index, error))
+            });
+        if let Some((index, error)) = failed {
             return Err(McpError::internal_error(
                 format!(
-                    "Cannot use database '{database}'. Server is configured to only use database '{configured_database}'"
+                    "Statement {index} of the script failed, so no changes were imported: {}",
+                    error.message
                 ),
                 None,
             ));
         }
-        // Get the namespace to use for the connection
-        let ns = namespace.or_else(|| self.namespace.clone());
-        // Get the database to use for the connection
-        let db = database.or_else(|| self.database.clone());
-        // Get the username to use for authentication
-        let user = username.or_else(|| self.user.clone());
-        // Get the password to use for authentication
-        let pass = password.or_else(|| self.pass.clone());
-        // Check if this is a cloud connection
-        let connection = if endpoint.starts_with("cloud:") {
-            // Extract instance ID from cloud:instance_id format
-            let instance_id = endpoint.strip_prefix("cloud:").ok_or_else(|| {
-                McpError::internal_error(
-                    "Invalid cloud endpoint format. Expected 'cloud:instance_id'".to_string(),
-                    None,
-                )
-            })?;
-            // Get the instance details to get the host
-            let instance = match self.cloud_client.get_instance(instance_id).await {
-                Ok(instance) => instance,
-                Err(e) => {
-                    error!(
-                        connection_id = %self.connection_id,
-                        instance_id = %instance_id,
-                        error = %e,
-                        "Failed to get cloud instance details"
-                    );
-                    counter!("surrealmcp.total_errors").increment(1);
-                    counter!("surrealmcp.total_connection_errors").increment(1);
-                    counter!("surrealmcp.errors.connect_endpoint").increment(1);
-                    return Err(McpError::internal_error(
-                        format!("Failed to get cloud instance details '{instance_id}': {e}"),
-                        None,
-                    ));
-                }
-            };
-            // Get the auth token for the cloud instance
-            let token = match self.cloud_client.get_instance_auth(instance_id).await {
-                Ok(token) => token,
-                Err(e) => {
-                    error!(
-                        connection_id = %self.connection_id,
-                        instance_id = %instance_id,
-                        error = %e,
-                        "Failed to get cloud instance token"
-                    );
-                    counter!("surrealmcp.total_errors").increment(1);
-                    counter!("surrealmcp.total_connection_errors").increment(1);
-                    counter!("surrealmcp.errors.connect_endpoint").increment(1);
-                    return Err(McpError::internal_error(
-                        format!("Failed to get cloud instance auth token '{instance_id}': {e}"),
-                        None,
-                    ));
-                }
-            };
-            // Check if instance state is ready
-            if let Some(state) = &instance.state
-                && state != "ready"
-            {
-                error!(
-                    connection_id = %self.connection_id,
-                    instance_id = %instance_id,
-                    state = %state,
-                    "Cloud instance is not ready to connect"
-                );
-                counter!("surrealmcp.total_errors").increment(1);
-                counter!("surrealmcp.total_connection_errors").increment(1);
-                counter!("surrealmcp.errors.connect_endpoint").increment(1);
-                return Err(McpError::internal_error(
-                    format!("Cloud instance '{instance_id}' is not ready (state: {state})"),
-                    None,
-                ));
-            }
-            // Get the host from the instance
-            let host = instance.host.ok_or_else(|| {
-                error!(
-                    connection_id = %self.connection_id,
-                    instance_id = %instance_id,
-                    "Cloud instance has no host information"
-                );
-                counter!("surrealmcp.total_errors").increment(1);
-                counter!("surrealmcp.total_connection_errors").increment(1);
-                counter!("surrealmcp.errors.connect_endpoint").increment(1);
-                McpError::internal_error(
-                    format!("Cloud instance '{instance_id}' has no host information"),
-                    None,
-                )
-            })?;
-            // Create a new SurrealDB connection with the token
-            db::create_client_connection_with_token(
-                &host,
-                &token,
-                user.as_deref(),
-                pass.as_deref(),
-                ns.as_deref(),
-                db.as_deref(),
-            )
-            .await
-        } else {
-            db::create_client_connection(
-                &endpoint,
-                user.as_deref(),
-                pass.as_deref(),
-                ns.as_deref(),
-                db.as_deref(),
-            )
-            .await
-        };
-        // Create a new SurrealDB connection
-        match connection {
-            Ok(instance) => {
-                // Calculate the elapsed time
-                let duration = start_time.elapsed();
-                // Update the service's database connection
-                let mut db_guard = self.db.lock().await;
-                *db_guard = Some(instance);
-                // Output debugging information
-                info!(
-                    connection_id = %self.connection_id,
-                    endpoint = %endpoint,
-                    namespace = ns.as_deref(),
-                    database = db.as_deref(),
-                    duration_ms = duration.as_millis(),
-                    "Successfully connected to SurrealDB endpoint"
-                );
-                // Return success message
-                let msg = format!("Successfully connected to endpoint '{endpoint}'");
-                Ok(CallToolResult::success(vec![Content::text(msg)]))
-            }
-            Err(e) => {
-                // Calculate the elapsed time
-                let duration = start_time.elapsed();
-                // Output debugging information
-                error!(
-                    connection_id = %self.connection_id,
-                    endpoint = %endpoint,
-                    namespace = ns.as_deref(),
-                    database = db.as_deref(),
-                    duration_ms = duration.as_millis(),
-                    error = %e,
-                    "Failed to connect to SurrealDB endpoint"
-                );
-                // Increment error metrics
-                counter!("surrealmcp.total_errors").increment(1);
-                counter!("surrealmcp.total_connection_errors").increment(1);
-                counter!("surrealmcp.errors.connect_endpoint").increment(1);
-                // Return error message
-                Err(McpError::internal_error(
-                    format!("Failed to connect to endpoint '{endpoint}': {e}"),
-                    None,
-                ))
-            }
-        }
+        res.to_mcp_result()?;
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            statements = statements.len(),
+            "Script import completed"
+        );
+        // Return the result
+        let result = serde_json::json!({ "statements": statements.len() });
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
     }
 
-    /// List available namespaces on the connected endpoint.
+    /// List the tables defined in the current database.
     ///
-    /// This function lists all namespaces available on the currently connected SurrealDB endpoint.
-    /// It returns a list of namespaces with their names.
-    ///
-    /// # Arguments
-    /// * `None` - No parameters are required for this tool
+    /// This function reads the table names from INFO FOR DB. When requested, the
+    /// number of records in each table is also counted, which requires scanning
+    /// every table and can be expensive for large databases.
     #[tool(description = r#"
-List available namespaces on the connected endpoint.
+List the tables defined in the current database.
 
-This function lists all namespaces available on the currently connected SurrealDB endpoint.
-It returns a list of namespaces with their names."#)]
-    pub async fn list_namespaces(&self) -> Result<CallToolResult, McpError> {
-        // Start the measurement timer
-        let start_time = Instant::now();
+This function returns the name of each table defined in the current database, sorted by 
+name. Set include_counts to true to also return the number of records in each table. 
+Counting requires scanning every table, so it can be slow for large databases.
+
+Examples:
+- list_tables()  # List the table names
+- list_tables(Some(true))  # List the table names with record counts
+"#)]
+    pub async fn list_tables(
+        &self,
+        params: Parameters<ListTablesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let ListTablesParams { include_counts } = params.0;
+        let include_counts = include_counts.unwrap_or(false);
         // Increment tool usage counter
-        counter!("surrealmcp.tools.list_namespaces").increment(1);
+        counter!("surrealmcp.tools.list_tables").increment(1);
         // Output debugging information
-        debug!("Listing available namespaces");
-        // Build the initial query string
-        let query = "INFO FOR ROOT STRUCTURE ".to_string();
-        // Execute INFO FOR ROOT STRUCTURE
+        debug!(include_counts, "Listing tables");
+        // Fetch the tables defined in the current database
+        let mut exec_res = self.query_internal("INFO FOR DB".to_string(), None).await?;
+        let info = exec_res
+            .take(0)
+            .map_err(McpError::from)?
+            .into_inner()
+            .into_json();
+        let mut tables: Vec<String> = info["tables"]
+            .as_object()
+            .map(|tables| tables.keys().cloned().collect())
+            .unwrap_or_default();
+        tables.sort();
+        // Return the table names if counts are not required
+        if !include_counts || tables.is_empty() {
+            let tables: Vec<serde_json::Value> = tables
+                .into_iter()
+                .map(|table| serde_json::json!({ "table": table }))
+                .collect();
+            let result = serde_json::json!({ "tables": tables });
+            return Ok(CallToolResult::success(vec![Content::text(
+                result.to_string(),
+            )]));
+        }
+        // Count the records in each table
+        let query = tables
+            .iter()
+            .map(|table| {
+                quote_ident(table).map(|table| format!("SELECT count() FROM {table} GROUP ALL;"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| McpError::internal_error(e, None))?
+            .join(" ");
+        // Output debugging information
+        trace!("Counting table records with query: {query}");
+        // Execute the count statements
         let mut exec_res = self.query_internal(query, None).await?;
-        // Match the result of the query
-        match exec_res.result.as_mut() {
-            Some(response) => {
-                // Calculate the elapsed time
-                let duration = start_time.elapsed();
-                // Take the first element of the response
-                let info_opt: Option<ListNamespaces> =
-                    response
-                        .take::<Option<ListNamespaces>>(0)
-                        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-                let info = info_opt.ok_or_else(|| {
-                    McpError::internal_error(
-                        "No namespaces returned when running INFO FOR ROOT".to_string(),
-                        None,
-                    )
-                })?;
-                // Convert the namespaces to a JSON object
-                let namespaces: Vec<serde_json::Value> = info
-                    .namespaces
-                    .into_iter()
-                    .map(|ns| serde_json::json!({ "name": ns.name }))
-                    .collect();
-                // Convert the namespaces to a JSON object
-                let result = serde_json::json!({
-                    "namespaces": namespaces,
-                    "count": namespaces.len()
-                });
-                // Output debugging information
-                info!(
-                    connection_id = %self.connection_id,
-                    duration_ms = duration.as_millis(),
-                    "Successfully listed available namespaces"
-                );
-                // Return the result
-                Ok(CallToolResult::success(vec![Content::text(
-                    result.to_string(),
-                )]))
-            }
-            None => {
-                // Calculate the elapsed time
-                let duration = start_time.elapsed();
-                // Output debugging information
-                warn!(
-                    connection_id = %self.connection_id,
-                    duration_ms = duration.as_millis(),
-                    "Unable to list available namespaces"
-                );
-                // Increment error metrics
-                counter!("surrealmcp.total_errors").increment(1);
-                counter!("surrealmcp.errors.list_namespaces").increment(1);
-                // Return error message
-                let err = exec_res
-                    .error
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(McpError::internal_error(err, None))
-            }
+        let mut result = Vec::with_capacity(tables.len());
+        for (index, table) in tables.into_iter().enumerate() {
+            let value = exec_res
+                .take(index)
+                .map_err(McpError::from)?
+                .into_inner()
+                .into_json();
+            // Empty tables return no rows when grouped
+            let count = value[0]["count"].as_u64().unwrap_or(0);
+            result.push(serde_json::json!({ "table": table, "count": count }));
         }
+        // Return the result
+        let result = serde_json::json!({ "tables": result });
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
     }
 
-    /// List available databases on the connected endpoint.
-    ///
-    /// This function lists all databases available on the currently connected SurrealDB endpoint.
-    /// It returns a list of databases with their names.
+    /// Describe the definitions at the root, namespace, database, or table level.
     ///
-    /// # Arguments
-    /// * `None` - No parameters are required for this tool
+    /// This function executes the matching INFO FOR statement and returns the
+    /// parsed definitions, giving the model the real schema of the database so
+    /// that it can write correct queries.
     #[tool(description = r#"
-List available databases on the connected endpoint.
+Describe the definitions at the root, namespace, database, or table level.
 
-This function lists all databases available on the currently connected SurrealDB endpoint.
-It returns a list of databases with their names."#)]
-    pub async fn list_databases(&self) -> Result<CallToolResult, McpError> {
-        // Start the measurement timer
-        let start_time = Instant::now();
+This function executes an INFO FOR statement and returns the definitions as JSON. Use it 
+to discover the real schema before writing queries against unfamiliar data.
+
+Levels:
+- root: The namespaces, root users, and root accesses
+- ns: The databases, users, and accesses in the current namespace
+- db: The tables, functions, params, analyzers, users, and accesses in the current database
+- table: The fields, indexes, events, and foreign tables of the specified table
+
+Examples:
+- info('db')  # Describe the current database
+- info('table', Some('person'))  # Describe the fields, indexes, and events of person
+"#)]
+    pub async fn info(&self, params: Parameters<InfoParams>) -> Result<CallToolResult, McpError> {
+        let InfoParams { level, table } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.list_databases").increment(1);
+        counter!("surrealmcp.tools.info").increment(1);
         // Output debugging information
-        debug!("Listing available databases");
-        // Build the initial query string
-        let query = "INFO FOR NAMESPACE STRUCTURE ".to_string();
-        // Execute INFO FOR ROOT STRUCTURE
-        let mut exec_res = self.query_internal(query, None).await?;
-        // Match the result of the query
-        match exec_res.result.as_mut() {
-            Some(response) => {
-                // Calculate the elapsed time
-                let duration = start_time.elapsed();
-                // Take the first element of the response
-                let info_opt: Option<ListDatabases> = response
-                    .take::<Option<ListDatabases>>(0)
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-                let info = info_opt.ok_or_else(|| {
-                    McpError::internal_error(
-                        "No databases returned when running INFO FOR NAMESPACE".to_string(),
-                        None,
-                    )
+        debug!(level = %level, table = ?table, "Describing definitions");
+        // Build the statement for the requested level
+        let query = match level.as_str() {
+            "root" => "INFO FOR ROOT".to_string(),
+            "ns" => "INFO FOR NS".to_string(),
+            "db" => "INFO FOR DB".to_string(),
+            "table" => {
+                // Validate the table name
+                let table = table.ok_or_else(|| {
+                    McpError::internal_error("The table level requires a table", None)
                 })?;
-                // Convert the databases to a JSON object
-                let databases: Vec<serde_json::Value> = info
-                    .databases
-                    .into_iter()
-                    .map(|db| serde_json::json!({ "name": db.name }))
-                    .collect();
-                // Convert the databases to a JSON object
-                let result = serde_json::json!({
-                    "databases": databases,
-                    "count": databases.len()
-                });
-                // Output debugging information
-                info!(
-                    connection_id = %self.connection_id,
-                    duration_ms = duration.as_millis(),
-                    "Successfully listed available databases"
-                );
-                // Return the result
-                Ok(CallToolResult::success(vec![Content::text(
-                    result.to_string(),
-                )]))
+                let table = quote_ident(&table).map_err(|e| McpError::internal_error(e, None))?;
+                format!("INFO FOR TABLE {table}")
             }
-            None => {
-                // Calculate the elapsed time
-                let duration = start_time.elapsed();
-                // Output debugging information
-                warn!(
-                    connection_id = %self.connection_id,
-                    duration_ms = duration.as_millis(),
-                    "Unable to list available databases"
-                );
-                // Increment error metrics
-                counter!("surrealmcp.total_errors").increment(1);
-                counter!("surrealmcp.errors.list_databases").increment(1);
-                // Return error message
-                let err = exec_res
-                    .error
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(McpError::internal_error(err, None))
+            level => {
+                return Err(McpError::internal_error(
+                    format!("Unknown info level '{level}', expected root, ns, db, or table"),
+                    None,
+                ));
             }
-        }
+        };
+        // Output debugging information
+        trace!("Describing definitions with query: {query}");
+        // Execute the info statement
+        let info = self
+            .query_internal(query, None)
+            .await?
+            .take(0)
+            .map_err(McpError::from)?
+            .into_inner()
+            .into_json();
+        // Return the definitions
+        Ok(CallToolResult::success(vec![Content::text(
+            info.to_string(),
+        )]))
     }
 
-    /// Change the namespace on the currently connected endpoint.
-    ///
-    /// This function allows you to switch to a different namespace on the currently
-    /// connected SurrealDB endpoint. The namespace change will apply to all subsequent
-    /// queries until you change it again or reconnect to a different endpoint.
+    /// Export the schema of the current database as a SurrealQL script.
     ///
-    /// # Arguments
-    /// * `namespace` - The namespace to switch to
+    /// This function reads the definitions from INFO FOR DB and INFO FOR TABLE,
+    /// and returns them as DEFINE statements in dependency order, so that the
+    /// script can be run against an empty database to reproduce the schema.
     #[tool(description = r#"
-Change the namespace on the currently connected endpoint.
+Export the schema of the current database as a SurrealQL script of DEFINE statements.
 
-This function allows you to switch to a different namespace on the currently connected 
-SurrealDB endpoint. The namespace change will apply to all subsequent queries until 
-you change it again or reconnect to a different endpoint.
+The script defines the analyzers, functions, params, accesses, tables, fields, indexes, 
+and events of the current database, in an order which can be run against an empty 
+database to reproduce the schema. Tables are defined before the views which select from 
+them. Use this to version control or migrate a schema.
 
-This is useful when you want to:
-- Organize data into different logical groups
-- Switch between development, staging, and production environments
-- Work with multiple applications using the same SurrealDB instance
+Users are not exported. Accesses whose keys are redacted by the server are included as 
+comments, as they must be defined again with their keys.
 
 Examples:
-- use_namespace('development')
-- use_namespace('production')
-- use_namespace('analytics')
+- dump_schema()  # "DEFINE TABLE person TYPE NORMAL SCHEMAFULL PERMISSIONS NONE;\n..."
 "#)]
-    pub async fn use_namespace(
+    pub async fn dump_schema(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.dump_schema").increment(1);
+        // Output debugging information
+        debug!("Dumping database schema");
+        // Fetch the definitions of the current database
+        let mut exec_res = self.query_internal("INFO FOR DB".to_string(), None).await?;
+        let info = exec_res
+            .take(0)
+            .map_err(McpError::from)?
+            .into_inner()
+            .into_json();
+        // Get the definitions of a kind, sorted by name
+        let definitions = |info: &serde_json::Value, kind: &str| -> Vec<(String, String)> {
+            let mut definitions: Vec<(String, String)> = info[kind]
+                .as_object()
+                .map(|defs| {
+                    defs.iter()
+                        .filter_map(|(name, def)| Some((name.clone(), def.as_str()?.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default();
+            definitions.sort();
+            definitions
+        };
+        let tables = definitions(&info, "tables");
+        // Fetch the definitions of each table
+        let mut table_info = Vec::with_capacity(tables.len());
+        if !tables.is_empty() {
+            let query = tables
+                .iter()
+                .map(|(table, _)| {
+                    quote_ident(table).map(|table| format!("INFO FOR TABLE {table};"))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| McpError::internal_error(e, None))?
+                .join(" ");
+            let mut exec_res = self.query_internal(query, None).await?;
+            for index in 0..tables.len() {
+                let info = exec_res
+                    .take(index)
+                    .map_err(McpError::from)?
+                    .into_inner()
+                    .into_json();
+                table_info.push(info);
+            }
+        }
+        // Order the tables so that views are defined after the tables they select from
+        let names: Vec<&str> = tables.iter().map(|(name, _)| name.as_str()).collect();
+        let mut sources: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (table, info) in names.iter().zip(&table_info) {
+            for (view, _) in definitions(info, "tables") {
+                if let Some(view) = names.iter().find(|name| **name == view) {
+                    sources.entry(view).or_default().push(table);
+                }
+            }
+        }
+        let mut order: Vec<usize> = Vec::with_capacity(tables.len());
+        while order.len() < tables.len() {
+            // Find the next table whose sources have all been defined
+            let next = (0..tables.len()).find(|index| {
+                !order.contains(index)
+                    && sources.get(names[*index]).is_none_or(|sources| {
+                        sources
+                            .iter()
+                            .all(|source| order.iter().any(|i| names[*i] == *source))
+                    })
+            });
+            // Define any remaining tables with cyclic sources in name order
+            let next = next.or_else(|| (0..tables.len()).find(|index| !order.contains(index)));
+            order.extend(next);
+        }
+        // Build the script in dependency order
+        let mut script = String::new();
+        for kind in ["analyzers", "functions", "params", "accesses"] {
+            for (name, def) in definitions(&info, kind) {
+                if def.contains("'[REDACTED]'") {
+                    // Comment out every line of the definition
+                    script.push_str(&format!(
+                        "-- The keys of {name} are redacted by the server, so define it again with its keys:\n"
+                    ));
+                    for line in format!("{def};").lines() {
+                        script.push_str(&format!("-- {line}\n"));
+                    }
+                } else {
+                    script.push_str(&format!("{def};\n"));
+                }
+            }
+        }
+        for index in &order {
+            script.push_str(&format!("{};\n", tables[*index].1));
+        }
+        for kind in ["fields", "indexes", "events"] {
+            for index in &order {
+                for (_, def) in definitions(&table_info[*index], kind) {
+                    script.push_str(&format!("{def};\n"));
+                }
+            }
+        }
+        // Return the script
+        Ok(CallToolResult::success(vec![Content::text(script)]))
+    }
+
+    /// Define a database-wide parameter.
+    ///
+    /// This function executes a DEFINE PARAM statement, storing a JSON value in a
+    /// global parameter which can be referenced by all subsequent queries against
+    /// the current database. The value is bound as a query parameter rather than
+    /// being inserted into the statement text.
+    #[tool(description = r#"
+Define a global parameter in the current database.
+
+This function executes a DEFINE PARAM statement, storing a value which can be referenced 
+as $name in all subsequent queries against the current database. The value can be any 
+JSON value. By default an error is returned if the parameter is already defined, unless 
+overwrite is set to true. This tool is only available when the server has been started 
+with --allow-define-statements.
+
+Examples:
+- define_param('max_results', 100)  # Define $max_results
+- define_param('$settings', {"theme": "dark"}, Some(true))  # Overwrite $settings
+"#)]
+    pub async fn define_param(
         &self,
-        params: Parameters<UseNamespaceParams>,
+        params: Parameters<DefineParamParams>,
     ) -> Result<CallToolResult, McpError> {
-        let UseNamespaceParams { namespace } = params.0;
-        // Start the measurement timer
-        let start_time = Instant::now();
+        let DefineParamParams {
+            name,
+            value,
+            overwrite,
+        } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.use_namespace").increment(1);
+        counter!("surrealmcp.tools.define_param").increment(1);
         // Output debugging information
-        info!(
-            connection_id = %self.connection_id,
-            namespace = %namespace,
-            "Attempting to change namespace"
-        );
-        // Check if namespace is restricted by startup configuration
-        if let Some(configured_namespace) = &self.namespace
-            && namespace != *configured_namespace
-        {
-            // Output debugging information
-            warn!(
-                connection_id = %self.connection_id,
-                requested_namespace = %namespace,
-                configured_namespace = %configured_namespace,
-                "Namespace change rejected: namespace not allowed by server configuration"
-            );
-            // Increment error metrics
-            counter!("surrealmcp.total_errors").increment(1);
-            counter!("surrealmcp.total_configuration_errors").increment(1);
-            counter!("surrealmcp.errors.use_namespace").increment(1);
-            // Return error message
+        debug!(name = %name, overwrite = ?overwrite, "Defining global parameter");
+        // Check that define statements are enabled
+        self.require_define_statements()?;
+        // Validate the parameter name
+        let name = name.strip_prefix('$').unwrap_or(&name);
+        if quote_ident(name).map_err(|e| McpError::internal_error(e, None))? != name {
             return Err(McpError::internal_error(
                 format!(
-                    "Cannot use namespace '{namespace}'. Server is configured to only use namespace '{configured_namespace}'"
+                    "Invalid parameter name '{name}'. Names can only contain letters, digits, and underscores"
                 ),
                 None,
             ));
         }
-        // Lock the database connection
-        let db_guard = self.db.lock().await;
-        // Match the database connection
-        match &*db_guard {
-            Some(db) => {
-                // Use the specified namespace
-                match db.use_ns(&namespace).await {
-                    Ok(_) => {
-                        let duration = start_time.elapsed();
-                        // Output debugging information
-                        info!(
-                            connection_id = %self.connection_id,
-                            namespace = %namespace,
-                            duration_ms = duration.as_millis(),
-                            "Successfully changed namespace"
-                        );
-                        // Return success message
-                        let msg = format!("Successfully switched to namespace '{namespace}'");
-                        Ok(CallToolResult::success(vec![Content::text(msg)]))
-                    }
-                    Err(e) => {
-                        let duration = start_time.elapsed();
-                        // Output debugging information
-                        error!(
-                            connection_id = %self.connection_id,
-                            namespace = %namespace,
-                            duration_ms = duration.as_millis(),
-                            error = %e,
-                            "Failed to change namespace"
-                        );
-                        // Increment error metrics
-                        counter!("surrealmcp.total_errors").increment(1);
-                        counter!("surrealmcp.total_connection_errors").increment(1);
-                        counter!("surrealmcp.errors.use_namespace").increment(1);
-                        // Return error message
-                        Err(McpError::internal_error(
-                            format!("Failed to change namespace to '{namespace}': {e}"),
-                            None,
-                        ))
-                    }
-                }
-            }
-            None => {
-                // Output debugging information
-                warn!(
-                    connection_id = %self.connection_id,
-                    namespace = %namespace,
-                    "Namespace change attempted without database connection"
-                );
-                // Increment error metrics
-                counter!("surrealmcp.total_errors").increment(1);
-                counter!("surrealmcp.total_configuration_errors").increment(1);
-                counter!("surrealmcp.errors.no_connection").increment(1);
-                // Return error message
-                Err(McpError::internal_error(
-                    "Not connected to any SurrealDB endpoint. Use connect_endpoint first."
-                        .to_string(),
+        // Build the DEFINE PARAM statement
+        let query = match overwrite {
+            Some(true) => format!("DEFINE PARAM OVERWRITE ${name} VALUE $define_param_value"),
+            _ => format!("DEFINE PARAM ${name} VALUE $define_param_value"),
+        };
+        // Bind the parameter value
+        let value = convert_json_to_surreal(value, "value")
+            .map_err(|e| McpError::internal_error(e, None))?;
+        let params = HashMap::from([("define_param_value".to_string(), value)]);
+        // Output debugging information
+        trace!("Defining global parameter with query: {query}");
+        // Execute the final query
+        self.query_internal(query, Some(params))
+            .await?
+            .to_mcp_result()
+    }
+
+    /// List the global parameters defined in the current database.
+    ///
+    /// This function reads the parameter definitions from INFO FOR DB, returning
+    /// the name and definition of each parameter.
+    #[tool(description = r#"
+List the global parameters defined in the current database.
+
+This function returns the name and the DEFINE PARAM statement of each global parameter 
+which has been defined in the current database.
+
+Examples:
+- list_params()  # List all global parameters
+"#)]
+    pub async fn list_params(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.list_params").increment(1);
+        // Output debugging information
+        debug!("Listing global parameters");
+        // Fetch the parameters defined in the current database
+        let mut exec_res = self.query_internal("INFO FOR DB".to_string(), None).await?;
+        let info = exec_res
+            .take(0)
+            .map_err(McpError::from)?
+            .into_inner()
+            .into_json();
+        // Collect the parameter definitions
+        let params: Vec<serde_json::Value> = info["params"]
+            .as_object()
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(name, definition)| {
+                        serde_json::json!({ "name": name, "definition": definition })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Return the result
+        let result = serde_json::json!({ "params": params });
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Define a system user.
+    ///
+    /// This function executes a DEFINE USER statement at the root, namespace, or
+    /// database level. The password is bound as a query parameter and hashed by
+    /// the database, so that only the resulting password hash is ever included
+    /// in the statement text, ensuring the password never appears in any logs.
+    #[tool(description = r#"
+Define a system user at the root, namespace, or database level.
+
+This function executes a DEFINE USER statement, creating a user which can sign in to 
+the database with the specified password. The level must be one of 'root', 'namespace', 
+or 'database', and applies to the currently selected namespace or database. The roles 
+can include 'owner', 'editor', and 'viewer', and default to 'viewer'. An error is 
+returned if the user is already defined. This tool is only available when the server 
+has been started with --allow-define-statements.
+
+Examples:
+- define_user('alice', 'database', 'p4ssw0rd')  # Define a database viewer
+- define_user('bob', 'namespace', 'p4ssw0rd', ['editor'])  # Define a namespace editor
+"#)]
+    pub async fn define_user(
+        &self,
+        params: Parameters<DefineUserParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let DefineUserParams {
+            name,
+            level,
+            password,
+            roles,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.define_user").increment(1);
+        // Output debugging information
+        debug!(name = %name, level = %level, roles = ?roles, "Defining system user");
+        // Check that define statements are enabled
+        self.require_define_statements()?;
+        // Validate the user name
+        let name = quote_ident(&name).map_err(|e| McpError::internal_error(e, None))?;
+        // Validate the user level
+        let level = match level.to_lowercase().as_str() {
+            "root" => "ROOT",
+            "namespace" | "ns" => "NAMESPACE",
+            "database" | "db" => "DATABASE",
+            _ => {
+                return Err(McpError::internal_error(
+                    format!(
+                        "Invalid user level '{level}'. Must be one of 'root', 'namespace', or 'database'"
+                    ),
                     None,
-                ))
+                ));
             }
+        };
+        // Validate the user roles
+        let roles = roles
+            .unwrap_or_else(|| vec!["viewer".to_string()])
+            .iter()
+            .map(|role| match role.to_lowercase().as_str() {
+                "owner" => Ok("OWNER"),
+                "editor" => Ok("EDITOR"),
+                "viewer" => Ok("VIEWER"),
+                _ => Err(McpError::internal_error(
+                    format!(
+                        "Invalid user role '{role}'. Must be one of 'owner', 'editor', or 'viewer'"
+                    ),
+                    None,
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if roles.is_empty() {
+            return Err(McpError::internal_error(
+                "At least one user role must be specified".to_string(),
+                None,
+            ));
         }
+        // Hash the bound password so that it is never included in the statement
+        let password = convert_json_to_surreal(password, "password")
+            .map_err(|e| McpError::internal_error(e, None))?;
+        let params = HashMap::from([("define_user_password".to_string(), password)]);
+        let mut exec_res = self
+            .query_internal(
+                "RETURN crypto::argon2::generate($define_user_password)".to_string(),
+                Some(params),
+            )
+            .await?;
+        let hash = exec_res
+            .take(0)
+            .map_err(McpError::from)?
+            .into_inner()
+            .to_string();
+        // Build the DEFINE USER statement
+        let query = format!(
+            "DEFINE USER {name} ON {level} PASSHASH {hash} ROLES {}",
+            roles.join(", ")
+        );
+        // Output debugging information
+        trace!("Defining system user with query: {query}");
+        // Execute the final query
+        self.query_internal(query, None).await?.to_mcp_result()
     }
 
-    /// Change the database on the currently connected endpoint.
+    /// Define a table and its fields.
     ///
-    /// This function allows you to switch to a different database on the currently
-    /// connected SurrealDB endpoint. The database change will apply to all subsequent
-    /// queries until you change it again or reconnect to a different endpoint.
+    /// This function generates a DEFINE TABLE statement, followed by a DEFINE
+    /// FIELD statement for each field, and executes them in a single query. The
+    /// generated statements are parsed before being executed, so that types and
+    /// permissions can not be used to inject additional statements.
+    #[tool(description = r#"
+Define a table, and optionally its fields, in the current database.
+
+This function generates a DEFINE TABLE statement, followed by a DEFINE FIELD statement 
+for each field, and executes them. Set schemafull to true to only store the defined 
+fields. Each field has a name, a SurrealQL type such as 'string', 'int', 'datetime', or 
+'record<person>', and can be marked as optional to allow it to be empty. The permissions 
+are added as the PERMISSIONS clause of the table. The generated statements are returned 
+along with the result. The statements are executed in a single transaction, so if the 
+table is already defined, an error is returned and none of the fields are defined. This 
+tool is only available when the server has been started with --allow-define-statements.
+
+Examples:
+- define_table('person')  # Define a schemaless table
+- define_table('person', true, [{"name": "name", "type": "string"}, {"name": "age", "type": "int", "optional": true}])  # Define a schemafull table with typed fields
+- define_table('post', None, None, 'FOR select FULL FOR create, update, delete NONE')  # Define a read-only table
+"#)]
+    pub async fn define_table(
+        &self,
+        params: Parameters<DefineTableParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let DefineTableParams {
+            name,
+            schemafull,
+            fields,
+            permissions,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.define_table").increment(1);
+        // Output debugging information
+        debug!(name = %name, schemafull = ?schemafull, "Defining table");
+        // Check that define statements are enabled
+        self.require_define_statements()?;
+        // Generate the DEFINE TABLE and DEFINE FIELD statements
+        let fields = fields.unwrap_or_default();
+        let query = define_table_statements(
+            &name,
+            schemafull.unwrap_or(false),
+            &fields,
+            permissions.as_deref(),
+        )
+        .map_err(|e| McpError::internal_error(e, None))?;
+        // Output debugging information
+        trace!("Defining table with query: {query}");
+        // Define the table and its fields together, so that no fields are added
+        // if the table can not be defined. Statements buffered in an open
+        // transaction are already executed within a transaction.
+        let statements = match self.transaction.lock().await.is_some() {
+            true => query.clone(),
+            false => format!("BEGIN TRANSACTION;\n{query}COMMIT TRANSACTION;"),
+        };
+        // Execute the final query
+        let result = self
+            .query_internal(statements, None)
+            .await?
+            .to_mcp_result()?;
+        // Return the generated statements along with the result
+        let mut content = vec![Content::text(query)];
+        content.extend(result.content);
+        Ok(CallToolResult::success(content))
+    }
+
+    /// Define an index on a table.
     ///
-    /// # Arguments
-    /// * `database` - The database to switch to
+    /// This function generates a DEFINE INDEX statement for a standard, unique,
+    /// full-text search, or vector index, and executes it. Only one of the unique,
+    /// search, and vector options can be specified for each index.
     #[tool(description = r#"
-Change the database on the currently connected endpoint.
+Define an index on a table in the current database.
 
-This function allows you to switch to a different database on the currently connected 
-SurrealDB endpoint. The database change will apply to all subsequent queries until 
-you change it again or reconnect to a different endpoint.
+This function generates a DEFINE INDEX statement on the specified fields, and executes 
+it. By default a standard index is defined. Set unique to true to require the indexed 
+values to be unique, or set search to true and specify an existing analyzer to define a 
+full-text search index. Set vector to define an MTREE or HNSW index on a field which 
+contains arrays of numbers, such as embeddings, with the number of dimensions and an 
+optional distance function. Only one of unique, search, and vector can be specified. 
+The generated statement is returned along with the result. This tool is only available 
+when the server has been started with --allow-define-statements.
 
-This is useful when you want to:
-- Switch between different databases within the same namespace
-- Organize data into different logical groups
-- Work with multiple applications using the same SurrealDB instance
+Examples:
+- define_index('person', 'person_email', ['email'], true)  # Define a unique index
+- define_index('post', 'post_body', ['body'], None, true, 'english')  # Define a full-text search index
+- define_index('document', 'document_embedding', ['embedding'], None, None, None, {"kind": "hnsw", "dimension": 384, "distance": "cosine"})  # Define a vector index
+"#)]
+    pub async fn define_index(
+        &self,
+        params: Parameters<DefineIndexParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.define_index").increment(1);
+        // Output debugging information
+        debug!(table = %params.table, name = %params.name, "Defining index");
+        // Check that define statements are enabled
+        self.require_define_statements()?;
+        // Generate the DEFINE INDEX statement
+        let query =
+            define_index_statement(&params).map_err(|e| McpError::internal_error(e, None))?;
+        // Output debugging information
+        trace!("Defining index with query: {query}");
+        // Execute the final query
+        let result = self
+            .query_internal(query.clone(), None)
+            .await?
+            .to_mcp_result()?;
+        // Return the generated statement along with the result
+        let mut content = vec![Content::text(query)];
+        content.extend(result.content);
+        Ok(CallToolResult::success(content))
+    }
+
+    /// Read the changes made to a table from its change feed.
+    ///
+    /// This function executes a SHOW CHANGES statement, returning the batches of
+    /// changes made to a table since a versionstamp or datetime. The table must
+    /// have been defined with a CHANGEFEED clause for changes to be recorded.
+    #[tool(description = r#"
+Read the changes made to a table since a versionstamp or datetime.
+
+This function executes a SHOW CHANGES statement, returning the change batches recorded 
+by the table's change feed. Each batch includes a versionstamp and the changes made to 
+records in that batch. To read changes incrementally, pass the versionstamp of the last 
+batch plus one as the since value of the next call. The table must have been defined 
+with a change feed (for example DEFINE TABLE person CHANGEFEED 1h).
 
 Examples:
-- use_database('users')
-- use_database('analytics')
-- use_database('events')
+- show_changes('person', '0')  # All changes still retained in the change feed
+- show_changes('person', '2025-01-01T00:00:00Z', 10)  # Up to 10 batches since a datetime
 "#)]
-    pub async fn use_database(
+    pub async fn show_changes(
         &self,
-        params: Parameters<UseDatabaseParams>,
+        params: Parameters<ShowChangesParams>,
     ) -> Result<CallToolResult, McpError> {
-        let UseDatabaseParams { database } = params.0;
-        // Start the measurement timer
-        let start_time = Instant::now();
+        let ShowChangesParams {
+            table,
+            since,
+            limit,
+        } = params.0;
         // Increment tool usage counter
-        counter!("surrealmcp.tools.use_database").increment(1);
+        counter!("surrealmcp.tools.show_changes").increment(1);
         // Output debugging information
-        info!(
-            connection_id = %self.connection_id,
-            database = %database,
-            "Attempting to change database"
-        );
-        // Check if database is restricted by startup configuration
-        if let Some(configured_database) = &self.database
-            && database != *configured_database
-        {
-            // Output debugging information
-            warn!(
-                connection_id = %self.connection_id,
-                requested_database = %database,
-                configured_database = %configured_database,
-                "Database change rejected: database not allowed by server configuration"
-            );
-            // Increment error metrics
-            counter!("surrealmcp.total_errors").increment(1);
-            counter!("surrealmcp.total_configuration_errors").increment(1);
-            counter!("surrealmcp.errors.use_database").increment(1);
-            // Return error message
+        debug!(table = %table, since = %since, limit = ?limit, "Showing table changes");
+        // Validate the table name
+        let table = quote_ident(&table).map_err(|e| McpError::internal_error(e, None))?;
+        // Validate the since value, which can not be bound as a parameter
+        let since = match since.trim().parse::<u64>() {
+            Ok(versionstamp) => versionstamp.to_string(),
+            Err(_) => surrealdb::sql::Datetime::try_from(since.trim())
+                .map(|datetime| datetime.to_string())
+                .map_err(|_| {
+                    McpError::internal_error(
+                        format!(
+                            "Invalid since value '{since}'. Must be a versionstamp or an RFC 3339 datetime"
+                        ),
+                        None,
+                    )
+                })?,
+        };
+        // Validate the limit
+        let limit = limit.unwrap_or(100);
+        if limit == 0 {
             return Err(McpError::internal_error(
-                format!(
-                    "Cannot use database '{database}'. Server is configured to only use database '{configured_database}'"
-                ),
+                "The limit must be greater than zero".to_string(),
                 None,
             ));
         }
-        // Lock the database connection
-        let db_guard = self.db.lock().await;
-        // Match the database connection
-        match &*db_guard {
-            Some(db) => {
-                // Use the specified database
-                match db.use_db(&database).await {
-                    Ok(_) => {
-                        let duration = start_time.elapsed();
-                        // Output debugging information
-                        info!(
-                            connection_id = %self.connection_id,
-                            database = %database,
-                            duration_ms = duration.as_millis(),
-                            "Successfully changed database"
-                        );
-                        // Return success message
-                        let msg = format!("Successfully switched to database '{database}'");
-                        Ok(CallToolResult::success(vec![Content::text(msg)]))
-                    }
-                    Err(e) => {
-                        let duration = start_time.elapsed();
-                        // Output debugging information
-                        error!(
-                            connection_id = %self.connection_id,
-                            database = %database,
-                            duration_ms = duration.as_millis(),
-                            error = %e,
-                            "Failed to change database"
-                        );
-                        // Increment error metrics
-                        counter!("surrealmcp.total_errors").increment(1);
-                        counter!("surrealmcp.total_connection_errors").increment(1);
-                        counter!("surrealmcp.errors.use_database").increment(1);
-                        // Return error message
-                        Err(McpError::internal_error(
-                            format!("Failed to change database to '{database}': {e}"),
-                            None,
-                        ))
-                    }
-                }
-            }
-            None => {
-                // Output debugging information
-                warn!(
-                    connection_id = %self.connection_id,
-                    database = %database,
-                    "Database change attempted without database connection"
-                );
-                // Increment error metrics
-                counter!("surrealmcp.total_errors").increment(1);
-                counter!("surrealmcp.total_configuration_errors").increment(1);
-                counter!("surrealmcp.errors.no_connection").increment(1);
-                // Return error message
-                Err(McpError::internal_error(
-                    "Not connected to any SurrealDB endpoint. Use connect_endpoint first."
-                        .to_string(),
-                    None,
-                ))
-            }
-        }
+        // Build the SHOW CHANGES statement
+        let query = format!("SHOW CHANGES FOR TABLE {table} SINCE {since} LIMIT {limit}");
+        // Output debugging information
+        trace!("Showing table changes with query: {query}");
+        // Execute the final query
+        self.query_internal(query, None).await?.to_mcp_result()
     }
 
-    /// Disconnect from the current SurrealDB endpoint.
+    /// Start a live query which receives notifications of changes to a table.
     ///
-    /// This function disconnects from the currently connected SurrealDB endpoint.
-    /// After disconnecting, you'll need to use connect_endpoint again to establish
-    /// a new connection before you can execute queries.
+    /// This function executes a LIVE SELECT statement, and buffers the notifications
+    /// which are received for it in the background. As MCP tools can not stream
+    /// results, the notifications are retrieved with poll_live_query, and the live
+    /// query is stopped with kill_live_query.
     #[tool(description = r#"
-Disconnect from the current SurrealDB endpoint.
+Start a live query which receives notifications of changes to a table.
 
-This function disconnects from the currently connected SurrealDB endpoint.
-After disconnecting, you'll need to use connect_endpoint again to establish
-a new connection before you can execute queries.
+This function executes a LIVE SELECT statement on the table, and returns the ID of the 
+live query. Notifications of records which are created, updated, or deleted are buffered 
+in the background, and can be retrieved by calling poll_live_query with the ID. Up to 
+1000 notifications are buffered, after which the oldest are discarded. Stop the live 
+query with kill_live_query once it is no longer needed. Live queries are stopped when 
+the session disconnects, connects to another endpoint, or reconnects after losing its 
+connection, after which polling them returns an error.
 
-This is useful when you want to:
-- Switch to a different database
-- Clean up resources
-- Ensure no active connections remain
+Examples:
+- live_query('person')  # {"id": "...", "table": "person"}
+- live_query('person', Some("age > 18"))  # Only notify about adults
 "#)]
-    pub async fn disconnect_endpoint(&self) -> Result<CallToolResult, McpError> {
-        // Increment tool usage metrics
-        counter!("surrealmcp.tools.disconnect_endpoint").increment(1);
+    pub async fn live_query(
+        &self,
+        params: Parameters<LiveQueryParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let LiveQueryParams {
+            table,
+            where_clause,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.live_query").increment(1);
         // Output debugging information
-        info!(
-            connection_id = %self.connection_id,
-            "Disconnecting from SurrealDB endpoint"
+        debug!(table = %table, "Starting live query");
+        // Build the LIVE SELECT statement
+        let mut query = format!(
+            "LIVE SELECT * FROM {}",
+            quote_ident(&table).map_err(|e| McpError::internal_error(e, None))?
+        );
+        // Add the where clause if provided
+        if let Some(v) = where_clause {
+            let v = validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" WHERE {v}"));
+        }
+        // Check the query can be run by this session
+        self.require_allowed_query(&query, None)?;
+        // Output debugging information
+        trace!("Starting live query with query: {query}");
+        // Get the database connection
+        let db = self.require_connection().await?;
+        // Register the live query
+        let mut response = db
+            .query(query)
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let id = response
+            .take::<Value>(0)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let surrealdb::sql::Value::Uuid(id) = id.into_inner() else {
+            return Err(McpError::internal_error(
+                "The live query did not return an ID".to_string(),
+                None,
+            ));
+        };
+        let mut stream = response
+            .stream::<Value>(0)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        // Buffer notifications in the background until the live query is killed
+        let notifications = Arc::new(Mutex::new(LiveNotifications::default()));
+        let task = tokio::spawn({
+            let notifications = notifications.clone();
+            async move {
+                while let Some(notification) = stream.next().await {
+                    let notification = serde_json::json!({
+                        "action": format!("{:?}", notification.action).to_uppercase(),
+                        "data": engine::to_json(notification.data.into_inner()),
+                    });
+                    notifications.lock().await.push(notification);
+                }
+            }
+        });
+        // Store the live query for this session
+        let id = id.0;
+        self.live_queries.lock().await.insert(
+            id,
+            LiveQuery {
+                table: table.clone(),
+                notifications,
+                task,
+            },
+        );
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            live_query_id = %id,
+            table = %table,
+            "Started live query"
+        );
+        // Return the live query ID
+        let result = serde_json::json!({
+            "id": id.to_string(),
+            "table": table,
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Retrieve the notifications received for a live query since it was last polled.
+    ///
+    /// This function drains the notifications buffered for a live query started
+    /// with live_query, returning them in the order in which they were received.
+    #[tool(description = r#"
+Retrieve the notifications received for a live query since it was last polled.
+
+This function returns and removes the notifications buffered for a live query started 
+with live_query, oldest first. Each notification has an action (CREATE, UPDATE, or 
+DELETE) and the data of the record. The dropped count is the number of notifications 
+which were discarded because the buffer was full. An empty list means that no changes 
+have been made since the last poll.
+
+Examples:
+- poll_live_query('0189d0c4-...')  # {"id": "...", "table": "person", "notifications": [{"action": "CREATE", "data": {...}}], "dropped": 0}
+"#)]
+    pub async fn poll_live_query(
+        &self,
+        params: Parameters<LiveQueryIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let LiveQueryIdParams { id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.poll_live_query").increment(1);
+        // Output debugging information
+        debug!(live_query_id = %id, "Polling live query");
+        // Find the live query for this session
+        let id = parse_live_query_id(&id)?;
+        let live_queries = self.live_queries.lock().await;
+        let live_query = live_queries.get(&id).ok_or_else(|| {
+            McpError::internal_error(format!("Live query '{id}' was not found"), None)
+        })?;
+        // Drain the buffered notifications
+        let mut notifications = live_query.notifications.lock().await;
+        let pending: Vec<_> = notifications.pending.drain(..).collect();
+        let dropped = std::mem::take(&mut notifications.dropped);
+        // Return the notifications
+        let result = serde_json::json!({
+            "id": id.to_string(),
+            "table": live_query.table,
+            "notifications": pending,
+            "dropped": dropped,
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Stop a live query started with live_query.
+    ///
+    /// This function executes a KILL statement for the live query, and discards
+    /// any notifications which have not yet been polled.
+    #[tool(description = r#"
+Stop a live query started with live_query.
+
+This function executes a KILL statement for the live query, so that no further 
+notifications are received, and discards any notifications which have not been polled.
+
+Examples:
+- kill_live_query('0189d0c4-...')
+"#)]
+    pub async fn kill_live_query(
+        &self,
+        params: Parameters<LiveQueryIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let LiveQueryIdParams { id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.kill_live_query").increment(1);
+        // Output debugging information
+        debug!(live_query_id = %id, "Killing live query");
+        // Remove the live query from this session
+        let id = parse_live_query_id(&id)?;
+        let live_query = self.live_queries.lock().await.remove(&id).ok_or_else(|| {
+            McpError::internal_error(format!("Live query '{id}' was not found"), None)
+        })?;
+        // Kill the live query, before the notification stream is dropped
+        let mut params = HashMap::new();
+        params.insert(
+            "id".to_string(),
+            Value::from_inner(surrealdb::sql::Value::Uuid(id.into())),
+        );
+        let result = self
+            .query_internal("KILL $id".to_string(), Some(params))
+            .await;
+        live_query.task.abort();
+        result?.to_mcp_result()?;
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            live_query_id = %id,
+            "Killed live query"
+        );
+        // Return success message
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Killed live query '{id}'"
+        ))]))
+    }
+
+    /// Begin a transaction spanning multiple tool calls.
+    ///
+    /// SurrealDB transactions are scoped to a single query, so the statements
+    /// which modify data are buffered by this session until commit_transaction
+    /// is called, when they are executed together within a single transaction.
+    #[tool(description = r#"
+Begin a transaction spanning multiple tool calls.
+
+After this function is called, every tool call which modifies data (such as create, 
+insert, update, delete, relate, or a query containing writes) is buffered rather than 
+executed. Call commit_transaction to execute all of the buffered statements atomically 
+in a single transaction, or cancel_transaction to discard them. Calls which only read 
+data are executed immediately, so do not see the changes buffered in the transaction. 
+Queries containing BEGIN, COMMIT, or CANCEL statements are rejected while the transaction 
+is open. Only one transaction can be open at a time.
+
+Examples:
+- begin_transaction()  # Then create(...), update(...), and commit_transaction()
+"#)]
+    pub async fn begin_transaction(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.begin_transaction").increment(1);
+        // Output debugging information
+        debug!(connection_id = %self.connection_id, "Beginning transaction");
+        // Check that no transaction is already open
+        let mut transaction = self.transaction.lock().await;
+        if transaction.is_some() {
+            return Err(McpError::internal_error(
+                "A transaction is already open. Commit or cancel it before beginning another"
+                    .to_string(),
+                None,
+            ));
+        }
+        // Open a new transaction
+        *transaction = Some(Vec::new());
+        // Return success message
+        Ok(CallToolResult::success(vec![Content::text(
+            "Began a transaction. Statements which modify data will be buffered until commit_transaction is called".to_string(),
+        )]))
+    }
+
+    /// Commit the open transaction.
+    ///
+    /// This function executes the statements buffered since begin_transaction
+    /// within a single BEGIN TRANSACTION and COMMIT TRANSACTION block, so either
+    /// all of the statements are applied, or none of them are.
+    #[tool(description = r#"
+Commit the open transaction, executing its buffered statements atomically.
+
+This function executes every statement buffered since begin_transaction in a single 
+transaction, returning the result of each statement in the order they were buffered. 
+If any statement fails, none of the changes are applied, and the error is returned. 
+The transaction is closed whether or not it succeeds.
+
+Examples:
+- commit_transaction()
+"#)]
+    pub async fn commit_transaction(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.commit_transaction").increment(1);
+        // Close the open transaction
+        let statements = self.transaction.lock().await.take().ok_or_else(|| {
+            McpError::internal_error(
+                "No transaction is open. Use begin_transaction first".to_string(),
+                None,
+            )
+        })?;
+        // Output debugging information
+        debug!(
+            connection_id = %self.connection_id,
+            statements = statements.len(),
+            "Committing transaction"
+        );
+        // Nothing needs to be executed for an empty transaction
+        if statements.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "[]".to_string(),
+            )]));
+        }
+        // Execute the buffered statements within a single transaction
+        let query = format!(
+            "BEGIN TRANSACTION;\n{}\nCOMMIT TRANSACTION;",
+            statements
+                .iter()
+                .map(|statement| statement.statements.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        // The statements were checked when they were buffered, including whether
+        // modifying whole tables was confirmed, so do not need to be confirmed again
+        let mut res = self
+            .query_internal_with(query, None, self.options.empty_string_as_none, Some(true))
+            .await?;
+        // Remove the results of the LET statements which bind and reset the parameters
+        let mut results = statements.iter().flat_map(|statement| &statement.results);
+        res.statements
+            .retain(|_| results.next().copied().unwrap_or_default());
+        res.to_mcp_result()
+    }
+
+    /// Cancel the open transaction.
+    ///
+    /// This function discards the statements buffered since begin_transaction,
+    /// so that none of them are executed.
+    #[tool(description = r#"
+Cancel the open transaction, discarding its buffered statements.
+
+This function closes the transaction opened with begin_transaction without executing 
+any of the statements buffered since, so none of their changes are applied.
+
+Examples:
+- cancel_transaction()
+"#)]
+    pub async fn cancel_transaction(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.cancel_transaction").increment(1);
+        // Close the open transaction
+        let statements = self.transaction.lock().await.take().ok_or_else(|| {
+            McpError::internal_error(
+                "No transaction is open. Use begin_transaction first".to_string(),
+                None,
+            )
+        })?;
+        // Output debugging information
+        debug!(
+            connection_id = %self.connection_id,
+            statements = statements.len(),
+            "Cancelled transaction"
+        );
+        // Return success message
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Cancelled the transaction, discarding {} buffered statements",
+            statements.len()
+        ))]))
+    }
+
+    #[tool(description = "List SurrealDB Cloud organizations")]
+    pub async fn list_cloud_organizations(
+        &self,
+        _params: Parameters<CloudParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.list_cloud_organizations").increment(1);
+        // Output debugging information
+        debug!("Listing cloud organizations");
+        // Fetch the cloud organisations
+        let organisations = self
+            .cloud_client
+            .list_organizations()
+            .await
+            .map_err(McpError::from)?;
+        // Convert result to JSON
+        let organisations: Vec<serde_json::Value> = organisations
+            .into_iter()
+            .map(|org| {
+                serde_json::json!({
+                    "id": org.id,
+                    "name": org.name,
+                    "billing_info": org.billing_info,
+                    "payment_info": org.payment_info,
+                    "max_free_instances": org.max_free_instances,
+                    "max_paid_instances": org.max_paid_instances,
+                    "member_count": org.member_count,
+                    "user_role": org.user_role,
+                    "plan": org.plan,
+                })
+            })
+            .collect();
+        // Create the result JSON
+        let result = serde_json::json!({
+            "organizations": organisations,
+            "count": organisations.len()
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = r#"
+List the SurrealDB Cloud regions available to an organization.
+
+Use this before create_cloud_instance to find a valid region. Each region has a slug, 
+which identifies it, and a display name. Organizations without a plan have no regions.
+"#)]
+    pub async fn list_cloud_regions(
+        &self,
+        params: Parameters<CloudOrganizationParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudOrganizationParams { organization_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.list_cloud_regions").increment(1);
+        // Output debugging information
+        debug!(
+            organization_id = organization_id,
+            "Listing cloud regions for organization"
+        );
+        // Fetch the cloud regions
+        let regions = self
+            .cloud_client
+            .list_regions(&organization_id)
+            .await
+            .map_err(McpError::from)?;
+        // Convert result to JSON, using the slug when there is no display name
+        let regions: Vec<serde_json::Value> = regions
+            .into_iter()
+            .map(|region| {
+                serde_json::json!({
+                    "name": region.display_name.as_deref().unwrap_or(&region.slug),
+                    "slug": region.slug,
+                    "provider": region.provider,
+                })
+            })
+            .collect();
+        // Create the result JSON
+        let mut result = serde_json::json!({
+            "organization_id": organization_id,
+            "regions": regions,
+            "count": regions.len()
+        });
+        // Explain why no regions are available
+        if regions.is_empty() {
+            result["message"] = serde_json::json!(
+                "No regions are available to this organization. It may not have a plan."
+            );
+        }
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = "List SurrealDB Cloud instances for a given organization")]
+    pub async fn list_cloud_instances(
+        &self,
+        params: Parameters<CloudOrganizationParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudOrganizationParams { organization_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.list_cloud_instances").increment(1);
+        // Output debugging information
+        debug!(
+            organization_id = organization_id,
+            "Listing cloud instances for organization"
+        );
+        // Fetch the cloud instances
+        let instances = self
+            .cloud_client
+            .list_instances(&organization_id)
+            .await
+            .map_err(McpError::from)?;
+        // Convert result to JSON
+        let instances: Vec<serde_json::Value> = instances
+            .into_iter()
+            .map(|instance| {
+                serde_json::json!({
+                    "id": instance.id,
+                    "name": instance.name,
+                    "slug": instance.slug,
+                    "version": instance.version,
+                    "available_versions": instance.available_versions,
+                    "host": instance.host,
+                    "region": instance.region,
+                    "organization_id": instance.organization_id,
+                    "compute_units": instance.compute_units,
+                    "state": instance.state,
+                    "storage_size": instance.storage_size,
+                    "can_update_storage_size": instance.can_update_storage_size,
+                    "storage_size_update_cooloff_hours": instance.storage_size_update_cooloff_hours,
+                })
+            })
+            .collect();
+        // Create the result JSON
+        let result = serde_json::json!({
+            "instances": instances,
+            "count": instances.len()
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = "Pause SurrealDB Cloud instance")]
+    pub async fn pause_cloud_instance(
+        &self,
+        params: Parameters<CloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudInstanceParams { instance_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.pause_cloud_instance").increment(1);
+        // Output debugging information
+        debug!(instance_id = instance_id, "Pausing cloud instance");
+        // Pause the cloud instance
+        let instance = self
+            .cloud_client
+            .pause_instance(&instance_id)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "message": "Successfully paused cloud instance",
+            "instance": instance,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = "Resume SurrealDB Cloud instance")]
+    pub async fn resume_cloud_instance(
+        &self,
+        params: Parameters<CloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudInstanceParams { instance_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.resume_cloud_instance").increment(1);
+        // Output debugging information
+        debug!(instance_id = instance_id, "Resuming cloud instance");
+        // Resume the cloud instance
+        let instance = self
+            .cloud_client
+            .resume_instance(&instance_id)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "message": "Successfully resumed cloud instance",
+            "instance": instance,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = r#"
+Update the storage size of a SurrealDB Cloud instance, in gigabytes (GB).
+
+The storage size can only be changed periodically. If the instance was resized recently, 
+an error is returned which gives the length of the cooloff period after each resize, in 
+hours (storage_size_update_cooloff_hours), rather than the time remaining. Use 
+list_cloud_instances to see the current storage size of an instance, in gigabytes, and 
+whether it can currently be resized (can_update_storage_size).
+"#)]
+    pub async fn update_cloud_instance_storage(
+        &self,
+        params: Parameters<UpdateCloudStorageParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let UpdateCloudStorageParams {
+            instance_id,
+            storage_size,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.update_cloud_instance_storage").increment(1);
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            storage_size = storage_size,
+            "Updating cloud instance storage size"
+        );
+        // Ensure that the storage size is valid
+        if storage_size <= 0 {
+            return Err(McpError::internal_error(
+                "The storage size must be greater than zero",
+                None,
+            ));
+        }
+        // Fetch the cloud instance
+        let instance = self
+            .cloud_client
+            .get_instance(&instance_id)
+            .await
+            .map_err(McpError::from)?;
+        // Check that the storage size can be updated now
+        if instance.can_update_storage_size == Some(false) {
+            let msg = match instance.storage_size_update_cooloff_hours {
+                Some(hours) => format!(
+                    "The storage size of cloud instance '{instance_id}' can not be updated yet, as it can only be updated once every {hours} hours"
+                ),
+                None => format!(
+                    "The storage size of cloud instance '{instance_id}' can not be updated at this time"
+                ),
+            };
+            return Err(McpError::internal_error(msg, None));
+        }
+        // Update the cloud instance
+        let instance = self
+            .cloud_client
+            .update_instance_storage(&instance_id, storage_size)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "message": "Successfully updated cloud instance storage size",
+            "instance": instance,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = r#"
+Upgrade a SurrealDB Cloud instance to a newer SurrealDB version.
+
+The version must be one of the available_versions of the instance, as listed by 
+list_cloud_instances. Otherwise an error is returned which lists the valid versions.
+"#)]
+    pub async fn upgrade_cloud_instance(
+        &self,
+        params: Parameters<UpgradeCloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let UpgradeCloudInstanceParams {
+            instance_id,
+            version,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.upgrade_cloud_instance").increment(1);
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            version = version,
+            "Upgrading cloud instance"
+        );
+        // Fetch the cloud instance
+        let instance = self
+            .cloud_client
+            .get_instance(&instance_id)
+            .await
+            .map_err(McpError::from)?;
+        // Check that the version is an available upgrade
+        let available = instance.available_versions.unwrap_or_default();
+        if !available.contains(&version) {
+            let msg = match available.is_empty() {
+                true => format!(
+                    "Cloud instance '{instance_id}' can not be upgraded to version '{version}', as no upgrades are available"
+                ),
+                false => format!(
+                    "Cloud instance '{instance_id}' can not be upgraded to version '{version}'. Available versions: {}",
+                    available.join(", ")
+                ),
+            };
+            return Err(McpError::internal_error(msg, None));
+        }
+        // Upgrade the cloud instance
+        let instance = self
+            .cloud_client
+            .upgrade_instance(&instance_id, &version)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "message": "Successfully started upgrading cloud instance",
+            "instance": instance,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = "Update the compute units of a SurrealDB Cloud instance")]
+    pub async fn update_cloud_instance_compute(
+        &self,
+        params: Parameters<UpdateCloudComputeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let UpdateCloudComputeParams {
+            instance_id,
+            compute_units,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.update_cloud_instance_compute").increment(1);
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            compute_units = compute_units,
+            "Updating cloud instance compute units"
+        );
+        // Ensure that the compute units are valid
+        if compute_units <= 0 {
+            return Err(McpError::internal_error(
+                "The number of compute units must be greater than zero",
+                None,
+            ));
+        }
+        // Update the cloud instance
+        let instance = self
+            .cloud_client
+            .update_instance_compute(&instance_id, compute_units)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "message": "Successfully updated cloud instance compute units",
+            "instance": instance,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = "Get SurrealDB Cloud instance status")]
+    pub async fn get_cloud_instance_status(
+        &self,
+        params: Parameters<CloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudInstanceParams { instance_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.get_cloud_instance_status").increment(1);
+        // Output debugging information
+        debug!("Getting status for cloud instance: {instance_id}");
+        // Fetch the cloud instance status
+        let status = self
+            .cloud_client
+            .get_instance_status(&instance_id)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "instance_id": instance_id,
+            "phase": status.phase,
+            "db_backups": status.db_backups,
+            "backup_count": status.db_backups.len()
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = r#"
+Get the capabilities of a SurrealDB Cloud instance.
+
+This function returns what the instance permits, so that its configuration can be 
+audited: whether scripting and guest access are allowed, and the allowed and denied 
+functions, networks, HTTP endpoints, RPC methods, arbitrary queries, and experimental 
+features. A value of "*" matches everything. Capabilities which are not reported by 
+SurrealDB Cloud are returned as null.
+"#)]
+    pub async fn get_cloud_instance_capabilities(
+        &self,
+        params: Parameters<CloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudInstanceParams { instance_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.get_cloud_instance_capabilities").increment(1);
+        // Output debugging information
+        debug!("Getting capabilities for cloud instance: {instance_id}");
+        // Fetch the cloud instance
+        let instance = self
+            .cloud_client
+            .get_instance(&instance_id)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "instance_id": instance_id,
+            "capabilities": instance.capabilities,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = "Create SurrealDB Cloud instance")]
+    pub async fn create_cloud_instance(
+        &self,
+        params: Parameters<CreateCloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CreateCloudInstanceParams {
+            name,
+            organization_id,
+            region,
+            version,
+            compute_units,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.create_cloud_instance").increment(1);
+        // Output debugging information
+        debug!("Creating cloud instance: {name} in organization: {organization_id}");
+        // Ensure that the compute units are valid
+        if compute_units.is_some_and(|units| units <= 0) {
+            return Err(McpError::internal_error(
+                "The number of compute units must be greater than zero",
+                None,
+            ));
+        }
+        // Check the version against the versions known to the organization
+        if let Some(version) = &version {
+            let instances = self
+                .cloud_client
+                .list_instances(&organization_id)
+                .await
+                .map_err(McpError::from)?;
+            let mut versions: Vec<String> = instances
+                .into_iter()
+                .flat_map(|instance| {
+                    let available = instance.available_versions.unwrap_or_default();
+                    instance.version.into_iter().chain(available)
+                })
+                .collect();
+            versions.sort();
+            versions.dedup();
+            if !versions.is_empty() && !versions.contains(version) {
+                return Err(McpError::internal_error(
+                    format!(
+                        "Unknown SurrealDB version '{version}'. Available versions: {}",
+                        versions.join(", ")
+                    ),
+                    None,
+                ));
+            }
+        }
+        // Create the request
+        let request = CloudCreateInstanceRequest {
+            name,
+            organization_id,
+            region,
+            version,
+            compute_units,
+        };
+        // Create the cloud instance
+        let instance = self
+            .cloud_client
+            .create_instance(&request)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "message": "Successfully created cloud instance",
+            "instance": instance,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = r#"
+Delete a SurrealDB Cloud instance.
+
+WARNING: This operation is irreversible. The instance and all of the data stored in it, 
+including its backups, are permanently deleted and can not be recovered. Only use this 
+tool when the user has explicitly asked for the instance to be deleted, and confirm the 
+instance id with get_cloud_instance_status or list_cloud_instances first.
+"#)]
+    pub async fn delete_cloud_instance(
+        &self,
+        params: Parameters<CloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudInstanceParams { instance_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.delete_cloud_instance").increment(1);
+        // Output debugging information
+        debug!(instance_id = instance_id, "Deleting cloud instance");
+        // Delete the cloud instance
+        self.cloud_client
+            .delete_instance(&instance_id)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "message": "Successfully deleted cloud instance",
+            "instance_id": instance_id,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = "List the backups of a SurrealDB Cloud instance")]
+    pub async fn list_cloud_backups(
+        &self,
+        params: Parameters<CloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudInstanceParams { instance_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.list_cloud_backups").increment(1);
+        // Output debugging information
+        debug!(instance_id = instance_id, "Listing cloud instance backups");
+        // Fetch the cloud instance status, which includes the backups
+        let status = self
+            .cloud_client
+            .get_instance_status(&instance_id)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "instance_id": instance_id,
+            "count": status.db_backups.len(),
+            "backups": status.db_backups,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    #[tool(description = r#"
+Restore a SurrealDB Cloud instance from one of its backups.
+
+WARNING: The data in the instance is replaced with the data in the backup, so any changes 
+made since the backup was taken are lost. Only use this tool when the user has explicitly 
+asked for the instance to be restored, and get the snapshot id from list_cloud_backups.
+"#)]
+    pub async fn restore_cloud_backup(
+        &self,
+        params: Parameters<RestoreCloudBackupParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let RestoreCloudBackupParams {
+            instance_id,
+            snapshot_id,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.restore_cloud_backup").increment(1);
+        // Output debugging information
+        debug!(
+            instance_id = instance_id,
+            snapshot_id = snapshot_id,
+            "Restoring cloud instance backup"
+        );
+        // Ensure that a snapshot is specified
+        if snapshot_id.trim().is_empty() {
+            return Err(McpError::internal_error(
+                "The snapshot id of the backup to restore can not be empty",
+                None,
+            ));
+        }
+        // Restore the cloud instance backup
+        self.cloud_client
+            .restore_backup(&instance_id, &snapshot_id)
+            .await
+            .map_err(McpError::from)?;
+        // Create the result JSON
+        let result = serde_json::json!({
+            "message": "Successfully started restoring cloud instance backup",
+            "instance_id": instance_id,
+            "snapshot_id": snapshot_id,
+        });
+        // Return the MCP result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Connect to a different SurrealDB endpoint.
+    ///
+    /// This function allows you to dynamically connect to a different SurrealDB
+    /// endpoint during your session. The endpoint can be any supported SurrealDB
+    /// engine type (memory, rocksdb, surrealkv, tikv, http, https, ws, wss).
+    ///
+    /// # Arguments
+    /// * `endpoint` - The SurrealDB endpoint URL (e.g., "memory", "file:/path/to/db", "ws://localhost:8000")
+    /// * `namespace` - The namespace to use (optional, defaults to "test")
+    /// * `database` - The database to use (optional, defaults to "test")
+    /// * `username` - Username for authentication (optional, only needed for remote connections)
+    /// * `password` - Password for authentication (optional, only needed for remote connections)
+    #[tool(description = r#"
+Connect to a different SurrealDB endpoint.
+
+This function allows you to dynamically connect to a different SurrealDB endpoint 
+during your session. The endpoint can be any supported SurrealDB engine type including 
+memory (for testing), file-based storage, distributed storage, remote connections, or 
+SurrealDB Cloud instances.
+
+Each client connection is completely isolated, so you can switch between different 
+databases as needed. The connection is persistent until you disconnect or connect to 
+a different endpoint. The username and password are optional.
+
+For SurrealDB Cloud instances, use the format 'cloud:instance_id' where instance_id 
+is the ID of your cloud instance. The tool will automatically fetch the authentication 
+token from the SurrealDB Cloud API and establish a secure connection.
+
+Examples:
+- connect_endpoint('memory')  # For testing
+- connect_endpoint('file:/tmp/mydb', Some('myapp'), Some('production'))  # Local file storage
+- connect_endpoint('ws://localhost:8000', Some('myapp'), Some('production'), Some('root'), Some('password'))  # Remote connection
+- connect_endpoint('rocksdb:/data/mydb', Some('analytics'), Some('events'))  # High-performance local storage
+- connect_endpoint('cloud:abc123def456', Some('myapp'), Some('production'))  # SurrealDB Cloud instance
+"#)]
+    pub async fn connect_endpoint(
+        &self,
+        params: Parameters<ConnectParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let ConnectParams {
+            endpoint,
+            namespace,
+            database,
+            username,
+            password,
+        } = params.0;
+        // Start the measurement timer
+        let start_time = Instant::now();
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.connect_endpoint").increment(1);
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            endpoint = %endpoint,
+            namespace = namespace.as_deref(),
+            database = database.as_deref(),
+            has_username = username.is_some(),
+            "Attempting to connect to SurrealDB endpoint"
+        );
+        // Check if endpoint is restricted by startup configuration
+        if let Some(configured_endpoint) = &self.endpoint
+            && endpoint != *configured_endpoint
+        {
+            // Output debugging information
+            warn!(
+                connection_id = %self.connection_id,
+                requested_endpoint = %endpoint,
+                configured_endpoint = %configured_endpoint,
+                "Connection rejected: endpoint not allowed by server configuration"
+            );
+            // Increment error metrics
+            counter!("surrealmcp.total_errors").increment(1);
+            counter!("surrealmcp.total_configuration_errors").increment(1);
+            counter!("surrealmcp.errors.connect_endpoint").increment(1);
+            // Return error message
+            return Err(McpError::internal_error(
+                format!(
+                    "Cannot connect to endpoint '{endpoint}'. Server is configured to only use endpoint '{configured_endpoint}'"
+                ),
+                None,
+            ));
+        }
+        // Get the namespace to use, checking it is allowed by startup configuration
+        let ns =
+            self.check_restriction("connect_endpoint", "namespace", namespace, &self.namespace)?;
+        // Get the database to use, checking it is allowed by startup configuration
+        let db =
+            self.check_restriction("connect_endpoint", "database", database, &self.database)?;
+        // Get the username to use for authentication
+        let user = username.or_else(|| self.user.clone());
+        // Get the password to use for authentication
+        let pass = password.or_else(|| self.pass.clone());
+        // Check if this is a cloud connection
+        let details = if endpoint.starts_with("cloud:") {
+            // Extract instance ID from cloud:instance_id format
+            let instance_id = endpoint.strip_prefix("cloud:").ok_or_else(|| {
+                McpError::internal_error(
+                    "Invalid cloud endpoint format. Expected 'cloud:instance_id'".to_string(),
+                    None,
+                )
+            })?;
+            // Get the instance details to get the host
+            let instance = match self.cloud_client.get_instance(instance_id).await {
+                Ok(instance) => instance,
+                Err(e) => {
+                    error!(
+                        connection_id = %self.connection_id,
+                        instance_id = %instance_id,
+                        error = %e,
+                        "Failed to get cloud instance details"
+                    );
+                    counter!("surrealmcp.total_errors").increment(1);
+                    counter!("surrealmcp.total_connection_errors").increment(1);
+                    counter!("surrealmcp.errors.connect_endpoint").increment(1);
+                    return Err(McpError::internal_error(
+                        e.to_string(),
+                        Some(engine::error_data(e.is_retryable())),
+                    ));
+                }
+            };
+            // Get the auth token for the cloud instance
+            let token = match self.cloud_client.get_instance_auth(instance_id).await {
+                Ok(token) => token,
+                Err(e) => {
+                    error!(
+                        connection_id = %self.connection_id,
+                        instance_id = %instance_id,
+                        error = %e,
+                        "Failed to get cloud instance token"
+                    );
+                    counter!("surrealmcp.total_errors").increment(1);
+                    counter!("surrealmcp.total_connection_errors").increment(1);
+                    counter!("surrealmcp.errors.connect_endpoint").increment(1);
+                    return Err(McpError::internal_error(
+                        e.to_string(),
+                        Some(engine::error_data(e.is_retryable())),
+                    ));
+                }
+            };
+            // Check if instance state is ready
+            if let Some(state) = &instance.state
+                && state != "ready"
+            {
+                error!(
+                    connection_id = %self.connection_id,
+                    instance_id = %instance_id,
+                    state = %state,
+                    "Cloud instance is not ready to connect"
+                );
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.total_connection_errors").increment(1);
+                counter!("surrealmcp.errors.connect_endpoint").increment(1);
+                return Err(McpError::internal_error(
+                    format!("Cloud instance '{instance_id}' is not ready (state: {state})"),
+                    None,
+                ));
+            }
+            // Get the host from the instance
+            let host = instance.host.ok_or_else(|| {
+                error!(
+                    connection_id = %self.connection_id,
+                    instance_id = %instance_id,
+                    "Cloud instance has no host information"
+                );
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.total_connection_errors").increment(1);
+                counter!("surrealmcp.errors.connect_endpoint").increment(1);
+                McpError::internal_error(
+                    format!("Cloud instance '{instance_id}' has no host information"),
+                    None,
+                )
+            })?;
+            // Connect to the instance host with the token
+            ConnectionDetails {
+                url: crate::cloud::instance_url(&host),
+                token: Some(token),
+                username: user,
+                password: pass,
+            }
+        } else {
+            ConnectionDetails {
+                url: endpoint.clone(),
+                token: None,
+                username: user,
+                password: pass,
+            }
+        };
+        // Create a new SurrealDB connection
+        match details.connect(ns.as_deref(), db.as_deref()).await {
+            Ok(instance) => {
+                // Calculate the elapsed time
+                let duration = start_time.elapsed();
+                // Record the version of the connected server
+                let version = db::probe_version(&instance).await;
+                // Stop live queries on the previous connection
+                self.stop_live_queries().await;
+                // Discard any transaction opened on the previous connection
+                *self.transaction.lock().await = None;
+                // Update the service's database connection
+                let mut db_guard = self.db.lock().await;
+                *db_guard = Some(instance);
+                // Keep the connection details for reconnecting
+                *self.connection.lock().await = Some(details);
+                // Track the current connection context
+                *self.context.lock().await = ConnectionContext {
+                    endpoint: Some(endpoint.clone()),
+                    namespace: ns.clone(),
+                    database: db.clone(),
+                    version,
+                    connected_at: Some(Instant::now()),
+                };
+                // Reset the statistics for the new connection
+                *self.stats.lock().await = SessionStats::default();
+                // Output debugging information
+                info!(
+                    connection_id = %self.connection_id,
+                    endpoint = %endpoint,
+                    namespace = ns.as_deref(),
+                    database = db.as_deref(),
+                    duration_ms = duration.as_millis(),
+                    "Successfully connected to SurrealDB endpoint"
+                );
+                // Return success message
+                let msg = format!("Successfully connected to endpoint '{endpoint}'");
+                Ok(CallToolResult::success(vec![Content::text(msg)]))
+            }
+            Err(e) => {
+                // Calculate the elapsed time
+                let duration = start_time.elapsed();
+                // Output debugging information
+                error!(
+                    connection_id = %self.connection_id,
+                    endpoint = %endpoint,
+                    namespace = ns.as_deref(),
+                    database = db.as_deref(),
+                    duration_ms = duration.as_millis(),
+                    error = %e,
+                    "Failed to connect to SurrealDB endpoint"
+                );
+                // Increment error metrics
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.total_connection_errors").increment(1);
+                counter!("surrealmcp.errors.connect_endpoint").increment(1);
+                // Return error message
+                Err(McpError::internal_error(
+                    format!("Failed to connect to endpoint '{endpoint}': {e}"),
+                    Some(engine::error_data(engine::is_retryable_message(
+                        &e.to_string(),
+                    ))),
+                ))
+            }
+        }
+    }
+
+    /// Connect to a SurrealDB Cloud instance.
+    ///
+    /// This function looks up the host of the instance, fetches an auth token
+    /// for it from the SurrealDB Cloud API, and connects to the instance with
+    /// the token. It is equivalent to connect_endpoint with 'cloud:instance_id'.
+    #[tool(description = r#"
+Connect to a SurrealDB Cloud instance by its ID.
+
+This function looks up the host of the instance and fetches an authentication token 
+from the SurrealDB Cloud API, then connects to the instance using the token, so no 
+host or credentials need to be supplied. The instance must be ready. The connection 
+replaces any current connection, using the namespace and database configured at 
+startup, which can then be changed with use_namespace and use_database.
+
+Examples:
+- connect_cloud_instance('abc123def456')
+"#)]
+    pub async fn connect_cloud_instance(
+        &self,
+        params: Parameters<CloudInstanceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let CloudInstanceParams { instance_id } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.connect_cloud_instance").increment(1);
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            instance_id = %instance_id,
+            "Connecting to cloud instance"
+        );
+        // Connect using the cloud endpoint of the instance
+        self.connect_endpoint(Parameters(ConnectParams {
+            endpoint: format!("cloud:{instance_id}"),
+            namespace: None,
+            database: None,
+            username: None,
+            password: None,
+        }))
+        .await
+    }
+
+    /// Save a named connection profile for this session.
+    ///
+    /// This function stores the endpoint, namespace, database, and credentials
+    /// under a name, so that the session can later connect with connect_profile
+    /// without supplying them again. Credentials are never returned or logged.
+    #[tool(description = r#"
+Save a named connection profile for this session.
+
+This function stores an endpoint, namespace, database, and credentials under a name, so 
+that you can later connect with connect_profile without supplying them again. Saving a 
+profile with an existing name replaces it. Profiles are kept for the rest of the session, 
+and credentials are never returned.
+
+Examples:
+- save_connection_profile('local', 'memory', Some('test'), Some('test'))
+- save_connection_profile('prod', 'wss://db.example.com', Some('app'), Some('prod'), Some('reader'), Some('secret'))
+"#)]
+    pub async fn save_connection_profile(
+        &self,
+        params: Parameters<SaveConnectionProfileParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let SaveConnectionProfileParams {
+            name,
+            endpoint,
+            namespace,
+            database,
+            username,
+            password,
+        } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.save_connection_profile").increment(1);
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            profile = %name,
+            endpoint = %endpoint,
+            namespace = namespace.as_deref(),
+            database = database.as_deref(),
+            has_username = username.is_some(),
+            "Saving connection profile"
+        );
+        // Profiles must be named
+        if name.trim().is_empty() {
+            return Err(McpError::internal_error(
+                "The connection profile name can not be empty",
+                None,
+            ));
+        }
+        // Store the profile for this session
+        let profile = ConnectParams {
+            endpoint,
+            namespace,
+            database,
+            username,
+            password,
+        };
+        let result = redacted_profile(&name, &profile);
+        self.profiles.lock().await.insert(name, profile);
+        // Return the saved profile, without its credentials
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// List the connection profiles saved by this session.
+    ///
+    /// This function returns each saved profile, sorted by name, with whether
+    /// credentials are set rather than the credentials themselves.
+    #[tool(description = r#"
+List the connection profiles saved by this session.
+
+This function returns the name, endpoint, namespace, and database of each saved profile. 
+Credentials are not returned, only whether a username and password are set.
+"#)]
+    pub async fn list_connection_profiles(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.list_connection_profiles").increment(1);
+        // Output debugging information
+        debug!(
+            connection_id = %self.connection_id,
+            "Listing connection profiles"
+        );
+        // Describe each of the saved profiles
+        let profiles = self.profiles.lock().await;
+        let mut names: Vec<&String> = profiles.keys().collect();
+        names.sort();
+        let profiles: Vec<serde_json::Value> = names
+            .into_iter()
+            .map(|name| redacted_profile(name, &profiles[name]))
+            .collect();
+        // Return the result
+        let result = serde_json::json!({ "profiles": profiles });
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Connect to the endpoint of a saved connection profile.
+    ///
+    /// This function connects using the endpoint, namespace, database, and
+    /// credentials stored by save_connection_profile, subject to the same
+    /// restrictions as connect_endpoint.
+    #[tool(description = r#"
+Connect using a connection profile saved by save_connection_profile.
+
+This function connects to the endpoint of the named profile, using its namespace, 
+database, and credentials. It behaves exactly like connect_endpoint.
+
+Examples:
+- connect_profile('prod')
+"#)]
+    pub async fn connect_profile(
+        &self,
+        params: Parameters<ConnectProfileParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let ConnectProfileParams { name } = params.0;
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.connect_profile").increment(1);
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            profile = %name,
+            "Connecting with connection profile"
+        );
+        // Get the saved profile
+        let profile = self.profiles.lock().await.get(&name).cloned();
+        let profile = profile.ok_or_else(|| {
+            McpError::internal_error(format!("Unknown connection profile: {name}"), None)
+        })?;
+        // Connect using the profile
+        self.connect_endpoint(Parameters(profile)).await
+    }
+
+    /// List available namespaces on the connected endpoint.
+    ///
+    /// This function lists all namespaces available on the currently connected SurrealDB endpoint.
+    /// It returns a list of namespaces with their names.
+    ///
+    /// # Arguments
+    /// * `None` - No parameters are required for this tool
+    #[tool(description = r#"
+List available namespaces on the connected endpoint.
+
+This function lists all namespaces available on the currently connected SurrealDB endpoint.
+It returns a list of namespaces with their names."#)]
+    pub async fn list_namespaces(&self) -> Result<CallToolResult, McpError> {
+        // Start the measurement timer
+        let start_time = Instant::now();
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.list_namespaces").increment(1);
+        // Output debugging information
+        debug!("Listing available namespaces");
+        // Build the initial query string
+        let query = "INFO FOR ROOT STRUCTURE ".to_string();
+        // Execute INFO FOR ROOT STRUCTURE
+        let mut exec_res = self.query_internal(query, None).await?;
+        // Match the result of the query
+        match exec_res.take(0) {
+            Ok(value) => {
+                // Calculate the elapsed time
+                let duration = start_time.elapsed();
+                // Parse the first element of the response
+                let info_opt: Option<ListNamespaces> =
+                    serde_json::from_value(value.into_inner().into_json())
+                        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                let info = info_opt.ok_or_else(|| {
+                    McpError::internal_error(
+                        "No namespaces returned when running INFO FOR ROOT".to_string(),
+                        None,
+                    )
+                })?;
+                // Convert the namespaces to a JSON object
+                let namespaces: Vec<serde_json::Value> = info
+                    .namespaces
+                    .into_iter()
+                    .map(|ns| serde_json::json!({ "name": ns.name }))
+                    .collect();
+                // Convert the namespaces to a JSON object
+                let result = serde_json::json!({
+                    "namespaces": namespaces,
+                    "count": namespaces.len()
+                });
+                // Output debugging information
+                info!(
+                    connection_id = %self.connection_id,
+                    duration_ms = duration.as_millis(),
+                    "Successfully listed available namespaces"
+                );
+                // Return the result
+                Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]))
+            }
+            Err(err) => {
+                // Calculate the elapsed time
+                let duration = start_time.elapsed();
+                // Output debugging information
+                warn!(
+                    connection_id = %self.connection_id,
+                    duration_ms = duration.as_millis(),
+                    "Unable to list available namespaces"
+                );
+                // Increment error metrics
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.errors.list_namespaces").increment(1);
+                // Return error message
+                Err(err.into())
+            }
+        }
+    }
+
+    /// List available databases on the connected endpoint.
+    ///
+    /// This function lists all databases available on the currently connected SurrealDB endpoint.
+    /// It returns a list of databases with their names.
+    ///
+    /// # Arguments
+    /// * `None` - No parameters are required for this tool
+    #[tool(description = r#"
+List available databases on the connected endpoint.
+
+This function lists all databases available on the currently connected SurrealDB endpoint.
+It returns a list of databases with their names. A namespace must be selected first."#)]
+    pub async fn list_databases(&self) -> Result<CallToolResult, McpError> {
+        // Start the measurement timer
+        let start_time = Instant::now();
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.list_databases").increment(1);
+        // Output debugging information
+        debug!("Listing available databases");
+        // Check that a namespace has been selected
+        let context = self.context.lock().await.clone();
+        if context.endpoint.is_some() && context.namespace.is_none() {
+            return Err(McpError::internal_error(
+                "No namespace is selected. Use use_namespace to select a namespace first."
+                    .to_string(),
+                None,
+            ));
+        }
+        // Build the initial query string
+        let query = "INFO FOR NAMESPACE STRUCTURE ".to_string();
+        // Execute INFO FOR NAMESPACE STRUCTURE
+        let mut exec_res = self.query_internal(query, None).await?;
+        // Match the result of the query
+        match exec_res.take(0) {
+            Ok(value) => {
+                // Calculate the elapsed time
+                let duration = start_time.elapsed();
+                // Parse the first element of the response
+                let info_opt: Option<ListDatabases> =
+                    serde_json::from_value(value.into_inner().into_json())
+                        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                let info = info_opt.ok_or_else(|| {
+                    McpError::internal_error(
+                        "No databases returned when running INFO FOR NAMESPACE".to_string(),
+                        None,
+                    )
+                })?;
+                // Convert the databases to a JSON object
+                let databases: Vec<serde_json::Value> = info
+                    .databases
+                    .into_iter()
+                    .map(|db| serde_json::json!({ "name": db.name }))
+                    .collect();
+                // Convert the databases to a JSON object
+                let result = serde_json::json!({
+                    "databases": databases,
+                    "count": databases.len()
+                });
+                // Output debugging information
+                info!(
+                    connection_id = %self.connection_id,
+                    duration_ms = duration.as_millis(),
+                    "Successfully listed available databases"
+                );
+                // Return the result
+                Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]))
+            }
+            Err(err) => {
+                // Calculate the elapsed time
+                let duration = start_time.elapsed();
+                // Output debugging information
+                warn!(
+                    connection_id = %self.connection_id,
+                    duration_ms = duration.as_millis(),
+                    "Unable to list available databases"
+                );
+                // Increment error metrics
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.errors.list_databases").increment(1);
+                // Return error message
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Change the namespace on the currently connected endpoint.
+    ///
+    /// This function allows you to switch to a different namespace on the currently
+    /// connected SurrealDB endpoint. The namespace change will apply to all subsequent
+    /// queries until you change it again or reconnect to a different endpoint.
+    ///
+    /// # Arguments
+    /// * `namespace` - The namespace to switch to
+    #[tool(description = r#"
+Change the namespace on the currently connected endpoint.
+
+This function allows you to switch to a different namespace on the currently connected 
+SurrealDB endpoint. The namespace change will apply to all subsequent queries until 
+you change it again or reconnect to a different endpoint.
+
+This is useful when you want to:
+- Organize data into different logical groups
+- Switch between development, staging, and production environments
+- Work with multiple applications using the same SurrealDB instance
+
+Examples:
+- use_namespace('development')
+- use_namespace('production')
+- use_namespace('analytics')
+"#)]
+    pub async fn use_namespace(
+        &self,
+        params: Parameters<UseNamespaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let UseNamespaceParams { namespace } = params.0;
+        // Start the measurement timer
+        let start_time = Instant::now();
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.use_namespace").increment(1);
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            namespace = %namespace,
+            "Attempting to change namespace"
+        );
+        // Check if namespace is restricted by startup configuration
+        self.check_restriction(
+            "use_namespace",
+            "namespace",
+            Some(namespace.clone()),
+            &self.namespace,
+        )?;
+        // Get the database connection
+        let db = self.require_connection().await?;
+        // Use the specified namespace
+        match db.use_ns(&namespace).await {
+            Ok(_) => {
+                let duration = start_time.elapsed();
+                // Track the current namespace
+                self.context.lock().await.namespace = Some(namespace.clone());
+                // Output debugging information
+                info!(
+                    connection_id = %self.connection_id,
+                    namespace = %namespace,
+                    duration_ms = duration.as_millis(),
+                    "Successfully changed namespace"
+                );
+                // Return success message
+                let msg = format!("Successfully switched to namespace '{namespace}'");
+                Ok(CallToolResult::success(vec![Content::text(msg)]))
+            }
+            Err(e) => {
+                let duration = start_time.elapsed();
+                // Output debugging information
+                error!(
+                    connection_id = %self.connection_id,
+                    namespace = %namespace,
+                    duration_ms = duration.as_millis(),
+                    error = %e,
+                    "Failed to change namespace"
+                );
+                // Increment error metrics
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.total_connection_errors").increment(1);
+                counter!("surrealmcp.errors.use_namespace").increment(1);
+                // Return error message
+                Err(McpError::internal_error(
+                    format!("Failed to change namespace to '{namespace}': {e}"),
+                    None,
+                ))
+            }
+        }
+    }
+
+    /// Change the database on the currently connected endpoint.
+    ///
+    /// This function allows you to switch to a different database on the currently
+    /// connected SurrealDB endpoint. The database change will apply to all subsequent
+    /// queries until you change it again or reconnect to a different endpoint.
+    ///
+    /// # Arguments
+    /// * `database` - The database to switch to
+    #[tool(description = r#"
+Change the database on the currently connected endpoint.
+
+This function allows you to switch to a different database on the currently connected 
+SurrealDB endpoint. The database change will apply to all subsequent queries until 
+you change it again or reconnect to a different endpoint.
+
+This is useful when you want to:
+- Switch between different databases within the same namespace
+- Organize data into different logical groups
+- Work with multiple applications using the same SurrealDB instance
+
+Examples:
+- use_database('users')
+- use_database('analytics')
+- use_database('events')
+"#)]
+    pub async fn use_database(
+        &self,
+        params: Parameters<UseDatabaseParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let UseDatabaseParams { database } = params.0;
+        // Start the measurement timer
+        let start_time = Instant::now();
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.use_database").increment(1);
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            database = %database,
+            "Attempting to change database"
+        );
+        // Check if database is restricted by startup configuration
+        self.check_restriction(
+            "use_database",
+            "database",
+            Some(database.clone()),
+            &self.database,
+        )?;
+        // Get the database connection
+        let db = self.require_connection().await?;
+        // Use the specified database
+        match db.use_db(&database).await {
+            Ok(_) => {
+                let duration = start_time.elapsed();
+                // Track the current database
+                self.context.lock().await.database = Some(database.clone());
+                // Output debugging information
+                info!(
+                    connection_id = %self.connection_id,
+                    database = %database,
+                    duration_ms = duration.as_millis(),
+                    "Successfully changed database"
+                );
+                // Return success message
+                let msg = format!("Successfully switched to database '{database}'");
+                Ok(CallToolResult::success(vec![Content::text(msg)]))
+            }
+            Err(e) => {
+                let duration = start_time.elapsed();
+                // Output debugging information
+                error!(
+                    connection_id = %self.connection_id,
+                    database = %database,
+                    duration_ms = duration.as_millis(),
+                    error = %e,
+                    "Failed to change database"
+                );
+                // Increment error metrics
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.total_connection_errors").increment(1);
+                counter!("surrealmcp.errors.use_database").increment(1);
+                // Return error message
+                Err(McpError::internal_error(
+                    format!("Failed to change database to '{database}': {e}"),
+                    None,
+                ))
+            }
+        }
+    }
+
+    /// Disconnect from the current SurrealDB endpoint.
+    ///
+    /// This function disconnects from the currently connected SurrealDB endpoint.
+    /// After disconnecting, you'll need to use connect_endpoint again to establish
+    /// a new connection before you can execute queries.
+    #[tool(description = r#"
+Disconnect from the current SurrealDB endpoint.
+
+This function disconnects from the currently connected SurrealDB endpoint.
+After disconnecting, you'll need to use connect_endpoint again to establish
+a new connection before you can execute queries.
+
+This is useful when you want to:
+- Switch to a different database
+- Clean up resources
+- Ensure no active connections remain
+"#)]
+    pub async fn disconnect_endpoint(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage metrics
+        counter!("surrealmcp.tools.disconnect_endpoint").increment(1);
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            "Disconnecting from SurrealDB endpoint"
+        );
+        // Stop live queries on the connection
+        self.stop_live_queries().await;
+        // Discard any open transaction
+        *self.transaction.lock().await = None;
+        // Lock the database connection
+        let mut db_guard = self.db.lock().await;
+        // Set the database connection to None
+        *db_guard = None;
+        // Forget the connection details so that the session does not reconnect
+        *self.connection.lock().await = None;
+        // Clear the current connection context
+        *self.context.lock().await = ConnectionContext::default();
+        // Output debugging information
+        info!(
+            connection_id = %self.connection_id,
+            "Successfully disconnected from SurrealDB endpoint"
+        );
+        // Return success message
+        Ok(CallToolResult::success(vec![Content::text(
+            "Successfully disconnected from SurrealDB endpoint".to_string(),
+        )]))
+    }
+
+    /// Get the current connection context.
+    ///
+    /// This function returns the endpoint, namespace, and database which are
+    /// currently in use by this session, as tracked across connect_endpoint,
+    /// use_namespace, and use_database calls, along with the server version.
+    #[tool(description = r#"
+Get the current connection context.
+
+This function returns the endpoint, namespace, and database which are currently in use 
+by this session. The values reflect the latest connect_endpoint, use_namespace, and 
+use_database calls. Values are null when no endpoint is connected, or when no namespace 
+or database has been selected. The version of the connected SurrealDB server is also 
+returned, which is recorded when the endpoint is connected.
+
+Examples:
+- current_context()  # {"endpoint": "memory", "namespace": "test", "database": "test", "version": "2.3.7"}
+"#)]
+    pub async fn current_context(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.current_context").increment(1);
+        // Output debugging information
+        debug!(
+            connection_id = %self.connection_id,
+            "Getting current connection context"
+        );
+        // Get the current connection context
+        let context = self.context.lock().await.clone();
+        // Convert the context to a JSON object
+        let result = serde_json::json!(context);
+        // Return the result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Get information about the current database connection.
+    ///
+    /// This function returns the endpoint, namespace, and database in use, along
+    /// with whether the connection is authenticated, the connection id, and the
+    /// uptime of the connection, so that clients can recover their context.
+    #[tool(description = r#"
+Get information about the current database connection.
+
+This function returns whether the session is connected, and if so the endpoint, namespace, 
+database, and server version in use, whether the connection signed in with credentials or 
+a token, the connection id of the session, and the uptime of the current connection, which 
+is reset when connecting again or reconnecting. Use this to check which namespace and database 
+subsequent queries will use, such as after use_namespace or use_database. When no endpoint is 
+connected, only connected is returned.
+
+Examples:
+- connection_info()  # {"connected": true, "endpoint": "memory", "namespace": "test", "database": "test", ...}
+- connection_info()  # {"connected": false}
+"#)]
+    pub async fn connection_info(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.connection_info").increment(1);
+        // Output debugging information
+        debug!(
+            connection_id = %self.connection_id,
+            "Getting connection information"
+        );
+        // Check whether the session is connected
+        let result = match self.db.lock().await.is_some() {
+            false => serde_json::json!({ "connected": false }),
+            true => {
+                // Get the current connection context
+                let context = self.context.lock().await.clone();
+                // Check whether the connection signed in
+                let authenticated = self.connection.lock().await.as_ref().is_some_and(|c| {
+                    c.token.is_some() || (c.username.is_some() && c.password.is_some())
+                });
+                serde_json::json!({
+                    "connected": true,
+                    "endpoint": context.endpoint,
+                    "namespace": context.namespace,
+                    "database": context.database,
+                    "version": context.version,
+                    "authenticated": authenticated,
+                    "connection_id": self.connection_id,
+                    "uptime": context.connected_at.map(|t| format_duration(t.elapsed())),
+                })
+            }
+        };
+        // Return the result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Get the authenticated identity of this session.
+    ///
+    /// This function returns the subject, issuer, and expiry of the bearer token
+    /// which authenticated the session, along with the connection id, so that
+    /// users of multi-tenant deployments can confirm which identity is active.
+    #[tool(description = r#"
+Get the authenticated identity of this session.
+
+This function returns the subject and email address of the bearer token which authenticated 
+this session, the issuer of the token, the time at which the token expires (in seconds since 
+the Unix epoch), and the connection id of the session. The identity values are null, and 
+authenticated is false, when the server is running without authentication, such as over stdio.
+
+Examples:
+- whoami()  # {"authenticated": true, "subject": "user-123", "email": null, "issuer": "https://auth.surrealdb.com/", "expires_at": 1767225600, "connection_id": "..."}
+"#)]
+    pub async fn whoami(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.whoami").increment(1);
+        // Output debugging information
+        debug!("Getting authenticated identity");
+        // Get the authenticated identity
+        let identity = self.identity.lock().await.clone();
+        // Convert the identity to a JSON object
+        let result = serde_json::json!({
+            "authenticated": identity.is_some(),
+            "subject": identity.as_ref().and_then(|i| i.subject.as_deref()),
+            "email": identity.as_ref().and_then(|i| i.email.as_deref()),
+            "issuer": identity.as_ref().map(|i| i.issuer.as_str()),
+            "expires_at": identity.as_ref().and_then(|i| i.expires_at),
+            "connection_id": self.connection_id,
+        });
+        // Return the result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Get the query statistics for this session.
+    ///
+    /// This function returns the number of queries executed by this session,
+    /// and the time spent executing them, since the session last connected.
+    #[tool(description = r#"
+Get the query statistics for this session.
+
+This function returns the number of queries executed by this session, the number 
+of queries which failed, and the total and average time spent executing them. The 
+statistics cover every query run by the tools in this session, and are reset each 
+time an endpoint is connected.
+
+Examples:
+- session_stats()  # {"queries": 3, "failed_queries": 1, "total_duration_ms": 1.2, "average_duration_ms": 0.4}
+"#)]
+    pub async fn session_stats(&self) -> Result<CallToolResult, McpError> {
+        // Increment tool usage counter
+        counter!("surrealmcp.tools.session_stats").increment(1);
+        // Output debugging information
+        debug!(
+            connection_id = %self.connection_id,
+            "Getting session statistics"
+        );
+        // Convert the session statistics to a JSON object
+        let result = self.stats.lock().await.to_json();
+        // Return the result
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    /// Check a requested namespace or database against the startup configuration.
+    ///
+    /// When the server is configured with a namespace or database, only that value
+    /// can be used. A requested value which differs from the configured value is
+    /// rejected. If no value is requested, the configured value (if any) is used.
+    ///
+    /// # Arguments
+    /// * `tool` - The name of the tool performing the check, for error metrics
+    /// * `kind` - The kind of value being checked ("namespace" or "database")
+    /// * `requested` - The value requested by the client, if any
+    /// * `configured` - The value configured at server startup, if any
+    fn check_restriction(
+        &self,
+        tool: &'static str,
+        kind: &'static str,
+        requested: Option<String>,
+        configured: &Option<String>,
+    ) -> Result<Option<String>, McpError> {
+        match (requested, configured) {
+            (Some(requested), Some(configured)) if requested != *configured => {
+                // Output debugging information
+                warn!(
+                    connection_id = %self.connection_id,
+                    tool,
+                    kind,
+                    requested = %requested,
+                    configured = %configured,
+                    "Request rejected: {kind} not allowed by server configuration"
+                );
+                // Increment error metrics
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.total_configuration_errors").increment(1);
+                counter!(format!("surrealmcp.errors.{tool}")).increment(1);
+                // Return error message
+                Err(McpError::internal_error(
+                    format!(
+                        "Cannot use {kind} '{requested}'. Server is configured to only use {kind} '{configured}'"
+                    ),
+                    None,
+                ))
+            }
+            (Some(requested), _) => Ok(Some(requested)),
+            (None, configured) => Ok(configured.clone()),
+        }
+    }
+
+    /// Get the MCP prompts, limited to the configured maximum
+    fn registered_prompts(&self) -> Vec<rmcp::model::Prompt> {
+        limit_registered(prompts::list_prompts(), self.options.max_prompts)
+    }
+
+    /// Get the MCP resources, limited to the configured maximum
+    fn registered_resources(&self) -> Vec<rmcp::model::Resource> {
+        limit_registered(resources::list_resources(), self.options.max_resources)
+    }
+
+    /// Store the bearer token and authenticated identity of the request.
+    ///
+    /// The token is used for SurrealDB Cloud requests, and the identity is
+    /// returned by the whoami tool.
+    async fn authenticate(&self, parts: &Parts) {
+        // Get the bearer token from the extensions
+        if let Some(token) = parts.extensions.get::<String>() {
+            self.cloud_client
+                .client_token
+                .write()
+                .await
+                .replace(token.clone());
+        }
+        // Get the authenticated identity from the extensions
+        if let Some(identity) = parts.extensions.get::<TokenIdentity>() {
+            debug!(
+                connection_id = %self.connection_id,
+                subject = ?identity.subject,
+                "Authenticated client session"
+            );
+            self.identity.lock().await.replace(identity.clone());
+        }
+    }
+
+    /// Limit the depth and size of a tool result, if configured
+    ///
+    /// This is applied to the result of every tool call, rather than by each
+    /// tool, so that tools which build their own results are limited too.
+    /// Each text content item which is JSON has any objects or arrays nested
+    /// deeper than the maximum depth replaced with a marker, and the text
+    /// content is then cut to the maximum result size.
+    fn limit_result(&self, tool: &str, mut result: CallToolResult) -> CallToolResult {
+        if let Some(max_depth) = self.options.max_result_depth {
+            for content in result.content.iter_mut() {
+                let rmcp::model::RawContent::Text(content) = &mut content.raw else {
+                    continue;
+                };
+                let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&content.text) else {
+                    continue;
+                };
+                // Truncate deeply nested values, such as transitively fetched records
+                if engine::truncate_depth(&mut value, max_depth) {
+                    debug!(
+                        connection_id = %self.connection_id,
+                        tool,
+                        max_depth,
+                        "Truncated deeply nested tool result"
+                    );
+                    content.text = value.to_string();
+                }
+            }
+        }
+        // Truncate the serialized results if they are too large
+        if let Some(max_bytes) = self.options.max_result_bytes
+            && let Some(size) = truncate_result(&mut result, max_bytes, max_bytes)
+        {
+            debug!(
+                connection_id = %self.connection_id,
+                tool,
+                size,
+                max_bytes,
+                "Truncated large tool result"
+            );
+            counter!("surrealmcp.result_truncations").increment(1);
+        }
+        result
+    }
+
+    /// Truncate a tool result which exceeds the maximum message size
+    ///
+    /// Oversized results can exceed the message size limits of the transport or
+    /// client, so they are cut down and marked as truncated instead of failing.
+    /// The size is measured on the serialized result after it has been passed
+    /// to `finish`, which adds the response envelope if enabled, so the final
+    /// message is within the limit.
+    fn limit_message_size(
+        &self,
+        tool: &str,
+        result: Result<CallToolResult, McpError>,
+        finish: impl Fn(Result<CallToolResult, McpError>) -> Result<CallToolResult, McpError>,
+    ) -> Result<CallToolResult, McpError> {
+        // Errors are small, so are returned unchanged
+        let (Some(max_bytes), Ok(result)) = (self.options.max_message_bytes, &result) else {
+            return finish(result);
+        };
+        // Measure the size of the serialized message
+        let measure = |message: &Result<CallToolResult, McpError>| match message {
+            Ok(message) => serde_json::to_vec(message).map_or(0, |json| json.len()),
+            Err(_) => 0,
+        };
+        let mut message = finish(Ok(result.clone()));
+        let mut size = measure(&message);
+        if size <= max_bytes {
+            return message;
+        }
+        // Cut the text in proportion to the excess until the message fits within the limit
+        let text_size = text_size(result);
+        let mut keep = text_size;
+        while size > max_bytes && keep > 0 {
+            keep = (keep.saturating_mul(max_bytes) / size).min(keep - 1);
+            let mut truncated = result.clone();
+            truncate_result(&mut truncated, max_bytes, keep);
+            message = finish(Ok(truncated));
+            size = measure(&message);
+        }
+        warn!(
+            connection_id = %self.connection_id,
+            tool,
+            size = text_size,
+            max_bytes,
+            "Truncated tool result exceeding the maximum message size"
+        );
+        message
+    }
+
+    /// Get a page of list items, starting at the position of the request cursor
+    ///
+    /// The cursor is the index of the first item in the page. A cursor for the
+    /// next page is returned when further items remain.
+    fn paginate<T>(
+        &self,
+        items: Vec<T>,
+        req: Option<rmcp::model::PaginatedRequestParam>,
+    ) -> Result<(Vec<T>, Option<String>), McpError> {
+        // Get the start of the requested page
+        let start = match req.and_then(|req| req.cursor) {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .ok()
+                .filter(|start| *start <= items.len())
+                .ok_or_else(|| {
+                    McpError::invalid_params(format!("Invalid cursor: {cursor}"), None)
+                })?,
+            None => 0,
+        };
+        // Return everything after the cursor if the page size is unlimited
+        let Some(size) = self.options.max_response_items else {
+            return Ok((items.into_iter().skip(start).collect(), None));
+        };
+        // Get the items in the page, and the cursor for the next page
+        let end = start.saturating_add(size).min(items.len());
+        let next_cursor = (end < items.len()).then(|| end.to_string());
+        let page = items.into_iter().skip(start).take(size).collect();
+        Ok((page, next_cursor))
+    }
+
+    /// Ensure that tools which execute DEFINE statements are enabled
+    fn require_define_statements(&self) -> Result<(), McpError> {
+        if self.options.allow_define_statements {
+            Ok(())
+        } else {
+            Err(McpError::internal_error(
+                "Define statements are disabled. Start the server with --allow-define-statements to enable them",
+                None,
+            ))
+        }
+    }
+
+    /// Ensure that a function can be called, based on the configured allowed and
+    /// denied functions.
+    fn require_allowed_function(&self, name: &str) -> Result<(), McpError> {
+        check_function(
+            name,
+            &self.options.allowed_functions,
+            &self.options.denied_functions,
+        )
+        .map_err(|e| McpError::internal_error(e, None))
+    }
+
+    /// Ensure that the functions called in a query can be called, when strict
+    /// function checking is enabled.
+    ///
+    /// This checks the whole query, including any clauses which were supplied
+    /// to the tool which built it, such as WHERE clauses or SET expressions.
+    fn require_allowed_query_functions(&self, query: &str) -> Result<(), McpError> {
+        // Only check queries in strict mode
+        if !self.options.strict_functions {
+            return Ok(());
+        }
+        // Check each function called in the query
+        let names = query_function_names(query).map_err(|e| McpError::internal_error(e, None))?;
+        for name in names {
+            self.require_allowed_function(&name)?;
+        }
+        Ok(())
+    }
+
+    /// Ensure that a query does not update or delete every record in a table,
+    /// unless confirmed, when a WHERE clause is required for mutations.
+    fn require_where_for_mutations(
+        &self,
+        query: &str,
+        confirm_full_table: Option<bool>,
+    ) -> Result<(), McpError> {
+        // Only check queries when configured
+        if !self.options.require_where_for_mutations {
+            return Ok(());
+        }
+        reject_full_table_mutations(query, confirm_full_table, "confirm_full_table")
+    }
+
+    /// Ensure that a query does not modify data when the server is read-only,
+    /// or when the bearer token does not grant the db:write scope.
+    fn require_read_only_query(&self, query: &str) -> Result<(), McpError> {
+        // Only check queries when configured, or when writes are not granted
+        let reason = match self.options.read_only {
+            true => "The server is in read-only mode",
+            false if !scope_granted("db:write") => {
+                "The 'db:write' scope is not granted to this token"
+            }
+            false => return Ok(()),
+        };
+        // Check for statements which modify data
+        let kinds = statement_kinds(query).map_err(|e| McpError::internal_error(e, None))?;
+        match kinds.iter().position(|kind| *kind == StatementKind::Write) {
+            Some(index) => Err(McpError::internal_error(
+                format!("{reason}, so statement {index} can not be run as it modifies data"),
+                None,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Ensure that a query can be run by this session.
+    ///
+    /// This rejects queries which modify data when the session is read-only,
+    /// which call disallowed functions in strict mode, or which modify whole
+    /// tables without confirmation when a WHERE clause is required.
+    fn require_allowed_query(
+        &self,
+        query: &str,
+        confirm_full_table: Option<bool>,
+    ) -> Result<(), McpError> {
+        // Reject statements which modify data when read-only
+        self.require_read_only_query(query)?;
+        // Check the functions called in the query
+        self.require_allowed_query_functions(query)?;
+        // Check the query does not modify whole tables unless confirmed
+        self.require_where_for_mutations(query, confirm_full_table)
+    }
+
+    /// Stop all of the live queries started by this session.
+    ///
+    /// Dropping each live query aborts the task receiving its notifications,
+    /// and dropping the notification stream kills it on the connection which
+    /// it was started on. Live queries are also stopped this way when the
+    /// session is dropped.
+    async fn stop_live_queries(&self) {
+        self.live_queries.lock().await.clear();
+    }
+
+    /// Internal query function that executes a SurrealQL query.
+    ///
+    /// This function accepts SurrealDB native Value types, allowing for direct use of
+    /// SurrealQL parameters without JSON conversion. This is used by other tools to
+    /// avoid JSON conversion overhead.
+    async fn query_internal(
+        &self,
+        query_string: String,
+        parameters: Option<HashMap<String, Value>>,
+    ) -> Result<engine::Response, McpError> {
+        self.query_internal_with(
+            query_string,
+            parameters,
+            self.options.empty_string_as_none,
+            None,
+        )
+        .await
+    }
+
+    /// Internal query function which executes a SurrealQL query, optionally binding
+    /// any parameters which are empty strings as NONE.
+    ///
+    /// The query is checked before it is executed or buffered, so the checks
+    /// apply to every query, whether raw or built by a tool. UPDATE and DELETE
+    /// statements without a WHERE clause are only allowed if confirm_full_table
+    /// is set, when a WHERE clause is required for mutations.
+    async fn query_internal_with(
+        &self,
+        query_string: String,
+        parameters: Option<HashMap<String, Value>>,
+        empty_string_as_none: bool,
+        confirm_full_table: Option<bool>,
+    ) -> Result<engine::Response, McpError> {
+        // Check the query can be run by this session
+        self.require_allowed_query(&query_string, confirm_full_table)?;
+        // Bind empty string parameters as NONE if configured
+        let parameters = match empty_string_as_none {
+            true => parameters.map(empty_strings_as_none),
+            false => parameters,
+        };
+        // Buffer statements which modify data while a transaction is open
+        if let Some(statements) = self.transaction.lock().await.as_mut() {
+            let writes = statement_kinds(&query_string)
+                .map(|kinds| kinds.contains(&StatementKind::Write))
+                .unwrap_or(false);
+            if writes {
+                return buffer_statement(statements, query_string, parameters);
+            }
+        }
+        // Increment the query counter
+        let query_id = QUERY_COUNTER.fetch_add(1, Ordering::SeqCst);
+        // Record the query id for the response envelope
+        let _ = LAST_QUERY_ID.try_with(|id| id.set(Some(query_id)));
+        // Get the database connection
+        let mut db = self.require_connection().await?;
+        // Execute the query on the engine
+        let mut res = engine::execute_query(
+            &db,
+            query_id,
+            query_string.clone(),
+            parameters.clone(),
+            &self.connection_id,
+            self.options.query_timeout,
+        )
+        .await;
+        // Reconnect once if the connection was lost, but only retry queries which
+        // read data, as a query which writes data may already have been applied
+        if res.connection_lost()
+            && let Some(instance) = self.reconnect().await
+        {
+            *db = instance;
+            let read_only = statement_kinds(&query_string)
+                .is_ok_and(|kinds| !kinds.contains(&StatementKind::Write));
+            if !read_only {
+                return Err(McpError::internal_error(
+                    "The connection to SurrealDB was lost while running the query, and has been re-established. The query modifies data, so it was not retried, as its changes may already have been applied. Check whether they were applied before running it again",
+                    Some(engine::error_data(true)),
+                ));
+            }
+            res = engine::execute_query(
+                &db,
+                query_id,
+                query_string,
+                parameters,
+                &self.connection_id,
+                self.options.query_timeout,
+            )
+            .await;
+        }
+        // Include statement timing if configured
+        res.include_timing = self.options.include_timing;
+        // Record the query in the session statistics
+        self.stats.lock().await.record(&res);
+        // Return the response
+        Ok(res)
+    }
+
+    /// Get the database connection, or an error if the session is not connected.
+    ///
+    /// The returned guard holds the connection lock, so that the connection can
+    /// not be replaced while it is in use. The error explains how to connect,
+    /// depending on whether an endpoint was configured at startup.
+    async fn require_connection(&self) -> Result<MappedMutexGuard<'_, Surreal<Any>>, McpError> {
+        // Lock the database connection
+        let db_guard = self.db.lock().await;
+        // Return the connection if there is one
+        MutexGuard::try_map(db_guard, Option::as_mut).map_err(|_| {
+            // Output debugging information
+            warn!(
+                connection_id = %self.connection_id,
+                "Operation attempted without database connection"
+            );
+            // Increment error metrics
+            counter!("surrealmcp.total_errors").increment(1);
+            counter!("surrealmcp.total_configuration_errors").increment(1);
+            counter!("surrealmcp.errors.no_connection").increment(1);
+            // Return error message
+            self.not_connected()
+        })
+    }
+
+    /// The error returned when an operation requires a database connection
+    fn not_connected(&self) -> McpError {
+        let msg = match &self.endpoint {
+            Some(endpoint) => format!(
+                "Not connected to the SurrealDB endpoint '{endpoint}' configured at startup. The connection may have failed or been closed. Use connect_endpoint('{endpoint}') to connect again."
+            ),
+            None => "Not connected to any SurrealDB endpoint. Use connect_endpoint or connect_profile to connect first.".to_string(),
+        };
+        McpError::internal_error(msg, None)
+    }
+
+    /// Reconnect using the details of the last successful connection.
+    ///
+    /// This is used when a query fails because the connection was lost. The
+    /// namespace and database currently in use are selected on the new
+    /// connection, and the live queries started on the lost connection are
+    /// stopped. Returns None if the session has never connected, if it is
+    /// connected to an in-memory database, or if the connection could not be
+    /// re-established.
+    async fn reconnect(&self) -> Option<Surreal<Any>> {
+        // Get the details of the last successful connection
+        let details = self.connection.lock().await.clone()?;
+        // Get the namespace and database currently in use
+        let context = self.context.lock().await.clone();
+        // In-memory databases can not be recovered by reconnecting
+        if !details.can_reconnect() {
+            warn!(
+                connection_id = %self.connection_id,
+                endpoint = context.endpoint.as_deref(),
+                "Connection to an in-memory database was lost, so it can not be reconnected"
+            );
+            return None;
+        }
+        // Output debugging information
+        warn!(
+            connection_id = %self.connection_id,
+            endpoint = context.endpoint.as_deref(),
+            "Connection to SurrealDB was lost, attempting to reconnect"
+        );
+        // Create a new SurrealDB connection
+        match details
+            .connect(context.namespace.as_deref(), context.database.as_deref())
+            .await
+        {
+            Ok(instance) => {
+                // Output debugging information
+                info!(
+                    connection_id = %self.connection_id,
+                    endpoint = context.endpoint.as_deref(),
+                    "Successfully reconnected to SurrealDB endpoint"
+                );
+                // Increment reconnection metrics
+                counter!("surrealmcp.reconnects").increment(1);
+                // Track the time at which the new connection was established
+                self.context.lock().await.connected_at = Some(Instant::now());
+                // Stop the live queries, which were lost with the connection
+                let live_queries = self.live_queries.lock().await.len();
+                if live_queries > 0 {
+                    self.stop_live_queries().await;
+                    warn!(
+                        connection_id = %self.connection_id,
+                        live_queries,
+                        "Stopped the live queries started on the lost connection"
+                    );
+                }
+                // Return the new connection
+                Some(instance)
+            }
+            Err(e) => {
+                // Output debugging information
+                error!(
+                    connection_id = %self.connection_id,
+                    endpoint = context.endpoint.as_deref(),
+                    error = %e,
+                    "Failed to reconnect to SurrealDB endpoint"
+                );
+                // Increment error metrics
+                counter!("surrealmcp.total_errors").increment(1);
+                counter!("surrealmcp.total_connection_errors").increment(1);
+                None
+            }
+        }
+    }
+
+    /// Initialize the database connection using startup configuration.
+    ///
+    /// This method attempts to connect to the database using the configuration
+    /// provided at startup. If no endpoint is configured, this method does nothing.
+    /// If an endpoint is configured, it will connect using the configured settings.
+    pub async fn initialize_connection(&self) -> Result<(), anyhow::Error> {
+        if let Some(endpoint) = &self.endpoint {
+            // Output debugging information
+            info!(
+                connection_id = %self.connection_id,
+                endpoint = %endpoint,
+                namespace = self.namespace.as_deref(),
+                database = self.database.as_deref(),
+                "Initializing database connection with startup configuration"
+            );
+            // Get the configured endpoint details
+            let details = ConnectionDetails {
+                url: endpoint.clone(),
+                token: None,
+                username: self.user.clone(),
+                password: self.pass.clone(),
+            };
+            let ns = self.namespace.as_deref();
+            let db = self.database.as_deref();
+            // Create a new SurrealDB connection
+            match details.connect(ns, db).await {
+                Ok(instance) => {
+                    // Record the version of the connected server
+                    let version = db::probe_version(&instance).await;
+                    // Update the service's database connection
+                    let mut db_guard = self.db.lock().await;
+                    *db_guard = Some(instance);
+                    // Keep the connection details for reconnecting
+                    *self.connection.lock().await = Some(details);
+                    // Track the current connection context
+                    *self.context.lock().await = ConnectionContext {
+                        endpoint: Some(endpoint.clone()),
+                        namespace: self.namespace.clone(),
+                        database: self.database.clone(),
+                        version,
+                        connected_at: Some(Instant::now()),
+                    };
+                    // Reset the statistics for the new connection
+                    *self.stats.lock().await = SessionStats::default();
+                    // Output debugging information
+                    info!(
+                        connection_id = %self.connection_id,
+                        endpoint = %endpoint,
+                        "Successfully initialized database connection"
+                    );
+                }
+                Err(e) => {
+                    // Output debugging information
+                    error!(
+                        connection_id = %self.connection_id,
+                        endpoint = %endpoint,
+                        error = %e,
+                        "Failed to initialize database connection"
+                    );
+                    // Increment error metrics
+                    counter!("surrealmcp.total_errors").increment(1);
+                    counter!("surrealmcp.total_connection_errors").increment(1);
+                    counter!("surrealmcp.errors.connect_endpoint").increment(1);
+                    // Return error message
+                    return Err(e);
+                }
+            }
+        } else {
+            debug!(
+                connection_id = %self.connection_id,
+                "No endpoint configured for startup connection"
+            );
+        }
+        // All ok
+        Ok(())
+    }
+}
+
+impl ServerHandler for SurrealService {
+    /// Get the MCP server info
+    fn get_info(&self) -> ServerInfo {
+        // Output debugging information
+        debug!("Getting server info");
+        // Report the disabled feature groups so that clients can adapt
+        let mut server = serde_json::Map::new();
+        server.insert(
+            "disabled_features".to_string(),
+            serde_json::json!(self.options.disabled_features()),
+        );
+        let experimental = std::collections::BTreeMap::from([("surrealmcp".to_string(), server)]);
+        // Get the server capabilities
+        let mut capabilities = ServerCapabilities::builder()
+            .enable_experimental_with(experimental)
+            .enable_resources()
+            .enable_prompts()
+            .enable_tools()
+            .build();
+        // Remove the disabled capabilities
+        if self.options.disable_prompts {
+            capabilities.prompts = None;
+        }
+        if self.options.disable_resources {
+            capabilities.resources = None;
+        }
+        // Get the server info
+        ServerInfo {
+            capabilities,
+            instructions: Some(include_str!("../../server.md").to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Initialize the MCP server
+    async fn initialize(
+        &self,
+        _req: rmcp::model::InitializeRequestParam,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::InitializeResult, McpError> {
+        // Output debugging information
+        debug!("Initializing MCP server");
+        // Store the bearer token and identity from the extensions
+        if let Some(parts) = ctx.extensions.get::<Parts>() {
+            self.authenticate(parts).await;
+        }
+        // Initialize the connection using startup configuration
+        if let Err(e) = self.initialize_connection().await {
+            error!(
+                connection_id = %self.connection_id,
+                error = %e,
+                "Failed to initialize database connection during MCP initialization"
+            );
+        }
+        Ok(self.get_info())
+    }
+
+    /// Call an MCP server tool
+    async fn call_tool(
+        &self,
+        req: rmcp::model::CallToolRequestParam,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tool = req.name.to_string();
+        // Get the scopes granted to the bearer token, if restricted
+        let scopes = ctx
+            .extensions
+            .get::<Parts>()
+            .and_then(|parts| parts.extensions.get::<TokenScopes>())
+            .map(|scopes| scopes.0.clone());
+        // Check that the token grants the scope required by the tool
+        if let Err(error) = require_tool_scope(&tool, scopes.as_deref()) {
+            return match self.options.response_envelope {
+                true => Ok(envelope(&tool, Err(error), None)),
+                false => Err(error),
+            };
+        }
+        // Call the tool directly if the response envelope is disabled
+        if !self.options.response_envelope {
+            let tcc = ToolCallContext::new(self, req, ctx);
+            let result = GRANTED_SCOPES
+                .scope(scopes, self.tool_router.call(tcc))
+                .await;
+            let result = result.map(|result| self.limit_result(&tool, result));
+            return self.limit_message_size(&tool, result, |result| result);
+        }
+        // Call the tool, recording the id of the last query executed
+        let tcc = ToolCallContext::new(self, req, ctx);
+        let (result, query_id) = GRANTED_SCOPES
+            .scope(
+                scopes,
+                LAST_QUERY_ID.scope(Cell::new(None), async {
+                    let result = self.tool_router.call(tcc).await;
+                    (result, LAST_QUERY_ID.with(Cell::get))
+                }),
+            )
+            .await;
+        let result = result.map(|result| self.limit_result(&tool, result));
+        // Wrap the tool result in the response envelope, within the maximum message size
+        self.limit_message_size(&tool, result, |result| {
+            Ok(envelope(&tool, result, query_id))
+        })
+    }
+
+    /// List the MCP server tools
+    async fn list_tools(
+        &self,
+        req: Option<rmcp::model::PaginatedRequestParam>,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListToolsResult, McpError> {
+        // Sort the tools by name, so that pages are consistent between requests
+        let mut tools = self.tool_router.list_all();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        // Get the requested page of tools
+        let (tools, next_cursor) = self.paginate(tools, req)?;
+        // Return the tools
+        Ok(rmcp::model::ListToolsResult { tools, next_cursor })
+    }
+
+    /// List the MCP server prompts
+    async fn list_prompts(
+        &self,
+        req: Option<rmcp::model::PaginatedRequestParam>,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListPromptsResult, McpError> {
+        // Output debugging information
+        debug!("Listing available prompts");
+        // Get prompts from the prompts module, unless disabled
+        let prompts = match self.options.disable_prompts {
+            true => vec![],
+            false => self.registered_prompts(),
+        };
+        // Get the requested page of prompts
+        let (prompts, next_cursor) = self.paginate(prompts, req)?;
+        // Return the prompts
+        Ok(rmcp::model::ListPromptsResult {
+            prompts,
+            next_cursor,
+        })
+    }
+
+    /// Get an MCP server prompt
+    async fn get_prompt(
+        &self,
+        req: rmcp::model::GetPromptRequestParam,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::GetPromptResult, McpError> {
+        // Output debugging information
+        debug!(prompt_name = %req.name, "Getting prompt");
+        // Check that prompts are enabled
+        if self.options.disable_prompts {
+            return Err(McpError::internal_error("Prompts are disabled", None));
+        }
+        // Check that the prompt is within the registered limit
+        if !self.registered_prompts().iter().any(|p| p.name == req.name) {
+            return Err(McpError::internal_error(
+                format!("Unknown prompt: {}", req.name),
+                None,
+            ));
+        }
+        // Get prompt from the prompts module
+        match prompts::get_prompt_with_arguments(&req.name, req.arguments) {
+            Some(Ok((description, messages))) => Ok(rmcp::model::GetPromptResult {
+                description: Some(description),
+                messages,
+            }),
+            Some(Err(e)) => Err(e),
+            None => Err(McpError::internal_error(
+                format!("Unknown prompt: {}", req.name),
+                None,
+            )),
+        }
+    }
+
+    /// List the MCP server resources
+    async fn list_resources(
+        &self,
+        req: Option<rmcp::model::PaginatedRequestParam>,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListResourcesResult, McpError> {
+        // Output debugging information
+        debug!("Listing available resources");
+        // Get resources from the resources module, unless disabled
+        let resources = match self.options.disable_resources {
+            true => vec![],
+            false => self.registered_resources(),
+        };
+        // Get the requested page of resources
+        let (resources, next_cursor) = self.paginate(resources, req)?;
+        // Return the resources
+        Ok(rmcp::model::ListResourcesResult {
+            resources,
+            next_cursor,
+        })
+    }
+
+    /// Get an MCP server resource
+    async fn read_resource(
+        &self,
+        req: rmcp::model::ReadResourceRequestParam,
+        _ctx: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ReadResourceResult, McpError> {
+        // Output debugging information
+        debug!(resource_uri = %req.uri, "Reading resource");
+        // Check that resources are enabled
+        if self.options.disable_resources {
+            return Err(McpError::internal_error("Resources are disabled", None));
+        }
+        // Check that the resource is within the registered limit
+        if !self.registered_resources().iter().any(|r| r.uri == req.uri) {
+            return Err(McpError::internal_error(
+                format!("Unknown resource: {}", req.uri),
+                None,
+            ));
+        }
+        // Get resource from the resources module
+        match resources::read_resource(&req.uri) {
+            Some(resource) => Ok(resource),
+            None => Err(McpError::internal_error(
+                format!("Unknown resource: {}", req.uri),
+                None,
+            )),
+        }
+    }
+}
+
+/// Build a SELECT statement and its bound parameters from the select tool parameters
+fn build_select_query(params: SelectParams) -> Result<(String, HashMap<String, Value>), McpError> {
+    let SelectParams {
+        mut targets,
+        range,
+        fields,
+        where_clause,
+        split_clause,
+        group_clause,
+        order_clause,
+        limit_clause,
+        start_clause,
+        fetch_clause,
+        parameters,
+        ids,
+        order_by,
+        group_by,
+        with_index,
+        explain,
+        explain_full,
+        parallel,
+        timeout,
+    } = params;
+    // Add the record range to the targets if provided
+    if let Some(RecordRange {
+        table,
+        start,
+        end,
+        inclusive,
+    }) = range
+    {
+        let range = range_target(&table, start, end, inclusive.unwrap_or(false))
+            .map_err(|e| McpError::internal_error(e, None))?;
+        targets.push(range);
+    }
+    // Ensure there is something to select from
+    if targets.is_empty() {
+        return Err(McpError::internal_error(
+            "At least one target or a range must be provided".to_string(),
+            None,
+        ));
+    }
+    // Get the fields to return, defaulting to all fields
+    let fields = match fields {
+        Some(fields) if fields.is_empty() => {
+            return Err(McpError::internal_error(
+                "The fields to select can not be empty".to_string(),
+                None,
+            ));
+        }
+        Some(fields) => fields
+            .iter()
+            .map(|field| validate_clause("field", field).map(str::trim))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| McpError::internal_error(e, None))?
+            .join(", "),
+        None => "*".to_string(),
+    };
+    // Build the initial query string
+    let mut query = format!("SELECT {fields} FROM ");
+    // Process the tables and Record IDs
+    query.push_str(&parse_targets(targets).map_err(|e| McpError::internal_error(e, None))?);
+    // Add the with index clause if provided
+    if let Some(indexes) = with_index {
+        if indexes.is_empty() {
+            return Err(McpError::internal_error(
+                "The with_index names can not be empty".to_string(),
+                None,
+            ));
+        }
+        let indexes = indexes
+            .iter()
+            .map(|index| quote_ident(index))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| McpError::internal_error(e, None))?;
+        query.push_str(&format!(" WITH INDEX {}", indexes.join(", ")));
+    }
+    // Create parameters with native SurrealDB types
+    let mut params = HashMap::new();
+    // Add the record ID filter and where clause if provided
+    match (ids, where_clause) {
+        (Some(ids), where_clause) => {
+            // Parse the record IDs so they are matched as record IDs, not strings
+            let ids = ids
+                .iter()
+                .map(|id| parse_record_id(id).map(|id| Value::from(id).into_inner()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| McpError::internal_error(e, None))?;
+            params.insert(
+                "ids".to_string(),
+                Value::from_inner(surrealdb::sql::Value::Array(ids.into())),
+            );
+            query.push_str(" WHERE id IN $ids");
+            if let Some(v) = where_clause {
+                let v =
+                    validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
+                query.push_str(&format!(" AND ({v})"));
+            }
+        }
+        (None, Some(v)) => {
+            let v = validate_clause("WHERE", &v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" WHERE {v}"));
+        }
+        (None, None) => {}
+    }
+    // Add the split on clause if provided
+    if let Some(v) = split_clause {
+        let v = validate_clause("SPLIT ON", &v).map_err(|e| McpError::internal_error(e, None))?;
+        query.push_str(&format!(" SPLIT ON {v}"));
+    }
+    // Add the group by clause if provided
+    match (group_by, group_clause) {
+        (Some(_), Some(_)) => {
+            return Err(McpError::internal_error(
+                "Only one of group_by and group_clause can be specified".to_string(),
+                None,
+            ));
+        }
+        (Some(fields), None) => {
+            let v = build_group_by(&fields).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" GROUP BY {v}"));
+        }
+        (None, Some(v)) => {
+            let v =
+                validate_clause("GROUP BY", &v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" GROUP BY {v}"));
+        }
+        (None, None) => {}
+    }
+    // Add the order by clause if provided
+    match (order_by, order_clause) {
+        (Some(_), Some(_)) => {
+            return Err(McpError::internal_error(
+                "Only one of order_by and order_clause can be specified".to_string(),
+                None,
+            ));
+        }
+        (Some(fields), None) => {
+            let v = build_order_by(&fields).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" ORDER BY {v}"));
+        }
+        (None, Some(v)) => {
+            let v =
+                validate_clause("ORDER BY", &v).map_err(|e| McpError::internal_error(e, None))?;
+            query.push_str(&format!(" ORDER BY {v}"));
+        }
+        (None, None) => {}
+    }
+    // Add the limit clause if provided
+    if let Some(v) = limit_clause {
+        let v = validate_clause("LIMIT", &v).map_err(|e| McpError::internal_error(e, None))?;
+        query.push_str(&format!(" LIMIT {v}"));
+    }
+    // Add the start at clause if provided
+    if let Some(v) = start_clause {
+        let v = validate_clause("START", &v).map_err(|e| McpError::internal_error(e, None))?;
+        query.push_str(&format!(" START AT {v}"));
+    }
+    // Add the fetch clause if provided
+    if let Some(v) = fetch_clause {
+        let v = validate_clause("FETCH", &v).map_err(|e| McpError::internal_error(e, None))?;
+        query.push_str(&format!(" FETCH {v}"));
+    }
+    // Add the timeout and parallel clauses if provided
+    query.push_str(
+        &execution_clauses(timeout.as_deref(), parallel)
+            .map_err(|e| McpError::internal_error(e, None))?,
+    );
+    // Return the query plan if requested
+    if explain_full == Some(true) {
+        query.push_str(" EXPLAIN FULL");
+    } else if explain == Some(true) {
+        query.push_str(" EXPLAIN");
+    }
+    // Add user-provided parameters if any
+    bind_user_parameters(&mut params, parameters)?;
+    // Return the query and its parameters
+    Ok((query, params))
+}
+
+/// Build the INSERT statement and its parameters for the insert tool
+fn build_insert_query(params: InsertParams) -> Result<(String, HashMap<String, Value>), McpError> {
+    let InsertParams {
+        target,
+        values,
+        ignore,
+        relation,
+        parallel,
+        timeout,
+    } = params;
+    // Build the initial query string
+    let mut query = "INSERT ".to_string();
+    // Add IGNORE keyword if specified
+    if ignore.unwrap_or(false) {
+        query.push_str("IGNORE ");
+    }
+    // Add RELATION keyword if specified
+    if relation.unwrap_or(false) {
+        query.push_str("RELATION ");
+    }
+    query.push_str("INTO ");
+    // Process the table and Record ID
+    query.push_str(&parse_target(target).map_err(|e| McpError::internal_error(e, None))?);
+    // Add the data content clause
+    query.push_str(" $data");
+    // Add the timeout and parallel clauses if provided
+    query.push_str(
+        &execution_clauses(timeout.as_deref(), parallel)
+            .map_err(|e| McpError::internal_error(e, None))?,
+    );
+    // Create parameters with native SurrealDB types
+    let mut params = HashMap::new();
+    // Add the record data
+    let values_array: Vec<serde_json::Value> =
+        values.into_iter().map(serde_json::Value::Object).collect();
+    let data = convert_json_to_surreal(serde_json::Value::Array(values_array), "data")
+        .map_err(|e| McpError::internal_error(e, None))?;
+    params.insert("data".to_string(), data);
+    // Return the query and its parameters
+    Ok((query, params))
+}
+
+/// Describe a saved connection profile, without revealing its credentials
+fn redacted_profile(name: &str, profile: &ConnectParams) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "endpoint": profile.endpoint,
+        "namespace": profile.namespace,
+        "database": profile.database,
+        "has_username": profile.username.is_some(),
+        "has_password": profile.password.is_some(),
+    })
+}
+
+/// Buffer a query in the open transaction, to be executed when it is committed
+///
+/// The parameters are bound with LET statements before the query, as the
+/// buffered queries are executed together without any bound parameters. These
+/// parameters, and any bound by LET statements in the query, are reset after
+/// the query, so they are not seen by the queries buffered after it. Queries
+/// which contain transaction statements are rejected.
+fn buffer_statement(
+    statements: &mut Vec<BufferedQuery>,
+    query: String,
+    parameters: Option<HashMap<String, Value>>,
+) -> Result<engine::Response, McpError> {
+    // Check the statements of the query
+    let (count, lets) =
+        buffered_statements(&query).map_err(|e| McpError::internal_error(e, None))?;
+    let mut buffered = BufferedQuery {
+        statements: String::new(),
+        results: Vec::new(),
+    };
+    // Bind the parameters in a consistent order
+    let mut parameters: Vec<_> = parameters.unwrap_or_default().into_iter().collect();
+    parameters.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut names = Vec::with_capacity(parameters.len() + lets.len());
+    for (key, value) in parameters {
+        let key = quote_ident(&key).map_err(|e| McpError::internal_error(e, None))?;
+        buffered
+            .statements
+            .push_str(&format!("LET ${key} = {};\n", value.into_inner()));
+        buffered.results.push(false);
+        names.push(key);
+    }
+    // Terminate the query on its own line, in case it ends with a comment
+    buffered
+        .statements
+        .push_str(query.trim_end().trim_end_matches(';'));
+    buffered.statements.push_str("\n;");
+    buffered.results.extend(std::iter::repeat_n(true, count));
+    // Reset the parameters bound for and by the query
+    for name in lets {
+        let name = quote_ident(&name).map_err(|e| McpError::internal_error(e, None))?;
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    for name in names {
+        buffered
+            .statements
+            .push_str(&format!("\nLET ${name} = NONE;"));
+        buffered.results.push(false);
+    }
+    statements.push(buffered);
+    // Describe the buffered statement in place of its result
+    let message = format!(
+        "Buffered as statement {} of the open transaction. Call commit_transaction to execute it",
+        statements.len()
+    );
+    Ok(engine::Response {
+        query_id: 0,
+        query,
+        duration: Duration::ZERO,
+        error: None,
+        statements: vec![engine::Statement {
+            time: None,
+            result: Ok(Value::from_inner(message.into())),
+        }],
+        include_timing: false,
+    })
+}
+
+/// Parse the ID of a live query started with the live_query tool
+fn parse_live_query_id(id: &str) -> Result<Uuid, McpError> {
+    Uuid::parse_str(id.trim())
+        .map_err(|_| McpError::internal_error(format!("Invalid live query ID '{id}'"), None))
+}
+
+/// Check that exactly one data mode was supplied to an upsert or update
+///
+/// Each mode is given as the name of its parameter, and whether it was set.
+fn check_data_mode(modes: &[(&str, bool)]) -> Result<(), McpError> {
+    let supplied: Vec<&str> = modes
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| *name)
+        .collect();
+    match supplied.len() {
+        1 => Ok(()),
+        0 => Err(McpError::internal_error(
+            "No data mode supplied; provide exactly one of content/merge/replace/patch/set",
+            None,
+        )),
+        _ => Err(McpError::internal_error(
+            format!(
+                "Multiple data modes supplied; provide exactly one (got {})",
+                supplied.join(", ")
+            ),
+            None,
+        )),
+    }
+}
+
+/// Build the data clause of an UPDATE or UPSERT statement for the supplied data mode
+///
+/// The data is bound as the `$data` parameter, except for SET expressions, which
+/// are validated and added to the statement directly.
+fn data_clause(
+    params: &mut HashMap<String, Value>,
+    content_data: Option<serde_json::Map<String, serde_json::Value>>,
+    merge_data: Option<serde_json::Map<String, serde_json::Value>>,
+    replace_data: Option<serde_json::Map<String, serde_json::Value>>,
+    patch_data: Option<Vec<serde_json::Map<String, serde_json::Value>>>,
+    set_clause: Option<String>,
+) -> Result<String, McpError> {
+    // Check that exactly one data mode was supplied
+    check_data_mode(&[
+        ("content_data", content_data.is_some()),
+        ("merge_data", merge_data.is_some()),
+        ("replace_data", replace_data.is_some()),
+        ("patch_data", patch_data.is_some()),
+        ("set_clause", set_clause.is_some()),
+    ])?;
+    // Add SET expressions to the statement
+    if let Some(v) = set_clause {
+        let v = validate_clause("SET", &v).map_err(|e| McpError::internal_error(e, None))?;
+        return Ok(format!(" SET {v}"));
+    }
+    // Get the clause and data for the supplied mode
+    let (clause, data) = match (content_data, merge_data, replace_data, patch_data) {
+        (Some(v), None, None, None) => (" CONTENT $data", convert_json_to_surreal(v, "data")),
+        (None, Some(v), None, None) => (" MERGE $data", convert_json_to_surreal(v, "data")),
+        (None, None, Some(v), None) => (" REPLACE $data", convert_json_to_surreal(v, "data")),
+        (None, None, None, Some(v)) => (" PATCH $data", convert_json_to_surreal(v, "data")),
+        _ => return Err(McpError::internal_error("Invalid data mode", None)),
+    };
+    // Add the data input as a parameter
+    let data = data.map_err(|e| McpError::internal_error(e, None))?;
+    params.insert("data".to_string(), data);
+    Ok(clause.to_string())
+}
+
+/// Add user-provided parameters to the parameters generated by a tool
+///
+/// Tools bind their own inputs as parameters, such as `$data` for record content
+/// or `$ids` for record IDs. A user-provided parameter with the same name would
+/// silently replace the tool input, so this is rejected instead.
+fn bind_user_parameters(
+    params: &mut HashMap<String, Value>,
+    parameters: Option<serde_json::Map<String, serde_json::Value>>,
+) -> Result<(), McpError> {
+    for (key, val) in parameters.unwrap_or_default() {
+        // Ensure the parameters generated by the tool are not overwritten
+        if params.contains_key(&key) {
+            return Err(McpError::internal_error(
+                format!(
+                    "Parameter '{key}' is reserved by this tool, so it can not be supplied in parameters; rename it in the query and parameters"
+                ),
+                None,
+            ));
+        }
+        let val =
+            convert_json_to_surreal(val, &key).map_err(|e| McpError::internal_error(e, None))?;
+        params.insert(key, val);
+    }
+    Ok(())
+}
+
+/// Replace any parameters which are empty strings with NONE
+///
+/// Only top-level parameter values are replaced. Empty strings nested within
+/// objects or arrays are left unchanged.
+fn empty_strings_as_none(mut parameters: HashMap<String, Value>) -> HashMap<String, Value> {
+    for value in parameters.values_mut() {
+        let inner = value.into_inner_mut();
+        if matches!(inner, surrealdb::sql::Value::Strand(s) if s.is_empty()) {
+            *inner = surrealdb::sql::Value::None;
+        }
+    }
+    parameters
+}
+
+/// Build the graph path expression which follows each step, such as ->knows->person
+fn graph_path(steps: &[TraverseStep]) -> Result<String, String> {
+    // A path must have at least one step
+    if steps.is_empty() {
+        return Err("The path must contain at least one step".to_string());
+    }
+    let mut path = String::new();
+    for step in steps {
+        // Validate the edge direction
+        let arrow = match step.direction.as_str() {
+            "out" => "->",
+            "in" => "<-",
+            "both" => "<->",
+            other => {
+                return Err(format!(
+                    "Invalid edge direction '{other}'. Expected 'in', 'out', or 'both'"
+                ));
+            }
+        };
+        // Validate the edge and target table names
+        let target = step.target.as_deref().unwrap_or("?");
+        for name in [step.edge.as_str(), target] {
+            if name != "?" && quote_ident(name)? != name {
+                return Err(format!(
+                    "Invalid table name '{name}'. Names can only contain letters, digits, and underscores"
+                ));
+            }
+        }
+        path.push_str(&format!("{arrow}{}{arrow}{target}", step.edge));
+    }
+    Ok(path)
+}
+
+/// Ensure that a field name is an identifier, or a path of identifiers separated by dots
+fn validate_field_name(field: &str) -> Result<(), String> {
+    if field
+        .split('.')
+        .any(|part| quote_ident(part).as_deref() != Ok(part))
+    {
+        return Err(format!(
+            "Invalid field name '{field}'. Names can only contain letters, digits, underscores, and dots"
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the DEFINE TABLE statement for a table, and a DEFINE FIELD statement for each field
+///
+/// The table and field names must be valid identifiers, the field types must be
+/// valid SurrealQL types, and the generated query must parse to exactly one
+/// statement for the table and each field.
+fn define_table_statements(
+    name: &str,
+    schemafull: bool,
+    fields: &[FieldDef],
+    permissions: Option<&str>,
+) -> Result<String, String> {
+    // Validate the table name
+    if quote_ident(name)? != name {
+        return Err(format!(
+            "Invalid table name '{name}'. Names can only contain letters, digits, and underscores"
+        ));
+    }
+    // Build the DEFINE TABLE statement
+    let mut query = format!(
+        "DEFINE TABLE {name} {}",
+        if schemafull {
+            "SCHEMAFULL"
+        } else {
+            "SCHEMALESS"
+        }
+    );
+    if let Some(permissions) = permissions.map(str::trim).filter(|p| !p.is_empty()) {
+        query.push_str(&format!(" PERMISSIONS {permissions}"));
+    }
+    query.push_str(";\n");
+    // Build the DEFINE FIELD statements
+    for field in fields {
+        validate_field_name(&field.name)?;
+        // Parse the type, so that only a type can be added to the statement
+        let kind = surrealdb::syn::kind(field.kind.trim()).map_err(|e| {
+            format!(
+                "Invalid type '{}' for field '{}': {e}",
+                field.kind.trim(),
+                field.name
+            )
+        })?;
+        let kind = match field.optional {
+            Some(true) => format!("option<{kind}>"),
+            _ => kind.to_string(),
+        };
+        query.push_str(&format!(
+            "DEFINE FIELD {} ON {name} TYPE {kind};\n",
+            field.name
+        ));
+    }
+    // Check the types and permissions did not add or break statements
+    let statements =
+        surrealdb::sql::parse(&query).map_err(|e| format!("Invalid table definition: {e}"))?;
+    if statements.len() != fields.len() + 1 {
+        return Err(
+            "Invalid table definition: field types and permissions can not contain additional statements"
+                .to_string(),
+        );
+    }
+    Ok(query)
+}
+
+/// Generate the DEFINE INDEX statement for a standard, unique, full-text search, or vector index
+fn define_index_statement(params: &DefineIndexParams) -> Result<String, String> {
+    // Validate the table, index, and analyzer names
+    let names = [
+        Some(&params.table),
+        Some(&params.name),
+        params.analyzer.as_ref(),
+    ];
+    for name in names.into_iter().flatten() {
+        if quote_ident(name)? != *name {
+            return Err(format!(
+                "Invalid name '{name}'. Names can only contain letters, digits, and underscores"
+            ));
+        }
+    }
+    // Validate the field names
+    if params.fields.is_empty() {
+        return Err("At least one field must be specified".to_string());
+    }
+    for field in &params.fields {
+        validate_field_name(field)?;
+    }
+    // Check that only one kind of index is specified
+    let unique = params.unique == Some(true);
+    let search = params.search == Some(true);
+    if [unique, search, params.vector.is_some()]
+        .iter()
+        .filter(|kind| **kind)
+        .count()
+        > 1
+    {
+        return Err("Only one of unique, search, and vector can be specified".to_string());
+    }
+    if params.analyzer.is_some() && !search {
+        return Err("An analyzer can only be specified for a search index".to_string());
+    }
+    // Build the index clause
+    let clause = match (&params.vector, &params.analyzer) {
+        _ if unique => " UNIQUE".to_string(),
+        (_, Some(analyzer)) => format!(" SEARCH ANALYZER {analyzer} BM25 HIGHLIGHTS"),
+        _ if search => {
+            return Err(
+                "A search index requires an analyzer, which must already be defined".to_string(),
+            );
+        }
+        (Some(vector), _) => {
+            let kind = match vector.kind.to_lowercase().as_str() {
+                "mtree" => "MTREE",
+                "hnsw" => "HNSW",
+                kind => {
+                    return Err(format!(
+                        "Invalid vector index kind '{kind}'. Must be one of 'mtree' or 'hnsw'"
+                    ));
+                }
+            };
+            if vector.dimension == 0 {
+                return Err("The vector dimension must be greater than zero".to_string());
+            }
+            let distance = vector
+                .distance
+                .as_deref()
+                .unwrap_or("euclidean")
+                .to_lowercase();
+            if !VECTOR_DISTANCES.contains(&distance.as_str()) {
+                return Err(format!(
+                    "Invalid vector distance '{distance}'. Must be one of 'euclidean', 'cosine', 'manhattan', 'chebyshev', 'hamming', 'jaccard', or 'pearson'"
+                ));
+            }
+            let element_type = vector
+                .element_type
+                .as_deref()
+                .unwrap_or("f64")
+                .to_lowercase();
+            if !["f64", "f32", "i64", "i32", "i16"].contains(&element_type.as_str()) {
+                return Err(format!(
+                    "Invalid vector element type '{element_type}'. Must be one of 'f64', 'f32', 'i64', 'i32', or 'i16'"
+                ));
+            }
+            format!(
+                " {kind} DIMENSION {} DIST {} TYPE {}",
+                vector.dimension,
+                distance.to_uppercase(),
+                element_type.to_uppercase()
+            )
+        }
+        _ => String::new(),
+    };
+    // Build the DEFINE INDEX statement
+    Ok(format!(
+        "DEFINE INDEX {} ON {} FIELDS {}{clause}",
+        params.name,
+        params.table,
+        params.fields.join(", ")
+    ))
+}
+
+/// Build the KNN condition which finds the k nearest vectors in a field
+///
+/// The condition uses an MTREE index by default, an HNSW index when the size of
+/// the candidate list is given, or compares every record when a distance is given.
+fn knn_operator(
+    field: &str,
+    k: u32,
+    ef: Option<u32>,
+    distance: Option<&str>,
+) -> Result<String, String> {
+    // Validate the field name
+    validate_field_name(field)?;
+    if k == 0 {
+        return Err("The number of nearest neighbours must be greater than zero".to_string());
+    }
+    // Build the KNN operator
+    match (ef, distance) {
+        (Some(_), Some(_)) => Err("Only one of ef and distance can be specified".to_string()),
+        (Some(ef), None) => Ok(format!("{field} <|{k}, {ef}|> $vector_search_vector")),
+        (None, Some(distance)) => {
+            let distance = distance.to_lowercase();
+            if !VECTOR_DISTANCES.contains(&distance.as_str()) {
+                return Err(format!(
+                    "Invalid vector distance '{distance}'. Must be one of 'euclidean', 'cosine', 'manhattan', 'chebyshev', 'hamming', 'jaccard', or 'pearson'"
+                ));
+            }
+            Ok(format!(
+                "{field} <|{k}, {}|> $vector_search_vector",
+                distance.to_uppercase()
+            ))
+        }
+        (None, None) => Ok(format!("{field} <|{k}|> $vector_search_vector")),
+    }
+}
+
+/// Ensure that a query does not update or delete every record in a table, unless
+/// confirmed with the named tool parameter
+fn reject_full_table_mutations(
+    query: &str,
+    confirmed: Option<bool>,
+    parameter: &str,
+) -> Result<(), McpError> {
+    // Confirmed queries can modify whole tables
+    if confirmed == Some(true) {
+        return Ok(());
+    }
+    // Check for statements without a WHERE clause
+    let mutations = full_table_mutations(query).map_err(|e| McpError::internal_error(e, None))?;
+    match mutations.first() {
+        Some(mutation) => Err(McpError::internal_error(
+            format!(
+                "Refusing to run '{mutation}' without a WHERE clause, as it can modify every record in the table. Add a WHERE clause, or set {parameter} to true to confirm"
+            ),
+            None,
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Check whether the bearer token of the current tool call grants a scope
+///
+/// Tool calls without a bearer token, or whose token has no scope claim, are
+/// not restricted, so every scope is granted.
+fn scope_granted(scope: &str) -> bool {
+    GRANTED_SCOPES
+        .try_with(|scopes| {
+            scopes
+                .as_ref()
+                .is_none_or(|scopes| scopes.iter().any(|s| s == scope))
+        })
+        .unwrap_or(true)
+}
+
+/// Ensure that the scopes granted to a bearer token permit calling a tool
+///
+/// Tools which modify data or resources require the scope listed in TOOL_SCOPES,
+/// and every tool can be called when the token is not restricted to any scopes.
+fn require_tool_scope(tool: &str, scopes: Option<&[String]>) -> Result<(), McpError> {
+    let Some(scopes) = scopes else {
+        return Ok(());
+    };
+    match TOOL_SCOPES.iter().find(|(name, _)| *name == tool) {
+        Some((_, scope)) if !scopes.iter().any(|s| s == scope) => Err(McpError::internal_error(
+            format!(
+                "The '{tool}' tool requires the '{scope}' scope, which is not granted to this token"
+            ),
+            None,
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Get the total size of the text content of a tool result
+fn text_size(result: &CallToolResult) -> usize {
+    result
+        .content
+        .iter()
+        .filter_map(|content| content.as_text())
+        .map(|content| content.text.len())
+        .sum()
+}
+
+/// Truncate the text content of a tool result to a number of bytes
+///
+/// Text content items are kept in order until `keep` bytes are reached, with
+/// the final item cut at a character boundary, and a marker is appended which
+/// explains that the result exceeded `max_bytes` and how to request a smaller
+/// result. Returns the original size of the text content if the result was
+/// truncated.
+fn truncate_result(result: &mut CallToolResult, max_bytes: usize, keep: usize) -> Option<usize> {
+    // Measure the total size of the text content
+    let size = text_size(result);
+    if size <= keep {
+        return None;
+    }
+    // Keep the text content which fits within the kept size
+    let mut remaining = keep;
+    let mut kept = 0;
+    let mut content = Vec::with_capacity(result.content.len() + 1);
+    for item in std::mem::take(&mut result.content) {
+        let Some(text) = item.as_text().map(|content| content.text.as_str()) else {
+            content.push(item);
+            continue;
+        };
+        if text.len() <= remaining {
+            remaining -= text.len();
+            kept += text.len();
+            content.push(item);
+            continue;
+        }
+        let mut end = remaining;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end > 0 {
+            content.push(Content::text(&text[..end]));
+        }
+        kept += end;
+        remaining = 0;
+    }
+    // Add a marker explaining why the result is incomplete
+    content.push(Content::text(format!(
+        "[TRUNCATED] The result was {size} bytes, which exceeds the maximum size of {max_bytes} bytes, so only the first {kept} bytes are included and the output above is incomplete. Narrow the query with a WHERE clause or LIMIT, or select only the fields which are needed (for example SELECT name, email FROM person) to return a smaller result."
+    )));
+    result.content = content;
+    Some(size)
+}
+
+/// Wrap a tool result in a consistent JSON response envelope
+///
+/// Successful results are returned as `{ "ok": true, "tool", "data", "query_id" }`,
+/// where the data is the parsed JSON of the tool output. Errors are returned as a
+/// tool error result containing `{ "ok": false, "tool", "error", "query_id" }`.
+fn envelope(
+    tool: &str,
+    result: Result<CallToolResult, McpError>,
+    query_id: Option<u64>,
+) -> CallToolResult {
+    // Parse each text content item as JSON, falling back to a string
+    let parse = |result: &CallToolResult| {
+        let mut values: Vec<serde_json::Value> = result
+            .content
+            .iter()
+            .filter_map(|content| content.as_text())
+            .map(|content| {
+                serde_json::from_str(&content.text)
+                    .unwrap_or_else(|_| serde_json::Value::String(content.text.clone()))
+            })
+            .collect();
+        match values.len() {
+            1 => values.remove(0),
+            _ => serde_json::Value::Array(values),
+        }
+    };
+    // Build the response envelope
+    let (ok, body) = match result {
+        Ok(result) if result.is_error != Some(true) => (true, ("data", parse(&result))),
+        Ok(result) => (
+            false,
+            ("error", serde_json::json!({ "message": parse(&result) })),
+        ),
+        Err(error) => (
+            false,
+            (
+                "error",
+                serde_json::json!({
+                    "code": error.code.0,
+                    "message": error.message,
+                    "data": error.data,
+                }),
+            ),
+        ),
+    };
+    let envelope = serde_json::json!({
+        "ok": ok,
+        "tool": tool,
+        body.0: body.1,
+        "query_id": query_id,
+    });
+    // Return the envelope as the tool result
+    let content = vec![Content::text(envelope.to_string())];
+    match ok {
+        true => CallToolResult::success(content),
+        false => CallToolResult::error(content),
+    }
+}
+
+/// Build a GROUP BY clause from a list of field paths
+fn build_group_by(fields: &[String]) -> Result<String, String> {
+    if fields.is_empty() {
+        return Err("The group_by fields can not be empty".to_string());
+    }
+    fields
+        .iter()
+        .map(|field| quote_field_path(field))
+        .collect::<Result<Vec<_>, _>>()
+        .map(|fields| fields.join(", "))
+}
+
+/// Build an ORDER BY clause from a list of fields and sort directions
+fn build_order_by(fields: &[OrderField]) -> Result<String, String> {
+    if fields.is_empty() {
+        return Err("The order_by fields can not be empty".to_string());
+    }
+    fields
+        .iter()
+        .map(|OrderField { field, direction }| {
+            let field = quote_field_path(field)?;
+            let direction = match direction.as_deref().map(str::to_uppercase).as_deref() {
+                None | Some("ASC") => "ASC",
+                Some("DESC") => "DESC",
+                Some(_) => {
+                    return Err(format!(
+                        "Invalid sort direction '{}'. Must be 'ASC' or 'DESC'",
+                        direction.as_deref().unwrap_or_default()
+                    ));
+                }
+            };
+            Ok(format!("{field} {direction}"))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|fields| fields.join(", "))
+}
+
+/// Get the name of the storage engine used by an endpoint
+fn storage_engine(endpoint: &str) -> &str {
+    match endpoint
+        .split_once(':')
+        .map_or(endpoint, |(scheme, _)| scheme)
+    {
+        "memory" | "mem" => "memory",
+        "file" | "rocksdb" => "rocksdb",
+        "surrealkv" | "surrealkv+versioned" => "surrealkv",
+        scheme => scheme,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_connection_id;
+
+    /// Create a service connected to an in-memory database
+    async fn connected_service() -> SurrealService {
+        let service = SurrealService::new(generate_connection_id());
+        service
+            .connect_endpoint(Parameters(ConnectParams {
+                endpoint: "memory".to_string(),
+                namespace: Some("test".to_string()),
+                database: Some("test".to_string()),
+                username: None,
+                password: None,
+            }))
+            .await
+            .expect("failed to connect to in-memory database");
+        service
+    }
+
+    /// Call a tool through the MCP server, as a client would
+    ///
+    /// The request is sent over the stateless streamable HTTP transport, with
+    /// the scopes granted by the bearer token if given, and the JSON-RPC
+    /// response message is returned.
+    async fn call_tool(
+        service: &SurrealService,
+        name: &str,
+        arguments: serde_json::Value,
+        scopes: Option<&[&str]>,
+    ) -> serde_json::Value {
+        let params = serde_json::json!({ "name": name, "arguments": arguments });
+        send_request(service, "tools/call", params, scopes).await
+    }
+
+    /// Send a request to the MCP server, and return the JSON-RPC response message
+    async fn send_request(
+        service: &SurrealService,
+        method: &str,
+        params: serde_json::Value,
+        scopes: Option<&[&str]>,
+    ) -> serde_json::Value {
+        use rmcp::transport::streamable_http_server::{
+            StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+        };
+        use tower::ServiceExt;
+        // Serve the session over a stateless transport
+        let service = service.clone();
+        let mcp = StreamableHttpService::new(
+            move || Ok(service.clone()),
+            LocalSessionManager::default().into(),
+            StreamableHttpServerConfig {
+                stateful_mode: false,
+                ..Default::default()
+            },
+        );
+        // Send the request
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut request = http::Request::post("/")
+            .header("content-type", "application/json")
+            .header("accept", "application/json, text/event-stream")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap();
+        if let Some(scopes) = scopes {
+            let scopes = scopes.iter().map(|scope| scope.to_string()).collect();
+            request.extensions_mut().insert(TokenScopes(scopes));
+        }
+        let response = mcp.oneshot(request).await.unwrap();
+        // Read the response message from the event stream
+        let mut stream = axum::body::Body::new(response.into_body()).into_data_stream();
+        let mut text = String::new();
+        while let Some(chunk) = stream.next().await {
+            text.push_str(&String::from_utf8_lossy(&chunk.unwrap()));
+            let message = text
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .find_map(|data| serde_json::from_str::<serde_json::Value>(data.trim()).ok());
+            if let Some(message) = message {
+                return message;
+            }
+        }
+        panic!("the request did not return a response: {text}");
+    }
+
+    /// Get the error message of a failed tool call returned by call_tool
+    fn message_error(message: &serde_json::Value) -> &str {
+        message["error"]["message"]
+            .as_str()
+            .expect("expected an error message")
+    }
+
+    /// Build tool parameters from a JSON object
+    fn params<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> Parameters<T> {
+        Parameters(serde_json::from_value(value).expect("invalid tool parameters"))
+    }
+
+    /// Get the text content of a successful tool result
+    fn result_text(result: &CallToolResult) -> String {
+        result.content[0]
+            .as_text()
+            .expect("expected text content")
+            .text
+            .clone()
+    }
+
+    /// Get the results of the statements of a query, checking that each succeeded
+    fn query_results(result: &CallToolResult) -> String {
+        let statements: serde_json::Value = serde_json::from_str(&result_text(result)).unwrap();
+        let results = statements
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|statement| {
+                assert_eq!(statement["status"], "OK", "{statement}");
+                statement["result"].clone()
+            })
+            .collect();
+        serde_json::Value::Array(results).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_current_context_reflects_use_calls() {
+        let service = connected_service().await;
+        let result = service.current_context().await.unwrap();
+        let context: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(context["endpoint"], "memory");
+        assert_eq!(context["namespace"], "test");
+        assert_eq!(context["database"], "test");
+        assert!(context["version"].is_string());
+
+        service
+            .use_namespace(Parameters(UseNamespaceParams {
+                namespace: "app".to_string(),
+            }))
+            .await
+            .unwrap();
+        service
+            .use_database(Parameters(UseDatabaseParams {
+                database: "users".to_string(),
+            }))
+            .await
+            .unwrap();
+        let result = service.current_context().await.unwrap();
+        let context: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(context["endpoint"], "memory");
+        assert_eq!(context["namespace"], "app");
+        assert_eq!(context["database"], "users");
+    }
+
+    #[tokio::test]
+    async fn test_connection_info() {
+        let service = SurrealService::new(generate_connection_id());
+        let result = service.connection_info().await.unwrap();
+        assert_eq!(result_text(&result), r#"{"connected":false}"#);
+        // The connection is described after connecting and switching namespace
+        let service = connected_service().await;
+        service
+            .use_namespace(params(serde_json::json!({ "namespace": "app" })))
+            .await
+            .unwrap();
+        let result = service.connection_info().await.unwrap();
+        let info: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(info["connected"], true);
+        assert_eq!(info["endpoint"], "memory");
+        assert_eq!(info["namespace"], "app");
+        assert_eq!(info["database"], "test");
+        assert_eq!(info["authenticated"], false);
+        assert_eq!(info["connection_id"], service.connection_id.as_str());
+        assert!(info["uptime"].as_str().unwrap().ends_with('s'));
+        // The uptime is measured from the latest connection, not the session creation
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        service
+            .connect_endpoint(params(serde_json::json!({ "endpoint": "memory" })))
+            .await
+            .unwrap();
+        let result = service.connection_info().await.unwrap();
+        let info: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert!(info["uptime"].as_str().unwrap().ends_with("ms"));
+        // Nothing is described after disconnecting
+        service.disconnect_endpoint().await.unwrap();
+        let result = service.connection_info().await.unwrap();
+        assert_eq!(result_text(&result), r#"{"connected":false}"#);
+    }
+
+    #[tokio::test]
+    async fn test_switching_namespace_keeps_startup_restrictions() {
+        let service = SurrealService::with_config(
+            generate_connection_id(),
+            Some("memory".to_string()),
+            Some("app".to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            ServiceOptions::default(),
+        );
+        service.initialize_connection().await.unwrap();
+        // Switching database updates the reported database
+        service
+            .use_database(params(serde_json::json!({ "database": "users" })))
+            .await
+            .unwrap();
+        let result = service.connection_info().await.unwrap();
+        let info: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(info["namespace"], "app");
+        assert_eq!(info["database"], "users");
+        // The configured namespace is still the only one allowed
+        assert_eq!(service.namespace.as_deref(), Some("app"));
+        assert_eq!(service.database, None);
+        let result = service
+            .use_namespace(params(serde_json::json!({ "namespace": "other" })))
+            .await;
+        assert!(result.is_err());
+        let result = service.connection_info().await.unwrap();
+        let info: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(info["namespace"], "app");
+        // Switching to the configured namespace is allowed
+        service
+            .use_namespace(params(serde_json::json!({ "namespace": "app" })))
+            .await
+            .unwrap();
+        let result = service.connection_info().await.unwrap();
+        let info: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(info["namespace"], "app");
+        // The restriction still applies after switching
+        assert_eq!(service.namespace.as_deref(), Some("app"));
+        let result = service
+            .use_namespace(params(serde_json::json!({ "namespace": "other" })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_current_context_cleared_on_disconnect() {
+        let service = connected_service().await;
+        service.disconnect_endpoint().await.unwrap();
+        let result = service.current_context().await.unwrap();
+        let context: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert!(context["endpoint"].is_null());
+        assert!(context["namespace"].is_null());
+        assert!(context["database"].is_null());
+    }
+    #[tokio::test]
+    async fn test_get_edges_returns_inbound_and_outbound_edges() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a, person:b, person:c; RELATE person:a->knows->person:b; RELATE person:c->knows->person:a; RELATE person:a->likes->person:c;",
+            })))
+            .await
+            .unwrap();
+        // Fetch edges in both directions
+        let result = service
+            .get_edges(Parameters(GetEdgesParams {
+                record: "person:a".to_string(),
+                direction: None,
+                edge: None,
+            }))
+            .await
+            .unwrap();
+        let edges: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(edges["out"].as_array().unwrap().len(), 2);
+        assert_eq!(edges["in"].as_array().unwrap().len(), 1);
+        assert_eq!(edges["in"][0]["target"]["id"], "person:c");
+        // Fetch only outbound edges of a single edge table
+        let result = service
+            .get_edges(Parameters(GetEdgesParams {
+                record: "person:a".to_string(),
+                direction: Some("out".to_string()),
+                edge: Some("knows".to_string()),
+            }))
+            .await
+            .unwrap();
+        let edges: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert!(edges.get("in").is_none());
+        assert_eq!(edges["out"].as_array().unwrap().len(), 1);
+        assert_eq!(edges["out"][0]["target"]["id"], "person:b");
+    }
+
+    #[tokio::test]
+    async fn test_get_edges_validates_input() {
+        let service = connected_service().await;
+        let result = service
+            .get_edges(Parameters(GetEdgesParams {
+                record: "person".to_string(),
+                direction: None,
+                edge: None,
+            }))
+            .await;
+        assert!(result.is_err());
+        let result = service
+            .get_edges(Parameters(GetEdgesParams {
+                record: "person:a".to_string(),
+                direction: Some("sideways".to_string()),
+                edge: None,
+            }))
+            .await;
+        assert!(result.is_err());
+        let result = service
+            .get_edges(Parameters(GetEdgesParams {
+                record: "person:a".to_string(),
+                direction: None,
+                edge: Some(String::new()),
+            }))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_includes_timing_when_requested() {
+        let service = connected_service().await;
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "RETURN 1; RETURN 2;",
+                "include_timing": true,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[1,2]");
+        let timing: serde_json::Value =
+            serde_json::from_str(&result.content[1].as_text().unwrap().text).unwrap();
+        let statements = timing["timing"]["statements"].as_array().unwrap();
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0]["execution_time_ms"].is_number());
+        assert!(statements[1]["execution_time_ms"].is_number());
+        // Timing is excluded unless configured or requested
+        let result = service
+            .query(params(serde_json::json!({ "query": "RETURN 1" })))
+            .await
+            .unwrap();
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_rebuilds_indexes_on_embedded_engine() {
+        let path = std::env::temp_dir().join(format!("surrealmcp-{}", generate_connection_id()));
+        let service = SurrealService::new(generate_connection_id());
+        service
+            .connect_endpoint(params(serde_json::json!({
+                "endpoint": format!("surrealkv://{}", path.display()),
+                "namespace": "test",
+                "database": "test",
+            })))
+            .await
+            .unwrap();
+        service
+            .query(params(serde_json::json!({
+                "query": "DEFINE INDEX email ON person FIELDS email UNIQUE; CREATE person:a SET email = 'a@example.com';",
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .maintenance(params(serde_json::json!({
+                "operation": "rebuild_indexes",
+                "table": "person",
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["engine"], "surrealkv");
+        assert_eq!(result["indexes"], serde_json::json!(["email"]));
+        // The rebuilt index is still enforced
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:b SET email = 'a@example.com'",
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result[0]["status"], "ERR");
+        // Compaction is rejected for the embedded engine
+        let err = service
+            .maintenance(params(serde_json::json!({ "operation": "compact" })))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(
+            err.message
+                .contains("not supported by the surrealkv engine")
+        );
+        service.disconnect_endpoint().await.unwrap();
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_rejects_unsupported_operations() {
+        let service = connected_service().await;
+        let err = service
+            .maintenance(params(serde_json::json!({ "operation": "compact" })))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("not supported by the memory engine"));
+        let err = service
+            .maintenance(params(serde_json::json!({ "operation": "vacuum" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Unknown maintenance operation"));
+        let result = service
+            .maintenance(params(
+                serde_json::json!({ "operation": "rebuild_indexes" }),
+            ))
+            .await;
+        assert!(result.is_err());
+        // Operations require a connection
+        let service = SurrealService::new(generate_connection_id());
+        let result = service
+            .maintenance(params(serde_json::json!({
+                "operation": "rebuild_indexes",
+                "table": "person",
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reset_database_clears_memory_database() {
+        let mut service = connected_service().await;
+        service.options.allow_reset = true;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a; CREATE company:b;",
+            })))
+            .await
+            .unwrap();
+        // The reset must be confirmed
+        let result = service
+            .reset_database(params(serde_json::json!({ "confirm": false })))
+            .await;
+        assert!(result.is_err());
+        let result = service
+            .reset_database(params(serde_json::json!({ "confirm": true })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let mut tables: Vec<_> = result["removed_tables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|table| table.as_str().unwrap().to_string())
+            .collect();
+        tables.sort();
+        assert_eq!(tables, vec!["company", "person"]);
+        // All data has been removed
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT * FROM person" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[[]]");
+    }
+
+    #[tokio::test]
+    async fn test_reset_database_refuses_when_not_allowed_or_remote() {
+        let mut service = connected_service().await;
+        // Resets are disabled by default
+        let err = service
+            .reset_database(params(serde_json::json!({ "confirm": true })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("--allow-reset"));
+        // Resets are refused for remote endpoints
+        service.options.allow_reset = true;
+        service.context.lock().await.endpoint = Some("ws://localhost:8000".to_string());
+        let err = service
+            .reset_database(params(serde_json::json!({ "confirm": true })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Refusing to reset a ws database"));
+    }
+
+    #[tokio::test]
+    async fn test_export_to_file_writes_ndjson_lines() {
+        let dir = std::env::temp_dir().join(format!("surrealmcp-{}", generate_connection_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut service = connected_service().await;
+        service.options.export_dir = Some(dir.clone());
+        service
+            .query(params(serde_json::json!({
+                "query": "FOR $i IN 1..=2500 { CREATE type::thing('person', $i) SET index = $i };",
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .export_to_file(params(serde_json::json!({
+                "table": "person",
+                "path": "person.ndjson",
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["count"], 2500);
+        // Each record is written once as a single JSON line, in order of the record id
+        let contents = std::fs::read_to_string(dir.join("person.ndjson")).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2500);
+        for (index, line) in lines.into_iter().enumerate() {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(record["index"], index + 1);
+        }
+        // Paths outside of the export directory are rejected
+        let result = service
+            .export_to_file(params(serde_json::json!({
+                "table": "person",
+                "path": "../person.ndjson",
+            })))
+            .await;
+        assert!(result.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_define_param_can_be_referenced_in_queries() {
+        let mut service = connected_service().await;
+        // Define statements are disabled by default
+        let err = service
+            .define_param(params(serde_json::json!({ "name": "limit", "value": 2 })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("--allow-define-statements"));
+        service.options.allow_define_statements = true;
+        service
+            .define_param(params(serde_json::json!({
+                "name": "$settings",
+                "value": { "multiplier": 3 },
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "RETURN $settings.multiplier * 2" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[6]");
+        // Existing parameters are only replaced when requested
+        let result = service
+            .define_param(params(
+                serde_json::json!({ "name": "settings", "value": 1 }),
+            ))
+            .await;
+        assert!(result.is_err());
+        service
+            .define_param(params(serde_json::json!({
+                "name": "settings",
+                "value": 1,
+                "overwrite": true,
+            })))
+            .await
+            .unwrap();
+        // Defined parameters are listed
+        let result = service.list_params().await.unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["params"][0]["name"], "settings");
+        // Invalid parameter names are rejected
+        let result = service
+            .define_param(params(serde_json::json!({ "name": "a b", "value": 1 })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_get_info_reports_disabled_features() {
+        let options = ServiceOptions {
+            read_only: true,
+            disable_cloud: true,
+            ..Default::default()
+        };
+        let service = SurrealService::with_config(
+            generate_connection_id(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            options,
+        );
+        let info = service.get_info();
+        let experimental = info.capabilities.experimental.unwrap();
+        assert_eq!(
+            experimental["surrealmcp"]["disabled_features"],
+            serde_json::json!(["cloud", "writes"])
+        );
+        // Prompts and resources remain enabled
+        assert!(info.capabilities.prompts.is_some());
+        assert!(info.capabilities.resources.is_some());
+        // The disabled tools are not listed
+        assert!(!service.tool_router.has_route("create"));
+        assert!(!service.tool_router.has_route("list_cloud_instances"));
+        assert!(service.tool_router.has_route("select"));
+        assert!(service.tool_router.has_route("query"));
+        // Every tool which runs raw SurrealQL is disabled with the query tool
+        let options = ServiceOptions {
+            disable_query: true,
+            ..Default::default()
+        };
+        let router = SurrealService::tool_router_with_options(&options);
+        assert!(!router.has_route("query"));
+        assert!(!router.has_route("compute"));
+        assert!(router.has_route("run_function"));
+    }
+
+    #[tokio::test]
+    async fn test_select_by_ids_matches_record_ids() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:1 SET age = 20; CREATE person:⟨1⟩ SET age = 30; CREATE person:2 SET age = 40;",
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "ids": ["person:1", "person:2", "person:3"],
+                "order_clause": "age",
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        // The numeric record ID does not match the string record ID
+        let ages: Vec<_> = result[0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["age"].clone())
+            .collect();
+        assert_eq!(ages, vec![serde_json::json!(20), serde_json::json!(40)]);
+        // The ids can be combined with a where clause
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "ids": ["person:1", "person:2"],
+                "where_clause": "age > $min",
+                "parameters": { "min": 30 },
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result[0].as_array().unwrap().len(), 1);
+        // Invalid record IDs are rejected
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "ids": ["person"],
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_tables_with_and_without_counts() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a; CREATE person:b; DEFINE TABLE empty; CREATE company:c;",
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .list_tables(params(serde_json::json!({})))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(
+            result["tables"],
+            serde_json::json!([{ "table": "company" }, { "table": "empty" }, { "table": "person" }])
+        );
+        let result = service
+            .list_tables(params(serde_json::json!({ "include_counts": true })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(
+            result["tables"],
+            serde_json::json!([
+                { "table": "company", "count": 1 },
+                { "table": "empty", "count": 0 },
+                { "table": "person", "count": 2 },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_namespace_and_database_restrictions() {
+        let service = SurrealService::with_config(
+            generate_connection_id(),
+            None,
+            Some("allowed_ns".to_string()),
+            Some("allowed_db".to_string()),
+            None,
+            None,
+            None,
+            None,
+            ServiceOptions::default(),
+        );
+        // Supplied values which differ from the configuration are denied
+        for (ns, db, kind) in [
+            (Some("other"), None, "namespace 'other'"),
+            (None, Some("other"), "database 'other'"),
+        ] {
+            let err = service
+                .connect_endpoint(params(serde_json::json!({
+                    "endpoint": "memory",
+                    "namespace": ns,
+                    "database": db,
+                })))
+                .await
+                .unwrap_err();
+            assert!(err.message.starts_with(&format!("Cannot use {kind}")));
+        }
+        // Omitted values fall back to the configured values
+        service
+            .connect_endpoint(params(serde_json::json!({ "endpoint": "memory" })))
+            .await
+            .unwrap();
+        let context = service.context.lock().await.clone();
+        assert_eq!(context.namespace.as_deref(), Some("allowed_ns"));
+        assert_eq!(context.database.as_deref(), Some("allowed_db"));
+        // Supplied values which match the configuration are allowed
+        service
+            .connect_endpoint(params(serde_json::json!({
+                "endpoint": "memory",
+                "namespace": "allowed_ns",
+                "database": "allowed_db",
+            })))
+            .await
+            .unwrap();
+        service
+            .use_namespace(params(serde_json::json!({ "namespace": "allowed_ns" })))
+            .await
+            .unwrap();
+        service
+            .use_database(params(serde_json::json!({ "database": "allowed_db" })))
+            .await
+            .unwrap();
+        // The same error messages are returned when switching
+        let err = service
+            .use_namespace(params(serde_json::json!({ "namespace": "other" })))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.message,
+            "Cannot use namespace 'other'. Server is configured to only use namespace 'allowed_ns'"
+        );
+        let err = service
+            .use_database(params(serde_json::json!({ "database": "other" })))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.message,
+            "Cannot use database 'other'. Server is configured to only use database 'allowed_db'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unrestricted_namespace_and_database() {
+        let service = connected_service().await;
+        // Any supplied value is allowed without a configured restriction
+        service
+            .use_namespace(params(serde_json::json!({ "namespace": "other" })))
+            .await
+            .unwrap();
+        service
+            .use_database(params(serde_json::json!({ "database": "other" })))
+            .await
+            .unwrap();
+        // Omitted values are left unset without a configured restriction
+        assert_eq!(
+            service
+                .check_restriction("use_namespace", "namespace", None, &None)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compute_returns_expression_value() {
+        let service = connected_service().await;
+        let result = service
+            .compute(params(serde_json::json!({
+                "expression": "$r * $r + 1",
+                "parameters": { "r": 3 },
+            })))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), "10");
+        let result = service
+            .compute(params(serde_json::json!({
+                "expression": "string::uppercase($name)",
+                "parameters": { "name": "surreal" },
+            })))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), "\"SURREAL\"");
+        // Multiple statements are rejected
+        let result = service
+            .compute(params(
+                serde_json::json!({ "expression": "1; DELETE person" }),
+            ))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_define_table_with_typed_fields() {
+        let mut service = connected_service().await;
+        let table = serde_json::json!({
+            "name": "person",
+            "schemafull": true,
+            "fields": [
+                { "name": "name", "type": "string" },
+                { "name": "age", "type": "int", "optional": true },
+            ],
+        });
+        // Define statements are disabled by default
+        let err = service
+            .define_table(params(table.clone()))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("--allow-define-statements"));
+        service.options.allow_define_statements = true;
+        // The generated statements are returned with the result
+        let result = service.define_table(params(table)).await.unwrap();
+        assert_eq!(
+            result_text(&result),
+            "DEFINE TABLE person SCHEMAFULL;\nDEFINE FIELD name ON person TYPE string;\nDEFINE FIELD age ON person TYPE option<int>;\n"
+        );
+        // The table and its fields are defined
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "INFO FOR TABLE person" }),
+            ))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        let fields = &result[0]["fields"];
+        assert!(fields["name"].as_str().unwrap().contains("TYPE string"));
+        assert!(fields["age"].as_str().unwrap().contains("TYPE option<int>"));
+        // The field types are enforced
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "CREATE person SET name = 42"
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result[0]["status"], "ERR");
+        assert!(
+            result[0]["error"]
+                .as_str()
+                .unwrap()
+                .contains("expected a string")
+        );
+        // Invalid names and injected statements are rejected
+        let err = service
+            .define_table(params(serde_json::json!({ "name": "a b" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Invalid table name"));
+        let err = service
+            .define_table(params(serde_json::json!({
+                "name": "post",
+                "fields": [{ "name": "title", "type": "string; REMOVE TABLE person" }],
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Invalid type"));
+        let err = service
+            .define_table(params(serde_json::json!({
+                "name": "post",
+                "fields": [{ "name": "title", "type": "string VALUE http::get('https://surrealdb.com')" }],
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Invalid type"));
+        // Fields are not added when the table can not be defined
+        let err = service
+            .define_table(params(serde_json::json!({
+                "name": "person",
+                "fields": [{ "name": "email", "type": "string" }],
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("already exists"));
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "INFO FOR TABLE person" }),
+            ))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        assert!(result[0]["fields"].get("email").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_traverse_follows_multiple_hops() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "
+                    CREATE person:john, person:jane, person:bob SET age = 30;
+                    CREATE person:amy SET age = 20;
+                    RELATE person:john->knows->person:jane;
+                    RELATE person:jane->knows->person:bob;
+                    RELATE person:jane->knows->person:amy;
+                    RELATE person:bob->likes->post:1;
+                "
+            })))
+            .await
+            .unwrap();
+        let ids = |result: CallToolResult| {
+            let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+            let mut ids = result[0]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|record| record["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+        let knows = serde_json::json!({ "direction": "out", "edge": "knows", "target": "person" });
+        // Two hops return the friends of friends
+        let result = service
+            .traverse(params(serde_json::json!({
+                "start": "person:john",
+                "path": [knows, knows],
+            })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["person:amy", "person:bob"]);
+        // The records at the end of the path can be filtered
+        let result = service
+            .traverse(params(serde_json::json!({
+                "start": "person:john",
+                "path": [knows, knows],
+                "where_clause": "age > $min_age",
+                "parameters": { "min_age": 25 },
+            })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["person:bob"]);
+        // Edges can be followed inwards
+        let result = service
+            .traverse(params(serde_json::json!({
+                "start": "post:1",
+                "path": [
+                    { "direction": "in", "edge": "likes" },
+                    { "direction": "in", "edge": "knows" },
+                ],
+            })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["person:jane"]);
+        // Empty paths and invalid names are rejected
+        let err = service
+            .traverse(params(
+                serde_json::json!({ "start": "person:john", "path": [] }),
+            ))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("at least one step"));
+        let err = service
+            .traverse(params(serde_json::json!({
+                "start": "person:john",
+                "path": [{ "direction": "out", "edge": "knows->person; DELETE person" }],
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Invalid table name"));
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search_finds_matching_records() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "
+                    DEFINE ANALYZER simple TOKENIZERS blank, class FILTERS lowercase, snowball(english);
+                    DEFINE INDEX post_body ON post FIELDS body SEARCH ANALYZER simple BM25 HIGHLIGHTS;
+                    CREATE post:a SET body = 'The quick brown fox';
+                    CREATE post:b SET body = 'Lazy dogs sleeping';
+                "
+            })))
+            .await
+            .unwrap();
+        // Words are matched by the analyzer, regardless of case and endings
+        let result = service
+            .full_text_search(params(serde_json::json!({
+                "target": "post",
+                "field": "body",
+                "query": "Foxes",
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(
+            result[0],
+            serde_json::json!([{ "id": "post:a", "body": "The quick brown fox" }])
+        );
+        // The matched terms can be highlighted
+        let result = service
+            .full_text_search(params(serde_json::json!({
+                "target": "post",
+                "field": "body",
+                "query": "quick",
+                "highlight": true,
+                "limit": 5,
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result[0][0]["highlight"], "The <b>quick</b> brown fox");
+        // No records are returned when nothing matches
+        let result = service
+            .full_text_search(params(serde_json::json!({
+                "target": "post",
+                "field": "body",
+                "query": "cat",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), "[[]]");
+        // Searching a field without a full-text search index is an error
+        let result = service
+            .full_text_search(params(serde_json::json!({
+                "target": "post",
+                "field": "title",
+                "query": "quick",
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vector_search_returns_nearest_neighbours() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "
+                    CREATE document:a SET embedding = [0.0, 0.0], lang = 'en';
+                    CREATE document:b SET embedding = [1.0, 1.0], lang = 'fr';
+                    CREATE document:c SET embedding = [5.0, 5.0], lang = 'en';
+                "
+            })))
+            .await
+            .unwrap();
+        let ids = |result: CallToolResult| {
+            let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+            result[0]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|record| record["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        // Without an index, every record is compared
+        let result = service
+            .vector_search(params(serde_json::json!({
+                "target": "document",
+                "field": "embedding",
+                "vector": [0.9, 0.9],
+                "k": 2,
+                "distance": "euclidean",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["document:b", "document:a"]);
+        // The records can be filtered
+        let result = service
+            .vector_search(params(serde_json::json!({
+                "target": "document",
+                "field": "embedding",
+                "vector": [0.9, 0.9],
+                "k": 2,
+                "where_clause": "lang = $lang",
+                "parameters": { "lang": "en" },
+                "distance": "euclidean",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["document:a"]);
+        // With an HNSW index, the nearest neighbour is returned first
+        service
+            .query(params(serde_json::json!({
+                "query": "DEFINE INDEX document_embedding ON document FIELDS embedding HNSW DIMENSION 2"
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .vector_search(params(serde_json::json!({
+                "target": "document",
+                "field": "embedding",
+                "vector": [4.0, 4.5],
+                "k": 1,
+                "ef": 40,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["document:c"]);
+        // Invalid options are rejected
+        let err = service
+            .vector_search(params(serde_json::json!({
+                "target": "document",
+                "field": "embedding",
+                "vector": [0.9, 0.9],
+                "k": 2,
+                "ef": 40,
+                "distance": "cosine",
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Only one of ef and distance"));
+    }
+
+    #[test]
+    fn test_define_index_statement() {
+        let index = |json: serde_json::Value| {
+            define_index_statement(&serde_json::from_value(json).unwrap())
+        };
+        // Unique indexes
+        assert_eq!(
+            index(serde_json::json!({
+                "table": "person",
+                "name": "person_email",
+                "fields": ["email"],
+                "unique": true,
+            }))
+            .unwrap(),
+            "DEFINE INDEX person_email ON person FIELDS email UNIQUE"
+        );
+        // Vector indexes
+        assert_eq!(
+            index(serde_json::json!({
+                "table": "document",
+                "name": "document_embedding",
+                "fields": ["embedding"],
+                "vector": { "kind": "hnsw", "dimension": 4, "distance": "cosine", "element_type": "f32" },
+            }))
+            .unwrap(),
+            "DEFINE INDEX document_embedding ON document FIELDS embedding HNSW DIMENSION 4 DIST COSINE TYPE F32"
+        );
+        // Full-text search indexes require an analyzer
+        assert_eq!(
+            index(serde_json::json!({
+                "table": "post",
+                "name": "post_body",
+                "fields": ["body"],
+                "search": true,
+                "analyzer": "english",
+            }))
+            .unwrap(),
+            "DEFINE INDEX post_body ON post FIELDS body SEARCH ANALYZER english BM25 HIGHLIGHTS"
+        );
+        let err = index(serde_json::json!({
+            "table": "post", "name": "post_body", "fields": ["body"], "search": true,
+        }))
+        .unwrap_err();
+        assert!(err.contains("requires an analyzer"));
+        // Only one kind of index can be specified
+        let err = index(serde_json::json!({
+            "table": "document",
+            "name": "document_embedding",
+            "fields": ["embedding"],
+            "unique": true,
+            "vector": { "kind": "mtree", "dimension": 4 },
+        }))
+        .unwrap_err();
+        assert!(err.contains("Only one of unique, search, and vector"));
+        // Invalid names and vector options are rejected
+        assert!(
+            index(serde_json::json!({ "table": "a b", "name": "i", "fields": ["x"] })).is_err()
+        );
+        assert!(index(serde_json::json!({ "table": "t", "name": "i", "fields": [] })).is_err());
+        assert!(
+            index(serde_json::json!({
+                "table": "t", "name": "i", "fields": ["v"],
+                "vector": { "kind": "flat", "dimension": 4 },
+            }))
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_define_index_is_enforced() {
+        let mut service = connected_service().await;
+        service.options.allow_define_statements = true;
+        // Define a unique index and a vector index
+        service
+            .define_index(params(serde_json::json!({
+                "table": "person",
+                "name": "person_email",
+                "fields": ["email"],
+                "unique": true,
+            })))
+            .await
+            .unwrap();
+        service
+            .define_index(params(serde_json::json!({
+                "table": "person",
+                "name": "person_embedding",
+                "fields": ["embedding"],
+                "vector": { "kind": "hnsw", "dimension": 2 },
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "INFO FOR TABLE person" }),
+            ))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        let indexes = &result[0]["indexes"];
+        assert!(indexes["person_email"].as_str().unwrap().contains("UNIQUE"));
+        assert!(
+            indexes["person_embedding"]
+                .as_str()
+                .unwrap()
+                .contains("HNSW DIMENSION 2")
+        );
+        // The unique index is enforced
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "CREATE person SET email = 'a@example.com', embedding = [1, 2]; CREATE person SET email = 'a@example.com', embedding = [3, 4];"
+            })))
+            .await
+            .unwrap();
+        let outcomes: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(outcomes[1]["status"], "ERR");
+        assert!(
+            outcomes[1]["error"]
+                .as_str()
+                .unwrap()
+                .contains("already contains")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_define_user_at_database_level() {
+        let mut service = connected_service().await;
+        let user = serde_json::json!({
+            "name": "alice",
+            "level": "database",
+            "password": "s3cret-p4ssw0rd",
+            "roles": ["editor"],
+        });
+        // Define statements are disabled by default
+        let err = service.define_user(params(user.clone())).await.unwrap_err();
+        assert!(err.message.contains("--allow-define-statements"));
+        service.options.allow_define_statements = true;
+        service.define_user(params(user)).await.unwrap();
+        // The user is defined on the database, without the plain password
+        let result = service
+            .query(params(serde_json::json!({ "query": "INFO FOR DB" })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        let definition = result[0]["users"]["alice"].as_str().unwrap();
+        assert!(definition.contains("ON DATABASE"));
+        assert!(definition.contains("ROLES EDITOR"));
+        assert!(!definition.contains("s3cret-p4ssw0rd"));
+        // Invalid levels and roles are rejected
+        let result = service
+            .define_user(params(serde_json::json!({
+                "name": "bob",
+                "level": "table",
+                "password": "pass",
+            })))
+            .await;
+        assert!(result.is_err());
+        let result = service
+            .define_user(params(serde_json::json!({
+                "name": "bob",
+                "level": "database",
+                "password": "pass",
+                "roles": ["superuser"],
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_order_by_with_mixed_directions() {
+        let fields: Vec<OrderField> = serde_json::from_value(serde_json::json!([
+            { "field": "age", "direction": "desc" },
+            { "field": "name" },
+            { "field": "address.post code", "direction": "ASC" },
+        ]))
+        .unwrap();
+        assert_eq!(
+            build_order_by(&fields).unwrap(),
+            "age DESC, name ASC, address.`post code` ASC"
+        );
+        // Invalid directions are rejected
+        let fields: Vec<OrderField> = serde_json::from_value(serde_json::json!([
+            { "field": "age", "direction": "DESC; DELETE person" },
+        ]))
+        .unwrap();
+        assert!(build_order_by(&fields).is_err());
+        assert!(build_order_by(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_select_with_structured_order_and_group_by() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a SET name = 'a', age = 30; CREATE person:b SET name = 'b', age = 20; CREATE person:c SET name = 'c', age = 30;"
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "order_by": [
+                    { "field": "age", "direction": "DESC" },
+                    { "field": "name", "direction": "ASC" },
+                ],
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let names: Vec<_> = result[0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].clone())
+            .collect();
+        assert_eq!(names, ["a", "c", "b"]);
+        // Invalid directions are rejected
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "order_by": [{ "field": "age", "direction": "SIDEWAYS" }],
+            })))
+            .await;
+        assert!(result.is_err());
+        // Structured and raw clauses can not be combined
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "group_by": ["age"],
+                "group_clause": "age",
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_response_envelope_wraps_results_and_errors() {
+        let mut service = connected_service().await;
+        service.options.response_envelope = true;
+        // Successful results include the parsed data and query id
+        let message = call_tool(
+            &service,
+            "query",
+            serde_json::json!({ "query": "RETURN 1 + 1" }),
+            None,
+        )
+        .await;
+        assert_eq!(message["result"]["isError"], false);
+        let text = message["result"]["content"][0]["text"].as_str().unwrap();
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert!(body["query_id"].is_u64());
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "ok": true,
+                "tool": "query",
+                "data": [{
+                    "statement": 0,
+                    "status": "OK",
+                    "result": 2,
+                    "time_ms": body["data"][0]["time_ms"],
+                }],
+                "query_id": body["query_id"],
+            })
+        );
+        // Errors include the error code, message, and data
+        let message = call_tool(
+            &service,
+            "query",
+            serde_json::json!({ "query": "SELEC 1" }),
+            None,
+        )
+        .await;
+        assert_eq!(message["result"]["isError"], true);
+        let text = message["result"]["content"][0]["text"].as_str().unwrap();
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(body["ok"], false);
+        assert_eq!(body["tool"], "query");
+        assert!(body["query_id"].is_u64());
+        assert!(body["error"]["message"].is_string());
+        assert_eq!(body["error"]["data"]["retryable"], false);
+        assert!(body.get("data").is_none());
+        // Errors raised before the tool is called are wrapped too
+        let message = call_tool(
+            &service,
+            "delete",
+            serde_json::json!({ "targets": ["person"] }),
+            Some(&["db:read"]),
+        )
+        .await;
+        assert_eq!(message["result"]["isError"], true);
+        let text = message["result"]["content"][0]["text"].as_str().unwrap();
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(body["ok"], false);
+        assert_eq!(body["tool"], "delete");
+        assert_eq!(body["query_id"], serde_json::Value::Null);
+        assert!(body["error"]["message"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_whoami_reflects_authenticated_identity() {
+        let service = SurrealService::new(generate_connection_id());
+        // Sessions without a bearer token are not authenticated
+        let result = service.whoami().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(body["authenticated"], false);
+        assert_eq!(body["subject"], serde_json::Value::Null);
+        assert_eq!(body["connection_id"], service.connection_id);
+        // The identity is read from the request extensions
+        let (mut parts, _) = http::Request::new(()).into_parts();
+        parts.extensions.insert("token".to_string());
+        parts.extensions.insert(TokenIdentity {
+            subject: Some("user-123".to_string()),
+            email: Some("tobie@surrealdb.com".to_string()),
+            issuer: "https://auth.surrealdb.com/".to_string(),
+            expires_at: Some(1767225600),
+        });
+        service.authenticate(&parts).await;
+        let result = service.whoami().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "authenticated": true,
+                "subject": "user-123",
+                "email": "tobie@surrealdb.com",
+                "issuer": "https://auth.surrealdb.com/",
+                "expires_at": 1767225600,
+                "connection_id": service.connection_id,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_scopes_restrict_mutating_tools() {
+        let service = connected_service().await;
+        service
+            .query(params(
+                serde_json::json!({ "query": "CREATE person:tobie" }),
+            ))
+            .await
+            .unwrap();
+        // A token without db:write can not call delete, but can call select
+        let message = call_tool(
+            &service,
+            "delete",
+            serde_json::json!({ "targets": ["person:tobie"] }),
+            Some(&["db:read"]),
+        )
+        .await;
+        assert!(message_error(&message).contains("'db:write' scope"));
+        let message = call_tool(
+            &service,
+            "select",
+            serde_json::json!({ "targets": ["person:tobie"] }),
+            Some(&["db:read"]),
+        )
+        .await;
+        assert_eq!(
+            message["result"]["content"][0]["text"],
+            r#"[[{"id":"person:tobie"}]]"#
+        );
+        // Tools which define resources or write files require db:admin
+        let message = call_tool(
+            &service,
+            "define_user",
+            serde_json::json!({ "name": "tobie", "level": "database", "password": "secret" }),
+            Some(&["db:read", "db:write"]),
+        )
+        .await;
+        assert!(message_error(&message).contains("'db:admin' scope"));
+        let message = call_tool(
+            &service,
+            "export_to_file",
+            serde_json::json!({ "table": "person", "path": "person.ndjson" }),
+            Some(&["db:read", "db:write"]),
+        )
+        .await;
+        assert!(message_error(&message).contains("'db:admin' scope"));
+        // Raw queries which modify data are rejected without db:write
+        let message = call_tool(
+            &service,
+            "query",
+            serde_json::json!({ "query": "DELETE person:tobie" }),
+            Some(&["db:read"]),
+        )
+        .await;
+        assert!(message_error(&message).contains("'db:write' scope"));
+        // Tokens with the required scope, or without a scope claim, are not restricted
+        let message = call_tool(
+            &service,
+            "delete",
+            serde_json::json!({ "targets": ["person:tobie"] }),
+            Some(&["db:read", "db:write"]),
+        )
+        .await;
+        assert!(message["error"].is_null());
+        let message = call_tool(
+            &service,
+            "select",
+            serde_json::json!({ "targets": ["person:tobie"] }),
+            None,
+        )
+        .await;
+        assert_eq!(message["result"]["content"][0]["text"], "[[]]");
+    }
+
+    #[tokio::test]
+    async fn test_typed_envelopes_are_stored_as_native_values() {
+        let service = connected_service().await;
+        service
+            .create(params(serde_json::json!({
+                "target": "event:launch",
+                "data": {
+                    "at": { "$datetime": "2024-01-01T00:00:00Z" },
+                    "lasts": { "$duration": "2h" },
+                    "host": { "$record": "person:john" },
+                },
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "SELECT type::is::datetime(at) AS at, type::is::duration(lasts) AS lasts, type::is::record(host) AS host FROM ONLY event:launch",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(
+            query_results(&result),
+            r#"[{"at":true,"host":true,"lasts":true}]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_results_are_truncated() {
+        let mut service = connected_service().await;
+        service.options.max_message_bytes = Some(1000);
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE document:big SET items = array::repeat('surrealdb', 10000)",
+            })))
+            .await
+            .unwrap();
+        // The oversized record is truncated instead of being returned whole
+        let message = call_tool(
+            &service,
+            "select",
+            serde_json::json!({ "targets": ["document:big"] }),
+            None,
+        )
+        .await;
+        let result = &message["result"];
+        assert!(result.to_string().len() <= 1000);
+        let content = result["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        assert!(
+            content[0]["text"]
+                .as_str()
+                .unwrap()
+                .starts_with(r#"[[{"id":"document:big""#)
+        );
+        let marker = content[1]["text"].as_str().unwrap();
+        assert!(marker.starts_with("[TRUNCATED]"));
+        assert!(marker.contains("exceeds the maximum size of 1000 bytes"));
+        assert!(marker.contains("WHERE clause"));
+        // The limit applies to the message after the response envelope is added
+        service.options.response_envelope = true;
+        let message = call_tool(
+            &service,
+            "select",
+            serde_json::json!({ "targets": ["document:big"] }),
+            None,
+        )
+        .await;
+        let result = &message["result"];
+        assert!(result.to_string().len() <= 1000);
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(body["ok"], true);
+        assert!(body["data"][1].as_str().unwrap().starts_with("[TRUNCATED]"));
+        // Results within the limit are returned unchanged
+        let message = call_tool(
+            &service,
+            "select",
+            serde_json::json!({ "targets": ["document:big"], "fields": ["id"] }),
+            None,
+        )
+        .await;
+        let text = message["result"]["content"][0]["text"].as_str().unwrap();
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(
+            body["data"],
+            serde_json::json!([[{ "id": "document:big" }]])
+        );
+        // Text is only cut at character boundaries
+        let mut result = CallToolResult::success(vec![Content::text("aé"), Content::text("b")]);
+        assert_eq!(truncate_result(&mut result, 2, 2), Some(4));
+        assert_eq!(result_text(&result), "a");
+        assert_eq!(result.content.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_show_changes_reads_change_feed() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "DEFINE TABLE person CHANGEFEED 1h; CREATE person:john SET age = 30; UPDATE person:john SET age = 31;"
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .show_changes(params(
+                serde_json::json!({ "table": "person", "since": "0" }),
+            ))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let updates: Vec<_> = result[0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|batch| batch["changes"].as_array().unwrap())
+            .filter_map(|change| change.get("update"))
+            .map(|update| update["age"].clone())
+            .collect();
+        assert_eq!(updates, [30, 31]);
+        // The number of change batches can be limited
+        let result = service
+            .show_changes(params(
+                serde_json::json!({ "table": "person", "since": "0", "limit": 1 }),
+            ))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result[0].as_array().unwrap().len(), 1);
+        // Invalid since values are rejected
+        let result = service
+            .show_changes(params(
+                serde_json::json!({ "table": "person", "since": "0 LIMIT 1; DELETE person" }),
+            ))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_select_query_with_limit() {
+        let params: SelectParams = serde_json::from_value(serde_json::json!({
+            "targets": ["person"],
+            "where_clause": "age > 25",
+            "limit_clause": "10",
+            "start_clause": "20",
+        }))
+        .unwrap();
+        let (query, _) = build_select_query(params).unwrap();
+        assert!(query.contains(" LIMIT 10"));
+        assert!(!query.contains("LIMIT BY"));
+        assert!(query.ends_with(" LIMIT 10 START AT 20"));
+    }
+
+    #[tokio::test]
+    async fn test_select_with_index_is_used_in_query_plan() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "DEFINE INDEX person_age ON person FIELDS age; DEFINE INDEX person_name ON person FIELDS name; CREATE person:a SET name = 'a', age = 30;"
+            })))
+            .await
+            .unwrap();
+        let select = serde_json::json!({
+            "targets": ["person"],
+            "where_clause": "name = 'a' AND age > 25",
+            "with_index": ["person_name"],
+            "explain": true,
+        });
+        // The clause is added after the targets
+        let (query, _) =
+            build_select_query(serde_json::from_value(select.clone()).unwrap()).unwrap();
+        assert!(query.starts_with("SELECT * FROM person WITH INDEX person_name WHERE"));
+        assert!(query.ends_with(" EXPLAIN"));
+        // The forced index is used in the query plan
+        let result = service.select(params(select)).await.unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let indexes: Vec<_> = result[0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|step| step["detail"]["plan"].get("index"))
+            .collect();
+        assert_eq!(indexes, ["person_name"]);
+        // Empty index lists are rejected
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "with_index": [],
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tools_require_a_connection() {
+        // Sessions without a connection are told how to connect
+        let service = SurrealService::new(generate_connection_id());
+        let err = service
+            .select(params(serde_json::json!({ "targets": ["person"] })))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.message,
+            "Not connected to any SurrealDB endpoint. Use connect_endpoint or connect_profile to connect first."
+        );
+        let err = service
+            .use_namespace(params(serde_json::json!({ "namespace": "test" })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .starts_with("Not connected to any SurrealDB endpoint")
+        );
+        // Sessions with a startup endpoint are told to reconnect to it
+        let service = SurrealService::with_config(
+            generate_connection_id(),
+            Some("memory".to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            ServiceOptions::default(),
+        );
+        let err = service
+            .select(params(serde_json::json!({ "targets": ["person"] })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("endpoint 'memory' configured at startup")
+        );
+        assert!(err.message.contains("connect_endpoint('memory')"));
+        let err = service
+            .use_database(params(serde_json::json!({ "database": "test" })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("endpoint 'memory' configured at startup")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_namespaces() {
+        // The tool is registered in the tool router
+        let service = SurrealService::new(generate_connection_id());
+        assert!(service.tool_router.has_route("list_namespaces"));
+        // The tool requires a database connection
+        let err = service.list_namespaces().await.unwrap_err();
+        assert!(err.message.contains("Not connected"));
+        // Defined namespaces are listed
+        let service = connected_service().await;
+        service
+            .query(params(
+                serde_json::json!({ "query": "DEFINE NAMESPACE other;" }),
+            ))
+            .await
+            .unwrap();
+        let result = service.list_namespaces().await.unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let names: Vec<_> = result["namespaces"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|ns| ns["name"].clone())
+            .collect();
+        assert!(names.contains(&serde_json::json!("other")));
+        assert_eq!(result["count"], names.len());
+    }
+
+    #[test]
+    fn test_prompts_and_resources_are_limited() {
+        let options = ServiceOptions {
+            max_prompts: Some(2),
+            max_resources: Some(0),
+            ..Default::default()
+        };
+        let service = SurrealService::with_config(
+            generate_connection_id(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            options,
+        );
+        // More prompts are registered than the limit
+        assert!(prompts::list_prompts().len() > 2);
+        let registered = service.registered_prompts();
+        assert_eq!(registered.len(), 2);
+        assert_eq!(registered[0].name, prompts::list_prompts()[0].name);
+        // A maximum of 0 is unlimited
+        assert_eq!(
+            service.registered_resources().len(),
+            resources::list_resources().len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_databases() {
+        // The tool is registered in the tool router
+        let service = SurrealService::new(generate_connection_id());
+        assert!(service.tool_router.has_route("list_databases"));
+        // The tool requires a selected namespace
+        service
+            .connect_endpoint(Parameters(ConnectParams {
+                endpoint: "memory".to_string(),
+                namespace: None,
+                database: None,
+                username: None,
+                password: None,
+            }))
+            .await
+            .unwrap();
+        let err = service.list_databases().await.unwrap_err();
+        assert!(err.message.contains("use_namespace"));
+        // Defined databases are listed
+        let service = connected_service().await;
+        service
+            .query(params(
+                serde_json::json!({ "query": "DEFINE DATABASE other;" }),
+            ))
+            .await
+            .unwrap();
+        let result = service.list_databases().await.unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let names: Vec<_> = result["databases"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|db| db["name"].clone())
+            .collect();
+        assert!(names.contains(&serde_json::json!("other")));
+        assert_eq!(result["count"], names.len());
+    }
+
+    #[tokio::test]
+    async fn test_preview_merge_matches_applied_merge() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:john SET name = 'John', address = { city: 'Paris', country: 'France' }, tags = ['a', 'b'];"
+            })))
+            .await
+            .unwrap();
+        let merge_data = serde_json::json!({
+            "address": { "city": "London", "postcode": "N1" },
+            "tags": ["c"],
+            "age": 31,
+        });
+        // Preview the merge
+        let result = service
+            .preview_merge(params(serde_json::json!({
+                "record": "person:john",
+                "merge_data": merge_data,
+            })))
+            .await
+            .unwrap();
+        let preview: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        // The preview does not modify the record
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT * FROM ONLY person:john" }),
+            ))
+            .await
+            .unwrap();
+        let current: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        assert_eq!(current[0]["address"]["city"], "Paris");
+        // The preview matches the applied merge
+        let result = service
+            .update(params(serde_json::json!({
+                "targets": ["person:john"],
+                "merge_data": merge_data,
+            })))
+            .await
+            .unwrap();
+        let applied: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(preview, applied[0][0]);
+        // Missing records can not be previewed
+        let result = service
+            .preview_merge(params(serde_json::json!({
+                "record": "person:jane",
+                "merge_data": { "age": 1 },
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_empty_string_parameters() {
+        let mut service = connected_service().await;
+        let query = |empty_string_as_none: Option<bool>| {
+            params(serde_json::json!({
+                "query": "RETURN [type::is::none($value), $nested.value]",
+                "parameters": { "value": "", "nested": { "value": "" } },
+                "empty_string_as_none": empty_string_as_none,
+            }))
+        };
+        let as_string = serde_json::json!([[false, ""]]);
+        let as_none = serde_json::json!([[true, ""]]);
+        // Empty strings are bound as strings by default
+        let result = service.query(query(None)).await.unwrap();
+        let value: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        assert_eq!(value, as_string);
+        // Empty strings are bound as NONE when enabled for a call
+        let result = service.query(query(Some(true))).await.unwrap();
+        let value: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        assert_eq!(value, as_none);
+        // Empty strings are bound as NONE when enabled for the server
+        service.options.empty_string_as_none = true;
+        let result = service.query(query(None)).await.unwrap();
+        let value: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        assert_eq!(value, as_none);
+        // The server default can be overridden for a call
+        let result = service.query(query(Some(false))).await.unwrap();
+        let value: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        assert_eq!(value, as_string);
+    }
+
+    #[tokio::test]
+    async fn test_session_stats_reflect_session_queries() {
+        let service = connected_service().await;
+        let stats = |service: &SurrealService| {
+            let service = service.clone();
+            async move {
+                let result = service.session_stats().await.unwrap();
+                serde_json::from_str::<serde_json::Value>(&result_text(&result)).unwrap()
+            }
+        };
+        // No queries have been executed yet
+        assert_eq!(stats(&service).await["queries"], 0);
+        // Run two successful queries and one failing query
+        for query in ["CREATE person:a", "SELECT * FROM person", "THROW 'failed'"] {
+            let _ = service
+                .query(params(serde_json::json!({ "query": query })))
+                .await;
+        }
+        let result = stats(&service).await;
+        assert_eq!(result["queries"], 3);
+        assert_eq!(result["failed_queries"], 1);
+        assert!(result["total_duration_ms"].as_f64().unwrap() > 0.0);
+        // Queries run by other tools are also counted
+        service
+            .select(params(serde_json::json!({ "targets": ["person"] })))
+            .await
+            .unwrap();
+        assert_eq!(stats(&service).await["queries"], 4);
+        // The statistics are reset when reconnecting
+        service
+            .connect_endpoint(params(serde_json::json!({ "endpoint": "memory" })))
+            .await
+            .unwrap();
+        let result = stats(&service).await;
+        assert_eq!(result["queries"], 0);
+        assert_eq!(result["failed_queries"], 0);
+        assert_eq!(result["total_duration_ms"], 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_get_cloud_instance_status_returns_status() {
+        let router = axum::Router::new().route(
+            "/instances/abc/status",
+            axum::routing::get(|| async {
+                axum::Json(serde_json::json!({
+                    "phase": "WaitingForDeployment",
+                    "db_backups": [{
+                        "snapshot_started_at": "2025-07-01T09:03:26Z",
+                        "snapshot_id": "8a638067-76a7-44d9-81a4-5c4eb71a8838",
+                    }],
+                }))
+            }),
+        );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        // The status of the instance is included in the result
+        let result = service
+            .get_cloud_instance_status(params(serde_json::json!({ "instance_id": "abc" })))
+            .await
+            .unwrap();
+        let status: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(status["instance_id"], "abc");
+        assert_eq!(status["phase"], "WaitingForDeployment");
+        assert_eq!(status["backup_count"], 1);
+        assert_eq!(
+            status["db_backups"][0]["snapshot_id"],
+            "8a638067-76a7-44d9-81a4-5c4eb71a8838"
+        );
+        assert_eq!(
+            status["db_backups"][0]["snapshot_started_at"],
+            "2025-07-01T09:03:26Z"
+        );
+        // Errors from the Cloud API are returned as errors
+        let result = service
+            .get_cloud_instance_status(params(serde_json::json!({ "instance_id": "missing" })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_cloud_instance_authenticates_with_token() {
+        let tokens = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requested = tokens.clone();
+        let router = axum::Router::new()
+            .route(
+                "/instances/{id}",
+                axum::routing::get(
+                    |axum::extract::Path(id): axum::extract::Path<String>| async move {
+                        axum::Json(serde_json::json!({
+                            "id": id,
+                            "name": "Test",
+                            "host": "mem://",
+                            "state": if id == "abc" { "ready" } else { "paused" },
+                        }))
+                    },
+                ),
+            )
+            .route(
+                "/instances/{id}/auth",
+                axum::routing::get(
+                    move |axum::extract::Path(id): axum::extract::Path<String>| async move {
+                        requested.lock().unwrap().push(id);
+                        axum::Json(serde_json::json!({ "token": "invalid" }))
+                    },
+                ),
+            );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        // The instance is connected to with the token fetched for it
+        let err = service
+            .connect_cloud_instance(params(serde_json::json!({ "instance_id": "abc" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("problem with authentication"));
+        assert_eq!(*tokens.lock().unwrap(), ["abc"]);
+        assert!(service.db.lock().await.is_none());
+        // Instances which are not ready are not connected to
+        let err = service
+            .connect_cloud_instance(params(serde_json::json!({ "instance_id": "def" })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("Cloud instance 'def' is not ready (state: paused)")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_and_restore_cloud_backups() {
+        let restored = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = restored.clone();
+        let router = axum::Router::new()
+            .route(
+                "/instances/abc/status",
+                axum::routing::get(|| async {
+                    axum::Json(serde_json::json!({
+                        "phase": "Ready",
+                        "db_backups": [{
+                            "snapshot_started_at": "2025-07-01T09:03:26Z",
+                            "snapshot_id": "8a638067-76a7-44d9-81a4-5c4eb71a8838",
+                        }],
+                    }))
+                }),
+            )
+            .route(
+                "/instances/abc/restore",
+                axum::routing::post(
+                    move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                        requests.lock().unwrap().push(body);
+                        axum::http::StatusCode::ACCEPTED
+                    },
+                ),
+            );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        // The backups of the instance are listed
+        let result = service
+            .list_cloud_backups(params(serde_json::json!({ "instance_id": "abc" })))
+            .await
+            .unwrap();
+        let backups: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(backups["count"], 1);
+        let snapshot_id = backups["backups"][0]["snapshot_id"].as_str().unwrap();
+        assert_eq!(snapshot_id, "8a638067-76a7-44d9-81a4-5c4eb71a8838");
+        // A listed backup can be restored
+        let result = service
+            .restore_cloud_backup(params(serde_json::json!({
+                "instance_id": "abc",
+                "snapshot_id": snapshot_id,
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["instance_id"], "abc");
+        assert_eq!(result["snapshot_id"], snapshot_id);
+        assert_eq!(
+            *restored.lock().unwrap(),
+            [serde_json::json!({ "snapshot_id": snapshot_id })]
+        );
+        // The snapshot id must be specified
+        let err = service
+            .restore_cloud_backup(params(serde_json::json!({
+                "instance_id": "abc",
+                "snapshot_id": " ",
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("can not be empty"));
+        // Errors from the Cloud API are returned as errors
+        let result = service
+            .restore_cloud_backup(params(serde_json::json!({
+                "instance_id": "missing",
+                "snapshot_id": snapshot_id,
+            })))
+            .await;
+        assert!(result.is_err());
+        assert_eq!(restored.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_cloud_instance_storage_respects_cooldown() {
+        let updates = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = updates.clone();
+        let router = axum::Router::new().route(
+            "/instances/{id}",
+            axum::routing::get(
+                |axum::extract::Path(id): axum::extract::Path<String>| async move {
+                    axum::Json(serde_json::json!({
+                        "id": id,
+                        "name": "Test",
+                        "storage_size": 10,
+                        "compute_units": 1,
+                        "can_update_storage_size": id != "cooling",
+                        "storage_size_update_cooloff_hours": 6,
+                    }))
+                },
+            )
+            .patch(
+                move |axum::extract::Path(id): axum::extract::Path<String>,
+                      axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    requests.lock().unwrap().push(body.clone());
+                    let mut instance = serde_json::json!({ "id": id, "name": "Test" });
+                    instance
+                        .as_object_mut()
+                        .unwrap()
+                        .extend(body.as_object().unwrap().clone());
+                    axum::Json(instance)
+                },
+            ),
+        );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        // Storage updates on cooldown are rejected without updating the instance
+        let err = service
+            .update_cloud_instance_storage(params(serde_json::json!({
+                "instance_id": "cooling",
+                "storage_size": 20,
+            })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("can only be updated once every 6 hours")
+        );
+        assert!(updates.lock().unwrap().is_empty());
+        // Storage can be updated when not on cooldown
+        let result = service
+            .update_cloud_instance_storage(params(serde_json::json!({
+                "instance_id": "abc",
+                "storage_size": 20,
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["instance"]["storage_size"], 20);
+        // Compute units are updated directly
+        let result = service
+            .update_cloud_instance_compute(params(serde_json::json!({
+                "instance_id": "abc",
+                "compute_units": 4,
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["instance"]["compute_units"], 4);
+        assert_eq!(
+            *updates.lock().unwrap(),
+            [
+                serde_json::json!({ "storage_size": 20 }),
+                serde_json::json!({ "compute_units": 4 }),
+            ]
+        );
+        // Sizes must be positive
+        let result = service
+            .update_cloud_instance_compute(params(serde_json::json!({
+                "instance_id": "abc",
+                "compute_units": 0,
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_cloud_regions() {
+        let router = axum::Router::new().route(
+            "/organizations/{id}/regions",
+            axum::routing::get(|axum::extract::Path(id): axum::extract::Path<String>| async move {
+                match id.as_str() {
+                    "org" => axum::Json(serde_json::json!([
+                        { "slug": "aws-euw1", "display_name": "Europe (Ireland)", "provider": "aws" },
+                        { "slug": "aws-use1" },
+                    ])),
+                    _ => axum::Json(serde_json::Value::Null),
+                }
+            }),
+        );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        // The regions are listed with their slugs and display names
+        let result = service
+            .list_cloud_regions(params(serde_json::json!({ "organization_id": "org" })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["count"], 2);
+        assert_eq!(result["regions"][0]["slug"], "aws-euw1");
+        assert_eq!(result["regions"][0]["name"], "Europe (Ireland)");
+        assert_eq!(result["regions"][1]["name"], "aws-use1");
+        // Organizations without a plan have no regions
+        let result = service
+            .list_cloud_regions(params(serde_json::json!({ "organization_id": "none" })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["count"], 0);
+        assert!(result["message"].as_str().unwrap().contains("plan"));
+    }
+
+    #[tokio::test]
+    async fn test_create_cloud_instance_checks_version() {
+        let created = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = created.clone();
+        let router = axum::Router::new().route(
+            "/organizations/org/instances",
+            axum::routing::get(|| async {
+                axum::Json(serde_json::json!([{
+                    "id": "abc",
+                    "name": "Existing",
+                    "version": "2.2.6",
+                    "available_versions": ["2.3.6"],
+                }]))
+            })
+            .post(
+                move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    requests.lock().unwrap().push(body);
+                    axum::Json(serde_json::json!({ "instance": { "id": "def", "name": "Test" } }))
+                },
+            ),
+        );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        // Unknown versions are rejected before creating the instance
+        let err = service
+            .create_cloud_instance(params(serde_json::json!({
+                "name": "Test",
+                "organization_id": "org",
+                "version": "9.9.9",
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Available versions: 2.2.6, 2.3.6"));
+        assert!(created.lock().unwrap().is_empty());
+        // The region and version are included in the request
+        service
+            .create_cloud_instance(params(serde_json::json!({
+                "name": "Test",
+                "organization_id": "org",
+                "region": "aws-euw1",
+                "version": "2.3.6",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(
+            *created.lock().unwrap(),
+            [serde_json::json!({
+                "name": "Test",
+                "organization_id": "org",
+                "region": "aws-euw1",
+                "version": "2.3.6",
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_cloud_instance_checks_available_versions() {
+        let upgrades = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = upgrades.clone();
+        let router = axum::Router::new()
+            .route(
+                "/instances/abc",
+                axum::routing::get(|| async {
+                    axum::Json(serde_json::json!({
+                        "id": "abc",
+                        "name": "Test",
+                        "version": "2.2.6",
+                        "available_versions": ["2.3.6", "2.3.7"],
+                    }))
+                }),
+            )
+            .route(
+                "/instances/abc/version",
+                axum::routing::post(
+                    move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                        requests.lock().unwrap().push(body.clone());
+                        axum::Json(serde_json::json!({
+                            "id": "abc",
+                            "name": "Test",
+                            "version": body["version"],
+                        }))
+                    },
+                ),
+            );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        // Versions which are not available are rejected with the valid versions
+        let err = service
+            .upgrade_cloud_instance(params(serde_json::json!({
+                "instance_id": "abc",
+                "version": "3.0.0",
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Available versions: 2.3.6, 2.3.7"));
+        assert!(upgrades.lock().unwrap().is_empty());
+        // Available versions are upgraded to
+        let result = service
+            .upgrade_cloud_instance(params(serde_json::json!({
+                "instance_id": "abc",
+                "version": "2.3.7",
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["instance"]["version"], "2.3.7");
+        assert_eq!(
+            *upgrades.lock().unwrap(),
+            [serde_json::json!({ "version": "2.3.7" })]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_cloud_instance_returns_confirmation() {
+        let router = axum::Router::new().route(
+            "/instances/abc",
+            axum::routing::delete(|| async { axum::http::StatusCode::NO_CONTENT }),
+        );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        // The deleted instance id is confirmed in the result
+        let result = service
+            .delete_cloud_instance(params(serde_json::json!({ "instance_id": "abc" })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["instance_id"], "abc");
+        // Errors from the Cloud API are returned as errors
+        let result = service
+            .delete_cloud_instance(params(serde_json::json!({ "instance_id": "missing" })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_select_record_range() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "FOR $i IN 1..=5 { CREATE type::thing('person', $i) };"
+            })))
+            .await
+            .unwrap();
+        let ids = |result: CallToolResult| -> Vec<String> {
+            let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+            result[0]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|record| record["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        // The end of the range is excluded by default
+        let result = service
+            .select(params(serde_json::json!({
+                "range": { "table": "person", "start": 2, "end": 4 },
+            })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["person:2", "person:3"]);
+        // The end of the range can be included
+        let result = service
+            .select(params(serde_json::json!({
+                "range": { "table": "person", "start": 2, "end": 4, "inclusive": true },
+            })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["person:2", "person:3", "person:4"]);
+        // Ranges can be open and passed directly as targets
+        let result = service
+            .select(params(serde_json::json!({ "targets": ["person:4.."] })))
+            .await
+            .unwrap();
+        assert_eq!(ids(result), ["person:4", "person:5"]);
+        // A target or range is required
+        let result = service.select(params(serde_json::json!({}))).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_function_with_allowed_and_denied_functions() {
+        let mut service = connected_service().await;
+        service.options.allowed_functions = vec!["string::*".to_string()];
+        service.options.denied_functions = vec!["http::*".to_string()];
+        let run = |name: &str, args: serde_json::Value| {
+            params(serde_json::json!({ "name": name, "args": args }))
+        };
+        // Allowed functions are run with their arguments
+        let result = service
+            .run_function(run("string::uppercase", serde_json::json!(["hello"])))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), r#""HELLO""#);
+        // Denied functions are rejected
+        let err = service
+            .run_function(run("http::get", serde_json::json!(["https://example.com"])))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("'http::get' is denied"));
+        // Functions which are not allowed are rejected
+        let err = service
+            .run_function(run("math::abs", serde_json::json!([-1])))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("not in the functions allowed"));
+        // Invalid function names are rejected
+        for name in [
+            "string::len(1); DELETE person; RETURN string::len",
+            "len",
+            "string::",
+        ] {
+            assert!(
+                service
+                    .run_function(run(name, serde_json::json!([])))
+                    .await
+                    .is_err()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strict_functions_checks_raw_queries() {
+        let mut service = connected_service().await;
+        service.options.allowed_functions = vec!["string::*".to_string()];
+        service.options.denied_functions = vec!["http::*".to_string()];
+        let query = |query: &str| params(serde_json::json!({ "query": query }));
+        // Functions in raw queries are not checked by default
+        let result = service.query(query("RETURN math::abs(-1)")).await.unwrap();
+        assert_eq!(query_results(&result), "[1]");
+        // Functions in raw queries are checked in strict mode
+        service.options.strict_functions = true;
+        let result = service
+            .query(query("RETURN string::len('http::get(1)')"))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[12]");
+        let err = service
+            .query(query(
+                "RETURN string::len(http::get('https://example.com'))",
+            ))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("'http::get' is denied"));
+        assert!(service.query(query("RETURN math::abs(-1)")).await.is_err());
+        let err = service
+            .compute(params(
+                serde_json::json!({ "expression": "http::get('x')" }),
+            ))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("'http::get' is denied"));
+    }
+
+    #[tokio::test]
+    async fn test_strict_functions_checks_tool_clauses() {
+        let mut service = connected_service().await;
+        service.options.denied_functions = vec!["crypto::*".to_string()];
+        service.options.strict_functions = true;
+        service
+            .query(params(
+                serde_json::json!({ "query": "CREATE person:tobie SET name = 'Tobie'" }),
+            ))
+            .await
+            .unwrap();
+        // Clauses passed to tools other than query are checked
+        let message = call_tool(
+            &service,
+            "select",
+            serde_json::json!({
+                "targets": ["person"],
+                "where_clause": "crypto::md5(name) != ''",
+            }),
+            None,
+        )
+        .await;
+        assert!(message_error(&message).contains("'crypto::md5' is denied"));
+        let message = call_tool(
+            &service,
+            "update",
+            serde_json::json!({
+                "targets": ["person:tobie"],
+                "set_clause": "hash = crypto::sha256(name)",
+            }),
+            None,
+        )
+        .await;
+        assert!(message_error(&message).contains("'crypto::sha256' is denied"));
+        let message = call_tool(
+            &service,
+            "live_query",
+            serde_json::json!({
+                "table": "person",
+                "where_clause": "crypto::md5(name) = ''",
+            }),
+            None,
+        )
+        .await;
+        assert!(message_error(&message).contains("'crypto::md5' is denied"));
+        assert!(service.live_queries.lock().await.is_empty());
+        // The record was not modified
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT VALUE hash FROM ONLY person:tobie" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[null]");
+    }
+
+    #[tokio::test]
+    async fn test_result_depth_is_limited_for_every_tool() {
+        let mut service = connected_service().await;
+        service.options.max_result_depth = Some(2);
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a SET friend = person:b; CREATE person:b SET friend = person:a",
+            })))
+            .await
+            .unwrap();
+        // Tools which build their own results are limited too
+        let message = call_tool(
+            &service,
+            "compute",
+            serde_json::json!({ "expression": "{ a: { b: { c: { d: 1 } } } }" }),
+            None,
+        )
+        .await;
+        let text = message["result"]["content"][0]["text"].as_str().unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(text).unwrap(),
+            serde_json::json!({ "a": { "b": { "c": engine::TRUNCATED_MARKER } } })
+        );
+        // Query results are limited from the top of the result, which is the list of statements
+        let message = call_tool(
+            &service,
+            "select",
+            serde_json::json!({
+                "targets": ["person:a"],
+                "fetch_clause": "friend, friend.friend, friend.friend.friend",
+            }),
+            None,
+        )
+        .await;
+        let text = message["result"]["content"][0]["text"].as_str().unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(text).unwrap(),
+            serde_json::json!([[{ "id": "person:a", "friend": engine::TRUNCATED_MARKER }]])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_result_size_is_limited_for_every_tool() {
+        let mut service = connected_service().await;
+        service.options.max_result_bytes = Some(100);
+        // Query results are cut at the limit and followed by the marker
+        let message = call_tool(
+            &service,
+            "query",
+            serde_json::json!({
+                "query": "FOR $i IN 0..20 { CREATE person SET name = 'person ' + <string> $i }; SELECT name FROM person ORDER BY name",
+            }),
+            None,
+        )
+        .await;
+        let content = message["result"]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        let text = content[0]["text"].as_str().unwrap();
+        assert_eq!(text.len(), 100);
+        assert!(text.starts_with(r#"[{"statement":0,"status":"OK""#));
+        let marker = content[1]["text"].as_str().unwrap();
+        assert!(marker.starts_with("[TRUNCATED]"));
+        assert!(marker.contains("exceeds the maximum size of 100 bytes"));
+        // Tools which build their own results are limited too
+        let message = call_tool(
+            &service,
+            "compute",
+            serde_json::json!({ "expression": "string::repeat('a', 500)" }),
+            None,
+        )
+        .await;
+        let content = message["result"]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["text"].as_str().unwrap().len(), 100);
+        let marker = content[1]["text"].as_str().unwrap();
+        assert!(marker.starts_with("[TRUNCATED] The result was 502 bytes"));
+        // Results within the limit are not truncated
+        let message = call_tool(
+            &service,
+            "compute",
+            serde_json::json!({ "expression": "1 + 1" }),
+            None,
+        )
+        .await;
+        assert_eq!(message["result"]["content"].as_array().unwrap().len(), 1);
+        assert_eq!(message["result"]["content"][0]["text"], "2");
+    }
+
+    #[tokio::test]
+    async fn test_get_cloud_instance_capabilities() {
+        let router = axum::Router::new().route(
+            "/instances/abc",
+            axum::routing::get(|| async {
+                axum::Json(serde_json::json!({
+                    "id": "abc",
+                    "name": "Test",
+                    "capabilities": {
+                        "allow_scripting": false,
+                        "allow_guests": true,
+                        "allowed_functions": ["string::*"],
+                        "denied_functions": ["http::*"],
+                    },
+                }))
+            }),
+        );
+        let mut service = SurrealService::new(generate_connection_id());
+        let base_url = crate::cloud::mock_cloud_api(router).await;
+        service.cloud_client = Arc::new(crate::cloud::mock_client(base_url));
+        let result = service
+            .get_cloud_instance_capabilities(params(serde_json::json!({ "instance_id": "abc" })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let capabilities = &result["capabilities"];
+        assert_eq!(capabilities["allow_scripting"], false);
+        assert_eq!(capabilities["allow_guests"], true);
+        assert_eq!(
+            capabilities["allowed_functions"],
+            serde_json::json!(["string::*"])
+        );
+        assert_eq!(
+            capabilities["denied_functions"],
+            serde_json::json!(["http::*"])
+        );
+        // Capabilities which are missing from the response are returned as null
+        assert_eq!(capabilities["allowed_networks"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_query_reports_each_statement() {
+        let service = connected_service().await;
+        // A failing statement does not fail the whole call
+        let result = service
+            .query(params(serde_json::json!({
+                "query": r#"SELECT VALUE 1 FROM ONLY {}; THROW "boom"; SELECT VALUE 2 FROM ONLY {}"#,
+            })))
+            .await
+            .unwrap();
+        let results: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let outcomes: Vec<_> = results
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| {
+                (
+                    result["statement"].as_u64().unwrap(),
+                    result["status"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(outcomes, [(0, "OK"), (1, "ERR"), (2, "OK")]);
+        assert_eq!(results[0]["result"], 1);
+        assert!(results[1]["error"].as_str().unwrap().contains("boom"));
+        assert_eq!(results[2]["result"], 2);
+        // Queries which can not be parsed still fail the whole call
+        let result = service
+            .query(params(serde_json::json!({ "query": "SELEC 1; SELECT 2" })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_reconnects_when_connection_is_lost() {
+        let path = std::env::temp_dir().join(format!("surrealmcp-{}", generate_connection_id()));
+        let service = SurrealService::new(generate_connection_id());
+        service
+            .connect_endpoint(params(serde_json::json!({
+                "endpoint": format!("surrealkv://{}", path.display()),
+                "namespace": "test",
+                "database": "test",
+            })))
+            .await
+            .unwrap();
+        service
+            .query(params(
+                serde_json::json!({ "query": "CREATE person:tobie" }),
+            ))
+            .await
+            .unwrap();
+        service
+            .live_query(params(serde_json::json!({ "table": "person" })))
+            .await
+            .unwrap();
+        // Simulate a dropped connection with a client which is not connected
+        let lose_connection = || async {
+            let db = service.db.lock().await.replace(Surreal::init());
+            drop(db);
+        };
+        // Queries which read data are retried on the new connection
+        lose_connection().await;
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT VALUE id FROM person" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), r#"[["person:tobie"]]"#);
+        // Live queries on the lost connection are stopped
+        assert!(service.live_queries.lock().await.is_empty());
+        // Queries which write data are not retried, as they may have been applied
+        lose_connection().await;
+        let err = service
+            .query(params(serde_json::json!({ "query": "CREATE person" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("was not retried"));
+        assert_eq!(err.data.unwrap()["retryable"], true);
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT VALUE id FROM person" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), r#"[["person:tobie"]]"#);
+        // Sessions which have disconnected are not reconnected
+        service.disconnect_endpoint().await.unwrap();
+        lose_connection().await;
+        let err = service
+            .query(params(serde_json::json!({ "query": "RETURN 1" })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .to_lowercase()
+                .contains("connection uninitialised")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_databases_are_not_reconnected() {
+        let service = connected_service().await;
+        *service.db.lock().await = Some(Surreal::init());
+        let err = service
+            .query(params(serde_json::json!({ "query": "RETURN 1" })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .to_lowercase()
+                .contains("connection uninitialised")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain_returns_query_plan() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "
+                    DEFINE INDEX person_age ON person FIELDS age;
+                    CREATE person:a SET age = 20;
+                    CREATE person:b SET age = 40;
+                "
+            })))
+            .await
+            .unwrap();
+        // The raw query plan shows the index which is used
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "SELECT * FROM person WHERE age > $min_age",
+                "parameters": { "min_age": 25 },
+                "explain": true,
+            })))
+            .await
+            .unwrap();
+        let plan: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        assert_eq!(plan[0][0]["operation"], "Iterate Index");
+        assert_eq!(plan[0][0]["detail"]["plan"]["index"], "person_age");
+        // The full query plan also counts the fetched records
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "where_clause": "age > 25",
+                "explain_full": true,
+            })))
+            .await
+            .unwrap();
+        let plan: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let fetch = plan[0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|row| row["operation"] == "Fetch")
+            .unwrap();
+        assert_eq!(fetch["detail"]["count"], 1);
+        // Statements which modify data can not be explained
+        let err = service
+            .query(params(serde_json::json!({
+                "query": "DELETE person WHERE age > 25",
+                "explain": true,
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("modify data"));
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT VALUE id FROM person" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), r#"[["person:a","person:b"]]"#);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_and_timeout_clauses() {
+        let select =
+            |value: serde_json::Value| build_select_query(serde_json::from_value(value).unwrap());
+        let insert =
+            |value: serde_json::Value| build_insert_query(serde_json::from_value(value).unwrap());
+        // The clauses end the SELECT statement, before the EXPLAIN keyword
+        let (query, _) = select(serde_json::json!({
+            "targets": ["post"],
+            "fetch_clause": "author",
+            "timeout": "5s",
+            "parallel": true,
+            "explain": true,
+        }))
+        .unwrap();
+        assert!(query.ends_with(" FETCH author TIMEOUT 5s PARALLEL EXPLAIN"));
+        // The clauses end the INSERT statement
+        let (query, _) = insert(serde_json::json!({
+            "target": "person",
+            "values": [{ "name": "John" }],
+            "timeout": "1m30s",
+            "parallel": true,
+        }))
+        .unwrap();
+        assert_eq!(query, "INSERT INTO person $data TIMEOUT 1m30s PARALLEL");
+        let (query, _) = insert(serde_json::json!({
+            "target": "person",
+            "values": [{ "name": "John" }],
+            "parallel": false,
+        }))
+        .unwrap();
+        assert_eq!(query, "INSERT INTO person $data");
+        // Invalid timeouts are rejected
+        let result = select(serde_json::json!({ "targets": ["post"], "timeout": "soon" }));
+        assert!(result.is_err());
+        // The generated statements are executed
+        let service = connected_service().await;
+        service
+            .insert(params(serde_json::json!({
+                "target": "person",
+                "values": [{ "name": "John" }, { "name": "Jane" }],
+                "timeout": "5s",
+                "parallel": true,
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "fields": ["name"],
+                "order_clause": "name",
+                "timeout": "5s",
+                "parallel": true,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(
+            result_text(&result),
+            r#"[[{"name":"Jane"},{"name":"John"}]]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_select_with_fetch_clause() {
+        let params_for =
+            |value: serde_json::Value| -> SelectParams { serde_json::from_value(value).unwrap() };
+        // The fetch clause is added after the limit and start clauses
+        let (query, _) = build_select_query(params_for(serde_json::json!({
+            "targets": ["post"],
+            "limit_clause": "10",
+            "start_clause": "20",
+            "fetch_clause": "author, comments",
+            "explain": true,
+        })))
+        .unwrap();
+        assert!(query.ends_with(" LIMIT 10 START AT 20 FETCH author, comments EXPLAIN"));
+        // Invalid fetch clauses are rejected
+        let result = build_select_query(params_for(serde_json::json!({
+            "targets": ["post"],
+            "fetch_clause": "author; DELETE post",
+        })));
+        assert!(result.is_err());
+        // Linked records are resolved in the results
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:tobie SET name = 'Tobie'; CREATE post:1 SET author = person:tobie;"
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["post"],
+                "fetch_clause": "author",
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result[0][0]["author"]["name"], "Tobie");
+    }
+
+    #[tokio::test]
+    async fn test_require_where_for_mutations() {
+        let mut service = connected_service().await;
+        service.options.require_where_for_mutations = true;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a SET age = 10; CREATE person:b SET age = 20;"
+            })))
+            .await
+            .unwrap();
+        let count = |service: SurrealService| async move {
+            let result = service
+                .query(params(serde_json::json!({
+                    "query": "RETURN count(SELECT * FROM person)"
+                })))
+                .await
+                .unwrap();
+            query_results(&result)
+        };
+        // Unguarded full-table updates and deletes are rejected
+        let err = service
+            .delete(params(serde_json::json!({ "targets": ["person"] })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("'DELETE person' without a WHERE clause")
+        );
+        let result = service
+            .update(params(serde_json::json!({
+                "targets": ["person"],
+                "merge_data": { "age": 0 },
+            })))
+            .await;
+        assert!(result.is_err());
+        let result = service
+            .query(params(serde_json::json!({ "query": "DELETE person" })))
+            .await;
+        assert!(result.is_err());
+        // Subqueries in expressions and clauses passed to other tools are rejected
+        let err = service
+            .compute(params(
+                serde_json::json!({ "expression": "(DELETE person)" }),
+            ))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("'DELETE person' without a WHERE clause")
+        );
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "fields": ["(UPDATE person SET age = 0) AS updated"],
+            })))
+            .await;
+        assert!(result.is_err());
+        let result = service
+            .count(params(serde_json::json!({
+                "target": "person",
+                "where_clause": "(DELETE person) = []",
+            })))
+            .await;
+        assert!(result.is_err());
+        assert_eq!(count(service.clone()).await, "[2]");
+        // Filtered and single record deletes proceed
+        service
+            .delete(params(serde_json::json!({
+                "targets": ["person"],
+                "where_clause": "age > 15",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(count(service.clone()).await, "[1]");
+        // Confirmed full-table deletes proceed, using the query tool parameter name
+        service
+            .delete(params(serde_json::json!({
+                "targets": ["person"],
+                "confirm_full_table": true,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(count(service.clone()).await, "[0]");
+    }
+
+    #[tokio::test]
+    async fn test_delete_requires_confirmation_for_whole_tables() {
+        // The delete tool is guarded even when WHERE clauses are not required
+        let service = connected_service().await;
+        assert!(!service.options.require_where_for_mutations);
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a SET age = 10; CREATE person:b SET age = 20; CREATE person:c SET age = 30;"
+            })))
+            .await
+            .unwrap();
+        let count = || async {
+            let result = service
+                .query(params(serde_json::json!({
+                    "query": "RETURN count(SELECT * FROM person)"
+                })))
+                .await
+                .unwrap();
+            query_results(&result)
+        };
+        // Deleting a whole table without confirmation is rejected
+        let err = service
+            .delete(params(
+                serde_json::json!({ "targets": ["person:a", "person"] }),
+            ))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("'DELETE person' without a WHERE clause")
+        );
+        assert!(err.message.contains("set confirm_all to true"));
+        assert_eq!(count().await, "[3]");
+        // Record IDs and filtered deletes proceed
+        service
+            .delete(params(serde_json::json!({ "targets": ["person:a"] })))
+            .await
+            .unwrap();
+        service
+            .delete(params(serde_json::json!({
+                "targets": ["person"],
+                "where_clause": "age > 25",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(count().await, "[1]");
+        // Confirmed full-table deletes proceed
+        service
+            .delete(params(serde_json::json!({
+                "targets": ["person"],
+                "confirm_all": true,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(count().await, "[0]");
+    }
+
+    #[tokio::test]
+    async fn test_select_with_field_projection() {
+        let params_for =
+            |value: serde_json::Value| -> SelectParams { serde_json::from_value(value).unwrap() };
+        // All fields are selected by default
+        let (query, _) =
+            build_select_query(params_for(serde_json::json!({ "targets": ["person"] }))).unwrap();
+        assert_eq!(query, "SELECT * FROM person");
+        // Only the given fields are selected
+        let (query, _) = build_select_query(params_for(serde_json::json!({
+            "targets": ["person"],
+            "fields": ["name", " address.city ", "count() AS total"],
+        })))
+        .unwrap();
+        assert_eq!(
+            query,
+            "SELECT name, address.city, count() AS total FROM person"
+        );
+        // Empty and malicious fields are rejected
+        for fields in [
+            serde_json::json!([]),
+            serde_json::json!([""]),
+            serde_json::json!(["name FROM person; DELETE person; SELECT name"]),
+            serde_json::json!(["name -- comment"]),
+        ] {
+            let params = params_for(serde_json::json!({ "targets": ["person"], "fields": fields }));
+            assert!(build_select_query(params).is_err());
+        }
+        // Only the given fields are returned
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a SET name = 'a', age = 30, address = { city: 'London' };"
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "fields": ["name", "address.city"],
+            })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(
+            result[0][0],
+            serde_json::json!({ "name": "a", "address": { "city": "London" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_count_records() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a SET age = 10; CREATE person:b SET age = 20; CREATE person:c SET age = 30;"
+            })))
+            .await
+            .unwrap();
+        let count = |value: serde_json::Value| {
+            let service = service.clone();
+            async move {
+                let result = service.count(params(value)).await.unwrap();
+                serde_json::from_str::<serde_json::Value>(&result_text(&result)).unwrap()
+            }
+        };
+        // All records are counted
+        let result = count(serde_json::json!({ "target": "person" })).await;
+        assert_eq!(result, serde_json::json!({ "count": 3 }));
+        // Filtered records are counted with bound parameters
+        let result = count(serde_json::json!({
+            "target": "person",
+            "where_clause": "age > $min_age",
+            "parameters": { "min_age": 15 },
+        }))
+        .await;
+        assert_eq!(result, serde_json::json!({ "count": 2 }));
+        // Tables without matching records have a count of zero
+        let result = count(serde_json::json!({ "target": "article" })).await;
+        assert_eq!(result, serde_json::json!({ "count": 0 }));
+    }
+
+    #[tokio::test]
+    async fn test_info_at_each_level() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "DEFINE TABLE person SCHEMAFULL; DEFINE FIELD name ON person TYPE string; DEFINE INDEX person_name ON person FIELDS name; DEFINE EVENT person_created ON person WHEN $event = 'CREATE' THEN {};",
+            })))
+            .await
+            .unwrap();
+        let info = |level: &str, table: Option<&str>| {
+            let service = &service;
+            let request = serde_json::json!({ "level": level, "table": table });
+            async move {
+                let result = service.info(params(request)).await.unwrap();
+                serde_json::from_str::<serde_json::Value>(&result_text(&result)).unwrap()
+            }
+        };
+        let result = info("root", None).await;
+        assert!(result["namespaces"]["test"].is_string());
+        let result = info("ns", None).await;
+        assert!(result["databases"]["test"].is_string());
+        let result = info("db", None).await;
+        assert!(result["tables"]["person"].is_string());
+        let result = info("table", Some("person")).await;
+        assert!(result["fields"]["name"].is_string());
+        assert!(result["indexes"]["person_name"].is_string());
+        assert!(result["events"]["person_created"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_info_validates_level_and_table() {
+        let service = connected_service().await;
+        let err = service
+            .info(params(serde_json::json!({ "level": "table" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("requires a table"));
+        let err = service
+            .info(params(serde_json::json!({ "level": "schema" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Unknown info level"));
+    }
+
+    #[tokio::test]
+    async fn test_write_tools_return_clause() {
+        let service = connected_service().await;
+        let result = service
+            .create(params(serde_json::json!({
+                "target": "person:john",
+                "data": { "name": "John", "age": 30 },
+                "return_clause": "NONE",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), "[[]]");
+        let result = service
+            .update(params(serde_json::json!({
+                "targets": ["person"],
+                "merge_data": { "age": 31 },
+                "return_clause": "none",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), "[[]]");
+        let result = service
+            .update(params(serde_json::json!({
+                "targets": ["person:john"],
+                "merge_data": { "age": 32 },
+                "return_clause": "DIFF",
+            })))
+            .await
+            .unwrap();
+        let diff: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(
+            diff[0][0],
+            serde_json::json!([{ "op": "replace", "path": "/age", "value": 32 }])
+        );
+        let result = service
+            .delete(params(serde_json::json!({
+                "targets": ["person:john"],
+                "return_clause": "BEFORE",
+            })))
+            .await
+            .unwrap();
+        let deleted: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(deleted[0][0]["name"], "John");
+        let result = service
+            .update(params(serde_json::json!({
+                "targets": ["person"],
+                "merge_data": { "age": 1 },
+                "return_clause": "NONE; DELETE person",
+            })))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_with_set_clause() {
+        // SET expressions are added to the statement without binding data
+        let mut bound = HashMap::new();
+        let clause = data_clause(&mut bound, None, None, None, None, Some("age += 1".into()));
+        assert_eq!(clause.unwrap(), " SET age += 1");
+        assert!(bound.is_empty());
+        assert!(data_clause(&mut bound, None, None, None, None, Some(" ".into())).is_err());
+        assert!(
+            data_clause(
+                &mut bound,
+                None,
+                None,
+                None,
+                None,
+                Some("age = 1; DELETE person".into())
+            )
+            .is_err()
+        );
+        // SET expressions can not be combined with data
+        let err = data_clause(
+            &mut bound,
+            Some(serde_json::Map::new()),
+            None,
+            None,
+            None,
+            Some("age += 1".into()),
+        )
+        .unwrap_err();
+        assert!(err.message.contains("content_data, set_clause"));
+        // SET expressions are applied to the filtered records
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:a SET age = 20; CREATE person:b SET age = 40;"
+            })))
+            .await
+            .unwrap();
+        service
+            .update(params(serde_json::json!({
+                "targets": ["person"],
+                "set_clause": "age += 1",
+                "where_clause": "age < 30",
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "SELECT VALUE age FROM person:a, person:b"
+            })))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[[21,40]]");
+        // SET expressions can create records when upserting
+        service
+            .upsert(params(serde_json::json!({
+                "targets": ["counter:visits"],
+                "set_clause": "count += $step",
+                "parameters": { "step": 2 },
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "SELECT VALUE count FROM counter:visits"
+            })))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[[2]]");
+    }
+
+    #[test]
+    fn test_check_data_mode() {
+        assert!(check_data_mode(&[("content_data", true), ("merge_data", false)]).is_ok());
+        let err = check_data_mode(&[("content_data", false), ("merge_data", false)]).unwrap_err();
+        assert_eq!(
+            err.message,
+            "No data mode supplied; provide exactly one of content/merge/replace/patch/set"
+        );
+        let err = check_data_mode(&[
+            ("content_data", true),
+            ("merge_data", true),
+            ("patch_data", false),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            err.message,
+            "Multiple data modes supplied; provide exactly one (got content_data, merge_data)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upsert_and_update_reject_invalid_data_modes() {
+        let service = connected_service().await;
+        let err = service
+            .upsert(params(serde_json::json!({ "targets": ["person:john"] })))
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("No data mode supplied"));
+        let err = service
+            .update(params(serde_json::json!({
+                "targets": ["person:john"],
+                "merge_data": { "age": 1 },
+                "replace_data": { "age": 2 },
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("(got merge_data, replace_data)"));
+    }
+
+    #[tokio::test]
+    async fn test_user_parameters_can_not_replace_tool_parameters() {
+        let service = connected_service().await;
+        // A user parameter named data would replace the record content
+        let err = service
+            .update(params(serde_json::json!({
+                "targets": ["person"],
+                "merge_data": { "age": 1 },
+                "where_clause": "age < $data",
+                "parameters": { "data": 10 },
+            })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .starts_with("Parameter 'data' is reserved by this tool")
+        );
+        let err = service
+            .upsert(params(serde_json::json!({
+                "targets": ["person:john"],
+                "content_data": { "age": 1 },
+                "parameters": { "data": { "age": 2 } },
+            })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .starts_with("Parameter 'data' is reserved by this tool")
+        );
+        // A user parameter named ids would replace the record ID filter
+        let err = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "ids": ["person:1"],
+                "parameters": { "ids": ["person:2"] },
+            })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .starts_with("Parameter 'ids' is reserved by this tool")
+        );
+        // Nothing was written by the rejected calls
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT * FROM person" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[[]]");
+        // Parameters which do not collide are still bound
+        let result = service
+            .upsert(params(serde_json::json!({
+                "targets": ["person:john"],
+                "content_data": { "age": 1 },
+                "where_clause": "$allowed",
+                "parameters": { "allowed": true },
+            })))
+            .await
+            .unwrap();
+        assert!(result_text(&result).contains("person:john"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_records() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:john SET name = 'John', age = 30, tags = ['a', 'b'], email = 'john@example.com'; CREATE person:jane SET name = 'Jane', age = 30, tags = ['a'], address = { city: 'London' };",
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .diff_records(params(serde_json::json!({
+                "from": "person:john",
+                "to": "person:jane",
+            })))
+            .await
+            .unwrap();
+        let patch: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let ops = patch.as_array().unwrap();
+        // Added, removed, and changed fields are described, but unchanged fields are not
+        assert!(ops.contains(
+            &serde_json::json!({ "op": "add", "path": "/address", "value": { "city": "London" } })
+        ));
+        assert!(ops.contains(&serde_json::json!({ "op": "remove", "path": "/email" })));
+        assert!(
+            ops.contains(&serde_json::json!({ "op": "replace", "path": "/name", "value": "Jane" }))
         );
-        // Lock the database connection
-        let mut db_guard = self.db.lock().await;
-        // Set the database connection to None
-        *db_guard = None;
-        // Output debugging information
-        info!(
-            connection_id = %self.connection_id,
-            "Successfully disconnected from SurrealDB endpoint"
+        assert!(ops.contains(&serde_json::json!({ "op": "remove", "path": "/tags/1" })));
+        assert_eq!(ops.len(), 4);
+        // Applying the patch to the first record makes it match the second
+        service
+            .update(params(serde_json::json!({
+                "targets": ["person:john"],
+                "patch_data": patch,
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .diff_records(params(serde_json::json!({
+                "from": "person:john",
+                "to": "person:jane",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), "[]");
+        // Missing records are reported
+        let err = service
+            .diff_records(params(serde_json::json!({
+                "from": "person:john",
+                "to": "person:missing",
+            })))
+            .await
+            .unwrap_err();
+        assert_eq!(err.message, "Record 'person:missing' does not exist");
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commit_and_cancel() {
+        let service = connected_service().await;
+        let count = || async {
+            let result = service
+                .query(params(serde_json::json!({
+                    "query": "RETURN count(SELECT * FROM person)",
+                })))
+                .await
+                .unwrap();
+            query_results(&result)
+        };
+        // Writes are buffered until the transaction is committed
+        service.begin_transaction().await.unwrap();
+        let err = service.begin_transaction().await.unwrap_err();
+        assert!(err.message.starts_with("A transaction is already open"));
+        let result = service
+            .create(params(serde_json::json!({
+                "target": "person:tobie",
+                "data": { "name": "Tobie", "joined": "2024-01-01T00:00:00Z" },
+            })))
+            .await
+            .unwrap();
+        assert!(result_text(&result).contains("Buffered as statement 1"));
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:jaime SET name = $name -- trailing comment",
+                "parameters": { "name": "Jaime" },
+            })))
+            .await
+            .unwrap();
+        assert_eq!(count().await, "[0]");
+        // Committing applies the writes, returning their results without the bound parameters
+        let result = service.commit_transaction().await.unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result.as_array().unwrap().len(), 2);
+        assert_eq!(result[0][0]["name"], "Tobie");
+        assert_eq!(result[1][0]["name"], "Jaime");
+        assert_eq!(count().await, "[2]");
+        // Cancelling discards the writes
+        service.begin_transaction().await.unwrap();
+        service
+            .delete(params(serde_json::json!({ "targets": ["person:tobie"] })))
+            .await
+            .unwrap();
+        service
+            .create(params(serde_json::json!({
+                "target": "person:lizzie",
+                "data": { "name": "Lizzie" },
+            })))
+            .await
+            .unwrap();
+        service.cancel_transaction().await.unwrap();
+        assert_eq!(count().await, "[2]");
+        let err = service.cancel_transaction().await.unwrap_err();
+        assert!(err.message.starts_with("No transaction is open"));
+        // A failing statement rolls back the whole transaction
+        service.begin_transaction().await.unwrap();
+        service
+            .create(params(serde_json::json!({
+                "target": "person:lizzie",
+                "data": { "name": "Lizzie" },
+            })))
+            .await
+            .unwrap();
+        service
+            .create(params(serde_json::json!({
+                "target": "person:tobie",
+                "data": { "name": "Tobie" },
+            })))
+            .await
+            .unwrap();
+        assert!(service.commit_transaction().await.is_err());
+        assert_eq!(count().await, "[2]");
+        let err = service.commit_transaction().await.unwrap_err();
+        assert!(err.message.starts_with("No transaction is open"));
+        // Transaction statements can not be buffered
+        service.begin_transaction().await.unwrap();
+        let err = service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:lizzie; COMMIT TRANSACTION; DELETE person:tobie",
+            })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("can not be used while a transaction is open")
+        );
+        // Parameters do not leak between buffered queries, and the results of
+        // LET statements in buffered queries are returned
+        service
+            .query(params(serde_json::json!({
+                "query": "LET $nick = 'Liz'; CREATE person:lizzie SET name = $name, nick = $nick",
+                "parameters": { "name": "Lizzie" },
+            })))
+            .await
+            .unwrap();
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:leak SET name = $name, nick = $nick",
+            })))
+            .await
+            .unwrap();
+        let result = service.commit_transaction().await.unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(
+            result,
+            serde_json::json!([
+                null,
+                [{ "id": "person:lizzie", "name": "Lizzie", "nick": "Liz" }],
+                [{ "id": "person:leak" }],
+            ])
         );
-        // Return success message
-        Ok(CallToolResult::success(vec![Content::text(
-            "Successfully disconnected from SurrealDB endpoint".to_string(),
-        )]))
     }
 
-    /// Internal query function that executes a SurrealQL query.
-    ///
-    /// This function accepts SurrealDB native Value types, allowing for direct use of
-    /// SurrealQL parameters without JSON conversion. This is used by other tools to
-    /// avoid JSON conversion overhead.
-    async fn query_internal(
-        &self,
-        query_string: String,
-        parameters: Option<HashMap<String, Value>>,
-    ) -> Result<engine::Response, McpError> {
-        // Increment the query counter
-        let query_id = QUERY_COUNTER.fetch_add(1, Ordering::SeqCst);
-        // Lock the database connection
-        let db_guard = self.db.lock().await;
-        // Match the database connection
-        match &*db_guard {
-            Some(db) => {
-                // Execute the query on the engine
-                let res = engine::execute_query(
-                    db,
-                    query_id,
-                    query_string,
-                    parameters,
-                    &self.connection_id,
-                )
-                .await;
-                // Return the response
-                Ok(res)
-            }
-            None => {
-                // Output debugging information
-                warn!(
-                    connection_id = %self.connection_id,
-                    query_id,
-                    query = %query_string,
-                    "Query attempted without database connection"
-                );
-                // Update the query errors metric
-                counter!("surrealmcp.total_errors").increment(1);
-                counter!("surrealmcp.total_configuration_errors").increment(1);
-                counter!("surrealmcp.errors.no_connection").increment(1);
-                // Return error message
-                Err(McpError::internal_error(
-                    "Not connected to any SurrealDB endpoint. Use connect_endpoint first."
-                        .to_string(),
-                    None,
-                ))
+    #[tokio::test]
+    async fn test_live_query_notifications() {
+        let service = connected_service().await;
+        let result = service
+            .live_query(params(serde_json::json!({ "table": "person" })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        let id = result["id"].as_str().unwrap().to_string();
+        assert_eq!(result["table"], "person");
+        // No notifications are received before any changes are made
+        let result = service
+            .poll_live_query(params(serde_json::json!({ "id": id })))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+        assert_eq!(result["notifications"], serde_json::json!([]));
+        // A notification is received after a record is created
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE person:tobie SET name = 'Tobie'",
+            })))
+            .await
+            .unwrap();
+        let mut notifications = Vec::new();
+        for _ in 0..50 {
+            let result = service
+                .poll_live_query(params(serde_json::json!({ "id": id })))
+                .await
+                .unwrap();
+            let result: serde_json::Value = serde_json::from_str(&result_text(&result)).unwrap();
+            notifications.extend(result["notifications"].as_array().unwrap().clone());
+            if !notifications.is_empty() {
+                break;
             }
+            tokio::time::sleep(Duration::from_millis(20)).await;
         }
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0]["action"], "CREATE");
+        assert_eq!(notifications[0]["data"]["name"], "Tobie");
+        // The live query can be killed, after which it can not be polled
+        service
+            .kill_live_query(params(serde_json::json!({ "id": id })))
+            .await
+            .unwrap();
+        let err = service
+            .poll_live_query(params(serde_json::json!({ "id": id })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("was not found"));
+        // Invalid live query IDs are rejected
+        let err = service
+            .kill_live_query(params(serde_json::json!({ "id": "person" })))
+            .await
+            .unwrap_err();
+        assert_eq!(err.message, "Invalid live query ID 'person'");
+        // Live queries are stopped when the session is dropped
+        service
+            .live_query(params(serde_json::json!({ "table": "person" })))
+            .await
+            .unwrap();
+        let task = service
+            .live_queries
+            .lock()
+            .await
+            .values()
+            .next()
+            .unwrap()
+            .task
+            .abort_handle();
+        drop(service);
+        tokio::task::yield_now().await;
+        assert!(task.is_finished());
     }
 
-    /// Initialize the database connection using startup configuration.
-    ///
-    /// This method attempts to connect to the database using the configuration
-    /// provided at startup. If no endpoint is configured, this method does nothing.
-    /// If an endpoint is configured, it will connect using the configured settings.
-    pub async fn initialize_connection(&self) -> Result<(), anyhow::Error> {
-        if let Some(endpoint) = &self.endpoint {
-            // Output debugging information
-            info!(
-                connection_id = %self.connection_id,
-                endpoint = %endpoint,
-                namespace = self.namespace.as_deref(),
-                database = self.database.as_deref(),
-                "Initializing database connection with startup configuration"
-            );
-            // Get the configured endpoint details
-            let user = self.user.as_deref();
-            let pass = self.pass.as_deref();
-            let ns = self.namespace.as_deref();
-            let db = self.database.as_deref();
-            // Create a new SurrealDB connection
-            match db::create_client_connection(endpoint, user, pass, ns, db).await {
-                Ok(instance) => {
-                    // Update the service's database connection
-                    let mut db_guard = self.db.lock().await;
-                    *db_guard = Some(instance);
-                    // Output debugging information
-                    info!(
-                        connection_id = %self.connection_id,
-                        endpoint = %endpoint,
-                        "Successfully initialized database connection"
-                    );
-                }
-                Err(e) => {
-                    // Output debugging information
-                    error!(
-                        connection_id = %self.connection_id,
-                        endpoint = %endpoint,
-                        error = %e,
-                        "Failed to initialize database connection"
-                    );
-                    // Increment error metrics
-                    counter!("surrealmcp.total_errors").increment(1);
-                    counter!("surrealmcp.total_connection_errors").increment(1);
-                    counter!("surrealmcp.errors.connect_endpoint").increment(1);
-                    // Return error message
-                    return Err(e);
-                }
+    #[test]
+    fn test_list_pagination() {
+        let mut service = SurrealService::new(generate_connection_id());
+        let request = |cursor: Option<String>| Some(rmcp::model::PaginatedRequestParam { cursor });
+        // All items are returned when the page size is unlimited
+        let (page, next) = service.paginate(vec![1, 2, 3], None).unwrap();
+        assert_eq!(page, vec![1, 2, 3]);
+        assert_eq!(next, None);
+        // Items are returned in pages of the configured size
+        service.options.max_response_items = Some(2);
+        let items: Vec<_> = prompts::list_prompts()
+            .into_iter()
+            .map(|prompt| prompt.name)
+            .collect();
+        assert!(items.len() > 2);
+        let mut collected = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = service.paginate(items.clone(), request(cursor)).unwrap();
+            assert!(!page.is_empty() && page.len() <= 2);
+            collected.extend(page);
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
             }
-        } else {
-            debug!(
-                connection_id = %self.connection_id,
-                "No endpoint configured for startup connection"
-            );
         }
-        // All ok
-        Ok(())
+        assert_eq!(collected, items);
+        // Invalid cursors are rejected
+        assert!(
+            service
+                .paginate(vec![1], request(Some("x".into())))
+                .is_err()
+        );
+        assert!(
+            service
+                .paginate(vec![1], request(Some("5".into())))
+                .is_err()
+        );
     }
-}
 
-#[tool_handler]
-impl ServerHandler for SurrealService {
-    /// Get the MCP server info
-    fn get_info(&self) -> ServerInfo {
-        // Output debugging information
-        debug!("Getting server info");
-        // Get the server info
-        ServerInfo {
-            capabilities: ServerCapabilities::builder()
-                .enable_resources()
-                .enable_prompts()
-                .enable_tools()
-                .build(),
-            instructions: Some(include_str!("../../server.md").to_string()),
-            ..Default::default()
+    #[tokio::test]
+    async fn test_tools_are_listed_in_pages_sorted_by_name() {
+        let mut service = SurrealService::new(generate_connection_id());
+        service.options.max_response_items = Some(10);
+        let mut names = Vec::new();
+        let mut params = serde_json::json!({});
+        loop {
+            let message = send_request(&service, "tools/list", params, None).await;
+            let tools = message["result"]["tools"].as_array().unwrap();
+            assert!(!tools.is_empty() && tools.len() <= 10);
+            names.extend(
+                tools
+                    .iter()
+                    .map(|tool| tool["name"].as_str().unwrap().to_string()),
+            );
+            match message["result"]["nextCursor"].as_str() {
+                Some(cursor) => params = serde_json::json!({ "cursor": cursor }),
+                None => break,
+            }
         }
+        // Every tool is listed exactly once, in order of name
+        let mut expected: Vec<String> = service
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        expected.sort();
+        assert_eq!(names, expected);
     }
 
-    /// Initialize the MCP server
-    async fn initialize(
-        &self,
-        _req: rmcp::model::InitializeRequestParam,
-        ctx: RequestContext<RoleServer>,
-    ) -> Result<rmcp::model::InitializeResult, McpError> {
-        // Output debugging information
-        debug!("Initializing MCP server");
-        // Get the bearer token from the extensions
-        if let Some(parts) = ctx.extensions.get::<Parts>()
-            && let Some(token) = parts.extensions.get::<String>()
-        {
-            self.cloud_client
-                .client_token
-                .write()
+    #[tokio::test]
+    async fn test_read_only_rejects_mutating_queries() {
+        let mut service = connected_service().await;
+        service
+            .query(params(serde_json::json!({ "query": "CREATE person:john" })))
+            .await
+            .unwrap();
+        service.options.read_only = true;
+        // Queries which only read data are allowed
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT * FROM person" }),
+            ))
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(&query_results(&result)).unwrap();
+        assert_eq!(result[0].as_array().unwrap().len(), 1);
+        // Queries which modify data are rejected before execution
+        for query in [
+            "CREATE person:jane",
+            "SELECT * FROM person; DELETE person",
+            "RETURN (UPDATE person SET age = 1)",
+            "DEFINE TABLE other",
+        ] {
+            let err = service
+                .query(params(serde_json::json!({ "query": query })))
                 .await
-                .replace(token.clone());
-        }
-        // Initialize the connection using startup configuration
-        if let Err(e) = self.initialize_connection().await {
-            error!(
-                connection_id = %self.connection_id,
-                error = %e,
-                "Failed to initialize database connection during MCP initialization"
-            );
+                .unwrap_err();
+            assert!(err.message.contains("read-only mode"), "{query}");
         }
-        Ok(self.get_info())
+        // Write tools are rejected too, as are writes hidden in clauses
+        let err = service
+            .create(params(
+                serde_json::json!({ "target": "person", "data": {} }),
+            ))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("read-only mode"));
+        let err = service
+            .select(params(serde_json::json!({
+                "targets": ["person"],
+                "where_clause": "(DELETE person) = []",
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("read-only mode"));
+        // No data was modified
+        service.options.read_only = false;
+        let result = service
+            .count(params(serde_json::json!({ "target": "person" })))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), r#"{"count":1}"#);
     }
 
-    /// List the MCP server prompts
-    async fn list_prompts(
-        &self,
-        _req: Option<rmcp::model::PaginatedRequestParam>,
-        _ctx: RequestContext<RoleServer>,
-    ) -> Result<rmcp::model::ListPromptsResult, McpError> {
-        // Output debugging information
-        debug!("Listing available prompts");
-        // Get prompts from the prompts module
-        let prompts = prompts::list_prompts();
-        // Return the prompts
-        Ok(rmcp::model::ListPromptsResult {
-            prompts,
-            next_cursor: None,
-        })
+    #[tokio::test]
+    async fn test_connection_profiles() {
+        let service = SurrealService::new(generate_connection_id());
+        // Profiles are saved without returning their credentials
+        let result = service
+            .save_connection_profile(params(serde_json::json!({
+                "name": "local",
+                "endpoint": "memory",
+                "namespace": "app",
+                "database": "main",
+                "username": "root",
+                "password": "secret",
+            })))
+            .await
+            .unwrap();
+        assert!(!result_text(&result).contains("secret"));
+        let result = service.list_connection_profiles().await.unwrap();
+        let text = result_text(&result);
+        assert!(!text.contains("secret") && !text.contains("root"));
+        let result: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            result["profiles"],
+            serde_json::json!([{
+                "name": "local",
+                "endpoint": "memory",
+                "namespace": "app",
+                "database": "main",
+                "has_username": true,
+                "has_password": true,
+            }])
+        );
+        // Connecting with a profile uses its endpoint, namespace, and database
+        service
+            .save_connection_profile(params(serde_json::json!({
+                "name": "local",
+                "endpoint": "memory",
+                "namespace": "app",
+                "database": "main",
+            })))
+            .await
+            .unwrap();
+        service
+            .connect_profile(params(serde_json::json!({ "name": "local" })))
+            .await
+            .unwrap();
+        let context = service.context.lock().await.clone();
+        assert_eq!(context.endpoint.as_deref(), Some("memory"));
+        assert_eq!(context.namespace.as_deref(), Some("app"));
+        assert_eq!(context.database.as_deref(), Some("main"));
+        // Unknown profiles are rejected
+        let err = service
+            .connect_profile(params(serde_json::json!({ "name": "other" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Unknown connection profile"));
     }
 
-    /// Get an MCP server prompt
-    async fn get_prompt(
-        &self,
-        req: rmcp::model::GetPromptRequestParam,
-        _ctx: RequestContext<RoleServer>,
-    ) -> Result<rmcp::model::GetPromptResult, McpError> {
-        // Output debugging information
-        debug!(prompt_name = %req.name, "Getting prompt");
-        // Get prompt from the prompts module
-        match prompts::get_prompt_with_arguments(&req.name, req.arguments) {
-            Some((description, messages)) => Ok(rmcp::model::GetPromptResult {
-                description: Some(description),
-                messages,
-            }),
-            None => Err(McpError::internal_error(
-                format!("Unknown prompt: {}", req.name),
-                None,
-            )),
+    #[tokio::test]
+    async fn test_dump_schema_reproduces_schema() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": r#"
+                    DEFINE ANALYZER simple TOKENIZERS blank FILTERS lowercase;
+                    DEFINE FUNCTION fn::greet($name: string) { RETURN 'Hi ' + $name; };
+                    DEFINE PARAM $limit VALUE 10;
+                    DEFINE ACCESS account ON DATABASE TYPE RECORD DURATION FOR SESSION 1h;
+                    DEFINE TABLE person SCHEMAFULL;
+                    DEFINE FIELD name ON person TYPE string;
+                    DEFINE FIELD address ON person TYPE object;
+                    DEFINE FIELD address.city ON person TYPE string;
+                    DEFINE INDEX person_name ON person FIELDS name SEARCH ANALYZER simple BM25;
+                    DEFINE EVENT created ON person WHEN $event = 'CREATE' THEN {};
+                    DEFINE TABLE a_count AS SELECT count() FROM person GROUP ALL;
+                "#,
+            })))
+            .await
+            .unwrap();
+        let result = service.dump_schema().await.unwrap();
+        let script = result_text(&result);
+        // Views are defined after the tables they select from
+        let view = script.find("DEFINE TABLE a_count").unwrap();
+        assert!(script.find("DEFINE TABLE person").unwrap() < view);
+        // Accesses with redacted keys are included as comments
+        assert!(script.contains("-- DEFINE ACCESS account"));
+        // Applying the script to an empty database reproduces the schema
+        let copy = connected_service().await;
+        copy.query(params(serde_json::json!({ "query": script })))
+            .await
+            .unwrap();
+        for query in [
+            "INFO FOR DB",
+            "INFO FOR TABLE person",
+            "INFO FOR TABLE a_count",
+        ] {
+            let mut infos = Vec::new();
+            for service in [&service, &copy] {
+                let result = service
+                    .query(params(serde_json::json!({ "query": query })))
+                    .await
+                    .unwrap();
+                let mut info: serde_json::Value =
+                    serde_json::from_str(&query_results(&result)).unwrap();
+                info[0].as_object_mut().unwrap().remove("accesses");
+                infos.push(info);
+            }
+            assert_eq!(infos[0], infos[1], "{query}");
         }
     }
 
-    /// List the MCP server resources
-    async fn list_resources(
-        &self,
-        _req: Option<rmcp::model::PaginatedRequestParam>,
-        _ctx: RequestContext<RoleServer>,
-    ) -> Result<rmcp::model::ListResourcesResult, McpError> {
-        // Output debugging information
-        debug!("Listing available prompts");
-        // Get resources from the resources module
-        let resources = resources::list_resources();
-        // Return the resources
-        Ok(rmcp::model::ListResourcesResult {
-            resources,
-            next_cursor: None,
-        })
+    #[tokio::test]
+    async fn test_export_round_trips_records() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": r#"
+                    DEFINE TABLE person SCHEMAFULL;
+                    DEFINE FIELD name ON person TYPE string;
+                    CREATE person:tobie SET name = 'Tobie';
+                    CREATE person:jaime SET name = 'Jaime';
+                    CREATE |order:1..5| SET total = 10;
+                "#,
+            })))
+            .await
+            .unwrap();
+        // Importing the SurrealQL export into an empty database restores the records
+        let result = service.export(params(serde_json::json!({}))).await.unwrap();
+        assert_eq!(result.content.len(), 1);
+        let script = result_text(&result);
+        let copy = connected_service().await;
+        copy.query(params(serde_json::json!({ "query": script })))
+            .await
+            .unwrap();
+        let count = "SELECT count() FROM person GROUP ALL; SELECT count() FROM order GROUP ALL";
+        let mut counts = Vec::new();
+        for service in [&service, &copy] {
+            let result = service
+                .query(params(serde_json::json!({ "query": count })))
+                .await
+                .unwrap();
+            counts.push(query_results(&result));
+        }
+        assert_eq!(counts[0], counts[1]);
+        assert!(counts[0].contains(r#""count":2"#) && counts[0].contains(r#""count":5"#));
+        // The NDJSON export contains one record per line
+        let result = service
+            .export(params(serde_json::json!({ "format": "ndjson" })))
+            .await
+            .unwrap();
+        let lines: Vec<serde_json::Value> = result_text(&result)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines.iter().any(|record| record["name"] == "Tobie"));
     }
 
-    /// Get an MCP server resource
-    async fn read_resource(
-        &self,
-        req: rmcp::model::ReadResourceRequestParam,
-        _ctx: RequestContext<RoleServer>,
-    ) -> Result<rmcp::model::ReadResourceResult, McpError> {
-        // Output debugging information
-        debug!(resource_uri = %req.uri, "Reading resource");
-        // Get resource from the resources module
-        match resources::read_resource(&req.uri) {
-            Some(resource) => Ok(resource),
-            None => Err(McpError::internal_error(
-                format!("Unknown resource: {}", req.uri),
-                None,
-            )),
+    #[tokio::test]
+    async fn test_import_applies_script_in_a_transaction() {
+        let service = connected_service().await;
+        let result = service
+            .import(params(serde_json::json!({
+                "content": r#"
+                    OPTION IMPORT;
+                    BEGIN TRANSACTION;
+                    DEFINE TABLE person SCHEMAFULL;
+                    DEFINE FIELD name ON person TYPE string;
+                    INSERT [{ id: person:tobie, name: 'Tobie' }, { id: person:jaime, name: 'Jaime' }];
+                    COMMIT TRANSACTION;
+                "#,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(result_text(&result), r#"{"statements":4}"#);
+        // The imported records are queryable
+        let result = service
+            .query(params(serde_json::json!({
+                "query": "SELECT VALUE name FROM person ORDER BY name",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), r#"[["Jaime","Tobie"]]"#);
+        // A failing statement leaves no partial data behind
+        let err = service
+            .import(params(serde_json::json!({
+                "content": "CREATE person:lizzie SET name = 'Lizzie'; CREATE person:tobie SET name = 'Tobie';",
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Statement 1 of the script failed"));
+        let result = service
+            .query(params(
+                serde_json::json!({ "query": "SELECT * FROM person:lizzie" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(query_results(&result), "[[]]");
+        // Imports are rejected in read-only mode
+        let mut service = service;
+        service.options.read_only = true;
+        let err = service
+            .import(params(serde_json::json!({ "content": "CREATE person" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("read-only"));
+        // Scripts are checked like any other query
+        service.options.read_only = false;
+        service.options.strict_functions = true;
+        service.options.denied_functions = vec!["http::*".to_string()];
+        service.options.require_where_for_mutations = true;
+        let err = service
+            .import(params(serde_json::json!({
+                "content": "CREATE person:lizzie SET page = http::get('https://surrealdb.com');",
+            })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("'http::get' is denied"));
+        let err = service
+            .import(params(serde_json::json!({ "content": "DELETE person;" })))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("'DELETE person' without a WHERE clause")
+        );
+        // Imports are disabled along with raw queries
+        let options = ServiceOptions {
+            disable_query: true,
+            ..Default::default()
+        };
+        let router = SurrealService::tool_router_with_options(&options);
+        assert!(!router.has_route("import"));
+    }
+
+    #[tokio::test]
+    async fn test_export_is_truncated_at_max_bytes() {
+        let service = connected_service().await;
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE |person:1..100| SET name = 'Tobie'",
+            })))
+            .await
+            .unwrap();
+        for format in ["surql", "ndjson"] {
+            let result = service
+                .export(params(
+                    serde_json::json!({ "format": format, "max_bytes": 500 }),
+                ))
+                .await
+                .unwrap();
+            let text = result_text(&result);
+            // The export ends on a complete line within the limit
+            assert!(text.len() <= 500, "{format}");
+            assert!(text.ends_with('\n'), "{format}");
+            let note = result.content[1].as_text().unwrap().text.clone();
+            assert!(note.contains("truncated"), "{format}");
         }
+        // Records are exported in order of their id
+        let result = service
+            .export(params(serde_json::json!({ "format": "ndjson" })))
+            .await
+            .unwrap();
+        let ids: Vec<String> = result_text(&result)
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["id"].to_string())
+            .collect();
+        assert_eq!(ids.len(), 100);
+        assert_eq!(ids[0], "\"person:1\"");
+        assert_eq!(ids[99], "\"person:100\"");
+        // The maximum size can not be raised above the largest size allowed
+        service
+            .query(params(serde_json::json!({
+                "query": "CREATE |document:1..10| SET text = string::repeat('surrealdb', 20000)",
+            })))
+            .await
+            .unwrap();
+        let result = service
+            .export(params(
+                serde_json::json!({ "format": "ndjson", "max_bytes": usize::MAX }),
+            ))
+            .await
+            .unwrap();
+        assert!(result_text(&result).len() <= EXPORT_MAX_BYTES);
+        let note = result.content[1].as_text().unwrap().text.clone();
+        assert!(note.contains(&format!("max_bytes ({EXPORT_MAX_BYTES})")));
+        let err = service
+            .export(params(serde_json::json!({ "format": "csv" })))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Unsupported export format"));
     }
 }
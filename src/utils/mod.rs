@@ -1,4 +1,7 @@
+use regex::Regex;
+use std::path::{Component, Path, PathBuf};
 use std::str::FromStr;
+use std::sync::LazyLock;
 
 /// Generate a unique connection ID
 pub fn generate_connection_id() -> String {
@@ -37,6 +40,11 @@ pub fn format_duration(duration: std::time::Duration) -> String {
 /// This function safely converts serde_json::Value or String to a SurrealDB Value,
 /// providing detailed error messages for conversion failures.
 ///
+/// JSON has no datetime, duration, or record ID types, so these can be passed
+/// as a typed envelope, which is an object with a single key, at any depth:
+/// `{"$datetime": "2024-01-01T00:00:00Z"}`, `{"$duration": "1h"}`, or
+/// `{"$record": "person:john"}`. All other values are converted unchanged.
+///
 /// # Arguments
 /// * `value` - The value to convert (serde_json::Value or String)
 /// * `name` - The name of the parameter being converted (for error messages)
@@ -63,25 +71,105 @@ pub fn convert_json_to_surreal(
 ) -> Result<surrealdb::Value, String> {
     // Ensure the value is a JSON value
     let json_value = value.into();
-    // Convert the JSON value to a SurrealQL Value
-    surrealdb::Value::from_str(&json_value.to_string())
+    // Render the JSON value as SurrealQL, converting any typed envelopes
+    let sql = json_to_surrealql(&json_value)
+        .map_err(|e| format!("Failed to convert parameter '{name}': {e}"))?;
+    // Convert the SurrealQL to a SurrealQL Value
+    surrealdb::Value::from_str(&sql)
         .map_err(|e| format!("Failed to convert parameter '{name}': {e}"))
 }
 
+/// Render a JSON value as a SurrealQL value literal, converting typed envelopes
+///
+/// The value of each envelope is parsed before it is rendered, so that only a
+/// valid datetime, duration, or record ID literal is included in the output.
+fn json_to_surrealql(value: &serde_json::Value) -> Result<String, String> {
+    match value {
+        serde_json::Value::Object(map) => {
+            // Convert typed envelopes to their native SurrealDB type
+            if let [(kind, serde_json::Value::String(inner))] =
+                map.iter().collect::<Vec<_>>().as_slice()
+            {
+                match kind.as_str() {
+                    "$datetime" => {
+                        return surrealdb::sql::Datetime::from_str(inner)
+                            .map(|v| v.to_string())
+                            .map_err(|_| format!("Invalid datetime '{inner}'"));
+                    }
+                    "$duration" => {
+                        return surrealdb::sql::Duration::from_str(inner)
+                            .map(|v| v.to_string())
+                            .map_err(|_| format!("Invalid duration '{inner}'"));
+                    }
+                    "$record" => return parse_record_id(inner).map(|v| v.to_string()),
+                    _ => {}
+                }
+            }
+            // Render each field of the object
+            let fields = map
+                .iter()
+                .map(|(key, value)| {
+                    Ok(format!(
+                        "{}: {}",
+                        serde_json::Value::String(key.clone()),
+                        json_to_surrealql(value)?
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(format!("{{ {} }}", fields.join(", ")))
+        }
+        serde_json::Value::Array(values) => {
+            // Render each item of the array
+            let values = values
+                .iter()
+                .map(json_to_surrealql)
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(format!("[{}]", values.join(", ")))
+        }
+        value => Ok(value.to_string()),
+    }
+}
+
 /// Parse a single item into a SurrealQL Value
 ///
-/// This function takes a single string and attempts to parse it into a SurrealQL Value.
-/// If a string cannot be parsed as a SurrealQL Value, an error is returned.
+/// This function takes a single string, such as a table name or a `table:id`
+/// record ID, and attempts to parse it into a SurrealQL Value. If the string is
+/// empty, lists several targets, or cannot be parsed as a SurrealQL Value, an
+/// error is returned. Use `parse_targets` for multiple targets.
 ///
 /// # Arguments
-/// * `value` - A vector of strings to parse
+/// * `value` - The string to parse
 pub fn parse_target(value: String) -> Result<String, String> {
+    // Check that a target was specified
+    if value.trim().is_empty() {
+        return Err(
+            "The target can not be empty, expected a table name or record ID such as 'person' or 'person:john'"
+                .to_string(),
+        );
+    }
     match surrealdb::Value::from_str(&value) {
         Ok(val) => Ok(val.to_string()),
+        // Commas are only valid within record IDs, such as person:[1, 2]
+        Err(_) if value.contains(',') => Err(format!(
+            "Invalid target '{value}': only a single table name or record ID can be specified"
+        )),
         Err(e) => Err(format!("Failed to parse SurrealQL Value {value}: {e}")),
     }
 }
 
+/// Parse a single item into a SurrealQL Record ID
+///
+/// This function takes a single string and attempts to parse it into a Record ID
+/// in the `table:id` format. If the string is not a valid Record ID, an error is
+/// returned.
+///
+/// # Arguments
+/// * `value` - The string to parse
+pub fn parse_record_id(value: &str) -> Result<surrealdb::RecordId, String> {
+    surrealdb::RecordId::from_str(value)
+        .map_err(|e| format!("Invalid record ID '{value}', expected the format 'table:id': {e}"))
+}
+
 /// Parse a list of items into a list of SurrealQL Values
 ///
 /// This function takes a list of strings and attempts to parse them into SurrealQL Values.
@@ -106,11 +194,675 @@ pub fn parse_targets(values: Vec<String>) -> Result<String, String> {
     Ok(items.join(", "))
 }
 
+/// Build a SurrealQL record range target, such as `person:1..=100`
+///
+/// Each bound is converted from JSON into a record ID key, which can be an
+/// integer, string, array, or object. A missing bound leaves that side of the
+/// range open. The end bound is only included in the range if `inclusive` is
+/// set.
+///
+/// # Arguments
+/// * `table` - The table containing the records
+/// * `start` - The record ID key which the range starts at, if bounded
+/// * `end` - The record ID key which the range ends at, if bounded
+/// * `inclusive` - Whether the end bound is included in the range
+pub fn range_target(
+    table: &str,
+    start: Option<serde_json::Value>,
+    end: Option<serde_json::Value>,
+    inclusive: bool,
+) -> Result<String, String> {
+    use std::ops::Bound;
+    use surrealdb::sql::{Id, IdRange, Thing};
+    // Tables can not be empty
+    if table.is_empty() {
+        return Err("The range table can not be empty".to_string());
+    }
+    // Convert a JSON value into a record ID key
+    let key = |value: serde_json::Value, name: &str| -> Result<Id, String> {
+        let value = convert_json_to_surreal(value, name)?;
+        Id::try_from(value.into_inner())
+            .map_err(|_| format!("The range {name} must be an integer, string, array, or object"))
+    };
+    // Get the start and end bounds of the range
+    let beg = match start {
+        Some(start) => Bound::Included(key(start, "start")?),
+        None => Bound::Unbounded,
+    };
+    let end = match end {
+        Some(end) if inclusive => Bound::Included(key(end, "end")?),
+        Some(end) => Bound::Excluded(key(end, "end")?),
+        None => Bound::Unbounded,
+    };
+    // Build the record range
+    let range = IdRange::try_from((beg, end)).map_err(|e| e.to_string())?;
+    // Return the range target
+    Ok(Thing::from((table.to_string(), range)).to_string())
+}
+
+/// Check whether a SurrealQL function name matches a configured pattern
+///
+/// A pattern of `*` matches every function, a pattern ending in `::*` matches
+/// every function within that package (such as `string::*`), and any other
+/// pattern matches a single function name. Matching is case-insensitive.
+fn function_matches(name: &str, pattern: &str) -> bool {
+    let name = name.to_lowercase();
+    let pattern = pattern.trim().to_lowercase();
+    match pattern.strip_suffix('*') {
+        Some("") => true,
+        Some(prefix) if prefix.ends_with("::") => name.starts_with(prefix),
+        _ => name == pattern,
+    }
+}
+
+/// Check whether a SurrealQL function can be called
+///
+/// Functions which match a denied pattern are always rejected. When any
+/// allowed patterns are configured, only functions which match one of them
+/// can be called.
+///
+/// # Arguments
+/// * `name` - The full function name, such as `string::len` or `fn::greet`
+/// * `allowed` - The patterns of functions which can be called (all if empty)
+/// * `denied` - The patterns of functions which can not be called
+pub fn check_function(name: &str, allowed: &[String], denied: &[String]) -> Result<(), String> {
+    if denied.iter().any(|pattern| function_matches(name, pattern)) {
+        return Err(format!(
+            "Function '{name}' is denied by the server configuration"
+        ));
+    }
+    if !allowed.is_empty()
+        && !allowed
+            .iter()
+            .any(|pattern| function_matches(name, pattern))
+    {
+        return Err(format!(
+            "Function '{name}' is not in the functions allowed by the server configuration"
+        ));
+    }
+    Ok(())
+}
+
+/// Get the names of the functions called in a SurrealQL query
+///
+/// The query is parsed, and every builtin and custom function call is
+/// returned, with custom functions prefixed with `fn::`. Embedded scripting
+/// functions are returned as `script`. Function names within strings are not
+/// returned, and method calls (such as `$value.len()`) can not be resolved to
+/// a function name, so are not returned either.
+///
+/// # Arguments
+/// * `query` - The SurrealQL query to check
+pub fn query_function_names(query: &str) -> Result<Vec<String>, String> {
+    // Parse the query into its syntax tree
+    let query = surrealdb::sql::parse(query)
+        .map_err(|e| format!("Failed to parse query to check its functions: {e}"))?;
+    let tree = serde_json::to_value(&query).map_err(|e| e.to_string())?;
+    // Collect the function names from the syntax tree
+    let mut names = Vec::new();
+    collect_function_names(&tree, &mut names);
+    Ok(names)
+}
+
+/// Get the UPDATE and DELETE statements in a SurrealQL query which have no WHERE clause
+///
+/// The query is parsed, and every UPDATE or DELETE statement without a WHERE
+/// clause is returned with its target, such as `DELETE person`, unless the
+/// statement only targets specific record IDs or ranges of record IDs. Targets
+/// which are parameters or expressions are returned too, as they can refer to
+/// a whole table.
+///
+/// # Arguments
+/// * `query` - The SurrealQL query to check
+pub fn full_table_mutations(query: &str) -> Result<Vec<String>, String> {
+    // Parse the query into its syntax tree
+    let query = surrealdb::sql::parse(query)
+        .map_err(|e| format!("Failed to parse query to check its statements: {e}"))?;
+    let tree = serde_json::to_value(&query).map_err(|e| e.to_string())?;
+    // Collect the unfiltered mutations from the syntax tree
+    let mut mutations = Vec::new();
+    collect_full_table_mutations(&tree, &mut mutations);
+    Ok(mutations)
+}
+
+/// Whether a SurrealQL statement reads or writes data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    /// The statement only reads data or session state
+    Read,
+    /// The statement modifies data or schema definitions
+    Write,
+}
+
+/// The pattern of a SurrealQL duration, such as `5s` or `1m30s`
+static DURATION_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:[0-9]+(?:ns|us|µs|ms|s|m|h|d|w|y))+$").unwrap());
+
+/// The statements which modify data or schema definitions
+const WRITE_STATEMENTS: &[&str] = &[
+    "Create", "Update", "Upsert", "Delete", "Insert", "Relate", "Define", "Remove", "Alter",
+    "Rebuild",
+];
+
+/// Classify each statement in a SurrealQL query as a read or a write
+///
+/// The query is parsed, and a statement is classified as a write if it, or
+/// any subquery or block within it, modifies data or schema definitions.
+/// Custom functions are not inspected, so a read which calls a custom
+/// function that writes data is still classified as a read.
+///
+/// # Arguments
+/// * `query` - The SurrealQL query to classify
+pub fn statement_kinds(query: &str) -> Result<Vec<StatementKind>, String> {
+    // Parse the query into its syntax tree
+    let query = surrealdb::sql::parse(query)
+        .map_err(|e| format!("Failed to parse query to check its statements: {e}"))?;
+    let tree = serde_json::to_value(&query).map_err(|e| e.to_string())?;
+    // Classify each of the top-level statements
+    let statements = tree.as_array().map(Vec::as_slice).unwrap_or_default();
+    Ok(statements
+        .iter()
+        .map(|statement| match contains_write_statement(statement) {
+            true => StatementKind::Write,
+            false => StatementKind::Read,
+        })
+        .collect())
+}
+
+/// Add an EXPLAIN clause to a query, so that it returns its query plan
+///
+/// Only a single SELECT statement can be explained. Statements which modify data,
+/// including SELECT statements with subqueries which modify data, are rejected,
+/// as explaining a statement does not prevent its subqueries from running.
+///
+/// # Arguments
+/// * `query` - The SurrealQL query to explain
+/// * `full` - Whether to use EXPLAIN FULL, which also returns the number of records fetched
+pub fn explain_query(query: &str, full: bool) -> Result<String, String> {
+    // Parse the query into its syntax tree
+    let parsed = surrealdb::sql::parse(query)
+        .map_err(|e| format!("Failed to parse query to explain it: {e}"))?;
+    // Check the query does not modify data
+    if statement_kinds(query)?.contains(&StatementKind::Write) {
+        return Err("EXPLAIN can not be used with statements which modify data".to_string());
+    }
+    // Add the EXPLAIN clause to the single SELECT statement
+    match parsed.as_slice() {
+        [surrealdb::sql::Statement::Select(select)] if select.explain.is_none() => Ok(match full {
+            true => format!("{select} EXPLAIN FULL"),
+            false => format!("{select} EXPLAIN"),
+        }),
+        [surrealdb::sql::Statement::Select(select)] => Ok(select.to_string()),
+        _ => Err("EXPLAIN can only be used with a single SELECT statement".to_string()),
+    }
+}
+
+/// Get the number of statements in a SurrealQL query, and the names of the
+/// parameters bound by its LET statements, so that it can be buffered in a transaction
+///
+/// Queries containing BEGIN, COMMIT, or CANCEL statements are rejected, as they
+/// would end the transaction which the query is buffered in.
+///
+/// # Arguments
+/// * `query` - The SurrealQL query to check
+pub fn buffered_statements(query: &str) -> Result<(usize, Vec<String>), String> {
+    // Parse the query into its syntax tree
+    let query = surrealdb::sql::parse(query)
+        .map_err(|e| format!("Failed to parse query to check its statements: {e}"))?;
+    // Check each of the top-level statements
+    let mut names = Vec::new();
+    for statement in query.iter() {
+        match statement {
+            surrealdb::sql::Statement::Begin(_)
+            | surrealdb::sql::Statement::Commit(_)
+            | surrealdb::sql::Statement::Cancel(_) => {
+                return Err(
+                    "BEGIN, COMMIT, and CANCEL statements can not be used while a transaction is open. Use commit_transaction or cancel_transaction instead".to_string(),
+                );
+            }
+            surrealdb::sql::Statement::Set(set) if !names.contains(&set.name) => {
+                names.push(set.name.clone());
+            }
+            _ => {}
+        }
+    }
+    Ok((query.len(), names))
+}
+
+/// Split a SurrealQL script into statements which can be run in a single transaction
+///
+/// BEGIN and COMMIT statements are removed, as transactions can not be nested
+/// within the transaction the statements are run in. Scripts containing CANCEL
+/// statements are rejected, as they can not be applied as a whole.
+///
+/// # Arguments
+/// * `script` - The SurrealQL script to split
+pub fn import_statements(script: &str) -> Result<Vec<String>, String> {
+    // Parse the script into its syntax tree
+    let query =
+        surrealdb::sql::parse(script).map_err(|e| format!("Failed to parse the script: {e}"))?;
+    // Format each statement which is not a transaction statement
+    let mut statements = Vec::with_capacity(query.len());
+    for statement in query.iter() {
+        match statement {
+            surrealdb::sql::Statement::Begin(_) | surrealdb::sql::Statement::Commit(_) => {}
+            surrealdb::sql::Statement::Cancel(_) => {
+                return Err("The script can not contain CANCEL statements".to_string());
+            }
+            statement => statements.push(statement.to_string()),
+        }
+    }
+    Ok(statements)
+}
+
+/// Recursively check a serialized syntax tree for statements which write data
+fn contains_write_statement(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => map.iter().any(|(key, value)| match key.as_str() {
+            // The keys of object literals are user data, so only check the values
+            "Object" => value
+                .as_object()
+                .is_some_and(|object| object.values().any(contains_write_statement)),
+            key if WRITE_STATEMENTS.contains(&key) => true,
+            _ => contains_write_statement(value),
+        }),
+        serde_json::Value::Array(items) => items.iter().any(contains_write_statement),
+        _ => false,
+    }
+}
+
+/// Recursively collect the unfiltered mutations from a serialized syntax tree
+fn collect_full_table_mutations(value: &serde_json::Value, mutations: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for kind in ["Update", "Delete"] {
+                // Check for a statement without a WHERE clause
+                let Some(serde_json::Value::Object(statement)) = map.get(kind) else {
+                    continue;
+                };
+                if !statement.get("cond").is_some_and(|cond| cond.is_null()) {
+                    continue;
+                }
+                let Some(serde_json::Value::Array(targets)) = statement.get("what") else {
+                    continue;
+                };
+                // Record IDs and ranges only modify specific records
+                for target in targets.iter().filter(|t| t.get("Thing").is_none()) {
+                    let target = match (target.get("Table"), target.get("Param")) {
+                        (Some(table), _) => table.as_str().unwrap_or_default().to_string(),
+                        (_, Some(param)) => format!("${}", param.as_str().unwrap_or_default()),
+                        _ => "an expression".to_string(),
+                    };
+                    mutations.push(format!("{} {target}", kind.to_uppercase()));
+                }
+            }
+            map.values()
+                .for_each(|value| collect_full_table_mutations(value, mutations));
+        }
+        serde_json::Value::Array(items) => {
+            items
+                .iter()
+                .for_each(|value| collect_full_table_mutations(value, mutations));
+        }
+        _ => {}
+    }
+}
+
+/// Recursively collect the function names from a serialized syntax tree
+fn collect_function_names(value: &serde_json::Value, names: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(serde_json::Value::Object(function)) = map.get("Function") {
+                let name = |kind: &str| function.get(kind)?.get(0)?.as_str();
+                if let Some(name) = name("Normal") {
+                    names.push(name.to_string());
+                }
+                if let Some(name) = name("Custom") {
+                    names.push(format!("fn::{name}"));
+                }
+                if function.contains_key("Script") {
+                    names.push("script".to_string());
+                }
+            }
+            map.values()
+                .for_each(|value| collect_function_names(value, names));
+        }
+        serde_json::Value::Array(items) => {
+            items
+                .iter()
+                .for_each(|value| collect_function_names(value, names));
+        }
+        _ => {}
+    }
+}
+
+/// Quote a SurrealQL identifier so that it can be safely used in a query
+///
+/// Identifiers containing only ASCII letters, digits, and underscores (and not
+/// starting with a digit) are returned unchanged. Any other identifier is
+/// wrapped in backticks, with embedded backticks and backslashes escaped.
+///
+/// # Arguments
+/// * `name` - The table, field, or other identifier name to quote
+pub fn quote_ident(name: &str) -> Result<String, String> {
+    // Identifiers can not be empty
+    if name.is_empty() {
+        return Err("Identifier can not be empty".to_string());
+    }
+    // Identifiers can not contain control characters
+    if name.chars().any(|c| c.is_control()) {
+        return Err(format!(
+            "Identifier '{}' contains control characters",
+            name.escape_debug()
+        ));
+    }
+    // Check if the identifier needs to be escaped
+    let simple = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    // Return the identifier, escaping if necessary
+    if simple {
+        Ok(name.to_string())
+    } else {
+        Ok(format!(
+            "`{}`",
+            name.replace('\\', "\\\\").replace('`', "\\`")
+        ))
+    }
+}
+
+/// Quote a SurrealQL field path so that it can be safely used in a query
+///
+/// Field paths consist of one or more identifiers separated by dots, such as
+/// `address.city`. Each part of the path is quoted using [`quote_ident`].
+///
+/// # Arguments
+/// * `path` - The field path to quote
+pub fn quote_field_path(path: &str) -> Result<String, String> {
+    path.split('.')
+        .map(|part| {
+            quote_ident(part).map_err(|_| format!("Invalid field path '{}'", path.escape_debug()))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|parts| parts.join("."))
+}
+
+/// Merge a JSON value into another, using the semantics of SurrealQL MERGE
+///
+/// Fields of nested objects are merged recursively. All other values, including
+/// arrays, replace the existing value.
+///
+/// # Arguments
+/// * `target` - The value to merge into
+/// * `patch` - The value to merge
+pub fn merge_json(target: &mut serde_json::Value, patch: serde_json::Value) {
+    match (target, patch) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// Compute a JSON Patch (RFC 6902) which transforms one JSON value into another
+///
+/// Fields of objects are compared recursively, and array elements are compared
+/// by position, with elements added to or removed from the end of the array.
+/// Only add, remove, and replace operations are generated.
+///
+/// # Arguments
+/// * `from` - The value to transform
+/// * `to` - The value to transform it into
+pub fn diff_json(from: &serde_json::Value, to: &serde_json::Value) -> Vec<serde_json::Value> {
+    let mut operations = Vec::new();
+    diff_json_at("", from, to, &mut operations);
+    operations
+}
+
+/// Append the operations which transform a value at a JSON Pointer path
+fn diff_json_at(
+    path: &str,
+    from: &serde_json::Value,
+    to: &serde_json::Value,
+    operations: &mut Vec<serde_json::Value>,
+) {
+    use serde_json::{Value, json};
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            for (key, value) in from {
+                let path = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match to.get(key) {
+                    Some(other) => diff_json_at(&path, value, other, operations),
+                    None => operations.push(json!({ "op": "remove", "path": path })),
+                }
+            }
+            for (key, value) in to {
+                if !from.contains_key(key) {
+                    let path = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                    operations.push(json!({ "op": "add", "path": path, "value": value }));
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            for (i, (value, other)) in from.iter().zip(to).enumerate() {
+                diff_json_at(&format!("{path}/{i}"), value, other, operations);
+            }
+            // Remove trailing elements from the end, so earlier indexes are unchanged
+            for i in (to.len()..from.len()).rev() {
+                operations.push(json!({ "op": "remove", "path": format!("{path}/{i}") }));
+            }
+            for (i, value) in to.iter().enumerate().skip(from.len()) {
+                operations
+                    .push(json!({ "op": "add", "path": format!("{path}/{i}"), "value": value }));
+            }
+        }
+        (from, to) if from != to => {
+            operations.push(json!({ "op": "replace", "path": path, "value": to }));
+        }
+        _ => {}
+    }
+}
+
+/// Limit a list of registered items, such as prompts or resources
+///
+/// When more items are registered than the configured maximum, the list is
+/// truncated to the maximum. A maximum of `None` or `Some(0)` does not limit
+/// the number of items, matching the CLI options where 0 is unlimited.
+///
+/// # Arguments
+/// * `items` - The registered items
+/// * `max` - The maximum number of items, if limited
+pub fn limit_registered<T>(mut items: Vec<T>, max: Option<usize>) -> Vec<T> {
+    if let Some(max) = max.filter(|max| *max > 0) {
+        items.truncate(max);
+    }
+    items
+}
+
+/// Validate a SurrealQL clause fragment before it is added to a query
+///
+/// Clause fragments (such as WHERE conditions or ORDER BY fields) are inserted
+/// into generated queries as-is. This function rejects fragments which could
+/// alter the structure of the generated query, including stacked statements,
+/// comments, and unterminated strings or identifiers. Characters inside quoted
+/// strings and escaped identifiers are not checked.
+///
+/// # Arguments
+/// * `name` - The name of the clause being validated (for error messages)
+/// * `clause` - The clause fragment to validate
+pub fn validate_clause<'a>(name: &str, clause: &'a str) -> Result<&'a str, String> {
+    // Clauses can not be empty
+    if clause.trim().is_empty() {
+        return Err(format!("The {name} clause can not be empty"));
+    }
+    // The currently open quote character, if any
+    let mut quote: Option<char> = None;
+    // Scan the clause, skipping over quoted sections
+    let mut chars = clause.chars().peekable();
+    while let Some(c) = chars.next() {
+        match quote {
+            // Skip over escaped characters within quotes
+            Some(_) if c == '\\' => {
+                chars.next();
+            }
+            // Check for the end of the quoted section
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '\'' | '"' | '`' => quote = Some(c),
+                '⟨' => quote = Some('⟩'),
+                ';' => {
+                    return Err(format!(
+                        "The {name} clause can not contain multiple statements"
+                    ));
+                }
+                '#' => return Err(format!("The {name} clause can not contain comments")),
+                '-' | '/' if chars.peek() == Some(&c) => {
+                    return Err(format!("The {name} clause can not contain comments"));
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    return Err(format!("The {name} clause can not contain comments"));
+                }
+                _ => {}
+            },
+        }
+    }
+    // Ensure all quoted sections were closed
+    if quote.is_some() {
+        return Err(format!(
+            "The {name} clause contains an unterminated string or identifier"
+        ));
+    }
+    // The clause is valid
+    Ok(clause)
+}
+
+/// Validate the RETURN clause of a generated CREATE, UPDATE, or DELETE statement
+///
+/// The clause can be one of the NONE, BEFORE, AFTER, or DIFF keywords, which
+/// are returned in uppercase, or a projection of fields such as `id, name`.
+/// Projections are checked with [`validate_clause`], and must parse as the
+/// RETURN clause of a single statement.
+///
+/// # Arguments
+/// * `clause` - The RETURN clause to validate
+pub fn validate_return_clause(clause: &str) -> Result<String, String> {
+    let clause = validate_clause("RETURN", clause)?.trim();
+    // Check for one of the RETURN keywords
+    let keyword = clause.to_uppercase();
+    if matches!(keyword.as_str(), "NONE" | "BEFORE" | "AFTER" | "DIFF") {
+        return Ok(keyword);
+    }
+    // Otherwise check that the projection is a valid field list
+    match surrealdb::sql::parse(&format!("DELETE table RETURN {clause}")) {
+        Ok(query) if query.len() == 1 => Ok(clause.to_string()),
+        _ => Err(format!(
+            "The RETURN clause must be NONE, BEFORE, AFTER, DIFF, or a list of fields, got '{clause}'"
+        )),
+    }
+}
+
+/// Build the TIMEOUT and PARALLEL clauses which end a generated statement
+///
+/// The timeout must be a SurrealQL duration, such as `5s` or `1m30s`. The clauses
+/// are returned with a leading space, or as an empty string if neither is set.
+///
+/// # Arguments
+/// * `timeout` - The maximum duration the statement can run for, if limited
+/// * `parallel` - Whether to process the records of the statement in parallel
+pub fn execution_clauses(timeout: Option<&str>, parallel: Option<bool>) -> Result<String, String> {
+    let mut clauses = String::new();
+    // Add the timeout clause if provided
+    if let Some(timeout) = timeout.map(str::trim) {
+        if !DURATION_PATTERN.is_match(timeout) {
+            return Err(format!(
+                "Invalid timeout '{timeout}'. Expected a duration such as '500ms', '5s', or '1m30s'"
+            ));
+        }
+        clauses.push_str(&format!(" TIMEOUT {timeout}"));
+    }
+    // Add the parallel clause if requested
+    if parallel == Some(true) {
+        clauses.push_str(" PARALLEL");
+    }
+    Ok(clauses)
+}
+
+/// Resolve a relative file path to a location within an allowed directory
+///
+/// The path must be relative, and can not contain parent directory components.
+/// Symbolic links in the parent directory are resolved, and the resulting path
+/// must remain within the allowed directory. Existing symbolic links are not
+/// accepted as the final path component.
+///
+/// # Arguments
+/// * `dir` - The directory which the path must remain within
+/// * `path` - The relative path to resolve
+pub fn resolve_path_within(dir: &Path, path: &str) -> Result<PathBuf, String> {
+    // Paths must be relative, and can not traverse to parent directories
+    let relative = Path::new(path);
+    if path.is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!(
+            "Path '{path}' must be a relative path within the allowed directory"
+        ));
+    }
+    // Resolve the allowed directory
+    let base = dir
+        .canonicalize()
+        .map_err(|e| format!("Directory '{}' is not accessible: {e}", dir.display()))?;
+    // Resolve the parent directory of the file
+    let full = base.join(relative);
+    let file_name = full
+        .file_name()
+        .ok_or_else(|| format!("Path '{path}' does not specify a file name"))?;
+    let parent = full
+        .parent()
+        .unwrap_or(&base)
+        .canonicalize()
+        .map_err(|e| format!("The parent directory of '{path}' is not accessible: {e}"))?;
+    // Ensure the resolved path remains within the allowed directory
+    if !parent.starts_with(&base) {
+        return Err(format!("Path '{path}' is outside of the allowed directory"));
+    }
+    let resolved = parent.join(file_name);
+    // Ensure the file itself is not a symbolic link
+    if resolved
+        .symlink_metadata()
+        .is_ok_and(|m| m.file_type().is_symlink())
+    {
+        return Err(format!("Path '{path}' can not be a symbolic link"));
+    }
+    // The path is valid
+    Ok(resolved)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_parse_record_id() {
+        let id = parse_record_id("person:john").unwrap();
+        assert_eq!(id.table(), "person");
+        assert_eq!(id.to_string(), "person:john");
+        assert!(parse_record_id("person").is_err());
+        assert!(parse_record_id("person:john; DELETE person").is_err());
+    }
+
     #[test]
     fn test_convert_json_to_surreal_with_object() {
         let json_val = json!({"name": "Alice", "age": 30, "active": true});
@@ -125,6 +877,46 @@ mod tests {
         assert!(val_str.contains("true"));
     }
 
+    #[test]
+    fn test_convert_json_to_surreal_with_typed_envelopes() {
+        let json_val = json!({
+            "created": { "$datetime": "2024-01-01T00:00:00Z" },
+            "ttl": { "$duration": "1h30m" },
+            "author": { "$record": "person:john" },
+            "tags": [{ "$record": "tag:rust" }, "plain"],
+            "note": { "$datetime": "2024-01-01T00:00:00Z", "other": 1 },
+            "text": "person:john",
+        });
+        let val = convert_json_to_surreal(json_val, "data")
+            .unwrap()
+            .into_inner();
+        let surrealdb::sql::Value::Object(object) = val else {
+            panic!("expected an object");
+        };
+        // Envelopes are converted to native values
+        assert!(matches!(
+            object["created"],
+            surrealdb::sql::Value::Datetime(_)
+        ));
+        assert_eq!(object["created"].to_string(), "d'2024-01-01T00:00:00Z'");
+        assert!(matches!(object["ttl"], surrealdb::sql::Value::Duration(_)));
+        assert_eq!(object["ttl"].to_string(), "1h30m");
+        assert!(matches!(object["author"], surrealdb::sql::Value::Thing(_)));
+        assert_eq!(object["tags"].to_string(), "[tag:rust, 'plain']");
+        // Other values are unchanged
+        assert!(matches!(object["note"], surrealdb::sql::Value::Object(_)));
+        assert!(matches!(object["text"], surrealdb::sql::Value::Strand(_)));
+        // Invalid envelope values are rejected
+        let err =
+            convert_json_to_surreal(json!({ "$datetime": "yesterday" }), "since").unwrap_err();
+        assert_eq!(
+            err,
+            "Failed to convert parameter 'since': Invalid datetime 'yesterday'"
+        );
+        assert!(convert_json_to_surreal(json!({ "$duration": "1h; DELETE person" }), "d").is_err());
+        assert!(convert_json_to_surreal(json!({ "$record": "person" }), "r").is_err());
+    }
+
     #[test]
     fn test_convert_json_to_surreal_with_array() {
         let json_val = json!([1, 2, 3, "hello"]);
@@ -275,4 +1067,382 @@ mod tests {
             assert!(error.contains("Failed to convert parameter 'test_param'"));
         }
     }
+
+    #[test]
+    fn test_quote_ident_valid_identifiers() {
+        assert_eq!(quote_ident("person").unwrap(), "person");
+        assert_eq!(quote_ident("user_profile").unwrap(), "user_profile");
+        assert_eq!(quote_ident("_internal2").unwrap(), "_internal2");
+    }
+
+    #[test]
+    fn test_quote_ident_escapes_identifiers() {
+        assert_eq!(quote_ident("my table").unwrap(), "`my table`");
+        assert_eq!(quote_ident("2fa").unwrap(), "`2fa`");
+        assert_eq!(quote_ident("we`ird").unwrap(), "`we\\`ird`");
+        assert_eq!(
+            quote_ident("person; REMOVE TABLE person").unwrap(),
+            "`person; REMOVE TABLE person`"
+        );
+    }
+
+    #[test]
+    fn test_quote_ident_rejects_invalid_identifiers() {
+        assert!(quote_ident("").is_err());
+        assert!(quote_ident("person\n").is_err());
+    }
+
+    #[test]
+    fn test_quote_field_path() {
+        assert_eq!(quote_field_path("name").unwrap(), "name");
+        assert_eq!(quote_field_path("address.city").unwrap(), "address.city");
+        assert_eq!(
+            quote_field_path("address.post code").unwrap(),
+            "address.`post code`"
+        );
+        assert!(quote_field_path("address.").is_err());
+        assert!(quote_field_path("").is_err());
+    }
+
+    #[test]
+    fn test_merge_json() {
+        let mut target = json!({ "a": { "b": 1, "c": 2 }, "d": 1, "e": [1, 2] });
+        merge_json(
+            &mut target,
+            json!({ "a": { "b": 5, "x": { "y": 1 } }, "d": null, "e": [3] }),
+        );
+        assert_eq!(
+            target,
+            json!({ "a": { "b": 5, "c": 2, "x": { "y": 1 } }, "d": null, "e": [3] })
+        );
+    }
+
+    #[test]
+    fn test_buffered_statements() {
+        assert_eq!(
+            buffered_statements(
+                "LET $data = { a: 1 }; CREATE person CONTENT $data; LET $data = 2; SELECT * FROM person;"
+            )
+            .unwrap(),
+            (4, vec!["data".to_string()])
+        );
+        assert!(buffered_statements("CREATE person; COMMIT TRANSACTION;").is_err());
+        assert!(buffered_statements("BEGIN; CREATE person").is_err());
+        assert!(buffered_statements("LET $a =").is_err());
+    }
+
+    #[test]
+    fn test_import_statements() {
+        assert_eq!(
+            import_statements(
+                "OPTION IMPORT; BEGIN TRANSACTION; INSERT [{ id: person:1, name: 'Tobie' }]; COMMIT TRANSACTION;"
+            )
+            .unwrap(),
+            vec![
+                "OPTION IMPORT".to_string(),
+                "INSERT [{ id: person:1, name: 'Tobie' }]".to_string(),
+            ]
+        );
+        assert!(import_statements("BEGIN; CREATE person; CANCEL;").is_err());
+        assert!(import_statements("CREATE person SET").is_err());
+    }
+
+    #[test]
+    fn test_diff_json() {
+        let from = json!({ "a": 1, "b": { "c": [1, 2, 3] }, "d/e": "x", "f": true });
+        let to = json!({ "a": 2, "b": { "c": [1, 5] }, "d/e": "x", "g": null });
+        assert_eq!(
+            diff_json(&from, &to),
+            vec![
+                json!({ "op": "replace", "path": "/a", "value": 2 }),
+                json!({ "op": "replace", "path": "/b/c/1", "value": 5 }),
+                json!({ "op": "remove", "path": "/b/c/2" }),
+                json!({ "op": "remove", "path": "/f" }),
+                json!({ "op": "add", "path": "/g", "value": null }),
+            ]
+        );
+        // Elements are appended to arrays, and identical values have no operations
+        assert_eq!(
+            diff_json(&json!([1]), &json!([1, { "a/~": 1 }])),
+            vec![json!({ "op": "add", "path": "/1", "value": { "a/~": 1 } })]
+        );
+        assert!(diff_json(&from, &from).is_empty());
+        // Values of different types are replaced
+        assert_eq!(
+            diff_json(&json!({ "a": [1] }), &json!({ "a": { "b": 1 } })),
+            vec![json!({ "op": "replace", "path": "/a", "value": { "b": 1 } })]
+        );
+    }
+
+    #[test]
+    fn test_limit_registered_truncates_to_the_maximum() {
+        // Items within the limit are unchanged
+        assert_eq!(limit_registered(vec![1, 2], Some(3)), [1, 2]);
+        assert_eq!(limit_registered(vec![1, 2, 3], None), [1, 2, 3]);
+        // A maximum of 0 is unlimited
+        assert_eq!(limit_registered(vec![1, 2, 3], Some(0)), [1, 2, 3]);
+        // Items over the limit are truncated
+        assert_eq!(limit_registered(vec![1, 2, 3, 4], Some(2)), [1, 2]);
+    }
+
+    #[test]
+    fn test_validate_clause_accepts_valid_clauses() {
+        assert!(validate_clause("WHERE", "age > 25 AND name CONTAINS 'John'").is_ok());
+        assert!(validate_clause("WHERE", "age > $min_age").is_ok());
+        assert!(validate_clause("WHERE", "note = 'a; b -- c /* d */ # e'").is_ok());
+        assert!(validate_clause("WHERE", "url = \"https://surrealdb.com\"").is_ok());
+        assert!(validate_clause("WHERE", "`odd;field` = 1").is_ok());
+        assert!(validate_clause("WHERE", "name = 'it\\'s'").is_ok());
+        assert!(validate_clause("ORDER BY", "name ASC, age DESC").is_ok());
+        assert!(validate_clause("WHERE", "score > 10 - 5").is_ok());
+    }
+
+    #[test]
+    fn test_explain_query() {
+        assert_eq!(
+            explain_query("SELECT * FROM person WHERE age > 25;", false).unwrap(),
+            "SELECT * FROM person WHERE age > 25 EXPLAIN"
+        );
+        assert_eq!(
+            explain_query("SELECT * FROM person", true).unwrap(),
+            "SELECT * FROM person EXPLAIN FULL"
+        );
+        // Only a single SELECT statement can be explained
+        assert!(explain_query("SELECT * FROM person; SELECT * FROM post", false).is_err());
+        assert!(explain_query("INFO FOR DB", false).is_err());
+        // Statements which modify data are rejected
+        let err = explain_query("DELETE person", false).unwrap_err();
+        assert!(err.contains("modify data"));
+        assert!(explain_query("SELECT * FROM (DELETE person RETURN BEFORE)", false).is_err());
+    }
+
+    #[test]
+    fn test_execution_clauses() {
+        assert_eq!(execution_clauses(None, None).unwrap(), "");
+        assert_eq!(execution_clauses(None, Some(false)).unwrap(), "");
+        assert_eq!(
+            execution_clauses(Some("5s"), Some(true)).unwrap(),
+            " TIMEOUT 5s PARALLEL"
+        );
+        assert_eq!(
+            execution_clauses(Some(" 1m30s "), None).unwrap(),
+            " TIMEOUT 1m30s"
+        );
+        assert!(execution_clauses(Some("5"), None).is_err());
+        assert!(execution_clauses(Some(""), None).is_err());
+        assert!(execution_clauses(Some("5s PARALLEL; DELETE person"), None).is_err());
+    }
+
+    #[test]
+    fn test_validate_clause_rejects_malicious_fragments() {
+        assert!(validate_clause("WHERE", "true; DELETE person").is_err());
+        assert!(validate_clause("WHERE", "true -- comment").is_err());
+        assert!(validate_clause("WHERE", "true // comment").is_err());
+        assert!(validate_clause("WHERE", "true /* comment */").is_err());
+        assert!(validate_clause("WHERE", "true # comment").is_err());
+        assert!(validate_clause("WHERE", "name = 'unterminated").is_err());
+        assert!(validate_clause("WHERE", "name = 'a\\'; DELETE person").is_err());
+        assert!(validate_clause("WHERE", "   ").is_err());
+    }
+
+    #[test]
+    fn test_statement_kinds() {
+        use StatementKind::{Read, Write};
+        let kinds = |query: &str| statement_kinds(query).unwrap();
+        assert_eq!(
+            kinds("SELECT * FROM person; INFO FOR DB; LET $a = 1; RETURN $a"),
+            vec![Read, Read, Read, Read]
+        );
+        assert_eq!(
+            kinds("CREATE person; UPDATE person:1 SET a = 1; DELETE person; UPSERT person:2"),
+            vec![Write, Write, Write, Write]
+        );
+        assert_eq!(
+            kinds("INSERT INTO person { a: 1 }; RELATE person:1->knows->person:2"),
+            vec![Write, Write]
+        );
+        assert_eq!(
+            kinds("DEFINE TABLE person; REMOVE TABLE person"),
+            vec![Write, Write]
+        );
+        // Writes nested within subqueries and blocks are detected
+        assert_eq!(
+            kinds("SELECT * FROM (CREATE person); IF true { DELETE person }"),
+            vec![Write, Write]
+        );
+        assert_eq!(kinds("FOR $x IN [1] { CREATE person }"), vec![Write]);
+        // Object keys are not mistaken for statements
+        assert_eq!(
+            kinds("SELECT * FROM person WHERE data = { Delete: 1 }"),
+            vec![Read]
+        );
+        assert!(statement_kinds("SELEC * FROM person").is_err());
+    }
+
+    #[test]
+    fn test_validate_return_clause() {
+        assert_eq!(validate_return_clause("none").unwrap(), "NONE");
+        assert_eq!(validate_return_clause(" DIFF ").unwrap(), "DIFF");
+        assert_eq!(validate_return_clause("before").unwrap(), "BEFORE");
+        assert_eq!(validate_return_clause("AFTER").unwrap(), "AFTER");
+        assert_eq!(validate_return_clause("id, name").unwrap(), "id, name");
+        assert_eq!(validate_return_clause("VALUE id").unwrap(), "VALUE id");
+        assert!(validate_return_clause("id; DELETE person").is_err());
+        assert!(validate_return_clause("id WHERE true").is_err());
+        assert!(validate_return_clause("").is_err());
+    }
+
+    #[test]
+    fn test_resolve_path_within_allowed_directory() {
+        let dir = std::env::temp_dir().join(format!("surrealmcp-{}", generate_connection_id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        let base = dir.canonicalize().unwrap();
+        assert_eq!(
+            resolve_path_within(&dir, "export.ndjson").unwrap(),
+            base.join("export.ndjson")
+        );
+        assert_eq!(
+            resolve_path_within(&dir, "./nested/export.ndjson").unwrap(),
+            base.join("nested").join("export.ndjson")
+        );
+        // Missing parent directories are rejected
+        assert!(resolve_path_within(&dir, "missing/export.ndjson").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_path_within_rejects_traversal() {
+        let dir = std::env::temp_dir();
+        assert!(resolve_path_within(&dir, "").is_err());
+        assert!(resolve_path_within(&dir, "../export.ndjson").is_err());
+        assert!(resolve_path_within(&dir, "nested/../../export.ndjson").is_err());
+        assert!(resolve_path_within(&dir, "/etc/passwd").is_err());
+        assert!(resolve_path_within(&dir, ".").is_err());
+    }
+
+    #[test]
+    fn test_parse_target() {
+        // Table names and record IDs are parsed
+        assert_eq!(parse_target("person".to_string()).unwrap(), "person");
+        assert_eq!(
+            parse_target("person:john".to_string()).unwrap(),
+            "person:john"
+        );
+        assert_eq!(
+            parse_target("person:[1, 2]".to_string()).unwrap(),
+            "person:[1, 2]"
+        );
+        // Empty and multiple targets are rejected
+        for value in ["", "  "] {
+            let err = parse_target(value.to_string()).unwrap_err();
+            assert!(err.starts_with("The target can not be empty"), "{value:?}");
+        }
+        assert_eq!(
+            parse_target("person, article".to_string()).unwrap_err(),
+            "Invalid target 'person, article': only a single table name or record ID can be specified"
+        );
+        // Invalid values include the parse error
+        let err = parse_target("person:john;".to_string()).unwrap_err();
+        assert!(err.starts_with("Failed to parse SurrealQL Value person:john;"));
+    }
+
+    #[test]
+    fn test_parse_targets_with_record_ranges() {
+        for target in [
+            "person:1..=100",
+            "person:1..100",
+            "person:1..",
+            "person:..5",
+        ] {
+            assert_eq!(parse_targets(vec![target.to_string()]).unwrap(), target);
+        }
+        assert!(parse_targets(vec!["person:|1..3|".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_range_target() {
+        let range = |start, end, inclusive| range_target("person", start, end, inclusive);
+        let (one, hundred) = (Some(json!(1)), Some(json!(100)));
+        assert_eq!(
+            range(one.clone(), hundred.clone(), true).unwrap(),
+            "person:1..=100"
+        );
+        assert_eq!(
+            range(one.clone(), hundred.clone(), false).unwrap(),
+            "person:1..100"
+        );
+        assert_eq!(range(one, None, true).unwrap(), "person:1..");
+        assert_eq!(range(None, hundred, false).unwrap(), "person:..100");
+        assert_eq!(
+            range(Some(json!("a")), Some(json!("m n")), false).unwrap(),
+            "person:a..⟨m n⟩"
+        );
+        assert_eq!(
+            range(Some(json!(["london", 1])), Some(json!(["london", 9])), true).unwrap(),
+            "person:['london', 1]..=['london', 9]"
+        );
+        assert_eq!(
+            range_target("my table", None, None, false).unwrap(),
+            "⟨my table⟩:.."
+        );
+        // Invalid keys and tables are rejected
+        assert!(range(Some(json!(1.5)), None, false).is_err());
+        assert!(range(Some(json!(true)), None, false).is_err());
+        assert!(range_target("", None, None, false).is_err());
+    }
+
+    #[test]
+    fn test_check_function() {
+        let allowed = vec!["string::*".to_string(), "math::abs".to_string()];
+        let denied = vec!["http::*".to_string(), "string::similarity::*".to_string()];
+        // Allowed functions can be called
+        assert!(check_function("string::uppercase", &allowed, &denied).is_ok());
+        assert!(check_function("STRING::len", &allowed, &denied).is_ok());
+        assert!(check_function("math::abs", &allowed, &denied).is_ok());
+        // Denied functions are rejected, even when also allowed
+        let err = check_function("http::get", &allowed, &denied).unwrap_err();
+        assert!(err.contains("'http::get' is denied"));
+        assert!(check_function("HTTP::post", &allowed, &denied).is_err());
+        assert!(check_function("string::similarity::fuzzy", &allowed, &denied).is_err());
+        // Functions which are not allowed are rejected
+        let err = check_function("math::max", &allowed, &denied).unwrap_err();
+        assert!(err.contains("not in the functions allowed"));
+        assert!(check_function("stringx::len", &allowed, &denied).is_err());
+        // All functions are allowed when no patterns are configured
+        assert!(check_function("http::get", &[], &[]).is_ok());
+        assert!(check_function("http::get", &["*".to_string()], &[]).is_ok());
+    }
+
+    #[test]
+    fn test_query_function_names() {
+        let names = query_function_names(
+            "RETURN string::len('a'); SELECT http::get(url) FROM site WHERE math::abs(n) > 1; \
+             RETURN fn::greet('string::len(1)'); -- crypto::md5('x')",
+        )
+        .unwrap();
+        assert_eq!(
+            names,
+            ["string::len", "http::get", "math::abs", "fn::greet"]
+        );
+        assert!(query_function_names("SELEC http::get('x')").is_err());
+    }
+
+    #[test]
+    fn test_full_table_mutations() {
+        let mutations = full_table_mutations(
+            "DELETE person; DELETE person:1, person:1..3; UPDATE person SET a = 1 WHERE b = 2; \
+             UPDATE $table SET a = 1; IF true { UPDATE type::table('x') MERGE {} }; SELECT * FROM person",
+        )
+        .unwrap();
+        assert_eq!(
+            mutations,
+            ["DELETE person", "UPDATE $table", "UPDATE an expression"]
+        );
+        assert!(
+            full_table_mutations("DELETE person:1 WHERE a = 1")
+                .unwrap()
+                .is_empty()
+        );
+        assert!(full_table_mutations("DELET person").is_err());
+    }
 }